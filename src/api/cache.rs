@@ -0,0 +1,176 @@
+/// Side-wallet result cache - short-lived, in-process, keyed by wallet and analysis parameters;
+/// also holds wallet summaries, keyed by wallet alone
+use crate::api::server::TraversalDirection;
+use crate::core::env::env_parse_in;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Parameters that change a side-wallet result; together with the wallet they form the key.
+#[derive(Debug, Clone, Copy)]
+pub struct SideWalletParams {
+    pub depth: usize,
+    pub threshold: f64,
+    pub limit: usize,
+    pub lookback_days: u32,
+    pub cex_hops: bool,
+    pub direction: TraversalDirection,
+    pub stop_at_hubs: bool,
+}
+
+/// Cached side-wallet and wallet-summary responses. Entries for a wallet are
+/// dropped whenever new transactions are ingested for it.
+pub struct SideWalletCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, (Instant, serde_json::Value)>>,
+}
+
+impl SideWalletCache {
+    /// TTL comes from `SIDE_WALLET_CACHE_TTL_SECS` (default 60; 0 disables caching).
+    pub fn from_env() -> Self {
+        let ttl_secs = env_parse_in("SIDE_WALLET_CACHE_TTL_SECS", 60u64, 0..=3_600);
+        Self::with_ttl(Duration::from_secs(ttl_secs))
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn key(wallet: &str, params: &SideWalletParams) -> String {
+        format!(
+            "sidewallets:{}:{}:{}:{}:{}:{}:{}:{}",
+            wallet,
+            params.depth,
+            params.threshold,
+            params.limit,
+            params.lookback_days,
+            params.cex_hops,
+            params.direction.as_str(),
+            params.stop_at_hubs
+        )
+    }
+
+    fn summary_key(wallet: &str) -> String {
+        format!("summary:{}", wallet)
+    }
+
+    pub async fn get(&self, wallet: &str, params: &SideWalletParams) -> Option<serde_json::Value> {
+        self.get_key(&Self::key(wallet, params)).await
+    }
+
+    pub async fn insert(&self, wallet: &str, params: &SideWalletParams, value: serde_json::Value) {
+        self.insert_key(Self::key(wallet, params), value).await
+    }
+
+    pub async fn get_summary(&self, wallet: &str) -> Option<serde_json::Value> {
+        self.get_key(&Self::summary_key(wallet)).await
+    }
+
+    pub async fn insert_summary(&self, wallet: &str, value: serde_json::Value) {
+        self.insert_key(Self::summary_key(wallet), value).await
+    }
+
+    async fn get_key(&self, key: &str) -> Option<serde_json::Value> {
+        let entries = self.entries.read().await;
+        entries
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    async fn insert_key(&self, key: String, value: serde_json::Value) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.write().await;
+        entries.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Results held, expired ones included until the next insert sweeps them.
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Drop every cached result.
+    pub async fn clear(&self) {
+        self.entries.write().await.clear();
+    }
+
+    /// Drop every cached result for `wallet`.
+    pub async fn invalidate_wallet(&self, wallet: &str) {
+        let prefix = format!("sidewallets:{}:", wallet);
+        let summary = Self::summary_key(wallet);
+        self.entries
+            .write()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix) && *key != summary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: SideWalletParams = SideWalletParams {
+        depth: 2,
+        threshold: 0.1,
+        limit: 15,
+        lookback_days: 30,
+        cex_hops: true,
+        direction: TraversalDirection::Both,
+        stop_at_hubs: true,
+    };
+
+    #[tokio::test]
+    async fn test_hit_miss_and_invalidation() {
+        let cache = SideWalletCache::with_ttl(Duration::from_secs(60));
+        assert!(cache.get("W1", &PARAMS).await.is_none());
+
+        cache
+            .insert("W1", &PARAMS, serde_json::json!({ "side_wallets": [] }))
+            .await;
+        cache
+            .insert("W10", &PARAMS, serde_json::json!({ "side_wallets": [] }))
+            .await;
+        assert!(cache.get("W1", &PARAMS).await.is_some());
+        // Different parameters are a different entry.
+        let deeper = SideWalletParams { depth: 3, ..PARAMS };
+        assert!(cache.get("W1", &deeper).await.is_none());
+        let inbound = SideWalletParams {
+            direction: TraversalDirection::Inbound,
+            ..PARAMS
+        };
+        assert!(cache.get("W1", &inbound).await.is_none());
+
+        cache
+            .insert_summary("W1", serde_json::json!({ "wallet": "W1" }))
+            .await;
+        assert!(cache.get_summary("W1").await.is_some());
+        assert!(cache.get_summary("W10").await.is_none());
+
+        cache.invalidate_wallet("W1").await;
+        assert!(cache.get("W1", &PARAMS).await.is_none());
+        assert!(cache.get_summary("W1").await.is_none());
+        assert!(cache.get("W10", &PARAMS).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_and_zero_ttl_disables() {
+        let cache = SideWalletCache::with_ttl(Duration::from_millis(20));
+        cache.insert("W1", &PARAMS, serde_json::json!({})).await;
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.get("W1", &PARAMS).await.is_none());
+
+        let disabled = SideWalletCache::with_ttl(Duration::ZERO);
+        disabled.insert("W1", &PARAMS, serde_json::json!({})).await;
+        assert!(disabled.get("W1", &PARAMS).await.is_none());
+    }
+}
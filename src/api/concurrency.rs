@@ -0,0 +1,323 @@
+/// Concurrency limits - a global cap on in-flight requests, with a smaller cap
+/// for routes that ingest over RPC
+///
+/// This is backpressure, not rate limiting: a request that finds no free permit
+/// is answered at once with `503` (code `SERVICE_BUSY`) and a `Retry-After`
+/// header instead of queueing behind work that already holds the database and
+/// RPC connections. Health checks are never limited.
+use crate::api::responses::ApiError;
+use crate::api::server::side_wallets_request_bootstraps;
+use crate::core::env::env_parse_in;
+use crate::middleware::timeout::{pattern_matches, BOOTSTRAP_ROUTES, SIDE_WALLETS_ROUTE};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::ResponseError;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Routes that bypass the limits so load balancers and scrapers can still probe
+/// a busy server.
+const EXEMPT_ROUTES: &[&str] = &["/health", "/health/deep", "/metrics"];
+
+/// Whether a request to a bootstrap route actually ingests over RPC.
+pub type BootstrapCheck = fn(&ServiceRequest) -> bool;
+
+/// Permits for all requests plus a smaller pool for bootstrap routes.
+#[derive(Debug)]
+pub struct ConcurrencyLimits {
+    requests: Semaphore,
+    max_requests: usize,
+    bootstrap: Semaphore,
+    max_bootstrap: usize,
+    /// Route patterns (`{name}` matches one path segment) drawing on `bootstrap`,
+    /// each with the check deciding whether a request to it bootstraps.
+    bootstrap_routes: Vec<(String, BootstrapCheck)>,
+    retry_after: Duration,
+}
+
+/// In-flight request gauge, reported by `/health`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InFlight {
+    pub requests: usize,
+    pub max_requests: usize,
+    pub bootstrap_requests: usize,
+    pub max_bootstrap_requests: usize,
+}
+
+impl ConcurrencyLimits {
+    /// Limits come from `MAX_CONCURRENT_REQUESTS` (default 256),
+    /// `MAX_CONCURRENT_BOOTSTRAP_REQUESTS` (default 16, for the routes that get
+    /// the bootstrap time budget; side-wallets only when it bootstraps) and
+    /// `BUSY_RETRY_AFTER_SECS` (default 1).
+    pub fn from_env() -> Self {
+        let number = |var: &str, default: usize| env_parse_in(var, default, 1..=100_000);
+        BOOTSTRAP_ROUTES.iter().fold(
+            Self::new(
+                number("MAX_CONCURRENT_REQUESTS", 256),
+                number("MAX_CONCURRENT_BOOTSTRAP_REQUESTS", 16),
+            )
+            .with_retry_after(Duration::from_secs(
+                number("BUSY_RETRY_AFTER_SECS", 1).min(3_600) as u64,
+            )),
+            |limits, route| match *route {
+                SIDE_WALLETS_ROUTE => {
+                    limits.with_bootstrap_route_when(route, side_wallets_request_bootstraps)
+                }
+                _ => limits.with_bootstrap_route(route),
+            },
+        )
+    }
+
+    pub fn new(max_requests: usize, max_bootstrap: usize) -> Self {
+        Self {
+            requests: Semaphore::new(max_requests),
+            max_requests,
+            bootstrap: Semaphore::new(max_bootstrap),
+            max_bootstrap,
+            bootstrap_routes: Vec::new(),
+            retry_after: Duration::from_secs(1),
+        }
+    }
+
+    /// Also take a bootstrap permit for paths matching `pattern`.
+    pub fn with_bootstrap_route(self, pattern: &str) -> Self {
+        self.with_bootstrap_route_when(pattern, |_| true)
+    }
+
+    /// Also take a bootstrap permit for paths matching `pattern` when `check`
+    /// says the request bootstraps (e.g. side-wallets without `bootstrap=false`).
+    pub fn with_bootstrap_route_when(mut self, pattern: &str, check: BootstrapCheck) -> Self {
+        self.bootstrap_routes.push((pattern.to_string(), check));
+        self
+    }
+
+    /// `Retry-After` sent with `503` answers.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    pub fn in_flight(&self) -> InFlight {
+        InFlight {
+            requests: self.max_requests - self.requests.available_permits(),
+            max_requests: self.max_requests,
+            bootstrap_requests: self.max_bootstrap - self.bootstrap.available_permits(),
+            max_bootstrap_requests: self.max_bootstrap,
+        }
+    }
+
+    fn is_bootstrap(&self, req: &ServiceRequest) -> bool {
+        self.bootstrap_routes
+            .iter()
+            .any(|(pattern, check)| pattern_matches(pattern, req.path()) && check(req))
+    }
+}
+
+/// Run the request while holding its permits, or answer `503 SERVICE_BUSY` with
+/// `Retry-After` when none is free. Wrap it inside `request_id_middleware` so
+/// the error carries the id.
+pub async fn concurrency_middleware<B: MessageBody>(
+    limits: &ConcurrencyLimits,
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    if EXEMPT_ROUTES.contains(&req.path()) {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+
+    let bootstrap = if limits.is_bootstrap(&req) {
+        match limits.bootstrap.try_acquire() {
+            Ok(permit) => Some(permit),
+            Err(_) => return Ok(busy(limits, req, "bootstrap requests")),
+        }
+    } else {
+        None
+    };
+    let Ok(_permit) = limits.requests.try_acquire() else {
+        return Ok(busy(limits, req, "requests"));
+    };
+
+    let res = next.call(req).await?;
+    drop(bootstrap);
+    Ok(res.map_into_left_body())
+}
+
+fn busy<B>(
+    limits: &ConcurrencyLimits,
+    req: ServiceRequest,
+    pool: &str,
+) -> ServiceResponse<EitherBody<B>> {
+    let in_flight = limits.in_flight();
+    tracing::warn!(
+        "{} {} rejected: server busy ({}/{} requests, {}/{} bootstrap requests in flight)",
+        req.method(),
+        req.path(),
+        in_flight.requests,
+        in_flight.max_requests,
+        in_flight.bootstrap_requests,
+        in_flight.max_bootstrap_requests
+    );
+    let res = ApiError::service_busy(format!("too many concurrent {}; retry shortly", pool))
+        .with_retry_after(limits.retry_after)
+        .error_response();
+    req.into_response(res).map_into_right_body()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::responses::{request_id_middleware, REQUEST_ID_HEADER};
+    use actix_web::http::header::RETRY_AFTER;
+    use actix_web::http::StatusCode;
+    use actix_web::{middleware, test, web, App, HttpResponse};
+    use std::sync::Arc;
+
+    /// Handler that blocks until the test adds permits to the gate.
+    async fn blocked(gate: web::Data<Semaphore>) -> HttpResponse {
+        gate.acquire().await.unwrap().forget();
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_request_over_the_limit_gets_503_with_retry_after() {
+        let limits = Arc::new(
+            ConcurrencyLimits::new(2, 1)
+                .with_bootstrap_route("/bootstrap/{id}")
+                .with_retry_after(Duration::from_secs(3)),
+        );
+        let gate = web::Data::new(Semaphore::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(gate.clone())
+                .wrap(middleware::from_fn({
+                    let limits = Arc::clone(&limits);
+                    move |req, next| {
+                        let limits = Arc::clone(&limits);
+                        async move { concurrency_middleware(&limits, req, next).await }
+                    }
+                }))
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route("/blocked", web::get().to(blocked))
+                .route("/bootstrap/{id}", web::get().to(blocked))
+                .route("/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+        let (first, second, rejected) = tokio::join!(
+            test::call_service(&app, get("/blocked")),
+            test::call_service(&app, get("/bootstrap/1")),
+            async {
+                // Both permits are held by now: the third request is turned away
+                // without waiting, while health checks still get through.
+                assert_eq!(
+                    limits.in_flight(),
+                    InFlight {
+                        requests: 2,
+                        max_requests: 2,
+                        bootstrap_requests: 1,
+                        max_bootstrap_requests: 1,
+                    }
+                );
+                let health = test::call_service(&app, get("/health")).await;
+                assert_eq!(health.status(), StatusCode::OK);
+                let req = test::TestRequest::get()
+                    .uri("/blocked")
+                    .insert_header((REQUEST_ID_HEADER, "req-busy"))
+                    .to_request();
+                let rejected = test::call_service(&app, req).await;
+                gate.add_permits(2);
+                rejected
+            }
+        );
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::OK);
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(rejected.headers().get(RETRY_AFTER).unwrap(), "3");
+        assert_eq!(
+            rejected.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "req-busy"
+        );
+        let body: serde_json::Value = test::read_body_json(rejected).await;
+        assert_eq!(body["error"]["code"], "SERVICE_BUSY");
+        assert_eq!(body["error"]["request_id"], "req-busy");
+        assert_eq!(limits.in_flight().requests, 0);
+    }
+
+    #[actix_web::test]
+    async fn test_conditional_bootstrap_route_takes_a_permit_only_when_it_bootstraps() {
+        let limits = Arc::new(
+            ConcurrencyLimits::new(4, 1).with_bootstrap_route_when("/maybe", |req| {
+                req.query_string().contains("ingest=true")
+            }),
+        );
+        let gate = web::Data::new(Semaphore::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(gate.clone())
+                .wrap(middleware::from_fn({
+                    let limits = Arc::clone(&limits);
+                    move |req, next| {
+                        let limits = Arc::clone(&limits);
+                        async move { concurrency_middleware(&limits, req, next).await }
+                    }
+                }))
+                .route("/maybe", web::get().to(blocked)),
+        )
+        .await;
+        let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+        let (ingesting, rejected, read_only) = tokio::join!(
+            test::call_service(&app, get("/maybe?ingest=true")),
+            test::call_service(&app, get("/maybe?ingest=true")),
+            async {
+                // The bootstrap permit is taken, but a request that doesn't
+                // bootstrap still gets through.
+                let read_only = test::call_service(&app, get("/maybe"));
+                gate.add_permits(2);
+                read_only.await
+            }
+        );
+        assert_eq!(ingesting.status(), StatusCode::OK);
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(read_only.status(), StatusCode::OK);
+        assert_eq!(limits.in_flight().bootstrap_requests, 0);
+    }
+
+    #[actix_web::test]
+    async fn test_bootstrap_routes_have_their_own_smaller_limit() {
+        let limits = Arc::new(ConcurrencyLimits::new(4, 1).with_bootstrap_route("/bootstrap/{id}"));
+        let gate = web::Data::new(Semaphore::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(gate.clone())
+                .wrap(middleware::from_fn({
+                    let limits = Arc::clone(&limits);
+                    move |req, next| {
+                        let limits = Arc::clone(&limits);
+                        async move { concurrency_middleware(&limits, req, next).await }
+                    }
+                }))
+                .route("/blocked", web::get().to(blocked))
+                .route("/bootstrap/{id}", web::get().to(blocked)),
+        )
+        .await;
+        let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+        let (bootstrap, rejected, other) = tokio::join!(
+            test::call_service(&app, get("/bootstrap/1")),
+            test::call_service(&app, get("/bootstrap/2")),
+            async {
+                let other = test::call_service(&app, get("/blocked"));
+                gate.add_permits(2);
+                other.await
+            }
+        );
+        assert_eq!(bootstrap.status(), StatusCode::OK);
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(other.status(), StatusCode::OK);
+        assert_eq!(limits.in_flight().bootstrap_requests, 0);
+    }
+}
@@ -0,0 +1,133 @@
+/// API docs - OpenAPI 3 spec generated from the route handlers
+///
+/// Handlers in `server` carry `#[utoipa::path]` attributes and their query structs
+/// derive `IntoParams`, so parameter docs come from the same doc comments the
+/// handlers are written against.
+use crate::api::responses::{ApiError, ErrorCode};
+use crate::api::server;
+use actix_web::HttpResponse;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+/// Body of every error response.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ErrorBody {
+    error: ApiError,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "onchain_beast",
+        description = "Side-wallet tracing and wallet analytics over Solana transfers"
+    ),
+    paths(
+        server::health_check,
+        server::find_side_wallets,
+        server::get_wallet_transactions,
+        server::get_wallet_transfers,
+        server::get_wallet_activity,
+        server::get_wallet_summary,
+        server::get_wallet_net_flows,
+        server::get_wallet_structuring,
+        server::get_wallet_risk,
+        server::get_high_risk_wallets,
+        server::get_similar_wallets,
+        server::get_wallet_connections,
+        server::get_wallet_swaps,
+        server::get_wallet_sandwiched,
+        server::get_wallet_cluster,
+        server::get_funding_source,
+        server::get_token_price_history,
+        server::investigate,
+        server::compare,
+        server::parse_batch,
+        server::ingest_wallet,
+        server::get_wallet_labels,
+        server::set_wallet_label,
+        server::delete_wallet_label,
+        server::rebuild_relationships,
+        server::retry_failures,
+        server::exhausted_failures,
+    ),
+    components(schemas(
+        ErrorBody,
+        ApiError,
+        ErrorCode,
+        server::ParseBatchRequest,
+        server::SetLabelRequest
+    )),
+    modifiers(&ApiKeyAuth)
+)]
+pub struct ApiDoc;
+
+/// Registers the `X-API-Key` header scheme referenced by `security(("api_key" = []))`.
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+/// Serve the OpenAPI spec as JSON.
+pub async fn openapi_spec() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_lists_documented_routes() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+        let paths = spec["paths"].as_object().unwrap();
+        for path in [
+            "/health",
+            "/api/v1/wallet/{address}/side-wallets",
+            "/api/v1/wallet/{address}/transfers",
+            "/api/v1/wallet/{address}/cluster",
+            "/api/v1/wallet/{address}/connections",
+            "/api/v1/parse/batch",
+            "/api/v1/wallet/{address}/ingest",
+            "/api/v1/analysis/high-risk-wallets",
+            "/api/v1/wallet/{address}/labels",
+            "/api/v1/wallet/{address}/labels/{label}",
+            "/api/v1/admin/rebuild-relationships",
+            "/api/v1/admin/retry-failures",
+            "/api/v1/admin/failures",
+        ] {
+            assert!(paths.contains_key(path), "missing {}", path);
+        }
+
+        let side_wallets = &paths["/api/v1/wallet/{address}/side-wallets"]["get"];
+        let params: Vec<&str> = side_wallets["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert!(params.contains(&"address"));
+        assert!(params.contains(&"lookback_days"));
+        assert!(params.contains(&"direction"));
+        assert!(spec["components"]["securitySchemes"]["api_key"].is_object());
+        assert!(spec["components"]["schemas"]["ErrorBody"].is_object());
+
+        let labels = &paths["/api/v1/wallet/{address}/labels"];
+        assert!(labels["get"].is_object() && labels["post"].is_object());
+        assert!(paths["/api/v1/wallet/{address}/labels/{label}"]["delete"].is_object());
+        assert!(paths["/api/v1/admin/retry-failures"]["post"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|p| p["name"] == "force"));
+    }
+}
@@ -0,0 +1,489 @@
+/// Idempotency keys - replay the first response to a `POST` for retries that
+/// carry the same `Idempotency-Key` header
+///
+/// A client that retries an expensive `POST` (a batch parse, a relationship
+/// rebuild) after a dropped connection would otherwise run it again. The first
+/// successful response is kept in memory for `IDEMPOTENCY_TTL_SECS` and
+/// replayed with its headers, plus `Idempotent-Replayed: true`, to later
+/// requests with the same key and body; a different body under the same key
+/// gets `422` (code `IDEMPOTENCY_KEY_REUSED`), and a retry that arrives while
+/// the first request is still running gets `409` (code `REQUEST_IN_PROGRESS`).
+/// Keys are scoped to the route and the caller's API key. Only `2xx` answers
+/// are kept: errors, throttling (`409`, `429`) and dropped requests can be
+/// retried. At most `IDEMPOTENCY_MAX_KEYS` keys are held; past that the oldest
+/// answer is evicted.
+use crate::api::responses::{ApiError, RequestId, REQUEST_ID_HEADER};
+use crate::core::env::{env_parse_in, env_parse_where};
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue, CONTENT_LENGTH, DATE};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::{web, HttpMessage, HttpResponse};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest key accepted; longer ones are rejected with `400`.
+const MAX_KEY_LEN: usize = 255;
+
+/// A response as first sent, minus the headers other middleware add again.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: web::Bytes,
+}
+
+/// Headers not replayed: the body's length and the date are set when the replay
+/// is sent, and `request_id_middleware` stamps the retry's own id.
+fn is_replayed(name: &HeaderName) -> bool {
+    name != CONTENT_LENGTH && name != DATE && name.as_str() != REQUEST_ID_HEADER
+}
+
+#[derive(Debug)]
+enum Slot {
+    /// The first request with this key has not answered yet.
+    Pending,
+    Done(CachedResponse),
+}
+
+#[derive(Debug)]
+struct Entry {
+    body_hash: u64,
+    stored_at: Instant,
+    slot: Slot,
+}
+
+/// Responses to `POST`s sent with an `Idempotency-Key`, held for `ttl`, at most
+/// `max_keys` at a time.
+#[derive(Debug)]
+pub struct IdempotencyStore {
+    entries: Mutex<HashMap<String, Entry>>,
+    ttl: Duration,
+    max_keys: usize,
+}
+
+/// Outcome of claiming a key for a request.
+enum Claim {
+    Run,
+    Replay(CachedResponse),
+    InProgress,
+    Mismatch,
+}
+
+impl IdempotencyStore {
+    /// Responses are kept for `IDEMPOTENCY_TTL_SECS` (default 86400, one day),
+    /// for at most `IDEMPOTENCY_MAX_KEYS` keys (default 10000).
+    pub fn from_env() -> Self {
+        let secs = env_parse_where(
+            "IDEMPOTENCY_TTL_SECS",
+            86_400,
+            |secs: &u64| *secs > 0,
+            "a positive number of seconds",
+        );
+        let max_keys = env_parse_in("IDEMPOTENCY_MAX_KEYS", 10_000, 1..=1_000_000);
+        Self::new(Duration::from_secs(secs), max_keys)
+    }
+
+    pub fn new(ttl: Duration, max_keys: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            max_keys: max_keys.max(1),
+        }
+    }
+
+    /// Keys currently held (pending or answered), expired ones included until
+    /// the next claim sweeps them.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn claim(&self, key: &str, body_hash: u64) -> Claim {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, entry| now.duration_since(entry.stored_at) < self.ttl);
+        match entries.get(key) {
+            Some(entry) if entry.body_hash != body_hash => Claim::Mismatch,
+            Some(Entry {
+                slot: Slot::Done(res),
+                ..
+            }) => Claim::Replay(res.clone()),
+            Some(_) => Claim::InProgress,
+            None => {
+                if entries.len() >= self.max_keys {
+                    // Oldest answer first; a pending key only when none is answered.
+                    let oldest = entries
+                        .iter()
+                        .min_by_key(|(_, e)| (matches!(e.slot, Slot::Pending), e.stored_at))
+                        .map(|(k, _)| k.clone());
+                    if let Some(oldest) = oldest {
+                        entries.remove(&oldest);
+                    }
+                }
+                entries.insert(
+                    key.to_string(),
+                    Entry {
+                        body_hash,
+                        stored_at: now,
+                        slot: Slot::Pending,
+                    },
+                );
+                Claim::Run
+            }
+        }
+    }
+
+    fn complete(&self, key: &str, res: CachedResponse) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(key) {
+            entry.stored_at = Instant::now();
+            entry.slot = Slot::Done(res);
+        }
+    }
+
+    fn release(&self, key: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        if matches!(
+            entries.get(key),
+            Some(Entry {
+                slot: Slot::Pending,
+                ..
+            })
+        ) {
+            entries.remove(key);
+        }
+    }
+}
+
+/// Frees a pending key when its request fails, times out or is dropped.
+struct PendingGuard<'a> {
+    store: &'a IdempotencyStore,
+    key: &'a str,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.store.release(self.key);
+    }
+}
+
+fn hash_of(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Run a keyed `POST` once and replay its response to retries; every other
+/// request passes straight through. Wrap it inside `request_id_middleware` so
+/// errors carry the id.
+pub async fn idempotency_middleware<B>(
+    store: &IdempotencyStore,
+    mut req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error>
+where
+    B: MessageBody + 'static,
+{
+    let key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(key) if req.method() == Method::POST => key,
+        _ => return Ok(next.call(req).await?.map_into_boxed_body()),
+    };
+    let request_id = req.extensions().get::<RequestId>().cloned();
+    let fail = |err: ApiError| -> actix_web::Error {
+        match &request_id {
+            Some(RequestId(id)) => err.with_request_id(id.clone()).into(),
+            None => err.into(),
+        }
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => key.to_string(),
+        _ => {
+            return Err(fail(ApiError::bad_request(format!(
+                "Idempotency-Key must be 1-{} visible ASCII characters",
+                MAX_KEY_LEN
+            ))))
+        }
+    };
+    let caller = req
+        .headers()
+        .get("x-api-key")
+        .map(|k| hash_of(k.as_bytes()))
+        .unwrap_or_default();
+    let key = format!("{:016x}:{}:{}", caller, req.path(), key);
+
+    let bytes = req.extract::<web::Bytes>().await?;
+    let body_hash = hash_of(&bytes);
+    req.set_payload(Payload::from(bytes));
+
+    match store.claim(&key, body_hash) {
+        Claim::Run => {}
+        Claim::Replay(cached) => {
+            tracing::debug!(
+                "{} {} replayed for idempotency key",
+                req.method(),
+                req.path()
+            );
+            let mut res = HttpResponse::build(cached.status);
+            for header in cached.headers {
+                res.append_header(header);
+            }
+            res.insert_header((
+                HeaderName::from_static(REPLAYED_HEADER),
+                HeaderValue::from_static("true"),
+            ));
+            return Ok(req.into_response(res.body(cached.body)));
+        }
+        Claim::InProgress => {
+            return Err(fail(ApiError::request_in_progress(
+                "a request with this Idempotency-Key is still running; retry shortly",
+            )))
+        }
+        Claim::Mismatch => {
+            return Err(fail(ApiError::idempotency_key_reused(
+                "Idempotency-Key was already used with a different request body",
+            )))
+        }
+    }
+
+    let _pending = PendingGuard { store, key: &key };
+    let res = next.call(req).await?;
+    if !res.status().is_success() {
+        return Ok(res.map_into_boxed_body());
+    }
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(e.into().to_string()))?;
+    store.complete(
+        &key,
+        CachedResponse {
+            status: res.status(),
+            headers: res
+                .headers()
+                .iter()
+                .filter(|(name, _)| is_replayed(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            body: bytes.clone(),
+        },
+    );
+    Ok(ServiceResponse::new(req, res.set_body(bytes)).map_into_boxed_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::responses::request_id_middleware;
+    use actix_web::http::header::{CONTENT_TYPE, LOCATION};
+    use actix_web::{middleware, test, App};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Handler that counts its runs and echoes the run number and body.
+    async fn ingest(runs: web::Data<AtomicUsize>, body: web::Bytes) -> HttpResponse {
+        let run = runs.fetch_add(1, Ordering::SeqCst) + 1;
+        HttpResponse::Created()
+            .insert_header((LOCATION, format!("/jobs/{}", run)))
+            .json(serde_json::json!({
+                "run": run,
+                "body": String::from_utf8_lossy(&body),
+            }))
+    }
+
+    fn post(uri: &str, key: Option<&str>, body: &str) -> test::TestRequest {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header((REQUEST_ID_HEADER, "req-idem"))
+            .set_payload(body.to_string());
+        match key {
+            Some(key) => req.insert_header((IDEMPOTENCY_KEY_HEADER, key)),
+            None => req,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_repeated_key_replays_the_cached_response_without_rerunning() {
+        let store = Arc::new(IdempotencyStore::new(Duration::from_secs(60), 100));
+        let runs = web::Data::new(AtomicUsize::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(runs.clone())
+                .wrap(middleware::from_fn({
+                    let store = Arc::clone(&store);
+                    move |req, next| {
+                        let store = Arc::clone(&store);
+                        async move { idempotency_middleware(&store, req, next).await }
+                    }
+                }))
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route("/ingest", web::post().to(ingest))
+                .route("/other", web::post().to(ingest)),
+        )
+        .await;
+
+        let first =
+            test::call_service(&app, post("/ingest", Some("k1"), "batch").to_request()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get(REPLAYED_HEADER).is_none());
+        let first: serde_json::Value = test::read_body_json(first).await;
+
+        let retry =
+            test::call_service(&app, post("/ingest", Some("k1"), "batch").to_request()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers().get(REPLAYED_HEADER).unwrap(), "true");
+        assert_eq!(
+            retry.headers().get(CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_eq!(retry.headers().get(LOCATION).unwrap(), "/jobs/1");
+        assert_eq!(retry.headers().get(REQUEST_ID_HEADER).unwrap(), "req-idem");
+        let retry: serde_json::Value = test::read_body_json(retry).await;
+        assert_eq!(retry, first);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Another key, another route or no key at all runs the handler again.
+        for req in [
+            post("/ingest", Some("k2"), "batch"),
+            post("/other", Some("k1"), "batch"),
+            post("/ingest", None, "batch"),
+            post("/ingest", None, "batch"),
+        ] {
+            test::call_service(&app, req.to_request()).await;
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 5);
+        assert_eq!(store.len(), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_same_key_with_a_different_body_is_rejected_with_422() {
+        let store = Arc::new(IdempotencyStore::new(Duration::from_secs(60), 100));
+        let runs = web::Data::new(AtomicUsize::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(runs.clone())
+                .wrap(middleware::from_fn({
+                    let store = Arc::clone(&store);
+                    move |req, next| {
+                        let store = Arc::clone(&store);
+                        async move { idempotency_middleware(&store, req, next).await }
+                    }
+                }))
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route("/ingest", web::post().to(ingest))
+                .route("/other", web::post().to(ingest)),
+        )
+        .await;
+
+        test::call_service(&app, post("/ingest", Some("k1"), "batch").to_request()).await;
+        // Middleware errors are rendered by the server, as `error_response` does here.
+        let res = test::try_call_service(
+            &app,
+            post("/ingest", Some("k1"), "other batch").to_request(),
+        )
+        .await
+        .err()
+        .expect("reused key should be rejected")
+        .error_response();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let bytes = body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "IDEMPOTENCY_KEY_REUSED");
+        assert_eq!(body["error"]["request_id"], "req-idem");
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    /// Handler that is throttled on its first run and succeeds afterwards.
+    async fn throttled_once(runs: web::Data<AtomicUsize>) -> HttpResponse {
+        match runs.fetch_add(1, Ordering::SeqCst) {
+            0 => HttpResponse::TooManyRequests().finish(),
+            run => HttpResponse::Ok().body(format!("run {}", run + 1)),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_only_successful_answers_are_kept() {
+        let store = Arc::new(IdempotencyStore::new(Duration::from_secs(60), 100));
+        let runs = web::Data::new(AtomicUsize::new(0));
+        let app = test::init_service(
+            App::new()
+                .app_data(runs.clone())
+                .wrap(middleware::from_fn({
+                    let store = Arc::clone(&store);
+                    move |req, next| {
+                        let store = Arc::clone(&store);
+                        async move { idempotency_middleware(&store, req, next).await }
+                    }
+                }))
+                .route("/ingest", web::post().to(throttled_once)),
+        )
+        .await;
+
+        let first =
+            test::call_service(&app, post("/ingest", Some("k1"), "batch").to_request()).await;
+        assert_eq!(first.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(store.is_empty());
+
+        // The throttled answer was not kept, so the retry runs and its answer is.
+        for _ in 0..2 {
+            let res =
+                test::call_service(&app, post("/ingest", Some("k1"), "batch").to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(test::read_body(res).await, "run 2");
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_oldest_answer_is_evicted_past_max_keys() {
+        let store = IdempotencyStore::new(Duration::from_secs(60), 2);
+        let done = |body: &'static [u8]| CachedResponse {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body: web::Bytes::from_static(body),
+        };
+        assert!(matches!(store.claim("a", 1), Claim::Run));
+        store.complete("a", done(b"a"));
+        assert!(matches!(store.claim("b", 1), Claim::Run));
+        // "b" is still running, so the answered "a" makes room for "c".
+        assert!(matches!(store.claim("c", 1), Claim::Run));
+        assert_eq!(store.len(), 2);
+        assert!(matches!(store.claim("b", 1), Claim::InProgress));
+        assert!(matches!(store.claim("a", 1), Claim::Run));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_pending_keys_block_retries_until_released_and_entries_expire() {
+        let store = IdempotencyStore::new(Duration::from_millis(50), 100);
+        assert!(matches!(store.claim("k", 1), Claim::Run));
+        assert!(matches!(store.claim("k", 1), Claim::InProgress));
+        store.release("k");
+        assert!(matches!(store.claim("k", 1), Claim::Run));
+        store.complete(
+            "k",
+            CachedResponse {
+                status: StatusCode::OK,
+                headers: Vec::new(),
+                body: web::Bytes::from_static(b"done"),
+            },
+        );
+        // Releasing an answered key keeps its response.
+        store.release("k");
+        assert!(matches!(store.claim("k", 1), Claim::Replay(res) if res.body == "done"));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(matches!(store.claim("k", 2), Claim::Run));
+        assert_eq!(store.len(), 1);
+    }
+}
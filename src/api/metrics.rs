@@ -0,0 +1,157 @@
+/// Metrics - Prometheus text exposition of live cache and connection pool gauges
+///
+/// A background task (`spawn_refresher`) samples the gauges every
+/// `METRICS_REFRESH_SECS` seconds (default 5); `/metrics` renders the latest
+/// sample, so a scrape never waits on cache locks or the database pool.
+use crate::api::idempotency::IdempotencyStore;
+use crate::api::server::ApiState;
+use crate::core::env::env_parse_in;
+use crate::storage::PoolStatus;
+use actix_web::{web, HttpResponse};
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// One sample of every gauge.
+#[derive(Debug, Clone, Default)]
+struct Sample {
+    /// Entries per in-process cache, by `cache_type` label.
+    cache_sizes: Vec<(&'static str, usize)>,
+    /// `None` for the in-memory backend.
+    db_pool: Option<PoolStatus>,
+    /// Unix time of the sample; 0 until the first refresh.
+    refreshed_at: u64,
+}
+
+/// Latest gauge values, shared by the refresher and the `/metrics` handler.
+#[derive(Debug, Default)]
+pub struct Gauges {
+    sample: RwLock<Sample>,
+}
+
+impl Gauges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sample cache sizes and pool usage now.
+    pub async fn refresh(&self, state: &ApiState, idempotency: &IdempotencyStore) {
+        let cache_sizes = vec![
+            ("transaction", state.tx_handler.cache_size().await),
+            (
+                "token_account",
+                state.tx_handler.token_account_cache_size().await,
+            ),
+            ("token_owner", state.tx_handler.token_owner_cache_size()),
+            ("side_wallet", state.side_wallet_cache.len().await),
+            ("idempotency", idempotency.len()),
+        ];
+        let sample = Sample {
+            cache_sizes,
+            db_pool: state.db_manager.pool_status(),
+            refreshed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        *self.sample.write().unwrap_or_else(|e| e.into_inner()) = sample;
+    }
+
+    /// The latest sample in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let sample = self
+            .sample
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, values: &[(Option<&str>, f64)]| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for (cache_type, value) in values {
+                match cache_type {
+                    Some(t) => {
+                        let _ = writeln!(out, "{}{{cache_type=\"{}\"}} {}", name, t, value);
+                    }
+                    None => {
+                        let _ = writeln!(out, "{} {}", name, value);
+                    }
+                }
+            }
+        };
+
+        let caches: Vec<(Option<&str>, f64)> = sample
+            .cache_sizes
+            .iter()
+            .map(|(t, n)| (Some(*t), *n as f64))
+            .collect();
+        gauge(
+            "onchain_beast_cache_size",
+            "Entries held per in-process cache.",
+            &caches,
+        );
+        if let Some(pool) = sample.db_pool {
+            let pool_gauges = [
+                (
+                    "onchain_beast_db_pool_active",
+                    "Postgres connections checked out.",
+                    pool.open.saturating_sub(pool.idle),
+                ),
+                (
+                    "onchain_beast_db_pool_idle",
+                    "Open Postgres connections waiting in the pool.",
+                    pool.idle,
+                ),
+                (
+                    "onchain_beast_db_pool_waiting",
+                    "Requests waiting for a Postgres connection.",
+                    pool.waiting,
+                ),
+                (
+                    "onchain_beast_db_pool_max",
+                    "Largest number of Postgres connections the pool opens.",
+                    pool.max_size,
+                ),
+            ];
+            for (name, help, value) in pool_gauges {
+                gauge(name, help, &[(None, value as f64)]);
+            }
+        }
+        gauge(
+            "onchain_beast_metrics_refreshed_at_seconds",
+            "Unix time the gauges were last sampled (0 before the first sample).",
+            &[(None, sample.refreshed_at as f64)],
+        );
+        out
+    }
+}
+
+/// Refresh interval from `METRICS_REFRESH_SECS` (default 5, at least 1).
+pub fn refresh_interval_from_env() -> Duration {
+    Duration::from_secs(env_parse_in("METRICS_REFRESH_SECS", 5, 1..=3_600))
+}
+
+/// Refresh `gauges` now and then every `interval` until the task is aborted.
+pub fn spawn_refresher(
+    gauges: Arc<Gauges>,
+    state: web::Data<ApiState>,
+    idempotency: Arc<IdempotencyStore>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            gauges.refresh(&state, &idempotency).await;
+        }
+    })
+}
+
+/// Prometheus scrape endpoint; no API key needed, like `/health`.
+pub async fn metrics(gauges: web::Data<Gauges>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(gauges.render())
+}
@@ -1,3 +1,10 @@
+pub mod cache;
+pub mod concurrency;
+pub mod docs;
+pub mod idempotency;
+pub mod metrics;
+pub mod responses;
 pub mod server;
 
+pub use responses::{ApiError, ErrorCode, RequestId};
 pub use server::*;
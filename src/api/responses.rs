@@ -0,0 +1,610 @@
+/// API Responses - uniform JSON error bodies and per-request ids
+///
+/// Every error leaves the API as
+/// `{"error": {"code": "...", "message": "...", "request_id": "..."}}` with a
+/// stable, machine-readable `code`. The request id is taken from the incoming
+/// `X-Request-Id` header (or generated) by `request_id_middleware` and echoed on
+/// every response.
+///
+/// Heavy successful responses can be MessagePack instead of JSON when the client
+/// asks for it with `Accept: application/msgpack` (see `Encoding`), and can be
+/// trimmed to the fields a client needs with `fields=` (see `FieldProjection`).
+use crate::core::errors::BeastError;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::middleware::Next;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError};
+use futures::future::{ready, Ready};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request id that is accepted as-is.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled; usable as a handler extractor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl FromRequest for RequestId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let id = req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(|| RequestId(generate_request_id()));
+        ready(Ok(id))
+    }
+}
+
+/// Request id of the current handler task, if running under `request_id_middleware`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{:016x}", hasher.finish())
+}
+
+fn sanitize_request_id(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let valid = !raw.is_empty()
+        && raw.len() <= MAX_REQUEST_ID_LEN
+        && raw
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| raw.to_string())
+}
+
+/// Assign a request id, expose it to handlers and errors, and echo it in the
+/// `X-Request-Id` response header.
+pub async fn request_id_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(sanitize_request_id)
+        .unwrap_or_else(generate_request_id);
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut res = REQUEST_ID.scope(id.clone(), next.call(req)).await?;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    Ok(res)
+}
+
+/// Stable error codes returned in `error.code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    InvalidAddress,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    WalletNotFound,
+    Unconfirmed,
+    RequestInProgress,
+    IdempotencyKeyReused,
+    RpcError,
+    NetworkError,
+    Timeout,
+    ServiceBusy,
+    DatabaseError,
+    ParseError,
+    AnalysisFailed,
+    ConfigError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::BadRequest | ErrorCode::InvalidAddress => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound | ErrorCode::WalletNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Unconfirmed | ErrorCode::RequestInProgress => StatusCode::CONFLICT,
+            ErrorCode::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::RpcError | ErrorCode::NetworkError => StatusCode::BAD_GATEWAY,
+            ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::ServiceBusy => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::DatabaseError
+            | ErrorCode::ParseError
+            | ErrorCode::AnalysisFailed
+            | ErrorCode::ConfigError
+            | ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Error returned by API handlers; renders as the uniform JSON error body.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Sent as the `Retry-After` header (whole seconds, at least 1).
+    #[serde(skip)]
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            request_id: None,
+            retry_after: None,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::BadRequest, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unauthorized, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn wallet_not_found(wallet: &str) -> Self {
+        Self::new(
+            ErrorCode::WalletNotFound,
+            format!("No data for wallet {}", wallet),
+        )
+    }
+
+    pub fn request_in_progress(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::RequestInProgress, message)
+    }
+
+    pub fn idempotency_key_reused(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::IdempotencyKeyReused, message)
+    }
+
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Timeout, message)
+    }
+
+    pub fn service_busy(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ServiceBusy, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InternalError, message)
+    }
+
+    /// Ask the client to retry after `retry_after`.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// Attach an explicit request id (otherwise the current request's id is used).
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.code.status()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut body = self.clone();
+        if body.request_id.is_none() {
+            body.request_id = current_request_id();
+        }
+        let mut res = HttpResponse::build(self.status_code());
+        // Errors raised by middleware skip `request_id_middleware`'s header, so
+        // echo the id here as well.
+        if let Some(id) = body.request_id.as_deref() {
+            res.insert_header((REQUEST_ID_HEADER, id));
+        }
+        if let Some(retry_after) = self.retry_after {
+            res.insert_header((header::RETRY_AFTER, retry_after.as_secs().max(1)));
+        }
+        res.json(serde_json::json!({ "error": body }))
+    }
+}
+
+/// Approximate Solana slot time, for `Retry-After` on unconfirmed transactions.
+const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+impl From<BeastError> for ApiError {
+    fn from(err: BeastError) -> Self {
+        let code = match &err {
+            BeastError::RpcError(_) => ErrorCode::RpcError,
+            BeastError::DatabaseError(_) => ErrorCode::DatabaseError,
+            BeastError::InvalidAddress(_) => ErrorCode::InvalidAddress,
+            BeastError::AnalysisFailed(_) => ErrorCode::AnalysisFailed,
+            BeastError::ConfigError(_) => ErrorCode::ConfigError,
+            BeastError::ParseError(_) => ErrorCode::ParseError,
+            BeastError::NotFound(_) => ErrorCode::NotFound,
+            BeastError::Unconfirmed { slots_missing, .. } => {
+                // Retry once the missing slots have been produced.
+                let wait = SLOT_DURATION * (*slots_missing).min(u32::MAX as u64) as u32;
+                return Self::new(ErrorCode::Unconfirmed, err.to_string())
+                    .with_retry_after(wait.max(Duration::from_secs(1)));
+            }
+            BeastError::NetworkError(_) => ErrorCode::NetworkError,
+            BeastError::Unknown(_) => ErrorCode::InternalError,
+        };
+        Self::new(code, err.to_string())
+    }
+}
+
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Body encoding negotiated from the `Accept` header; usable as a handler
+/// extractor. MessagePack (`application/msgpack` or `application/x-msgpack`,
+/// with a non-zero q) wins whenever it is acceptable; anything else gets JSON.
+/// Maps are encoded with field names, so the bytes decode back into the same
+/// structs. Errors are always JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        let wants_msgpack = accept.unwrap_or("").split(',').any(|range| {
+            let mut parts = range.split(';');
+            let media = parts.next().unwrap_or("").trim();
+            let rejected = parts.any(|p| {
+                p.trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            !rejected
+                && (media.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE)
+                    || media.eq_ignore_ascii_case("application/x-msgpack"))
+        });
+        if wants_msgpack {
+            Encoding::MessagePack
+        } else {
+            Encoding::Json
+        }
+    }
+
+    /// `200 OK` with `body` in this encoding.
+    pub fn ok<T: Serialize>(self, body: &T) -> Result<HttpResponse, ApiError> {
+        let mut res = HttpResponse::Ok();
+        res.insert_header((header::VARY, "Accept"));
+        match self {
+            Encoding::Json => Ok(res.json(body)),
+            Encoding::MessagePack => {
+                let bytes = rmp_serde::to_vec_named(body).map_err(|e| {
+                    ApiError::internal(format!("Failed to encode MessagePack: {}", e))
+                })?;
+                Ok(res.content_type(MSGPACK_CONTENT_TYPE).body(bytes))
+            }
+        }
+    }
+}
+
+/// Field allowlist from a `fields=address,score` query param, applied after
+/// serialization to the objects in a response's result lists. Other top-level
+/// keys are kept and unknown names are ignored. Without the param (or with an
+/// empty one) every field is kept.
+#[derive(Debug, Clone, Default)]
+pub struct FieldProjection(Option<HashSet<String>>);
+
+impl FieldProjection {
+    pub fn parse(raw: Option<&str>) -> Self {
+        let fields: HashSet<String> = raw
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self((!fields.is_empty()).then_some(fields))
+    }
+
+    /// Drop unrequested keys from every object in the arrays at `body[list]`.
+    pub fn apply(&self, body: &mut serde_json::Value, lists: &[&str]) {
+        let Some(fields) = &self.0 else {
+            return;
+        };
+        for list in lists {
+            let Some(items) = body.get_mut(*list).and_then(|v| v.as_array_mut()) else {
+                continue;
+            };
+            for item in items.iter_mut().filter_map(|i| i.as_object_mut()) {
+                item.retain(|key, _| fields.contains(key));
+            }
+        }
+    }
+}
+
+impl FromRequest for Encoding {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let accept = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok());
+        ready(Ok(Encoding::from_accept(accept)))
+    }
+}
+
+/// Map query-string and JSON body rejections to `BAD_REQUEST` errors.
+pub fn bad_request_handler<E: fmt::Display>(err: E, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::bad_request(err.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware, test, web, App};
+
+    async fn body_json(resp: HttpResponse) -> serde_json::Value {
+        let bytes = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_field_projection_keeps_only_requested_item_fields() {
+        let body = serde_json::json!({
+            "main_wallet": "MAIN",
+            "side_wallets": [
+                {"address": "A", "score": 0.9, "reasons": ["r"], "shared_funders": []},
+                {"address": "B", "score": 0.4, "reasons": []}
+            ],
+            "cex_funded_wallets": [{"wallet": "C", "score": 0.3, "paths": []}]
+        });
+
+        let mut projected = body.clone();
+        FieldProjection::parse(Some("address, score,wallet,no_such_field")).apply(
+            &mut projected,
+            &["side_wallets", "cex_funded_wallets", "missing"],
+        );
+        assert_eq!(projected["main_wallet"], "MAIN");
+        assert_eq!(
+            projected["side_wallets"],
+            serde_json::json!([{"address": "A", "score": 0.9}, {"address": "B", "score": 0.4}])
+        );
+        assert_eq!(
+            projected["cex_funded_wallets"],
+            serde_json::json!([{"wallet": "C", "score": 0.3}])
+        );
+
+        for raw in [None, Some(""), Some(" , ")] {
+            let mut untouched = body.clone();
+            FieldProjection::parse(raw).apply(&mut untouched, &["side_wallets"]);
+            assert_eq!(untouched, body);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_beast_errors_map_to_codes_and_statuses() {
+        let cases = [
+            (
+                BeastError::InvalidAddress("bad".to_string()),
+                "INVALID_ADDRESS",
+                400,
+            ),
+            (BeastError::NotFound("x".to_string()), "NOT_FOUND", 404),
+            (BeastError::RpcError("down".to_string()), "RPC_ERROR", 502),
+            (
+                BeastError::DatabaseError("boom".to_string()),
+                "DATABASE_ERROR",
+                500,
+            ),
+        ];
+
+        for (err, code, status) in cases {
+            let message = err.to_string();
+            let resp = ApiError::from(err).error_response();
+            assert_eq!(resp.status().as_u16(), status);
+            let body = body_json(resp).await;
+            assert_eq!(body["error"]["code"], code);
+            assert_eq!(body["error"]["message"], message);
+            // No request in flight, so no id to report.
+            assert!(body["error"].get("request_id").is_none());
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unconfirmed_is_a_conflict_with_retry_after() {
+        let err = BeastError::Unconfirmed {
+            message: "sig is 2 slots deep, 32 required".to_string(),
+            slots_missing: 30,
+        };
+        let resp = ApiError::from(err).error_response();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        // 30 slots at 400ms.
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "12");
+        let body = body_json(resp).await;
+        assert_eq!(body["error"]["code"], "UNCONFIRMED");
+        assert!(body["error"].get("retry_after").is_none());
+
+        let one_slot = BeastError::Unconfirmed {
+            message: String::new(),
+            slots_missing: 1,
+        };
+        let resp = ApiError::from(one_slot).error_response();
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "1");
+    }
+
+    #[actix_web::test]
+    async fn test_error_body_carries_request_id_from_header() {
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route(
+                    "/wallet",
+                    web::get()
+                        .to(|| async { Err::<HttpResponse, _>(ApiError::wallet_not_found("W1")) }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/wallet")
+            .insert_header((REQUEST_ID_HEADER, "req-123"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "req-123");
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": {
+                "code": "WALLET_NOT_FOUND",
+                "message": "No data for wallet W1",
+                "request_id": "req-123"
+            }})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_generates_request_id_when_header_missing_or_invalid() {
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route(
+                    "/fail",
+                    web::get()
+                        .to(|| async { Err::<HttpResponse, _>(ApiError::bad_request("nope")) }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/fail")
+            .insert_header((REQUEST_ID_HEADER, "bad id with spaces"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        let header = resp
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(header.len(), 16);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], "BAD_REQUEST");
+        assert_eq!(body["error"]["request_id"], header.as_str());
+    }
+
+    #[actix_web::test]
+    async fn test_encoding_from_accept_header() {
+        assert_eq!(Encoding::from_accept(None), Encoding::Json);
+        assert_eq!(
+            Encoding::from_accept(Some("application/json")),
+            Encoding::Json
+        );
+        assert_eq!(Encoding::from_accept(Some("*/*")), Encoding::Json);
+        assert_eq!(
+            Encoding::from_accept(Some("application/json;q=0.5, application/msgpack")),
+            Encoding::MessagePack
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("Application/X-MsgPack")),
+            Encoding::MessagePack
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("application/msgpack;q=0, application/json")),
+            Encoding::Json
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_msgpack_body_round_trips() {
+        #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+        struct Page {
+            wallet: String,
+            count: usize,
+            amounts: Vec<f64>,
+        }
+        let app = test::init_service(App::new().route(
+            "/page",
+            web::get().to(|encoding: Encoding| async move {
+                encoding.ok(&Page {
+                    wallet: "W1".to_string(),
+                    count: 2,
+                    amounts: vec![1.5, 2.25],
+                })
+            }),
+        ))
+        .await;
+        let expected = Page {
+            wallet: "W1".to_string(),
+            count: 2,
+            amounts: vec![1.5, 2.25],
+        };
+
+        let req = test::TestRequest::get()
+            .uri("/page")
+            .insert_header((header::ACCEPT, MSGPACK_CONTENT_TYPE))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            MSGPACK_CONTENT_TYPE
+        );
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept");
+        let bytes = test::read_body(resp).await;
+        assert_eq!(rmp_serde::from_slice::<Page>(&bytes).unwrap(), expected);
+
+        let req = test::TestRequest::get().uri("/page").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let page: Page = test::read_body_json(resp).await;
+        assert_eq!(page, expected);
+    }
+}
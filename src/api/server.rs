@@ -1,14 +1,42 @@
 /// Minimal REST API server for side-wallet tracing (including CEX-hop heuristics).
+use actix_web::dev::ServiceRequest;
 use actix_web::{middleware, web, App, HttpResponse, HttpServer};
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::auth::ApiKey;
-use crate::core::rpc_client::SolanaRpcClient;
-use crate::modules::{TransactionHandler, TransferAnalytics};
-use crate::storage::{BehavioralProfile, DatabaseManager, TransferEvent};
+use crate::api::cache::{SideWalletCache, SideWalletParams};
+use crate::api::concurrency::{self, ConcurrencyLimits};
+use crate::api::docs::{self, ErrorBody};
+use crate::api::idempotency::{self, IdempotencyStore};
+use crate::api::metrics::{self, Gauges};
+use crate::api::responses::{self, ApiError, Encoding, FieldProjection};
+use crate::auth::{self, Admin, ApiKey, RequireScope, Scope};
+use crate::core::address::{is_valid_address, validate_address};
+use crate::core::enhanced_parser::{EnhancedTransaction, TransactionType};
+use crate::core::env::env_parse_in;
+use crate::core::errors::{BeastError, BeastResult};
+use crate::core::rpc_client::{Commitment, SolanaRpcClient};
+use crate::core::units::lamports_to_sol;
+use crate::graph::{
+    CentralityOptions, ExchangeRouteOptions, GraphAnalysisEngine, NodeAttributeMap, NodeAttributes,
+    WalletGraph,
+};
+use crate::middleware::timeout::{self, RequestTimeouts};
+use crate::modules::{
+    assess_risk, behavioral_similarity, detect_sandwiches, detect_structuring,
+    detect_structuring_filtered, ignored_mints_from_env, price_history, rank_similar_wallets,
+    terminal_accounts_from_env, AnomalyDetector, ExchangeRegistry, HoldingsTracker, PriceBucket,
+    RiskAssessment, RiskWeights, SandwichEvent, SideWalletScoringConfig, TokenSupplyTracker,
+    TransactionHandler, TransferAnalytics, WalletAge, FRESH_WALLET_DAYS, SANDWICH_SLOT_WINDOW,
+};
+use crate::storage::{
+    DatabaseManager, SharedWalletSignal, StoredOutcome, TemporalOverlap, TransferEvent,
+};
 
 /// Shared server state.
 pub struct ApiState {
@@ -16,8 +44,53 @@ pub struct ApiState {
     pub tx_handler: Arc<TransactionHandler>,
     pub transfer_analytics: Arc<TransferAnalytics>,
     pub db_manager: Arc<DatabaseManager>,
+    pub graph_engine: Arc<GraphAnalysisEngine>,
+    pub anomaly_detector: Arc<AnomalyDetector>,
+    pub holdings: Arc<HoldingsTracker>,
+    pub token_supply: Arc<TokenSupplyTracker>,
+    pub exchange_registry: Arc<ExchangeRegistry>,
+    pub side_wallet_cache: Arc<SideWalletCache>,
 }
 
+impl ApiState {
+    /// Copy of this state whose storage (and the analytics built on it) is an
+    /// in-memory copy of the history around `wallet` (see
+    /// `DatabaseManager::scratch_copy`), for dry runs that must not write to the
+    /// real store: their writes land on top of what it already holds.
+    pub async fn with_scratch_storage(
+        &self,
+        wallet: &str,
+        depth: usize,
+        since_epoch: u64,
+    ) -> BeastResult<ApiState> {
+        let db_manager = Arc::new(
+            self.db_manager
+                .scratch_copy(
+                    wallet,
+                    depth,
+                    since_epoch,
+                    self.transfer_analytics.records_self_transfers(),
+                )
+                .await?,
+        );
+        Ok(ApiState {
+            rpc_client: Arc::clone(&self.rpc_client),
+            tx_handler: Arc::clone(&self.tx_handler),
+            transfer_analytics: Arc::new(TransferAnalytics::new(Arc::clone(&db_manager))),
+            graph_engine: Arc::new(GraphAnalysisEngine::new(Arc::clone(&db_manager))),
+            anomaly_detector: Arc::new(AnomalyDetector::new(Arc::clone(&db_manager))),
+            db_manager,
+            holdings: Arc::clone(&self.holdings),
+            token_supply: Arc::clone(&self.token_supply),
+            exchange_registry: Arc::clone(&self.exchange_registry),
+            side_wallet_cache: Arc::clone(&self.side_wallet_cache),
+        })
+    }
+}
+
+/// Longest wait for in-flight ingestion writes once the HTTP server has stopped.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub async fn start_server(
     rpc_client: Arc<SolanaRpcClient>,
     db_manager: Arc<DatabaseManager>,
@@ -25,30 +98,239 @@ pub async fn start_server(
     port: u16,
 ) -> std::io::Result<()> {
     let tx_handler = Arc::new(TransactionHandler::new(Arc::clone(&rpc_client)));
-    let transfer_analytics = Arc::new(TransferAnalytics::new(Arc::clone(&db_manager)));
+    // Ingestion writes run on this (the main) runtime rather than a worker's, so
+    // they survive the workers stopping and are still there to drain.
+    let transfer_analytics = Arc::new(
+        TransferAnalytics::new(Arc::clone(&db_manager))
+            .with_runtime(tokio::runtime::Handle::current()),
+    );
+    let graph_engine = Arc::new(GraphAnalysisEngine::new(Arc::clone(&db_manager)));
+    let anomaly_detector = Arc::new(AnomalyDetector::new(Arc::clone(&db_manager)));
+    let holdings = Arc::new(HoldingsTracker::new(Arc::clone(&rpc_client)));
+    let token_supply = Arc::new(TokenSupplyTracker::new(Arc::clone(&rpc_client)));
+    let exchange_registry = Arc::new(ExchangeRegistry::from_env());
+    let side_wallet_cache = Arc::new(SideWalletCache::from_env());
 
     let state = web::Data::new(ApiState {
         rpc_client,
         tx_handler,
-        transfer_analytics,
-        db_manager,
+        transfer_analytics: Arc::clone(&transfer_analytics),
+        db_manager: Arc::clone(&db_manager),
+        graph_engine,
+        anomaly_detector,
+        holdings,
+        token_supply,
+        exchange_registry,
+        side_wallet_cache,
     });
 
+    let timeouts = Arc::new(RequestTimeouts::from_env());
+    let limits = web::Data::new(ConcurrencyLimits::from_env());
+    let idempotency = Arc::new(IdempotencyStore::from_env());
+    let gauges = web::Data::new(Gauges::new());
+    let gauge_refresher = metrics::spawn_refresher(
+        gauges.clone().into_inner(),
+        state.clone(),
+        Arc::clone(&idempotency),
+        metrics::refresh_interval_from_env(),
+    );
+
+    // actix stops accepting connections on SIGINT/SIGTERM and lets in-flight
+    // requests finish; ingestion writes detached from cancelled requests are
+    // drained below before the process exits.
     HttpServer::new(move || {
         App::new()
             .app_data(state.clone())
+            .app_data(limits.clone())
+            .app_data(gauges.clone())
+            .app_data(web::QueryConfig::default().error_handler(responses::bad_request_handler))
+            .app_data(web::JsonConfig::default().error_handler(responses::bad_request_handler))
+            .app_data(web::PathConfig::default().error_handler(responses::bad_request_handler))
+            .wrap(middleware::from_fn({
+                let timeouts = Arc::clone(&timeouts);
+                move |req, next| {
+                    let timeouts = Arc::clone(&timeouts);
+                    async move { timeout::timeout_middleware(&timeouts, req, next).await }
+                }
+            }))
+            .wrap(middleware::from_fn({
+                let limits = limits.clone();
+                move |req, next| {
+                    let limits = limits.clone();
+                    async move { concurrency::concurrency_middleware(&limits, req, next).await }
+                }
+            }))
+            .wrap(middleware::from_fn({
+                let idempotency = Arc::clone(&idempotency);
+                move |req, next| {
+                    let idempotency = Arc::clone(&idempotency);
+                    async move { idempotency::idempotency_middleware(&idempotency, req, next).await }
+                }
+            }))
+            .wrap(middleware::from_fn(responses::request_id_middleware))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
             .route("/", web::get().to(index))
             .route("/health", web::get().to(health_check))
+            .route("/health/deep", web::get().to(deep_health_check))
+            .route("/metrics", web::get().to(metrics::metrics))
             .route(
                 "/api/v1/wallet/{address}/side-wallets",
                 web::get().to(find_side_wallets),
             )
+            .route(
+                "/api/v1/wallet/{address}/transactions",
+                web::get().to(get_wallet_transactions),
+            )
+            .route(
+                "/api/v1/wallet/{address}/transfers",
+                web::get().to(get_wallet_transfers),
+            )
+            .route(
+                "/api/v1/wallet/{address}/activity",
+                web::get().to(get_wallet_activity),
+            )
+            .route(
+                "/api/v1/wallet/{address}/summary",
+                web::get().to(get_wallet_summary),
+            )
+            .route(
+                "/api/v1/wallet/{address}/net-flows",
+                web::get().to(get_wallet_net_flows),
+            )
+            .route(
+                "/api/v1/wallet/{address}/structuring",
+                web::get().to(get_wallet_structuring),
+            )
+            .route(
+                "/api/v1/wallet/{address}/risk",
+                web::get().to(get_wallet_risk),
+            )
+            .route(
+                "/api/v1/wallet/{address}/similar",
+                web::get().to(get_similar_wallets),
+            )
+            .route(
+                "/api/v1/wallet/{address}/connections",
+                web::get().to(get_wallet_connections),
+            )
+            .route(
+                "/api/v1/wallet/{address}/swaps",
+                web::get().to(get_wallet_swaps),
+            )
+            .route(
+                "/api/v1/wallet/{address}/sandwiched",
+                web::get().to(get_wallet_sandwiched),
+            )
+            .route(
+                "/api/v1/wallet/{address}/nft-trades",
+                web::get().to(get_wallet_nft_trades),
+            )
+            .route(
+                "/api/v1/wallet/{address}/holdings",
+                web::get().to(get_wallet_holdings),
+            )
+            .route(
+                "/api/v1/wallet/{address}/cluster",
+                web::get().to(get_wallet_cluster),
+            )
+            .route(
+                "/api/v1/wallet/{address}/investigate",
+                web::get().to(investigate),
+            )
+            .route(
+                "/api/v1/wallet/{address}/labels",
+                web::get().to(get_wallet_labels),
+            )
+            .route(
+                "/api/v1/wallet/{address}/labels",
+                web::post().to(set_wallet_label),
+            )
+            .route(
+                "/api/v1/wallet/{address}/labels/{label}",
+                web::delete().to(delete_wallet_label),
+            )
+            .route(
+                "/api/v1/wallet/{address}/graph.graphml",
+                web::get().to(export_wallet_graphml),
+            )
+            .route(
+                "/api/v1/wallet/{address}/graph.dot",
+                web::get().to(export_wallet_dot),
+            )
+            .route("/api/v1/token/{mint}", web::get().to(get_token_info))
+            .route(
+                "/api/v1/token/{mint}/price-history",
+                web::get().to(get_token_price_history),
+            )
+            .route("/api/v1/compare", web::get().to(compare))
+            .route(
+                "/api/v1/wallet/{address}/funding-source",
+                web::get().to(get_funding_source),
+            )
+            .route(
+                "/api/v1/trace/exchange-routes",
+                web::get().to(trace_exchange_routes),
+            )
+            .route(
+                "/api/v1/network/pagerank",
+                web::get().to(get_network_pagerank),
+            )
+            .route(
+                "/api/v1/network/anomalies",
+                web::get().to(get_network_anomalies),
+            )
+            .route(
+                "/api/v1/analysis/high-risk-wallets",
+                web::get().to(get_high_risk_wallets),
+            )
+            .route("/api/v1/parse/batch", web::post().to(parse_batch))
+            .route(
+                "/api/v1/wallet/{address}/ingest",
+                web::post().to(ingest_wallet),
+            )
+            .route("/api/v1/docs", web::get().to(docs::openapi_spec))
+            .route(
+                "/api/v1/stream/whale-transfers",
+                web::get().to(stream_whale_transfers),
+            )
+            .route("/api/v1/admin/keys", web::get().to(list_api_keys))
+            .route("/api/v1/admin/keys", web::post().to(add_api_key))
+            .route("/api/v1/admin/keys/{id}", web::delete().to(revoke_api_key))
+            .route(
+                "/api/v1/admin/rebuild-relationships",
+                web::post().to(rebuild_relationships),
+            )
+            .route(
+                "/api/v1/admin/retry-failures",
+                web::post().to(retry_failures),
+            )
+            .route(
+                "/api/v1/admin/failures",
+                web::get().to(exhausted_failures),
+            )
     })
     .bind((host, port))?
     .run()
-    .await
+    .await?;
+    gauge_refresher.abort();
+
+    tracing::info!(
+        "Shutting down; waiting for {} in-flight ingestion writes",
+        transfer_analytics.pending_writes()
+    );
+    let summary = transfer_analytics.drain(SHUTDOWN_DRAIN_TIMEOUT).await;
+    db_manager.close();
+    tracing::info!(
+        "Shutdown complete: {} of {} in-flight writes flushed, {} abandoned; {} stored, {} failed, {} cancelled this run",
+        summary.flushed,
+        summary.pending,
+        summary.unfinished,
+        summary.completed_total,
+        summary.failed_total,
+        summary.cancelled_total
+    );
+    Ok(())
 }
 
 async fn index() -> HttpResponse {
@@ -57,29 +339,154 @@ async fn index() -> HttpResponse {
         "feature": "side-wallet tracing",
         "endpoints": {
             "health": "/health",
-            "side_wallets": "/api/v1/wallet/{address}/side-wallets"
+            "health_deep": "/health/deep",
+            "metrics": "/metrics",
+            "side_wallets": "/api/v1/wallet/{address}/side-wallets",
+            "transactions": "/api/v1/wallet/{address}/transactions",
+            "transfers": "/api/v1/wallet/{address}/transfers",
+            "activity": "/api/v1/wallet/{address}/activity",
+            "summary": "/api/v1/wallet/{address}/summary",
+            "net_flows": "/api/v1/wallet/{address}/net-flows",
+            "structuring": "/api/v1/wallet/{address}/structuring",
+            "risk": "/api/v1/wallet/{address}/risk",
+            "similar": "/api/v1/wallet/{address}/similar",
+            "connections": "/api/v1/wallet/{address}/connections",
+            "swaps": "/api/v1/wallet/{address}/swaps",
+            "sandwiched": "/api/v1/wallet/{address}/sandwiched",
+            "nft_trades": "/api/v1/wallet/{address}/nft-trades",
+            "holdings": "/api/v1/wallet/{address}/holdings",
+            "investigate": "/api/v1/wallet/{address}/investigate",
+            "cluster": "/api/v1/wallet/{address}/cluster",
+            "labels": "/api/v1/wallet/{address}/labels",
+            "graph_graphml": "/api/v1/wallet/{address}/graph.graphml",
+            "graph_dot": "/api/v1/wallet/{address}/graph.dot",
+            "funding_source": "/api/v1/wallet/{address}/funding-source",
+            "token": "/api/v1/token/{mint}",
+            "token_price_history": "/api/v1/token/{mint}/price-history",
+            "compare": "/api/v1/compare?a={wallet}&b={wallet}",
+            "exchange_routes": "/api/v1/trace/exchange-routes",
+            "pagerank": "/api/v1/network/pagerank",
+            "anomalies": "/api/v1/network/anomalies",
+            "high_risk_wallets": "/api/v1/analysis/high-risk-wallets",
+            "parse_batch": "/api/v1/parse/batch",
+            "ingest": "/api/v1/wallet/{address}/ingest",
+            "whale_transfer_stream": "/api/v1/stream/whale-transfers",
+            "admin_keys": "/api/v1/admin/keys",
+            "admin_rebuild_relationships": "/api/v1/admin/rebuild-relationships",
+            "admin_retry_failures": "/api/v1/admin/retry-failures",
+            "admin_failures": "/api/v1/admin/failures",
+            "api_docs": "/api/v1/docs"
         }
     }))
 }
 
-async fn health_check(state: web::Data<ApiState>) -> HttpResponse {
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "RPC connectivity and per-endpoint health"),
+    ),
+)]
+async fn health_check(
+    state: web::Data<ApiState>,
+    limits: Option<web::Data<ConcurrencyLimits>>,
+) -> HttpResponse {
+    let endpoints = state.rpc_client.endpoint_health();
+    let in_flight = limits.map(|l| l.in_flight());
     match state.rpc_client.health_check().await {
         Ok(true) => HttpResponse::Ok().json(json!({
             "status": "healthy",
-            "rpc": "connected"
+            "rpc": "connected",
+            "rpc_endpoints": endpoints,
+            "in_flight": in_flight
         })),
         Ok(false) => HttpResponse::ServiceUnavailable().json(json!({
             "status": "unhealthy",
-            "rpc": "disconnected"
+            "rpc": "disconnected",
+            "rpc_endpoints": endpoints,
+            "in_flight": in_flight
         })),
         Err(e) => HttpResponse::ServiceUnavailable().json(json!({
             "status": "error",
-            "error": e.to_string()
+            "error": e.to_string(),
+            "rpc_endpoints": endpoints,
+            "in_flight": in_flight
         })),
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// Upper bound for a single dependency check in `/health/deep`.
+const DEEP_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, serde::Serialize)]
+struct ComponentHealth {
+    /// `up` or `down`
+    status: &'static str,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run one dependency check with a timeout and record its latency.
+async fn check_component<F>(check: F) -> ComponentHealth
+where
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    let started = std::time::Instant::now();
+    let outcome = tokio::time::timeout(DEEP_HEALTH_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err("timed out".to_string()));
+    ComponentHealth {
+        status: if outcome.is_ok() { "up" } else { "down" },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: outcome.err(),
+    }
+}
+
+/// Ping the database and RPC concurrently. The database is critical (`unhealthy`
+/// when down); the RPC only degrades the service, since stored analysis is still served.
+async fn deep_health_report(state: &ApiState) -> (bool, serde_json::Value) {
+    let (database, rpc) = tokio::join!(
+        check_component(async { state.db_manager.ping().await.map_err(|e| e.to_string()) }),
+        check_component(async {
+            match state.rpc_client.health_check().await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err("RPC reported unhealthy".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }),
+    );
+
+    let status = if database.status != "up" {
+        "unhealthy"
+    } else if rpc.status != "up" {
+        "degraded"
+    } else {
+        "healthy"
+    };
+    let report = json!({
+        "status": status,
+        "components": {
+            "database": database,
+            "rpc": rpc,
+        },
+        "rpc_endpoints": state.rpc_client.endpoint_health(),
+        "database_pool": state.db_manager.pool_status(),
+    });
+    (status != "unhealthy", report)
+}
+
+/// Readiness probe: 503 only when a critical dependency (the database) is down.
+async fn deep_health_check(state: web::Data<ApiState>) -> HttpResponse {
+    match deep_health_report(&state).await {
+        (true, report) => HttpResponse::Ok().json(report),
+        (false, report) => HttpResponse::ServiceUnavailable().json(report),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SideWalletQuery {
     /// Graph expansion depth (1-3 recommended)
     pub depth: Option<usize>,
@@ -97,9 +504,93 @@ pub struct SideWalletQuery {
     pub cex_hops: Option<bool>,
     /// Extra signatures to ingest for intermediary wallets during cex_hops.
     pub cex_bootstrap_limit: Option<u64>,
+    /// If false, ingest into a throwaway in-memory store and analyse only that
+    /// (dry run: nothing is written to the database).
+    pub persist: Option<bool>,
+    /// If true, ignore a cached result and recompute.
+    pub skip_cache: Option<bool>,
+    /// Edges the graph expansion follows: `both` (default), `inbound` or `outbound`
+    pub direction: Option<String>,
+    /// If true (default), list exchange wallets and known program accounts as
+    /// candidates but do not expand through them
+    pub stop_at_hubs: Option<bool>,
+    /// Comma-separated candidate fields to return (e.g. `address,score`); default all
+    pub fields: Option<String>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+impl SideWalletQuery {
+    /// Whether to ingest the wallet's recent signatures first: by default only
+    /// when the key can write.
+    fn bootstrap(&self, can_ingest: bool) -> bool {
+        self.bootstrap.unwrap_or(can_ingest)
+    }
+
+    fn bootstrap_limit(&self) -> u64 {
+        self.bootstrap_limit.unwrap_or(25).min(100)
+    }
+
+    /// Signatures ingested per intermediary wallet during `cex_hops`.
+    fn cex_bootstrap_limit(&self, can_ingest: bool) -> u64 {
+        self.cex_bootstrap_limit
+            .unwrap_or(if can_ingest { 15 } else { 0 })
+            .min(100)
+    }
+
+    /// Whether the request fetches anything over RPC.
+    fn ingests(&self, can_ingest: bool) -> bool {
+        (self.bootstrap(can_ingest) && self.bootstrap_limit() > 0)
+            || (self.cex_hops.unwrap_or(true) && self.cex_bootstrap_limit(can_ingest) > 0)
+    }
+}
+
+/// Whether a side-wallets request will bootstrap, decided from its query and key
+/// the same way `find_side_wallets` does; the concurrency limits take a
+/// bootstrap permit only for those. Unparseable queries are answered 400 by
+/// the handler, so they don't count.
+pub(crate) fn side_wallets_request_bootstraps(req: &ServiceRequest) -> bool {
+    web::Query::<SideWalletQuery>::from_query(req.query_string())
+        .map(|query| query.ingests(auth::request_has_scope(req.request(), Scope::Write)))
+        .unwrap_or(false)
+}
+
+/// Which relationship edges the side-wallet BFS follows, relative to the wallet
+/// being expanded: `inbound` traces where funds came from, `outbound` where they went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraversalDirection {
+    Both,
+    Inbound,
+    Outbound,
+}
+
+impl TraversalDirection {
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("both") => Ok(Self::Both),
+            Some("inbound") => Ok(Self::Inbound),
+            Some("outbound") => Ok(Self::Outbound),
+            Some(other) => Err(format!(
+                "unknown direction '{}' (expected both, inbound or outbound)",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Both => "both",
+            Self::Inbound => "inbound",
+            Self::Outbound => "outbound",
+        }
+    }
+
+    /// Whether an edge with this `direction_label` may be followed.
+    fn allows(self, label: &str) -> bool {
+        self == Self::Both || label == self.as_str()
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
 struct SideWalletCandidate {
     address: String,
     score: f64,
@@ -113,20 +604,37 @@ struct SideWalletCandidate {
     direction: String,
     shared_funders_count: u32,
     shared_counterparties_count: u32,
+    shared_mints_count: u32,
     shared_funders: Vec<String>,
     shared_counterparties: Vec<String>,
+    shared_mints: Vec<String>,
     behavioral_similarity: f64,
     temporal_overlap_ratio: f64,
     same_block_count: u32,
+    /// Current SOL balance (best effort; `None` if the lookup failed).
+    balance_sol: Option<f64>,
+    /// Investigator labels on the address.
+    labels: Vec<String>,
+    /// Exchange or program account the expansion stopped at (`stop_at_hubs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hub: Option<String>,
+    /// Days since the address's first stored transfer, if it has any.
+    age_days: Option<f64>,
+    /// First seen less than `FRESH_WALLET_DAYS` ago.
+    fresh_wallet: bool,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 struct BootstrapStats {
     wallet: String,
     signatures: usize,
     parsed_ok: usize,
     parsed_failed: usize,
     persisted_failed: usize,
+    /// Persisted transactions not stored before.
+    new_transactions: usize,
+    /// Persisted transactions that were already stored (redundant re-ingestion).
+    updated_transactions: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -150,6 +658,9 @@ struct CexHopPath {
     deposit_amount_ui: f64,
     withdrawal_amount_ui: f64,
     delta_seconds: i64,
+    /// Exchange name when the hot (or deposit) wallet is in the exchange registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exchange: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -193,6 +704,15 @@ fn recency_score(last_seen_epoch: u64) -> f64 {
     clamp01(0.15 + 0.85 * decay)
 }
 
+/// Days since `first_seen_epoch` (0 for timestamps in the future).
+fn age_days(first_seen_epoch: u64) -> f64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    now.saturating_sub(first_seen_epoch) as f64 / 86_400.0
+}
+
 fn direction_label(current: &str, conn_from: &str, conn_to: &str) -> String {
     if current == conn_from {
         "outbound".to_string()
@@ -239,57 +759,6 @@ fn build_reason(from: &str, to: &str, tx_count: u32, total_sol: f64, total_token
     }
 }
 
-fn compute_behavioral_similarity(
-    profile_a: &BehavioralProfile,
-    profile_b: &BehavioralProfile,
-) -> f64 {
-    // Similarity based on transaction patterns (0.0 - 1.0)
-
-    // 1. Average SOL amount similarity (normalize by log scale)
-    let avg_sol_sim = if profile_a.avg_sol_per_tx > 0.0 && profile_b.avg_sol_per_tx > 0.0 {
-        let ratio = (profile_a.avg_sol_per_tx / profile_b.avg_sol_per_tx)
-            .max(profile_b.avg_sol_per_tx / profile_a.avg_sol_per_tx);
-        let log_ratio = ratio.ln().abs();
-        (-log_ratio / 2.0).exp()
-    } else {
-        0.5
-    };
-
-    // 2. Transaction frequency similarity (tx per day)
-    let freq_sim = if profile_a.avg_tx_per_day > 0.0 && profile_b.avg_tx_per_day > 0.0 {
-        let ratio = (profile_a.avg_tx_per_day / profile_b.avg_tx_per_day)
-            .max(profile_b.avg_tx_per_day / profile_a.avg_tx_per_day);
-        let log_ratio = ratio.ln().abs();
-        (-log_ratio / 1.5).exp()
-    } else {
-        0.5
-    };
-
-    // 3. Most active hour similarity (time-of-day clustering)
-    let hour_sim = match (
-        profile_a.most_active_hour_utc,
-        profile_b.most_active_hour_utc,
-    ) {
-        (Some(h_a), Some(h_b)) => {
-            let diff = (h_a - h_b).abs();
-            let circular_diff = diff.min(24 - diff);
-            if circular_diff <= 2 {
-                1.0
-            } else if circular_diff <= 4 {
-                0.7
-            } else if circular_diff <= 8 {
-                0.4
-            } else {
-                0.1
-            }
-        }
-        _ => 0.3,
-    };
-
-    let combined = (avg_sol_sim * 0.40) + (freq_sim * 0.35) + (hour_sim * 0.25);
-    clamp01(combined)
-}
-
 fn event_asset_key(ev: &TransferEvent) -> Option<AssetKey> {
     match ev.kind.as_str() {
         "sol" => Some(AssetKey::Sol),
@@ -324,24 +793,210 @@ fn amount_score(ratio: f64) -> f64 {
     clamp01((-log_ratio / 0.7).exp())
 }
 
-async fn bootstrap_ingest_wallet(state: &ApiState, wallet: &str, limit: u64) -> BootstrapStats {
-    let mut stats = BootstrapStats {
-        wallet: wallet.to_string(),
-        signatures: 0,
-        parsed_ok: 0,
-        parsed_failed: 0,
-        persisted_failed: 0,
+/// Result of fetching, parsing and persisting one signature during bootstrap.
+enum IngestOutcome {
+    Persisted(StoredOutcome),
+    PersistFailed(String),
+    ParseFailed(String),
+}
+
+impl IngestOutcome {
+    fn stage(&self) -> &'static str {
+        match self {
+            IngestOutcome::ParseFailed(_) => "parse",
+            _ => "persist",
+        }
+    }
+}
+
+impl BootstrapStats {
+    fn new(wallet: &str) -> Self {
+        Self {
+            wallet: wallet.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Count an ingestion outcome; returns the error message, if any.
+    fn record<'a>(&mut self, outcome: &'a IngestOutcome) -> Option<&'a str> {
+        match outcome {
+            IngestOutcome::Persisted(stored) => {
+                self.parsed_ok += 1;
+                match stored {
+                    StoredOutcome::Inserted => self.new_transactions += 1,
+                    StoredOutcome::Updated => self.updated_transactions += 1,
+                }
+                None
+            }
+            IngestOutcome::PersistFailed(e) => {
+                self.parsed_ok += 1;
+                self.persisted_failed += 1;
+                Some(e)
+            }
+            IngestOutcome::ParseFailed(e) => {
+                self.parsed_failed += 1;
+                Some(e)
+            }
+        }
+    }
+}
+
+/// Max signatures fetched/parsed at once during bootstrap, from
+/// `BOOTSTRAP_CONCURRENCY` (default 8).
+fn bootstrap_concurrency() -> usize {
+    static CONCURRENCY: OnceLock<usize> = OnceLock::new();
+    *CONCURRENCY.get_or_init(|| env_parse_in("BOOTSTRAP_CONCURRENCY", 8, 1..=64))
+}
+
+/// Fetch, parse and persist `signatures` with bounded concurrency.
+///
+/// RPC calls still go through the client's shared rate limiter, so concurrency
+/// mainly overlaps response latency and parsing: with the default 120ms spacing
+/// and ~400ms RPC latency, 100 signatures drop from ~50s sequentially to roughly
+/// the rate-limit floor of ~12s. Persistence is safe to interleave because event
+/// writes are idempotent per (signature, event_index) and relationship upserts
+/// are atomic in both backends.
+///
+/// Failed signatures are recorded in the dead-letter store for
+/// `retry_ingestion_failures`.
+async fn ingest_signatures(
+    state: &ApiState,
+    signatures: Vec<String>,
+) -> Vec<(String, IngestOutcome)> {
+    stream::iter(signatures)
+        .map(|signature| async move {
+            let outcome = match state.tx_handler.process_transaction(&signature, None).await {
+                Ok(tx) => match state.transfer_analytics.analyze_transaction(&tx).await {
+                    Ok(stored) => IngestOutcome::Persisted(stored),
+                    Err(e) => IngestOutcome::PersistFailed(e.to_string()),
+                },
+                Err(e) => IngestOutcome::ParseFailed(e.to_string()),
+            };
+            if let IngestOutcome::ParseFailed(e) | IngestOutcome::PersistFailed(e) = &outcome {
+                if let Err(record_err) = state
+                    .db_manager
+                    .record_ingestion_failure(&signature, outcome.stage(), e)
+                    .await
+                {
+                    tracing::warn!(
+                        "Could not record ingestion failure for {}: {}",
+                        signature,
+                        record_err
+                    );
+                }
+            }
+            (signature, outcome)
+        })
+        .buffer_unordered(bootstrap_concurrency())
+        .collect()
+        .await
+}
+
+/// Attempts after which a failed signature is no longer retried.
+const MAX_INGESTION_ATTEMPTS: u32 = 8;
+
+/// Outcome of one pass over the ingestion dead-letter store.
+#[derive(Debug, Default, serde::Serialize)]
+struct RetryReport {
+    /// Failures still below `MAX_INGESTION_ATTEMPTS`, due or not.
+    pending: u64,
+    /// Skipped because their backoff has not elapsed.
+    waiting: u64,
+    /// Out of attempts; listed by `GET /api/v1/admin/failures`.
+    exhausted: u64,
+    retried: usize,
+    /// Ingested this time and removed from the store.
+    succeeded: Vec<String>,
+    /// Failed again (attempt count bumped).
+    failed: Vec<String>,
+}
+
+/// Re-ingest up to `limit` failed signatures whose backoff has elapsed at `now`,
+/// removing the ones that now succeed.
+async fn retry_ingestion_failures(
+    state: &ApiState,
+    limit: usize,
+    now: u64,
+) -> BeastResult<RetryReport> {
+    let counts = state
+        .db_manager
+        .count_ingestion_failures(MAX_INGESTION_ATTEMPTS, now)
+        .await?;
+    let due: Vec<String> = state
+        .db_manager
+        .get_pending_failures(MAX_INGESTION_ATTEMPTS, now, limit)
+        .await?
+        .into_iter()
+        .map(|f| f.signature)
+        .collect();
+    let mut report = RetryReport {
+        pending: counts.due + counts.waiting,
+        waiting: counts.waiting,
+        exhausted: counts.exhausted,
+        retried: due.len(),
+        ..Default::default()
     };
 
+    for (signature, outcome) in ingest_signatures(state, due).await {
+        match outcome {
+            IngestOutcome::Persisted(_) => {
+                state.db_manager.clear_failure(&signature).await?;
+                report.succeeded.push(signature);
+            }
+            _ => report.failed.push(signature),
+        }
+    }
+    report.succeeded.sort();
+    report.failed.sort();
+    Ok(report)
+}
+
+/// Signatures requested per `getSignaturesForAddress` page during bootstrap.
+const BOOTSTRAP_PAGE_SIZE: u64 = 50;
+
+/// Up to `limit` of the wallet's newest signatures, paging back until one is older
+/// than `since_epoch` (it and everything after it are dropped) or history runs out.
+/// Signatures without a block time are kept.
+async fn signatures_since(
+    state: &ApiState,
+    wallet: &str,
+    limit: u64,
+    since_epoch: u64,
+) -> BeastResult<Vec<String>> {
+    let mut out: Vec<String> = Vec::new();
+    while (out.len() as u64) < limit {
+        let want = (limit - out.len() as u64).min(BOOTSTRAP_PAGE_SIZE);
+        let page = state
+            .rpc_client
+            .get_signatures_page(wallet, want, out.last().map(|s| s.as_str()), None)
+            .await?;
+        let exhausted = (page.len() as u64) < want;
+        for sig in page {
+            if sig.block_time > 0 && sig.block_time < since_epoch {
+                return Ok(out);
+            }
+            out.push(sig.signature);
+        }
+        if exhausted {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+async fn bootstrap_ingest_wallet(
+    state: &ApiState,
+    wallet: &str,
+    limit: u64,
+    since_epoch: u64,
+) -> BootstrapStats {
+    let mut stats = BootstrapStats::new(wallet);
+
     if limit == 0 {
         return stats;
     }
 
-    let sigs = match state
-        .rpc_client
-        .get_signatures(wallet, limit.min(100))
-        .await
-    {
+    let signatures = match signatures_since(state, wallet, limit.min(100), since_epoch).await {
         Ok(s) => s,
         Err(e) => {
             tracing::debug!("bootstrap get_signatures failed for {}: {}", wallet, e);
@@ -349,30 +1004,19 @@ async fn bootstrap_ingest_wallet(state: &ApiState, wallet: &str, limit: u64) ->
         }
     };
 
-    stats.signatures = sigs.len();
-    if sigs.is_empty() {
+    stats.signatures = signatures.len();
+    if signatures.is_empty() {
         return stats;
     }
 
-    for s in sigs {
-        match state
-            .tx_handler
-            .process_transaction(&s.signature, None)
-            .await
-        {
-            Ok(tx) => {
-                stats.parsed_ok += 1;
-                if let Err(e) = state.transfer_analytics.analyze_transaction(&tx).await {
-                    stats.persisted_failed += 1;
-                    tracing::debug!("bootstrap persist failed {}: {}", s.signature, e);
-                }
-            }
-            Err(e) => {
-                stats.parsed_failed += 1;
-                tracing::debug!("bootstrap parse failed {}: {}", s.signature, e);
-            }
+    for (signature, outcome) in ingest_signatures(state, signatures).await {
+        if let Some(e) = stats.record(&outcome) {
+            tracing::debug!("bootstrap {} {}: {}", outcome.stage(), signature, e);
         }
     }
+    if stats.parsed_ok > 0 {
+        state.side_wallet_cache.invalidate_wallet(wallet).await;
+    }
 
     stats
 }
@@ -400,7 +1044,7 @@ async fn compute_cex_hops(
 
     let top_recipients = match state
         .db_manager
-        .get_top_outbound_recipients(main_wallet, Some(since_epoch), 30)
+        .get_top_outbound_recipients_by_mint(main_wallet, Some(since_epoch), 30)
         .await
     {
         Ok(v) => v,
@@ -440,8 +1084,10 @@ async fn compute_cex_hops(
 
         // Bootstrap deposit wallet so we can see sweeps in transfer_events.
         if bootstrapped_wallets.insert(deposit_wallet.clone()) {
-            bootstrap_stats
-                .push(bootstrap_ingest_wallet(state, &deposit_wallet, cex_bootstrap_limit).await);
+            bootstrap_stats.push(
+                bootstrap_ingest_wallet(state, &deposit_wallet, cex_bootstrap_limit, since_epoch)
+                    .await,
+            );
         }
 
         for deposit_ev in deposit_events {
@@ -509,7 +1155,13 @@ async fn compute_cex_hops(
             for (hot_wallet, sweep_sig, sweep_time, _sweep_amount) in hot_wallets {
                 if bootstrapped_wallets.insert(hot_wallet.clone()) {
                     bootstrap_stats.push(
-                        bootstrap_ingest_wallet(state, &hot_wallet, cex_bootstrap_limit).await,
+                        bootstrap_ingest_wallet(
+                            state,
+                            &hot_wallet,
+                            cex_bootstrap_limit,
+                            since_epoch,
+                        )
+                        .await,
                     );
                 }
 
@@ -565,6 +1217,16 @@ async fn compute_cex_hops(
                         continue;
                     }
 
+                    let exchange = state
+                        .exchange_registry
+                        .identify_exchange(&hot_wallet)
+                        .or_else(|| state.exchange_registry.identify_exchange(&deposit_wallet))
+                        .map(|info| info.exchange.clone());
+                    let exchange_label = exchange
+                        .as_deref()
+                        .map(|name| format!(" [{}]", name))
+                        .unwrap_or_default();
+
                     let path = CexHopPath {
                         deposit_wallet: deposit_wallet.clone(),
                         hot_wallet: hot_wallet.clone(),
@@ -579,6 +1241,7 @@ async fn compute_cex_hops(
                         deposit_amount_ui: deposit_amount,
                         withdrawal_amount_ui: withdraw_amount,
                         delta_seconds,
+                        exchange,
                     };
 
                     let entry = agg
@@ -598,7 +1261,8 @@ async fn compute_cex_hops(
                     }
                     if entry.reasons.len() < 5 {
                         entry.reasons.push(format!(
-                            "CEX hop: {} -> {} -> {} -> {} (dt={}s, score={:.2})",
+                            "CEX hop{}: {} -> {} -> {} -> {} (dt={}s, score={:.2})",
+                            exchange_label,
                             main_wallet,
                             deposit_wallet,
                             hot_wallet,
@@ -623,11 +1287,41 @@ async fn compute_cex_hops(
     (out, bootstrap_stats)
 }
 
+/// Side-wallet score weights from `SIDE_WALLET_WEIGHT_*`, read once.
+fn side_wallet_scoring() -> SideWalletScoringConfig {
+    static SCORING: OnceLock<SideWalletScoringConfig> = OnceLock::new();
+    *SCORING.get_or_init(SideWalletScoringConfig::from_env)
+}
+
+/// Mints that never count as shared-mint evidence (`SHARED_MINT_IGNORE`), read once.
+fn shared_mint_ignore() -> &'static [String] {
+    static IGNORED: OnceLock<Vec<String>> = OnceLock::new();
+    IGNORED.get_or_init(ignored_mints_from_env)
+}
+
+/// Accounts never expanded through when `stop_at_hubs` is set
+/// (`SIDE_WALLET_TERMINAL_ACCOUNTS`), read once.
+fn terminal_accounts() -> &'static HashSet<String> {
+    static TERMINAL: OnceLock<HashSet<String>> = OnceLock::new();
+    TERMINAL.get_or_init(|| terminal_accounts_from_env().into_iter().collect())
+}
+
+/// What makes `wallet` a hub the side-wallet expansion stops at, if anything.
+fn hub_label(state: &ApiState, wallet: &str) -> Option<String> {
+    if let Some(info) = state.exchange_registry.identify_exchange(wallet) {
+        return Some(format!("{} exchange", info.exchange));
+    }
+    terminal_accounts()
+        .contains(wallet)
+        .then(|| "program account".to_string())
+}
+
 async fn enrich_candidates_with_event_signals(
     state: &ApiState,
     main_wallet: &str,
     candidates: &mut [SideWalletCandidate],
     lookback_days: u32,
+    scoring: &SideWalletScoringConfig,
 ) {
     let since_epoch = since_epoch_from_days(lookback_days);
 
@@ -701,8 +1395,37 @@ async fn enrich_candidates_with_event_signals(
             Err(e) => tracing::debug!("counterparty query failed: {}", e),
         }
 
-        let bump = 0.06 * (c.shared_funders_count.min(3) as f64)
-            + 0.03 * (c.shared_counterparties_count.min(5) as f64);
+        match state
+            .db_manager
+            .get_shared_mints(
+                main_wallet,
+                &c.address,
+                Some(since_epoch),
+                shared_mint_ignore(),
+                3,
+            )
+            .await
+        {
+            Ok(mints) => {
+                c.shared_mints_count = mints.len() as u32;
+                c.shared_mints = mints
+                    .iter()
+                    .map(|m| format_signal(&m.mint, m.count_a + m.count_b, m.last_seen_epoch))
+                    .collect();
+                for m in c.shared_mints.iter() {
+                    if c.reasons.len() < 8 {
+                        c.reasons.push(format!("Shared token: {}", m));
+                    }
+                }
+            }
+            Err(e) => tracing::debug!("shared mints query failed: {}", e),
+        }
+
+        let bump = scoring.shared_evidence_bump(
+            c.shared_funders_count,
+            c.shared_counterparties_count,
+            c.shared_mints_count,
+        );
         c.score = clamp01(c.score + bump);
     }
 
@@ -722,7 +1445,7 @@ async fn enrich_candidates_with_event_signals(
                 .await
             {
                 Ok(Some(cand_prof)) => {
-                    let similarity = compute_behavioral_similarity(&main_prof, &cand_prof);
+                    let similarity = behavioral_similarity(&main_prof, &cand_prof);
                     c.behavioral_similarity = similarity;
                     if similarity > 0.65 {
                         if c.reasons.len() < 8 {
@@ -731,7 +1454,7 @@ async fn enrich_candidates_with_event_signals(
                                 similarity
                             ));
                         }
-                        c.score = clamp01(c.score + similarity * 0.12);
+                        c.score = clamp01(c.score + similarity * scoring.behavioral);
                     }
                 }
                 Ok(None) => c.behavioral_similarity = 0.0,
@@ -761,8 +1484,7 @@ async fn enrich_candidates_with_event_signals(
                             overlap.same_block_count
                         ));
                     }
-                    c.score =
-                        clamp01(c.score + 0.08 * (overlap.same_block_count.min(5) as f64 * 0.2));
+                    c.score = clamp01(c.score + scoring.same_block_bump(overlap.same_block_count));
                 } else if overlap.overlap_ratio > 0.15 {
                     if c.reasons.len() < 8 {
                         c.reasons.push(format!(
@@ -770,7 +1492,7 @@ async fn enrich_candidates_with_event_signals(
                             overlap.overlap_ratio * 100.0
                         ));
                     }
-                    c.score = clamp01(c.score + overlap.overlap_ratio * 0.10);
+                    c.score = clamp01(c.score + overlap.overlap_ratio * scoring.temporal_overlap);
                 }
             }
             Err(e) => {
@@ -782,17 +1504,32 @@ async fn enrich_candidates_with_event_signals(
     }
 }
 
+/// Relationships read per expanded wallet, busiest first.
+const CONNECTIONS_PER_WALLET: usize = 100;
+
+/// Side-wallet candidates, and whether the expansion hit the graph cap (or a
+/// wallet had more than `CONNECTIONS_PER_WALLET` relationships) and so only saw
+/// part of the graph.
+struct SideWalletSearch {
+    candidates: Vec<SideWalletCandidate>,
+    sampled: bool,
+}
+
+/// BFS over stored relationships from `main_wallet`, scored and enriched with
+/// event evidence. `params.cex_hops` is not used here. With `params.stop_at_hubs`,
+/// exchange wallets and known program accounts are listed but not expanded, since
+/// they connect to countless unrelated wallets. The expansion reads at most the
+/// graph cap's `max_edges` relationships and keeps at most `max_nodes` candidates,
+/// so mega-hubs are sampled instead of timing out.
 async fn compute_side_wallets(
     state: &ApiState,
     main_wallet: &str,
-    max_depth: usize,
-    threshold: f64,
-    limit: usize,
-    lookback_days: u32,
-) -> Result<Vec<SideWalletCandidate>, String> {
-    let max_depth = max_depth.clamp(1, 5);
-    let threshold = clamp01(threshold);
-    let limit = limit.clamp(1, 100);
+    params: &SideWalletParams,
+) -> Result<SideWalletSearch, String> {
+    let max_depth = params.depth.clamp(1, 5);
+    let threshold = clamp01(params.threshold);
+    let limit = params.limit.clamp(1, 100);
+    let (lookback_days, direction) = (params.lookback_days, params.direction);
 
     // BFS over wallet_relationships graph.
     let mut queue: VecDeque<(String, usize, f64)> = VecDeque::new();
@@ -802,23 +1539,42 @@ async fn compute_side_wallets(
     queue.push_back((main_wallet.to_string(), 0, 1.0));
     visited.insert(main_wallet.to_string());
 
+    let cap = state.graph_engine.graph_cap();
+    let mut edges_read = 0usize;
+    let mut sampled = false;
+
     while let Some((current, depth, parent_score)) = queue.pop_front() {
         if depth >= max_depth {
             continue;
         }
+        let budget = cap.max_edges.saturating_sub(edges_read);
+        if budget == 0 {
+            sampled = true;
+            break;
+        }
 
-        let connections = state
+        let page = state
             .db_manager
-            .get_wallet_connections(&current)
+            .get_wallet_connections_page(&current, 0, CONNECTIONS_PER_WALLET.min(budget))
             .await
             .map_err(|e| format!("Failed to get connections: {}", e))?;
+        sampled |= page.has_more;
+        edges_read += page.connections.len();
 
-        for conn in connections {
+        for conn in page.connections {
             let (from, to) = (&conn.from_wallet, &conn.to_wallet);
             let other = if from == &current { to } else { from };
             if other == main_wallet {
                 continue;
             }
+            let dir = direction_label(&current, from, to);
+            if !direction.allows(&dir) {
+                continue;
+            }
+            if best.len() >= cap.max_nodes && !best.contains_key(other.as_str()) {
+                sampled = true;
+                continue;
+            }
 
             let mut s = edge_score(
                 conn.transaction_count,
@@ -842,7 +1598,6 @@ async fn compute_side_wallets(
                 continue;
             }
 
-            let dir = direction_label(&current, from, to);
             let reason = build_reason(
                 from,
                 to,
@@ -866,11 +1621,18 @@ async fn compute_side_wallets(
                     direction: dir.clone(),
                     shared_funders_count: 0,
                     shared_counterparties_count: 0,
+                    shared_mints_count: 0,
                     shared_funders: Vec::new(),
                     shared_counterparties: Vec::new(),
+                    shared_mints: Vec::new(),
                     behavioral_similarity: 0.0,
                     temporal_overlap_ratio: 0.0,
                     same_block_count: 0,
+                    balance_sol: None,
+                    labels: Vec::new(),
+                    hub: None,
+                    age_days: None,
+                    fresh_wallet: false,
                 });
 
             if combined > entry.score {
@@ -895,7 +1657,16 @@ async fn compute_side_wallets(
                 }
             }
 
-            if !visited.contains(other) {
+            if params.stop_at_hubs && entry.hub.is_none() {
+                entry.hub = hub_label(state, other);
+                if let Some(hub) = &entry.hub {
+                    entry
+                        .reasons
+                        .insert(0, format!("Known {}; not expanded", hub));
+                    entry.reasons.truncate(5);
+                }
+            }
+            if entry.hub.is_none() && !visited.contains(other) {
                 visited.insert(other.to_string());
                 queue.push_back((other.to_string(), depth + 1, combined));
             }
@@ -913,7 +1684,29 @@ async fn compute_side_wallets(
     });
     results.truncate(limit);
 
-    enrich_candidates_with_event_signals(state, main_wallet, &mut results, lookback_days).await;
+    enrich_candidates_with_event_signals(
+        state,
+        main_wallet,
+        &mut results,
+        lookback_days,
+        &side_wallet_scoring(),
+    )
+    .await;
+
+    let addresses: Vec<String> = results.iter().map(|c| c.address.clone()).collect();
+    let first_seen = state
+        .db_manager
+        .get_first_seen_epochs(&addresses)
+        .await
+        .map_err(|e| format!("Failed to get first-seen times: {}", e))?;
+    for c in &mut results {
+        c.age_days = first_seen.get(&c.address).map(|&t| age_days(t));
+        if let Some(age) = c.age_days.filter(|&d| d < FRESH_WALLET_DAYS) {
+            c.fresh_wallet = true;
+            c.reasons
+                .push(format!("Fresh wallet: first seen {:.1} days ago", age));
+        }
+    }
 
     results.sort_by(|a, b| {
         b.score
@@ -922,67 +1715,138 @@ async fn compute_side_wallets(
     });
     results.truncate(limit);
 
-    Ok(results)
+    Ok(SideWalletSearch {
+        candidates: results,
+        sampled,
+    })
 }
 
-/// Find side wallets (direct graph + event signals) and CEX-hop funded wallets (heuristic).
-async fn find_side_wallets(
-    _auth: ApiKey,
-    state: web::Data<ApiState>,
-    address: web::Path<String>,
+/// Best-effort SOL balances for `wallets`, fetched with batched `getMultipleAccounts`.
+/// Wallets that are missing, malformed, or whose batch failed are left out.
+async fn fetch_sol_balances(state: &ApiState, wallets: &[String]) -> HashMap<String, f64> {
+    let addresses: Vec<&str> = wallets
+        .iter()
+        .map(|w| w.as_str())
+        .filter(|w| is_valid_address(w))
+        .collect();
+    if addresses.is_empty() {
+        return HashMap::new();
+    }
+
+    match state.rpc_client.get_multiple_accounts(&addresses).await {
+        Ok(accounts) => accounts
+            .into_iter()
+            .flatten()
+            .map(|a| (a.address, lamports_to_sol(a.balance)))
+            .collect(),
+        Err(e) => {
+            tracing::warn!(
+                "Balance lookup failed for {} wallets: {}",
+                addresses.len(),
+                e
+            );
+            HashMap::new()
+        }
+    }
+}
+
+/// The `{address}` path segment, rejected with 400 unless it is a valid Solana
+/// address, so junk never reaches storage or the RPC.
+fn path_address(address: web::Path<String>) -> Result<String, ApiError> {
+    let address = address.into_inner();
+    validate_address(&address)?;
+    Ok(address)
+}
+
+/// Find side wallets (direct graph + event signals) and CEX-hop funded wallets (heuristic).
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/side-wallets",
+    tag = "side-wallets",
+    params(("address" = String, Path, description = "Wallet address"), SideWalletQuery),
+    responses(
+        (status = 200, description = "Ranked side-wallet candidates (`side_wallets`), CEX-funded wallets (`cex_funded_wallets`), bootstrap stats and the active `scoring_weights`"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn find_side_wallets(
+    auth: ApiKey,
+    encoding: Encoding,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
     query: web::Query<SideWalletQuery>,
-) -> HttpResponse {
-    let wallet = address.into_inner();
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
 
     let depth = query.depth.unwrap_or(2);
     let threshold = query.threshold.unwrap_or(0.10);
     let limit = query.limit.unwrap_or(15);
-    let bootstrap = query.bootstrap.unwrap_or(true);
-    let bootstrap_limit = query.bootstrap_limit.unwrap_or(25).min(100);
+    // Bootstrap ingests over RPC, so it needs write scope. Read-only keys default to
+    // analysing what is already stored and get 403 if they ask for ingestion.
+    let can_ingest = auth.has_scope(Scope::Write);
+    if query.bootstrap == Some(true) || query.cex_bootstrap_limit.is_some_and(|l| l > 0) {
+        auth.require(Scope::Write)?;
+    }
+    let bootstrap = query.bootstrap(can_ingest);
+    let bootstrap_limit = query.bootstrap_limit();
     let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
     let cex_hops = query.cex_hops.unwrap_or(true);
-    let cex_bootstrap_limit = query.cex_bootstrap_limit.unwrap_or(15).min(100);
+    let cex_bootstrap_limit = query.cex_bootstrap_limit(can_ingest);
+    let persist = query.persist.unwrap_or(true);
+    let direction =
+        TraversalDirection::parse(query.direction.as_deref()).map_err(ApiError::bad_request)?;
+    let stop_at_hubs = query.stop_at_hubs.unwrap_or(true);
+    let params = SideWalletParams {
+        depth,
+        threshold,
+        limit,
+        lookback_days,
+        cex_hops,
+        direction,
+        stop_at_hubs,
+    };
+    // Projection runs on the way out, so the cache keeps full results.
+    let projection = FieldProjection::parse(query.fields.as_deref());
+    let candidate_lists = ["side_wallets", "cex_funded_wallets"];
+    if persist && !query.skip_cache.unwrap_or(false) {
+        if let Some(mut cached) = state.side_wallet_cache.get(&wallet, &params).await {
+            cached["cached"] = json!(true);
+            projection.apply(&mut cached, &candidate_lists);
+            return encoding.ok(&cached);
+        }
+    }
+    // Labels live in the real store even when the analysis runs on scratch storage.
+    let labels_db = Arc::clone(&state.db_manager);
+    let scratch;
+    let state: &ApiState = if persist {
+        &state
+    } else {
+        scratch = state
+            .with_scratch_storage(&wallet, depth, since_epoch_from_days(lookback_days))
+            .await?;
+        &scratch
+    };
 
     // Bootstrap main wallet: fetch recent signatures, parse transactions, and persist events/relationships.
-    let mut bootstrap_stats = BootstrapStats {
-        wallet: wallet.clone(),
-        signatures: 0,
-        parsed_ok: 0,
-        parsed_failed: 0,
-        persisted_failed: 0,
-    };
+    let mut bootstrap_stats = BootstrapStats::new(&wallet);
     let mut bootstrap_errors: Vec<String> = Vec::new();
 
     if bootstrap {
-        match state
-            .rpc_client
-            .get_signatures(&wallet, bootstrap_limit)
-            .await
-        {
-            Ok(sigs) => {
-                bootstrap_stats.signatures = sigs.len();
-                for s in sigs {
-                    match state
-                        .tx_handler
-                        .process_transaction(&s.signature, None)
-                        .await
-                    {
-                        Ok(tx) => {
-                            bootstrap_stats.parsed_ok += 1;
-                            if let Err(e) = state.transfer_analytics.analyze_transaction(&tx).await
-                            {
-                                bootstrap_stats.persisted_failed += 1;
-                                if bootstrap_errors.len() < 3 {
-                                    bootstrap_errors
-                                        .push(format!("persist {}: {}", s.signature, e));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            bootstrap_stats.parsed_failed += 1;
-                            if bootstrap_errors.len() < 3 {
-                                bootstrap_errors.push(format!("parse {}: {}", s.signature, e));
-                            }
+        let since_epoch = since_epoch_from_days(lookback_days);
+        match signatures_since(state, &wallet, bootstrap_limit, since_epoch).await {
+            Ok(signatures) => {
+                bootstrap_stats.signatures = signatures.len();
+                for (signature, outcome) in ingest_signatures(state, signatures).await {
+                    if let Some(e) = bootstrap_stats.record(&outcome) {
+                        if bootstrap_errors.len() < 3 {
+                            bootstrap_errors.push(format!(
+                                "{} {}: {}",
+                                outcome.stage(),
+                                signature,
+                                e
+                            ));
                         }
                     }
                 }
@@ -992,26 +1856,36 @@ async fn find_side_wallets(
                 bootstrap_errors.push(format!("get_signatures: {}", e));
             }
         }
+        if bootstrap_stats.parsed_ok > 0 {
+            state.side_wallet_cache.invalidate_wallet(&wallet).await;
+        }
     }
 
-    let candidates =
-        match compute_side_wallets(&state, &wallet, depth, threshold, limit, lookback_days).await {
-            Ok(v) => v,
-            Err(e) => {
-                return HttpResponse::InternalServerError().json(json!({
-                    "error": e
-                }));
-            }
-        };
+    let SideWalletSearch {
+        mut candidates,
+        sampled,
+    } = compute_side_wallets(state, &wallet, &params)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let mut addresses: Vec<String> = candidates.iter().map(|c| c.address.clone()).collect();
+    let balances = fetch_sol_balances(state, &addresses).await;
+    addresses.push(wallet.clone());
+    let mut labels = label_names(&labels_db, &addresses).await?;
+    for c in &mut candidates {
+        c.balance_sol = balances.get(&c.address).copied();
+        c.labels = labels.remove(&c.address).unwrap_or_default();
+    }
 
     let (cex_candidates, cex_bootstrap) = if cex_hops {
-        compute_cex_hops(&state, &wallet, lookback_days, cex_bootstrap_limit, 10).await
+        compute_cex_hops(state, &wallet, lookback_days, cex_bootstrap_limit, 10).await
     } else {
         (Vec::new(), Vec::new())
     };
 
-    HttpResponse::Ok().json(json!({
+    let mut response = json!({
         "main_wallet": wallet,
+        "main_wallet_labels": labels.remove(&wallet).unwrap_or_default(),
         "side_wallets": candidates,
         "cex_hops_enabled": cex_hops,
         "cex_funded_wallets": cex_candidates,
@@ -1019,193 +1893,4160 @@ async fn find_side_wallets(
         "cex_bootstrap_stats": cex_bootstrap,
         "confidence_threshold": threshold,
         "analysis_depth": depth,
+        "direction": direction,
+        "stop_at_hubs": stop_at_hubs,
+        "sampled": sampled,
+        "graph_cap": state.graph_engine.graph_cap(),
         "lookback_days": lookback_days,
+        "scoring_weights": side_wallet_scoring(),
         "bootstrap": bootstrap,
         "bootstrap_stats": bootstrap_stats,
+        "persisted": persist,
+        "cached": false,
         "bootstrap_errors": bootstrap_errors,
         "message": if candidates.is_empty() && cex_candidates.is_empty() {
             "No candidates yet. Try increasing bootstrap_limit/cex_bootstrap_limit or lookback_days."
         } else {
             "OK"
         }
-    }))
+    });
+    if persist {
+        state
+            .side_wallet_cache
+            .insert(&wallet, &params, response.clone())
+            .await;
+    }
+    projection.apply(&mut response, &candidate_lists);
+    encoding.ok(&response)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::core::enhanced_parser::{EnhancedTransaction, SolTransfer, TransactionType};
-    use std::collections::HashSet;
-    use std::time::{SystemTime, UNIX_EPOCH};
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SwapQuery {
+    /// Only return swaps at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Max results returned
+    pub limit: Option<usize>,
+}
 
-    fn now_epoch() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-    }
+/// List decoded DEX swaps executed by a wallet (newest first).
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/swaps",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), SwapQuery),
+    responses(
+        (status = 200, description = "Decoded DEX swaps (`swaps`), newest first"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_swaps(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<SwapQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
 
-    fn sol_transfer(
-        from: &str,
-        to: &str,
-        amount_sol: f64,
-        instruction_index: usize,
-    ) -> SolTransfer {
-        SolTransfer {
-            from: from.to_string(),
-            to: to.to_string(),
-            amount_lamports: (amount_sol * 1_000_000_000.0) as u64,
-            amount_sol,
-            instruction_index,
-            transfer_type: "system".to_string(),
-        }
-    }
+    let swaps = state
+        .db_manager
+        .get_swaps_for_wallet(&wallet, query.since_epoch, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "count": swaps.len(),
+        "swaps": swaps
+    })))
+}
 
-    fn tx(
-        signature: &str,
-        slot: u64,
-        block_time: u64,
-        sol_transfers: Vec<SolTransfer>,
-    ) -> EnhancedTransaction {
-        EnhancedTransaction {
-            signature: signature.to_string(),
-            slot,
-            block_time: Some(block_time),
-            fee: 0,
-            success: true,
-            error: None,
-            accounts: Vec::new(),
-            signers: Vec::new(),
-            writable_accounts: Vec::new(),
-            sol_transfers,
-            token_transfers: Vec::new(),
-            balance_changes: Vec::new(),
-            programs_called: Vec::new(),
-            program_names: Vec::new(),
-            tx_type: TransactionType::Unknown,
-            is_versioned: false,
-        }
-    }
+/// List the wallet's swaps that were sandwiched (largest estimated loss first).
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/sandwiched",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), SwapQuery),
+    responses(
+        (status = 200, description = "Sandwiches where the wallet was the victim (`sandwiches`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_sandwiched(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<SwapQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
 
-    async fn test_state() -> ApiState {
-        let db_manager = Arc::new(DatabaseManager::new("memory").await.unwrap());
-        db_manager.init_schema().await.unwrap();
+    let (swaps_checked, sandwiches) =
+        wallet_sandwiches(&state, &wallet, query.since_epoch, limit).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "swaps_checked": swaps_checked,
+        "count": sandwiches.len(),
+        "sandwiches": sandwiches
+    })))
+}
 
-        let rpc_client = Arc::new(SolanaRpcClient::new("http://localhost".to_string()));
-        let tx_handler = Arc::new(TransactionHandler::new(Arc::clone(&rpc_client)));
-        let transfer_analytics = Arc::new(TransferAnalytics::new(Arc::clone(&db_manager)));
+/// Check the wallet's latest `limit` swaps against every swap through the same pool
+/// within `SANDWICH_SLOT_WINDOW` slots. Returns the number of swaps checked and the
+/// sandwiches where the wallet is the victim.
+async fn wallet_sandwiches(
+    state: &ApiState,
+    wallet: &str,
+    since_epoch: Option<u64>,
+    limit: usize,
+) -> BeastResult<(usize, Vec<SandwichEvent>)> {
+    let own = state
+        .db_manager
+        .get_swaps_for_wallet(wallet, since_epoch, limit)
+        .await?;
+    let neighbourhoods: Vec<_> = stream::iter(own.iter().filter_map(|swap| {
+        let pool = swap.pool_address.clone()?;
+        let db = state.db_manager.clone();
+        let slot = swap.slot;
+        Some(async move {
+            db.get_pool_swaps_in_slots(
+                &pool,
+                slot.saturating_sub(SANDWICH_SLOT_WINDOW),
+                slot + SANDWICH_SLOT_WINDOW,
+                200,
+            )
+            .await
+        })
+    }))
+    .buffered(bootstrap_concurrency())
+    .collect()
+    .await;
 
-        ApiState {
-            rpc_client,
-            tx_handler,
-            transfer_analytics,
-            db_manager,
+    let mut seen = HashSet::new();
+    let mut swaps = Vec::new();
+    for swap in own.iter().cloned().chain(
+        neighbourhoods
+            .into_iter()
+            .collect::<BeastResult<Vec<_>>>()?
+            .into_iter()
+            .flatten(),
+    ) {
+        if seen.insert((swap.signature.clone(), swap.event_index)) {
+            swaps.push(swap);
         }
     }
 
-    #[tokio::test]
-    async fn detects_side_wallets_from_graph_edges() {
-        let state = test_state().await;
-        let now = now_epoch();
+    let sandwiches = detect_sandwiches(&swaps)
+        .into_iter()
+        .filter(|s| s.victim == wallet)
+        .collect();
+    Ok((own.len(), sandwiches))
+}
 
-        let main = "MAIN";
-        let side1 = "SIDE1";
-        let side2 = "SIDE2";
+#[derive(Debug, Deserialize)]
+pub struct NftTradeQuery {
+    /// Only return sales at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Max results returned
+    pub limit: Option<usize>,
+}
 
-        let mut transfers_a = Vec::new();
-        for i in 0..10 {
-            transfers_a.push(sol_transfer(main, side1, 1.0, i));
-        }
-        let mut transfers_b = Vec::new();
-        for i in 0..10 {
-            transfers_b.push(sol_transfer(side1, side2, 1.0, i));
-        }
+/// List NFT marketplace sales where a wallet bought or sold (newest first).
+async fn get_wallet_nft_trades(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<NftTradeQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
 
-        let tx_a = tx("sig_main_side1", 1, now - 3600, transfers_a);
-        let tx_b = tx("sig_side1_side2", 2, now - 3500, transfers_b);
+    let trades = state
+        .db_manager
+        .get_nft_trades_for_wallet(&wallet, query.since_epoch, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "count": trades.len(),
+        "nft_trades": trades
+    })))
+}
 
-        state
-            .transfer_analytics
-            .analyze_transaction(&tx_a)
-            .await
-            .unwrap();
-        state
-            .transfer_analytics
-            .analyze_transaction(&tx_b)
-            .await
-            .unwrap();
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TransactionQuery {
+    /// Max signatures fetched for this page
+    pub limit: Option<u64>,
+    /// Start strictly before this signature (the previous page's `next_before`)
+    pub before: Option<String>,
+    /// Stop at this signature (exclusive)
+    pub until: Option<String>,
+    /// Drop signatures below this slot
+    pub min_slot: Option<u64>,
+    /// Drop signatures above this slot
+    pub max_slot: Option<u64>,
+    /// Drop signatures with a block time before this unix timestamp
+    pub min_block_time: Option<u64>,
+    /// Drop signatures with a block time after this unix timestamp
+    pub max_block_time: Option<u64>,
+    /// Keep only ingested transactions of this type (e.g. `TokenSwap`)
+    pub tx_type: Option<String>,
+    /// `processed`, `confirmed` or `finalized` (default: `RPC_COMMITMENT`)
+    #[param(value_type = Option<String>)]
+    pub commitment: Option<Commitment>,
+}
 
-        let candidates = compute_side_wallets(&state, main, 2, 0.10, 25, 30)
-            .await
-            .unwrap();
-        let addrs: HashSet<String> = candidates.iter().map(|c| c.address.clone()).collect();
+/// Validate a `tx_type` filter against the `TransactionType` variant names.
+fn parse_tx_type_filter(raw: Option<&str>) -> Result<Option<String>, String> {
+    let Some(raw) = raw.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    serde_json::from_value::<TransactionType>(json!(raw))
+        .map(|t| Some(format!("{:?}", t)))
+        .map_err(|_| format!("unknown tx_type '{}'", raw))
+}
 
-        assert!(addrs.contains(side1), "expected {} in {:?}", side1, addrs);
-        assert!(addrs.contains(side2), "expected {} in {:?}", side2, addrs);
+/// Validate a transfer `kind` filter (`sol` or `token`).
+fn parse_kind_filter(raw: Option<&str>) -> Result<Option<String>, String> {
+    match raw.map(|s| s.trim().to_ascii_lowercase()) {
+        None => Ok(None),
+        Some(k) if k.is_empty() => Ok(None),
+        Some(k) if k == "sol" || k == "token" => Ok(Some(k)),
+        Some(k) => Err(format!("unknown kind '{}' (expected sol or token)", k)),
     }
+}
 
-    #[tokio::test]
-    async fn detects_cex_hop_withdrawal_recipient() {
-        let state = test_state().await;
-        let now = now_epoch();
+/// One page of a wallet's signatures, newest first.
+#[derive(Debug, serde::Serialize)]
+struct TransactionPage {
+    transactions: Vec<crate::core::rpc_client::TransactionSignature>,
+    /// Pass as `before` to fetch the next (older) page; `None` once history is exhausted.
+    next_before: Option<String>,
+}
 
-        let main = "MAIN";
-        let deposit = "CEX_DEPOSIT";
-        let hot = "CEX_HOT";
-        let recipient = "RECIPIENT";
+/// Fetch a signature page and apply the slot/time filters. The cursor follows the
+/// raw page so filtering never skips or repeats signatures.
+async fn fetch_transaction_page(
+    state: &ApiState,
+    wallet: &str,
+    query: &TransactionQuery,
+) -> Result<TransactionPage, BeastError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 1000);
+    let rpc = query
+        .commitment
+        .map(|c| state.rpc_client.with_commitment(c));
+    let page = rpc
+        .as_ref()
+        .unwrap_or(&state.rpc_client)
+        .get_signatures_page(
+            wallet,
+            limit,
+            query.before.as_deref(),
+            query.until.as_deref(),
+        )
+        .await?;
 
-        let deposit_time = now - 2 * 86_400;
-        let sweep_time = deposit_time + 3600;
-        let withdraw_time = deposit_time + 86_400;
+    // Pages are newest first: once the oldest entry is below `min_slot` or
+    // `min_block_time`, older pages cannot match either.
+    let exhausted = page.len() < limit as usize
+        || page.last().is_some_and(|last| {
+            query.min_slot.is_some_and(|min| last.slot < min)
+                || query
+                    .min_block_time
+                    .is_some_and(|min| last.block_time > 0 && last.block_time < min)
+        });
+    let next_before = if exhausted {
+        None
+    } else {
+        page.last().map(|last| last.signature.clone())
+    };
 
-        let tx_deposit = tx(
-            "sig_deposit",
-            10,
-            deposit_time,
-            vec![sol_transfer(main, deposit, 50.0, 0)],
-        );
-        let tx_sweep = tx(
-            "sig_sweep",
-            11,
-            sweep_time,
-            vec![sol_transfer(deposit, hot, 49.0, 0)],
-        );
-        let tx_withdraw = tx(
-            "sig_withdraw",
-            12,
-            withdraw_time,
-            vec![sol_transfer(hot, recipient, 45.0, 0)],
-        );
+    let mut transactions: Vec<_> = page
+        .into_iter()
+        .filter(|sig| query.min_slot.is_none_or(|min| sig.slot >= min))
+        .filter(|sig| query.max_slot.is_none_or(|max| sig.slot <= max))
+        .filter(|sig| query.min_block_time.is_none_or(|min| sig.block_time >= min))
+        .filter(|sig| query.max_block_time.is_none_or(|max| sig.block_time <= max))
+        .collect();
 
-        state
-            .transfer_analytics
-            .analyze_transaction(&tx_deposit)
-            .await
-            .unwrap();
-        state
-            .transfer_analytics
-            .analyze_transaction(&tx_sweep)
-            .await
-            .unwrap();
-        state
-            .transfer_analytics
-            .analyze_transaction(&tx_withdraw)
-            .await
-            .unwrap();
+    // Types are only known for ingested transactions; others are dropped.
+    if let Some(tx_type) = query.tx_type.as_deref() {
+        let signatures: Vec<String> = transactions.iter().map(|s| s.signature.clone()).collect();
+        let types = state.db_manager.get_transaction_types(&signatures).await?;
+        transactions.retain(|sig| types.get(&sig.signature).map(String::as_str) == Some(tx_type));
+    }
 
-        let (cands, _bootstrap) = compute_cex_hops(&state, main, 30, 0, 10).await;
-        let cand = cands
-            .into_iter()
-            .find(|c| c.wallet == recipient)
-            .expect("expected cex-funded recipient");
+    Ok(TransactionPage {
+        transactions,
+        next_before,
+    })
+}
 
-        assert!(cand.score > 0.1);
-        assert!(
-            cand.paths
-                .iter()
-                .any(|p| p.deposit_wallet == deposit && p.hot_wallet == hot),
-            "expected path via deposit/hot; got {:?}",
-            cand.paths
-        );
+/// Page backwards through a wallet's signature history.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/transactions",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), TransactionQuery),
+    responses(
+        (status = 200, description = "Page of signatures (`transactions`) and the `next_before` cursor"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_transactions(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<TransactionQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let mut query = query.into_inner();
+    query.tx_type =
+        parse_tx_type_filter(query.tx_type.as_deref()).map_err(ApiError::bad_request)?;
+
+    let page = fetch_transaction_page(&state, &wallet, &query).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "commitment": query.commitment.unwrap_or(state.rpc_client.commitment()),
+        "filter": { "tx_type": query.tx_type },
+        "count": page.transactions.len(),
+        "transactions": page.transactions,
+        "next_before": page.next_before
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TransferQuery {
+    /// `sol` or `token`
+    pub kind: Option<String>,
+    /// Keep only transfers from transactions of this type (e.g. `TokenSwap`)
+    pub tx_type: Option<String>,
+    /// Only return transfers at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Max results returned
+    pub limit: Option<usize>,
+}
+
+/// List stored transfer events sent or received by a wallet (newest first).
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/transfers",
+    tag = "transfers",
+    params(("address" = String, Path, description = "Wallet address"), TransferQuery),
+    responses(
+        (status = 200, description = "Stored transfer events (`transfers`), newest first"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_transfers(
+    _auth: ApiKey,
+    encoding: Encoding,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<TransferQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let filters = parse_kind_filter(query.kind.as_deref()).and_then(|kind| {
+        parse_tx_type_filter(query.tx_type.as_deref()).map(|tx_type| (kind, tx_type))
+    });
+    let (kind, tx_type) = filters.map_err(ApiError::bad_request)?;
+
+    let transfers = state
+        .db_manager
+        .get_transfer_events_for_wallet(
+            &wallet,
+            kind.as_deref(),
+            tx_type.as_deref(),
+            query.since_epoch,
+            limit,
+        )
+        .await?;
+    encoding.ok(&json!({
+        "wallet": wallet,
+        "filter": { "kind": kind, "tx_type": tx_type },
+        "count": transfers.len(),
+        "transfers": transfers
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    /// `hour` or `day` (default)
+    pub bucket: Option<String>,
+    /// Only count transfers at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+}
+
+/// Transfer counts and volumes per hour or day, for time-series charts.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/activity",
+    tag = "transfers",
+    params(("address" = String, Path, description = "Wallet address"), ActivityQuery),
+    responses(
+        (status = 200, description = "Transfer counts and volumes per time bucket (`buckets`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_activity(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<ActivityQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let bucket = query.bucket.as_deref().unwrap_or("day");
+    let bucket_secs = match bucket {
+        "hour" => 3_600,
+        "day" => 86_400,
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "bucket must be hour or day, got {}",
+                other
+            )))
+        }
+    };
+
+    let buckets = state
+        .db_manager
+        .get_activity_histogram(&wallet, bucket_secs, query.since_epoch)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "bucket": bucket,
+        "bucket_secs": bucket_secs,
+        "buckets": buckets
+    })))
+}
+
+/// Lifetime totals for a wallet: first/last seen, SOL and token volume each way,
+/// transaction and counterparty counts. One aggregate query, no graph expansion.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/summary",
+    tag = "transfers",
+    params(("address" = String, Path, description = "Wallet address")),
+    responses(
+        (status = 200, description = "Lifetime transfer summary (`cached` when served from cache)"),
+        (status = 400, description = "Invalid address", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 404, description = "No stored transfers for the wallet", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_summary(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    match wallet_summary(&state, &wallet).await? {
+        Some(summary) => Ok(HttpResponse::Ok().json(summary)),
+        None => Err(ApiError::wallet_not_found(&wallet)),
+    }
+}
+
+/// The wallet's summary as JSON, from the side-wallet cache when fresh (same TTL,
+/// dropped on ingestion for the wallet).
+async fn wallet_summary(state: &ApiState, wallet: &str) -> BeastResult<Option<serde_json::Value>> {
+    if let Some(mut cached) = state.side_wallet_cache.get_summary(wallet).await {
+        cached["cached"] = json!(true);
+        return Ok(Some(cached));
+    }
+    let Some(summary) = state.db_manager.get_wallet_summary(wallet).await? else {
+        return Ok(None);
+    };
+    let mut body = json!(summary);
+    body["cached"] = json!(false);
+    state
+        .side_wallet_cache
+        .insert_summary(wallet, body.clone())
+        .await;
+    Ok(Some(body))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NetFlowQuery {
+    /// Only count transfers at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Max counterparties returned
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SimilarWalletsQuery {
+    /// Only profile transfers at or after this unix timestamp (default: 30 days ago)
+    pub since_epoch: Option<u64>,
+    /// Max wallets returned
+    pub limit: Option<usize>,
+    /// Max wallet profiles compared (most active first)
+    pub profile_limit: Option<usize>,
+}
+
+/// Wallets whose transfer patterns resemble the target's, linked or not.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/similar",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), SimilarWalletsQuery),
+    responses(
+        (status = 200, description = "Wallets ranked by behavioral similarity (`similar_wallets`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_similar_wallets(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<SimilarWalletsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let since_epoch = query
+        .since_epoch
+        .unwrap_or_else(|| since_epoch_from_days(30));
+    let limit = query.limit.unwrap_or(20).clamp(1, 200);
+    let profile_limit = query.profile_limit.unwrap_or(5_000).clamp(1, 50_000);
+
+    let target = state
+        .db_manager
+        .get_behavioral_profile(&wallet, Some(since_epoch))
+        .await?
+        .ok_or_else(|| ApiError::wallet_not_found(&wallet))?;
+    let profiles = state
+        .db_manager
+        .get_all_behavioral_profiles(Some(since_epoch), profile_limit)
+        .await?;
+    let compared = profiles.iter().filter(|p| p.wallet != wallet).count();
+    let similar = rank_similar_wallets(&target, profiles, limit);
+
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "since_epoch": since_epoch,
+        "profile": target,
+        "wallets_compared": compared,
+        "similar_wallets": similar
+    })))
+}
+
+/// Who a wallet net-accumulates from and net-distributes to.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/net-flows",
+    tag = "transfers",
+    params(("address" = String, Path, description = "Wallet address"), NetFlowQuery),
+    responses(
+        (status = 200, description = "Per-counterparty inbound, outbound and net amounts (`counterparties`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_net_flows(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<NetFlowQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    let flows = state
+        .db_manager
+        .get_net_flow_by_counterparty(&wallet, query.since_epoch, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "count": flows.len(),
+        "net_flows": flows
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StructuringQuery {
+    /// Only consider transfers at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Same-amount transfers needed inside one window to flag it
+    pub min_count: Option<usize>,
+    /// Window length in hours
+    pub window_hours: Option<u64>,
+    /// Drop flags whose confidence is below this (0-1)
+    pub min_confidence: Option<f64>,
+}
+
+/// Flag bursts of near-identical outbound SOL transfers (structuring).
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/structuring",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), StructuringQuery),
+    responses(
+        (status = 200, description = "Flagged same-amount transfer bursts (`flagged`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_structuring(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<StructuringQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let min_count = query.min_count.unwrap_or(5).clamp(2, 1000);
+    let window_hours = query.window_hours.unwrap_or(24).clamp(1, 24 * 30);
+    let min_confidence = query.min_confidence.unwrap_or(0.0).clamp(0.0, 1.0);
+
+    let buckets = state
+        .db_manager
+        .get_amount_distribution(&wallet, query.since_epoch)
+        .await?;
+    let flagged =
+        detect_structuring_filtered(&buckets, min_count, window_hours * 3600, min_confidence);
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "min_count": min_count,
+        "window_hours": window_hours,
+        "min_confidence": min_confidence,
+        "amount_buckets": buckets.len(),
+        "flagged": flagged
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RiskQuery {
+    /// Days of transfer history considered
+    pub lookback_days: Option<u32>,
+}
+
+/// Lookback (days) of the risk scores stored for the high-risk listing; the
+/// `risk` default.
+const RISK_SCORE_WINDOW_DAYS: u32 = 30;
+
+/// Risk factor weights from `RISK_WEIGHT_*`, read once.
+fn risk_weights() -> RiskWeights {
+    static WEIGHTS: OnceLock<RiskWeights> = OnceLock::new();
+    *WEIGHTS.get_or_init(RiskWeights::from_env)
+}
+
+/// Weighted wallet risk score with the contribution of each factor. Wallet age
+/// comes from the first transfer ever stored for the wallet and its
+/// counterparties, not just the lookback window. Scores over the
+/// `RISK_SCORE_WINDOW_DAYS` lookback are stored as the wallet's latest for
+/// `/analysis/high-risk-wallets`; a failed write is logged and does not fail the
+/// assessment.
+async fn assess_wallet_risk(
+    state: &ApiState,
+    wallet: &str,
+    lookback_days: u32,
+) -> BeastResult<RiskAssessment> {
+    let since_epoch = since_epoch_from_days(lookback_days);
+    let buckets = state
+        .db_manager
+        .get_amount_distribution(wallet, Some(since_epoch))
+        .await?;
+    let structuring = detect_structuring(&buckets, 5, 24 * 3600);
+    let flows = state
+        .db_manager
+        .get_net_flow_by_counterparty(wallet, Some(since_epoch), 500)
+        .await?;
+    let mut wallets: Vec<String> = flows.iter().map(|f| f.counterparty.clone()).collect();
+    wallets.push(wallet.to_string());
+    let first_seen = state.db_manager.get_first_seen_epochs(&wallets).await?;
+    let fresh_counterparties = flows
+        .iter()
+        .filter(|f| {
+            first_seen
+                .get(&f.counterparty)
+                .is_some_and(|&t| age_days(t) < FRESH_WALLET_DAYS)
+        })
+        .count();
+    let age = WalletAge {
+        age_days: first_seen.get(wallet).map(|&t| age_days(t)),
+        fresh_counterparty_share: if flows.is_empty() {
+            0.0
+        } else {
+            fresh_counterparties as f64 / flows.len() as f64
+        },
+    };
+    let risk = assess_risk(&structuring, &flows, age, risk_weights());
+    // Only scores over the same window are comparable in the high-risk listing.
+    if lookback_days == RISK_SCORE_WINDOW_DAYS {
+        if let Err(e) = state
+            .db_manager
+            .set_wallet_risk_score(wallet, risk.score)
+            .await
+        {
+            tracing::warn!("Failed to store risk score for {}: {}", wallet, e);
+        }
+    }
+    Ok(risk)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HighRiskQuery {
+    /// Lowest stored risk score listed (0-1, default: 0.5)
+    pub min_score: Option<f64>,
+    /// Wallets to skip (highest score first)
+    pub offset: Option<usize>,
+    /// Max wallets returned (default: 20, max: 200)
+    pub limit: Option<usize>,
+}
+
+/// Wallets ranked by their latest stored risk score, as `[address, score, computed_at]`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/analysis/high-risk-wallets",
+    tag = "wallet",
+    params(HighRiskQuery),
+    responses(
+        (status = 200, description = "`wallets` as `[address, score, computed_at]`, highest score first; scores cover the last `window_days` days; `has_more` when another page exists"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_high_risk_wallets(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<HighRiskQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let min_score = query.min_score.unwrap_or(0.5);
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(ApiError::bad_request("min_score must be between 0 and 1"));
+    }
+    let offset = query.offset.unwrap_or(0).min(100_000);
+    let limit = query.limit.unwrap_or(20).clamp(1, 200);
+
+    // One extra row tells whether another page exists.
+    let mut scores = state
+        .db_manager
+        .get_high_risk_wallets(min_score, offset, limit + 1)
+        .await?;
+    let has_more = scores.len() > limit;
+    scores.truncate(limit);
+
+    Ok(HttpResponse::Ok().json(json!({
+        "min_score": min_score,
+        "offset": offset,
+        "limit": limit,
+        "has_more": has_more,
+        "window_days": RISK_SCORE_WINDOW_DAYS,
+        "wallets": scores
+            .iter()
+            .map(|s| json!([s.wallet, s.score, s.computed_at]))
+            .collect::<Vec<_>>()
+    })))
+}
+
+/// Explainable risk score built from stored transfer patterns.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/risk",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), RiskQuery),
+    responses(
+        (status = 200, description = "Risk `score` with per-factor contributions (`factors`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_risk(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<RiskQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let lookback_days = query
+        .lookback_days
+        .unwrap_or(RISK_SCORE_WINDOW_DAYS)
+        .clamp(1, 365);
+
+    let risk = assess_wallet_risk(&state, &wallet, lookback_days).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "lookback_days": lookback_days,
+        "stored": lookback_days == RISK_SCORE_WINDOW_DAYS,
+        "risk_score": risk.score,
+        "factors": risk.factors
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConnectionQuery {
+    /// Connections to skip (strongest first)
+    pub offset: Option<usize>,
+    /// Max connections returned
+    pub limit: Option<usize>,
+}
+
+/// Page through a wallet's relationship edges, strongest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/connections",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), ConnectionQuery),
+    responses(
+        (status = 200, description = "Page of direct connections (`connections`) with `total`"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_connections(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<ConnectionQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    let page = state
+        .db_manager
+        .get_wallet_connections_page(&wallet, offset, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "offset": offset,
+        "count": page.connections.len(),
+        "has_more": page.has_more,
+        "connections": page.connections
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HoldingsQuery {
+    /// `processed`, `confirmed` or `finalized` (default: `RPC_COMMITMENT`)
+    pub commitment: Option<Commitment>,
+}
+
+/// Current SOL and token balances for a wallet.
+async fn get_wallet_holdings(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<HoldingsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+
+    let holdings = match query.commitment {
+        Some(commitment) => state.holdings.get_holdings_at(&wallet, commitment).await?,
+        None => state.holdings.get_holdings(&wallet).await?,
+    };
+    Ok(HttpResponse::Ok().json(holdings))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvestigateQuery {
+    /// Days of transfer history considered
+    pub lookback_days: Option<u32>,
+}
+
+/// Upper bound for one section of `/investigate`.
+const INVESTIGATE_SECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, serde::Serialize)]
+struct InvestigateSection {
+    /// `ok` or `error`
+    status: &'static str,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run one section with a timeout; a failure is reported in the section only.
+async fn investigate_section<T, E, F>(section: F) -> InvestigateSection
+where
+    T: serde::Serialize,
+    E: std::fmt::Display,
+    F: std::future::Future<Output = Result<T, E>>,
+{
+    let started = std::time::Instant::now();
+    let outcome = match tokio::time::timeout(INVESTIGATE_SECTION_TIMEOUT, section).await {
+        Ok(Ok(data)) => serde_json::to_value(data).map_err(|e| e.to_string()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timed out".to_string()),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        Ok(data) => InvestigateSection {
+            status: "ok",
+            latency_ms,
+            data: Some(data),
+            error: None,
+        },
+        Err(e) => InvestigateSection {
+            status: "error",
+            latency_ms,
+            data: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// Holdings, behavioral profile, top counterparties, side-wallet candidates,
+/// detected patterns and risk for `wallet`, computed concurrently from stored data
+/// (holdings come from the RPC). Side wallets use the `/side-wallets` defaults
+/// without bootstrap.
+async fn investigate_wallet(
+    state: &ApiState,
+    wallet: &str,
+    lookback_days: u32,
+) -> serde_json::Value {
+    let since_epoch = since_epoch_from_days(lookback_days);
+    let side_wallet_params = SideWalletParams {
+        depth: 2,
+        threshold: 0.10,
+        limit: 15,
+        lookback_days,
+        cex_hops: false,
+        direction: TraversalDirection::Both,
+        stop_at_hubs: true,
+    };
+    let (holdings, profile, counterparties, side_wallets, patterns, risk) = tokio::join!(
+        investigate_section(state.holdings.get_holdings(wallet)),
+        investigate_section(
+            state
+                .db_manager
+                .get_behavioral_profile(wallet, Some(since_epoch))
+        ),
+        investigate_section(async {
+            state
+                .db_manager
+                .get_wallet_connections_page(wallet, 0, 10)
+                .await
+                .map(|page| page.connections)
+        }),
+        investigate_section(async {
+            compute_side_wallets(state, wallet, &side_wallet_params)
+                .await
+                .map(|search| search.candidates)
+        }),
+        investigate_section(async {
+            let buckets = state
+                .db_manager
+                .get_amount_distribution(wallet, Some(since_epoch))
+                .await?;
+            let (_, sandwiches) = wallet_sandwiches(state, wallet, Some(since_epoch), 50).await?;
+            Ok::<_, BeastError>(json!({
+                "structuring": detect_structuring(&buckets, 5, 24 * 3600),
+                "sandwiched": sandwiches,
+            }))
+        }),
+        investigate_section(async {
+            assess_wallet_risk(state, wallet, lookback_days)
+                .await
+                .map(|risk| json!({ "risk_score": risk.score, "factors": risk.factors }))
+        }),
+    );
+
+    json!({
+        "wallet": wallet,
+        "lookback_days": lookback_days,
+        "sections": {
+            "holdings": holdings,
+            "profile": profile,
+            "top_counterparties": counterparties,
+            "side_wallets": side_wallets,
+            "patterns": patterns,
+            "risk": risk,
+        }
+    })
+}
+
+/// Every per-wallet signal in one call; each section succeeds or fails on its own.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/investigate",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), InvestigateQuery),
+    responses(
+        (status = 200, description = "`sections` (holdings, profile, top_counterparties, side_wallets, patterns, risk), each with `status` and `data` or `error`"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn investigate(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<InvestigateQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
+
+    Ok(HttpResponse::Ok().json(investigate_wallet(&state, &wallet, lookback_days).await))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CompareQuery {
+    /// First wallet address
+    pub a: String,
+    /// Second wallet address
+    pub b: String,
+    /// Days of transfer history considered
+    pub lookback_days: Option<u32>,
+}
+
+/// Pairwise link evidence between two wallets, as served by `/compare`.
+#[derive(Debug, serde::Serialize)]
+struct WalletComparison {
+    a: String,
+    b: String,
+    lookback_days: u32,
+    /// `likely_linked`, `possibly_linked` or `no_link_found`
+    verdict: &'static str,
+    /// Human-readable evidence behind the verdict, strongest first.
+    reasons: Vec<String>,
+    /// Wallets that sent to both.
+    shared_funders: Vec<SharedWalletSignal>,
+    /// Wallets among both wallets' top counterparties.
+    shared_counterparties: Vec<String>,
+    temporal_overlap: TemporalOverlap,
+    /// `behavioral_similarity` of the two profiles; `None` if either has no transfers.
+    behavioral_similarity: Option<f64>,
+}
+
+/// Shared funders, shared counterparties, temporal overlap and behavioral
+/// similarity of `a` and `b`, using the same signals and thresholds as side-wallet
+/// scoring. Three or more independent signals make a likely link; one shared
+/// funder on its own is at most a possible link.
+async fn compare_wallets(
+    state: &ApiState,
+    a: &str,
+    b: &str,
+    lookback_days: u32,
+) -> BeastResult<WalletComparison> {
+    let since_epoch = Some(since_epoch_from_days(lookback_days));
+    let db = &state.db_manager;
+    let (mut funders, counterparties_a, counterparties_b, overlap, profile_a, profile_b) = tokio::try_join!(
+        db.get_shared_inbound_senders(a, b, since_epoch, 50),
+        db.get_top_counterparties(a, since_epoch, 80),
+        db.get_top_counterparties(b, since_epoch, 80),
+        db.get_temporal_overlap(a, b, since_epoch, 5),
+        db.get_behavioral_profile(a, since_epoch),
+        db.get_behavioral_profile(b, since_epoch),
+    )?;
+
+    // Each signal must be independent evidence, so a shared funder is not also a
+    // shared counterparty.
+    let funder_wallets: HashSet<&str> = funders.iter().map(|s| s.wallet.as_str()).collect();
+    let of_b: HashSet<&str> = counterparties_b.iter().map(|s| s.wallet.as_str()).collect();
+    let mut shared_counterparties: Vec<String> = counterparties_a
+        .iter()
+        .map(|s| s.wallet.as_str())
+        .filter(|w| of_b.contains(w) && !funder_wallets.contains(w) && *w != a && *w != b)
+        .map(str::to_string)
+        .collect();
+    shared_counterparties.sort();
+    shared_counterparties.dedup();
+    funders.truncate(5);
+    // Timing and behavior only say something about the wallets' own activity
+    // when both have sent funds; otherwise they only reflect who paid them (a
+    // shared funder paying both at once looks synchronized and alike).
+    let both_active = [&profile_a, &profile_b]
+        .iter()
+        .all(|p| p.as_ref().is_some_and(|p| p.outbound_count > 0));
+    let behavioral_similarity = profile_a
+        .zip(profile_b)
+        .map(|(pa, pb)| behavioral_similarity(&pa, &pb));
+
+    let mut reasons = Vec::new();
+    if let Some(top) = funders.first() {
+        reasons.push(format!(
+            "Shared inbound funder: {}",
+            format_signal(&top.wallet, top.count, top.last_seen_epoch)
+        ));
+    }
+    if overlap.same_block_count > 0 {
+        reasons.push(format!(
+            "Same-block activity ({} shared blocks)",
+            overlap.same_block_count
+        ));
+    } else if both_active && overlap.overlap_ratio > 0.15 {
+        reasons.push(format!(
+            "Synchronized activity windows ({:.1}% overlap)",
+            overlap.overlap_ratio * 100.0
+        ));
+    }
+    if let Some(similarity) = behavioral_similarity.filter(|s| both_active && *s > 0.65) {
+        reasons.push(format!(
+            "Behavioral pattern match (similarity: {:.2})",
+            similarity
+        ));
+    }
+    if !shared_counterparties.is_empty() {
+        reasons.push(format!(
+            "Shared counterparties: {}",
+            shared_counterparties.len()
+        ));
+    }
+    let verdict = match reasons.len() {
+        0 => "no_link_found",
+        1 | 2 => "possibly_linked",
+        _ => "likely_linked",
+    };
+
+    Ok(WalletComparison {
+        a: a.to_string(),
+        b: b.to_string(),
+        lookback_days,
+        verdict,
+        reasons,
+        shared_funders: funders,
+        shared_counterparties,
+        temporal_overlap: overlap,
+        behavioral_similarity,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/compare",
+    tag = "wallet",
+    params(CompareQuery),
+    responses(
+        (status = 200, description = "`verdict` and `reasons` with the shared funders, shared counterparties, temporal overlap and behavioral similarity behind them"),
+        (status = 400, description = "Invalid or identical addresses", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn compare(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<CompareQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let (a, b) = (query.a.trim(), query.b.trim());
+    validate_address(a)?;
+    validate_address(b)?;
+    if a == b {
+        return Err(ApiError::bad_request("a and b must be different wallets"));
+    }
+    let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
+
+    Ok(HttpResponse::Ok().json(compare_wallets(&state, a, b, lookback_days).await?))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClusterQuery {
+    /// How many days of transfer events to build the graph from
+    pub lookback_days: Option<u32>,
+    /// Max transfer events loaded into the graph
+    pub event_limit: Option<usize>,
+    /// Ignore edges that moved less SOL than this; with `token_weight`, edges that
+    /// moved tokens are kept (default: 0)
+    pub min_sol: Option<f64>,
+    /// Ignore edges with fewer transfers than this (default: 1)
+    pub min_tx_count: Option<u32>,
+    /// Also weight edges by token volume per mint, not just SOL (default: false)
+    pub token_weight: Option<bool>,
+    /// Comma-separated member fields to return (e.g. `wallet,labels`); default all
+    pub fields: Option<String>,
+}
+
+/// Community detected for a wallet over recent transfer events.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/cluster",
+    tag = "cluster",
+    params(("address" = String, Path, description = "Wallet address"), ClusterQuery),
+    responses(
+        (status = 200, description = "Wallets clustered with the target and the evidence linking them"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_cluster(
+    _auth: ApiKey,
+    encoding: Encoding,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<ClusterQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
+    let event_limit = query.event_limit.unwrap_or(10_000).clamp(1, 50_000);
+    let (min_sol, min_tx_count) = edge_thresholds(query.min_sol, query.min_tx_count)?;
+
+    let mut cluster = state
+        .graph_engine
+        .wallet_cluster(
+            &wallet,
+            Some(since_epoch_from_days(lookback_days)),
+            event_limit,
+            min_sol,
+            min_tx_count,
+            query.token_weight.unwrap_or(false),
+        )
+        .await?;
+    let addresses: Vec<String> = cluster.members.iter().map(|m| m.wallet.clone()).collect();
+    let balances = fetch_sol_balances(&state, &addresses).await;
+    let mut labels = label_names(&state.db_manager, &addresses).await?;
+    for m in &mut cluster.members {
+        m.balance_sol = balances.get(&m.wallet).copied();
+        m.labels = labels.remove(&m.wallet).unwrap_or_default();
+    }
+    let mut body = serde_json::to_value(&cluster)
+        .map_err(|e| ApiError::internal(format!("Failed to encode cluster: {}", e)))?;
+    FieldProjection::parse(query.fields.as_deref()).apply(&mut body, &["members"]);
+    encoding.ok(&body)
+}
+
+/// Label names per address, for annotating analysis results.
+async fn label_names(
+    db: &DatabaseManager,
+    addresses: &[String],
+) -> BeastResult<HashMap<String, Vec<String>>> {
+    Ok(db
+        .get_labels_for_wallets(addresses)
+        .await?
+        .into_iter()
+        .map(|(wallet, labels)| (wallet, labels.into_iter().map(|l| l.label).collect()))
+        .collect())
+}
+
+/// Labels investigators have put on the wallet.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/labels",
+    tag = "labels",
+    params(("address" = String, Path, description = "Wallet address")),
+    responses(
+        (status = 200, description = "The wallet's labels (`labels`) with note, author and timestamps"),
+        (status = 400, description = "Invalid address", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_wallet_labels(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let labels = state.db_manager.get_labels(&wallet).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "count": labels.len(),
+        "labels": labels
+    })))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetLabelRequest {
+    /// Label name (1-64 characters, stored lowercase), e.g. `exchange` or `flagged`
+    pub label: String,
+    /// Free-text context (up to 1000 characters)
+    pub note: Option<String>,
+    /// Who is setting the label
+    pub author: Option<String>,
+}
+
+/// Add a label to the wallet, or update the note and author of an existing one.
+#[utoipa::path(
+    post,
+    path = "/api/v1/wallet/{address}/labels",
+    tag = "labels",
+    params(("address" = String, Path, description = "Wallet address")),
+    request_body = SetLabelRequest,
+    responses(
+        (status = 201, description = "Label added (`outcome` is `inserted`)"),
+        (status = 200, description = "Existing label's note and author updated (`outcome` is `updated`)"),
+        (status = 400, description = "Invalid address, label, note or author", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 403, description = "Key lacks admin scope", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn set_wallet_label(
+    _auth: RequireScope<Admin>,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    body: web::Json<SetLabelRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let body = body.into_inner();
+    let label = body.label.trim().to_lowercase();
+    if label.is_empty() || label.chars().count() > 64 || label.chars().any(char::is_control) {
+        return Err(ApiError::bad_request(
+            "label must be 1-64 characters without control characters",
+        ));
+    }
+    let note = body
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    if note.is_some_and(|n| n.chars().count() > 1000) {
+        return Err(ApiError::bad_request(
+            "note must be at most 1000 characters",
+        ));
+    }
+    let author = body
+        .author
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty());
+    if author.is_some_and(|a| a.chars().count() > 64) {
+        return Err(ApiError::bad_request(
+            "author must be at most 64 characters",
+        ));
+    }
+
+    let outcome = state
+        .db_manager
+        .set_label(&wallet, &label, note, author)
+        .await?;
+    // Labels show up in other wallets' cached side-wallet results too.
+    state.side_wallet_cache.clear().await;
+
+    let body = json!({
+        "wallet": wallet,
+        "label": label,
+        "note": note,
+        "author": author,
+        "outcome": outcome
+    });
+    Ok(match outcome {
+        StoredOutcome::Inserted => HttpResponse::Created().json(body),
+        StoredOutcome::Updated => HttpResponse::Ok().json(body),
+    })
+}
+
+/// Remove a label from the wallet.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/wallet/{address}/labels/{label}",
+    tag = "labels",
+    params(
+        ("address" = String, Path, description = "Wallet address"),
+        ("label" = String, Path, description = "Label name (case-insensitive)"),
+    ),
+    responses(
+        (status = 200, description = "Label removed"),
+        (status = 400, description = "Invalid address", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 403, description = "Key lacks admin scope", body = ErrorBody),
+        (status = 404, description = "The wallet has no such label", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn delete_wallet_label(
+    _auth: RequireScope<Admin>,
+    state: web::Data<ApiState>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let (wallet, label) = path.into_inner();
+    validate_address(&wallet)?;
+    let label = label.trim().to_lowercase();
+    if !state.db_manager.remove_label(&wallet, &label).await? {
+        return Err(ApiError::not_found(format!(
+            "wallet {} has no label '{}'",
+            wallet, label
+        )));
+    }
+    state.side_wallet_cache.clear().await;
+    Ok(HttpResponse::Ok().json(json!({ "wallet": wallet, "label": label, "removed": true })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphExportQuery {
+    /// How many days of transfer events to build the graph from
+    pub lookback_days: Option<u32>,
+    /// Max transfer events loaded into the graph
+    pub event_limit: Option<usize>,
+    /// Hops from the wallet included in the export
+    pub depth: Option<usize>,
+}
+
+/// Build the wallet's transfer neighbourhood and render it with `render`.
+async fn export_wallet_graph(
+    state: &ApiState,
+    wallet: &str,
+    query: &GraphExportQuery,
+    content_type: &str,
+    render: fn(&WalletGraph, &NodeAttributeMap) -> String,
+) -> Result<HttpResponse, ApiError> {
+    let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
+    let event_limit = query.event_limit.unwrap_or(10_000).clamp(100, 100_000);
+    let depth = query.depth.unwrap_or(2).clamp(1, 4);
+
+    let graph = state
+        .graph_engine
+        .wallet_neighborhood(
+            wallet,
+            depth,
+            Some(since_epoch_from_days(lookback_days)),
+            event_limit,
+        )
+        .await?;
+    let attributes: NodeAttributeMap = graph
+        .nodes()
+        .filter(|node| state.exchange_registry.is_exchange(node))
+        .map(|node| {
+            (
+                node.clone(),
+                NodeAttributes {
+                    is_exchange: true,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(("X-Graph-Sampled", graph.is_sampled().to_string()))
+        .body(render(&graph, &attributes)))
+}
+
+/// Wallet transfer neighbourhood as GraphML (Gephi).
+async fn export_wallet_graphml(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<GraphExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    export_wallet_graph(
+        &state,
+        &path_address(address)?,
+        &query,
+        "application/graphml+xml",
+        WalletGraph::to_graphml,
+    )
+    .await
+}
+
+/// Wallet transfer neighbourhood as DOT (Graphviz).
+async fn export_wallet_dot(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<GraphExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    export_wallet_graph(
+        &state,
+        &path_address(address)?,
+        &query,
+        "text/vnd.graphviz",
+        WalletGraph::to_dot,
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenQuery {
+    /// Also count distinct holders (slow: scans every token account of the mint)
+    pub include_holders: Option<bool>,
+    /// `processed`, `confirmed` or `finalized` (default: `RPC_COMMITMENT`)
+    pub commitment: Option<Commitment>,
+}
+
+/// Supply (cached) and, on request, holder count for a token mint.
+async fn get_token_info(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    mint: web::Path<String>,
+    query: web::Query<TokenQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let mint = path_address(mint)?;
+    let supply = match query.commitment {
+        Some(commitment) => state.token_supply.get_supply_at(&mint, commitment).await?,
+        None => state.token_supply.get_supply(&mint).await?,
+    };
+
+    let mut body = json!({
+        "mint": mint,
+        "decimals": supply.decimals,
+        "supply": supply
+    });
+    if query.include_holders.unwrap_or(false) {
+        // Many RPC providers reject getProgramAccounts; keep the supply either way.
+        match state.token_supply.count_holders(&mint).await {
+            Ok(holders) => body["holders"] = json!(holders),
+            Err(e) => body["holders_error"] = json!(e.to_string()),
+        }
+    }
+    Ok(HttpResponse::Ok().json(body))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceHistoryQuery {
+    /// `hour` (default) or `day`
+    pub bucket: Option<String>,
+    /// Only use swaps at or after this unix timestamp
+    pub since_epoch: Option<u64>,
+    /// Most recent swaps priced (max 1000)
+    pub limit: Option<usize>,
+}
+
+/// SOL price candles for a token, derived from stored swaps against SOL rather
+/// than an external oracle, so thinly traded tokens get a price too.
+#[utoipa::path(
+    get,
+    path = "/api/v1/token/{mint}/price-history",
+    tag = "token",
+    params(("mint" = String, Path, description = "Token mint"), PriceHistoryQuery),
+    responses(
+        (status = 200, description = "Open/high/low/close, VWAP and volume per time bucket (`buckets`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_token_price_history(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    mint: web::Path<String>,
+    query: web::Query<PriceHistoryQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let mint = path_address(mint)?;
+    let bucket = query.bucket.as_deref().unwrap_or("hour");
+    let bucket_secs = match bucket {
+        "hour" => 3_600,
+        "day" => 86_400,
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "bucket must be hour or day, got {}",
+                other
+            )))
+        }
+    };
+    let limit = query.limit.unwrap_or(1_000).clamp(1, 1_000);
+
+    let (considered, buckets) =
+        token_price_history(&state, &mint, query.since_epoch, limit, bucket_secs).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "mint": mint,
+        "quote": "SOL",
+        "bucket": bucket,
+        "bucket_secs": bucket_secs,
+        "swaps_considered": considered,
+        "buckets": buckets
+    })))
+}
+
+/// Price candles from the mint's latest `limit` stored swaps. Returns the number
+/// of swaps considered and the buckets, oldest first.
+async fn token_price_history(
+    state: &ApiState,
+    mint: &str,
+    since_epoch: Option<u64>,
+    limit: usize,
+    bucket_secs: u64,
+) -> BeastResult<(usize, Vec<PriceBucket>)> {
+    let swaps = state
+        .db_manager
+        .get_swaps_for_mint(mint, since_epoch, limit)
+        .await?;
+    Ok((swaps.len(), price_history(&swaps, mint, bucket_secs)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FundingSourceQuery {
+    /// Max first-funder hops to follow
+    pub depth: Option<usize>,
+}
+
+/// One hop of a funding chain: `funder` first funded `wallet` with this transfer.
+#[derive(Debug, Clone, serde::Serialize)]
+struct FundingHop {
+    wallet: String,
+    funder: String,
+    signature: String,
+    block_time: Option<i64>,
+    kind: String,
+    mint: Option<String>,
+    amount_sol: Option<f64>,
+    token_amount_ui: Option<f64>,
+    /// Set when the funder is a known exchange wallet (the chain stops there).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exchange: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct FundingChain {
+    wallet: String,
+    chain: Vec<FundingHop>,
+    /// `no_funder`, `exchange`, `cycle` or `max_depth`
+    stop_reason: &'static str,
+}
+
+/// Follow first inbound funders from `wallet` back up to `depth` hops, stopping at
+/// exchanges (funds are pooled there) and at wallets already on the chain.
+async fn trace_funding_chain(
+    state: &ApiState,
+    wallet: &str,
+    depth: usize,
+) -> Result<FundingChain, BeastError> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::from([wallet.to_string()]);
+    let mut current = wallet.to_string();
+
+    let stop_reason = loop {
+        if chain.len() >= depth {
+            break "max_depth";
+        }
+        let Some(ev) = state.db_manager.get_first_inbound_funder(&current).await? else {
+            break "no_funder";
+        };
+        let funder = ev.from_wallet.unwrap_or_default();
+        let exchange = state
+            .exchange_registry
+            .identify_exchange(&funder)
+            .map(|info| info.exchange.clone());
+        let is_exchange = exchange.is_some();
+        chain.push(FundingHop {
+            wallet: std::mem::replace(&mut current, funder.clone()),
+            funder: funder.clone(),
+            signature: ev.signature,
+            block_time: ev.block_time,
+            kind: ev.kind,
+            mint: ev.mint,
+            amount_sol: ev.amount_sol,
+            token_amount_ui: ev.token_amount_ui,
+            exchange,
+        });
+        if is_exchange {
+            break "exchange";
+        }
+        if !seen.insert(funder) {
+            break "cycle";
+        }
+    };
+
+    Ok(FundingChain {
+        wallet: wallet.to_string(),
+        chain,
+        stop_reason,
+    })
+}
+
+/// Who originally funded this wallet, and who funded them.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/{address}/funding-source",
+    tag = "wallet",
+    params(("address" = String, Path, description = "Wallet address"), FundingSourceQuery),
+    responses(
+        (status = 200, description = "First-funder chain (`chain`) and why it stopped (`stop_reason`)"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_funding_source(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    address: web::Path<String>,
+    query: web::Query<FundingSourceQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path_address(address)?;
+    let depth = query.depth.unwrap_or(5).clamp(1, 20);
+
+    let funding = trace_funding_chain(&state, &wallet, depth).await?;
+    Ok(HttpResponse::Ok().json(funding))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeRouteQuery {
+    pub source: String,
+    pub destination: String,
+    /// How many days of transfer events to search
+    pub lookback_days: Option<u32>,
+    /// Max transfer events loaded into the graph
+    pub event_limit: Option<usize>,
+    /// Max transfer hops in a route
+    pub max_hops: Option<usize>,
+    /// Max routes returned
+    pub limit: Option<usize>,
+    /// Max hours between a deposit and the matching withdrawal for a pass-through
+    pub window_hours: Option<u32>,
+    /// Allowed relative difference between deposit and withdrawal amounts
+    pub amount_tolerance: Option<f64>,
+}
+
+async fn trace_exchange_routes(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<ExchangeRouteQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let lookback_days = query.lookback_days.unwrap_or(30).clamp(1, 365);
+    let event_limit = query.event_limit.unwrap_or(10_000).clamp(1, 50_000);
+    let options = ExchangeRouteOptions {
+        max_hops: query.max_hops.unwrap_or(4).clamp(2, 6),
+        max_routes: query.limit.unwrap_or(20).clamp(1, 100),
+        window_secs: i64::from(query.window_hours.unwrap_or(72).clamp(1, 24 * 30)) * 3600,
+        amount_tolerance: query.amount_tolerance.unwrap_or(0.10).clamp(0.0, 1.0),
+    };
+
+    let report = state
+        .graph_engine
+        .exchange_routes(
+            query.source.trim(),
+            query.destination.trim(),
+            &state.exchange_registry,
+            &options,
+            Some(since_epoch_from_days(lookback_days)),
+            event_limit,
+        )
+        .await?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageRankQuery {
+    /// How many days of transfer events to build the graph from
+    pub lookback_days: Option<u32>,
+    /// Max transfer events loaded into the graph
+    pub event_limit: Option<usize>,
+    /// PageRank damping factor (0.0-1.0)
+    pub damping: Option<f64>,
+    /// Max PageRank iterations (stops early on convergence)
+    pub iterations: Option<usize>,
+    /// Max results returned
+    pub limit: Option<usize>,
+    /// Ignore edges that moved less SOL than this; with `token_weight`, edges that
+    /// moved tokens are kept (default: 0)
+    pub min_sol: Option<f64>,
+    /// Ignore edges with fewer transfers than this (default: 1)
+    pub min_tx_count: Option<u32>,
+    /// Also weight edges by token volume per mint, not just SOL (default: false)
+    pub token_weight: Option<bool>,
+    /// Max source wallets sampled for betweenness centrality (default: 500)
+    pub betweenness_sources: Option<usize>,
+    /// Order wallets by `pagerank` (default) or `betweenness`
+    pub sort: Option<String>,
+}
+
+/// Validated edge-pruning thresholds for the graph algorithms.
+fn edge_thresholds(
+    min_sol: Option<f64>,
+    min_tx_count: Option<u32>,
+) -> Result<(f64, u32), ApiError> {
+    let min_sol = min_sol.unwrap_or(0.0);
+    if !min_sol.is_finite() || min_sol < 0.0 {
+        return Err(ApiError::bad_request(
+            "min_sol must be a non-negative number",
+        ));
+    }
+    Ok((min_sol, min_tx_count.unwrap_or(1).clamp(1, 1_000)))
+}
+
+/// Rank wallets by weighted PageRank over recent transfer events, with the
+/// betweenness centrality that singles out pass-through wallets.
+async fn get_network_pagerank(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<PageRankQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let lookback_days = query.lookback_days.unwrap_or(7).clamp(1, 365);
+    let event_limit = query.event_limit.unwrap_or(10_000).clamp(1, 50_000);
+    let damping = query.damping.unwrap_or(0.85).clamp(0.0, 0.99);
+    let iterations = query.iterations.unwrap_or(50).clamp(1, 500);
+    let limit = query.limit.unwrap_or(25).clamp(1, 500);
+    let (min_sol, min_tx_count) = edge_thresholds(query.min_sol, query.min_tx_count)?;
+    let token_weight = query.token_weight.unwrap_or(false);
+    let betweenness_sources = query.betweenness_sources.unwrap_or(500).clamp(1, 5_000);
+    let by_betweenness = match query.sort.as_deref().map(str::trim) {
+        None | Some("") | Some("pagerank") => false,
+        Some("betweenness") => true,
+        Some(other) => {
+            return Err(ApiError::bad_request(format!(
+                "sort must be pagerank or betweenness, got {}",
+                other
+            )))
+        }
+    };
+
+    let mut centrality = state
+        .graph_engine
+        .network_centrality(
+            Some(since_epoch_from_days(lookback_days)),
+            event_limit,
+            min_sol,
+            min_tx_count,
+            token_weight,
+            CentralityOptions {
+                damping,
+                iterations,
+                betweenness_sources,
+            },
+        )
+        .await?;
+    if by_betweenness {
+        centrality.wallets.sort_by(|a, b| {
+            b.betweenness
+                .partial_cmp(&a.betweenness)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.wallet.cmp(&b.wallet))
+        });
+    }
+    centrality.wallets.truncate(limit);
+
+    Ok(HttpResponse::Ok().json(json!({
+        "lookback_days": lookback_days,
+        "nodes": centrality.nodes,
+        "edges": centrality.edges,
+        "min_sol": min_sol,
+        "min_tx_count": min_tx_count,
+        "token_weight": token_weight,
+        "damping": damping,
+        "betweenness_sources": centrality.betweenness_sources,
+        "sampled": centrality.sampled,
+        "graph_cap": centrality.graph_cap,
+        "sort": if by_betweenness { "betweenness" } else { "pagerank" },
+        "wallets": centrality.wallets
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnomalyQuery {
+    /// How many days of transfer events to profile
+    pub lookback_days: Option<u32>,
+    /// Z-score above the population mean that flags a wallet
+    pub z_threshold: Option<f64>,
+    /// Max wallet profiles forming the population (most active first)
+    pub profile_limit: Option<usize>,
+    /// Max results returned
+    pub limit: Option<usize>,
+}
+
+/// Wallets whose daily transfer count or SOL volume is a statistical outlier.
+async fn get_network_anomalies(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<AnomalyQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let lookback_days = query.lookback_days.unwrap_or(7).clamp(1, 365);
+    let z_threshold = query.z_threshold.unwrap_or(3.0).clamp(0.5, 20.0);
+    let profile_limit = query.profile_limit.unwrap_or(5_000).clamp(1, 50_000);
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    let (wallets_scanned, mut unusual) = state
+        .anomaly_detector
+        .detect(
+            Some(since_epoch_from_days(lookback_days)),
+            profile_limit,
+            z_threshold,
+        )
+        .await?;
+    unusual.truncate(limit);
+    Ok(HttpResponse::Ok().json(json!({
+        "lookback_days": lookback_days,
+        "z_threshold": z_threshold,
+        "wallets_scanned": wallets_scanned,
+        "unusual_wallets": unusual
+    })))
+}
+
+/// Most signatures accepted by one `parse/batch` request.
+const MAX_PARSE_BATCH: usize = 100;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ParseBatchRequest {
+    /// Transaction signatures to fetch and parse (at most 100)
+    pub signatures: Vec<String>,
+    /// Also store the parsed transfers (default: false; needs write scope)
+    pub persist: Option<bool>,
+}
+
+/// Per-signature result of a batch parse; `error` and `stage` are set when it failed.
+#[derive(Debug, serde::Serialize)]
+pub struct ParsedSignature {
+    pub signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<EnhancedTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<&'static str>,
+}
+
+/// Fetch and parse `signatures` with the bootstrap concurrency bound, keeping
+/// input order. RPC calls share the client's rate limiter and circuit breaker.
+/// With `persist`, parsed transactions are stored; a failed write still returns
+/// the parsed transaction alongside the error.
+async fn parse_signatures(
+    state: &ApiState,
+    signatures: Vec<String>,
+    persist: bool,
+) -> Vec<ParsedSignature> {
+    stream::iter(signatures)
+        .map(|signature| async move {
+            let tx = match state.tx_handler.process_transaction(&signature, None).await {
+                Ok(tx) => tx,
+                Err(e) => {
+                    return ParsedSignature {
+                        signature,
+                        transaction: None,
+                        error: Some(e.to_string()),
+                        stage: Some("parse"),
+                    }
+                }
+            };
+            let mut error = None;
+            if persist {
+                match state.transfer_analytics.analyze_transaction(&tx).await {
+                    Ok(_) => {
+                        for wallet in transfer_participants(&tx) {
+                            state.side_wallet_cache.invalidate_wallet(wallet).await;
+                        }
+                    }
+                    Err(e) => error = Some(e.to_string()),
+                }
+            }
+            ParsedSignature {
+                signature,
+                stage: error.as_ref().map(|_| "persist"),
+                error,
+                transaction: Some(tx),
+            }
+        })
+        .buffered(bootstrap_concurrency())
+        .collect()
+        .await
+}
+
+/// Wallets on either side of a transaction's SOL and token transfers.
+fn transfer_participants(tx: &EnhancedTransaction) -> HashSet<&str> {
+    let sol = tx
+        .sol_transfers
+        .iter()
+        .flat_map(|t| [t.from.as_str(), t.to.as_str()]);
+    let token = tx
+        .token_transfers
+        .iter()
+        .flat_map(|t| [t.from_owner.as_deref(), t.to_owner.as_deref()])
+        .flatten();
+    sol.chain(token).collect()
+}
+
+/// Fetch and parse a list of signatures (optionally persisting them).
+#[utoipa::path(
+    post,
+    path = "/api/v1/parse/batch",
+    tag = "transactions",
+    request_body = ParseBatchRequest,
+    responses(
+        (status = 200, description = "Per-signature results (`results`): parsed transaction or error and failing stage"),
+        (status = 400, description = "Empty or oversized batch", body = ErrorBody),
+        (status = 403, description = "`persist=true` without write scope", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn parse_batch(
+    auth: ApiKey,
+    state: web::Data<ApiState>,
+    body: web::Json<ParseBatchRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let persist = body.persist.unwrap_or(false);
+    if persist {
+        auth.require(Scope::Write)?;
+    }
+
+    let mut seen = HashSet::new();
+    let signatures: Vec<String> = body
+        .signatures
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty() && seen.insert(*s))
+        .map(str::to_string)
+        .collect();
+    if signatures.is_empty() {
+        return Err(ApiError::bad_request("signatures must not be empty"));
+    }
+    if signatures.len() > MAX_PARSE_BATCH {
+        return Err(ApiError::bad_request(format!(
+            "at most {} signatures per batch, got {}",
+            MAX_PARSE_BATCH,
+            signatures.len()
+        )));
+    }
+
+    let results = parse_signatures(&state, signatures, persist).await;
+    let parsed = results.iter().filter(|r| r.transaction.is_some()).count();
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    Ok(HttpResponse::Ok().json(json!({
+        "requested": results.len(),
+        "parsed": parsed,
+        "failed": failed,
+        "persisted": persist,
+        "results": results
+    })))
+}
+
+/// Signatures listed per `getSignaturesForAddress` page by `ingest`.
+const INGEST_PAGE_SIZE: u64 = 100;
+/// Default and largest `limit` for one `ingest` request.
+const DEFAULT_INGEST_LIMIT: u64 = 100;
+const MAX_INGEST_LIMIT: u64 = 1_000;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IngestQuery {
+    /// Stop at this signature (exclusive), e.g. the newest one seen by the previous call;
+    /// omit to start from the newest signature
+    pub until: Option<String>,
+    /// Max signatures ingested (default: 100, max: 1000)
+    pub limit: Option<u64>,
+}
+
+/// Signatures of `wallet` newer than `until` (newest first), paging until a short
+/// page or `limit`. The flag is true when `limit` cut the listing short.
+async fn signatures_until(
+    state: &ApiState,
+    wallet: &str,
+    until: Option<&str>,
+    limit: u64,
+) -> BeastResult<(Vec<String>, bool)> {
+    let mut signatures: Vec<String> = Vec::new();
+    loop {
+        let want = (limit - signatures.len() as u64).min(INGEST_PAGE_SIZE);
+        let page = state
+            .rpc_client
+            .get_signatures_page(wallet, want, signatures.last().map(String::as_str), until)
+            .await?;
+        let full = page.len() as u64 >= want;
+        signatures.extend(page.into_iter().map(|s| s.signature));
+        if !full {
+            return Ok((signatures, false));
+        }
+        if signatures.len() as u64 >= limit {
+            return Ok((signatures, true));
+        }
+    }
+}
+
+/// Fetch and parse a wallet's signatures newer than `until`, returning the SOL
+/// transfers that touch it. No analysis runs, so this is cheap to poll.
+#[utoipa::path(
+    post,
+    path = "/api/v1/wallet/{address}/ingest",
+    tag = "transactions",
+    params(("address" = String, Path, description = "Wallet address"), IngestQuery),
+    responses(
+        (status = 200, description = "`signatures` ingested, `newest_signature` to pass as the next `until`, `truncated` when `limit` was hit, SOL `transfers` touching the wallet and per-signature `failures`"),
+        (status = 400, description = "Invalid address or limit", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn ingest_wallet(
+    auth: ApiKey,
+    state: web::Data<ApiState>,
+    path: web::Path<String>,
+    query: web::Query<IngestQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wallet = path.into_inner();
+    validate_address(&wallet)?;
+    let limit = query.limit.unwrap_or(DEFAULT_INGEST_LIMIT);
+    if !(1..=MAX_INGEST_LIMIT).contains(&limit) {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_INGEST_LIMIT
+        )));
+    }
+    let until = query
+        .until
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    // Like `side-wallets` bootstrapping, only keys that can write store what they fetch.
+    let persist = auth.has_scope(Scope::Write);
+
+    let (signatures, truncated) = signatures_until(&state, &wallet, until, limit).await?;
+    let newest = signatures.first().cloned();
+    let results = parse_signatures(&state, signatures, persist).await;
+
+    let transfers: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.transaction.as_ref())
+        .flat_map(|tx| {
+            tx.sol_transfers
+                .iter()
+                .filter(|t| t.from == wallet || t.to == wallet)
+                .map(move |t| {
+                    json!({
+                        "signature": tx.signature,
+                        "block_time": tx.block_time,
+                        "from_wallet": t.from,
+                        "to_wallet": t.to,
+                        "amount_sol": t.amount_sol,
+                    })
+                })
+        })
+        .collect();
+    let failures: Vec<&ParsedSignature> = results.iter().filter(|r| r.error.is_some()).collect();
+
+    Ok(HttpResponse::Ok().json(json!({
+        "wallet": wallet,
+        "signatures": results.len(),
+        "newest_signature": newest,
+        "truncated": truncated,
+        "persisted": persist,
+        "transfers": transfers,
+        "failures": failures
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhaleStreamQuery {
+    /// Smallest SOL transfer pushed to the stream
+    pub min_sol: Option<f64>,
+}
+
+/// Comment sent on an idle stream so proxies keep the connection open.
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Server-sent events for transfers of at least `min_sol`: each transfer is a
+/// `data:` line with the `TransferEvent` JSON; a subscriber that fell behind gets
+/// an `event: lag` frame with the number of transfers it missed.
+fn whale_transfer_frames(
+    rx: tokio::sync::broadcast::Receiver<TransferEvent>,
+    min_sol: f64,
+) -> impl futures::Stream<Item = Result<web::Bytes, std::convert::Infallible>> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    stream::unfold(rx, move |mut rx| async move {
+        loop {
+            let frame = match tokio::time::timeout(SSE_KEEPALIVE, rx.recv()).await {
+                Err(_) => ": keepalive\n\n".to_string(),
+                Ok(Ok(ev)) if ev.amount_sol.unwrap_or(0.0) >= min_sol => {
+                    format!("data: {}\n\n", json!(ev))
+                }
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    format!("event: lag\ndata: {}\n\n", json!({ "skipped": skipped }))
+                }
+                Ok(Err(RecvError::Closed)) => return None,
+            };
+            return Some((Ok(web::Bytes::from(frame)), rx));
+        }
+    })
+}
+
+/// Live feed of newly persisted SOL transfers above a threshold.
+async fn stream_whale_transfers(
+    _auth: ApiKey,
+    state: web::Data<ApiState>,
+    query: web::Query<WhaleStreamQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let min_sol = query.min_sol.unwrap_or(100.0);
+    if !min_sol.is_finite() || min_sol < 0.0 {
+        return Err(ApiError::bad_request(
+            "min_sol must be a non-negative number",
+        ));
+    }
+
+    let rx = state.transfer_analytics.subscribe_sol_transfers();
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-cache"))
+        // Stops the compression middleware from buffering the stream.
+        .insert_header(actix_web::http::header::ContentEncoding::Identity)
+        .streaming(whale_transfer_frames(rx, min_sol)))
+}
+
+/// Configured API keys by id with scope, expiry and revocation state.
+async fn list_api_keys(_auth: RequireScope<Admin>) -> Result<HttpResponse, ApiError> {
+    let keys: Vec<_> = auth::get_api_keys()
+        .list()
+        .into_iter()
+        .map(|(id, record)| {
+            json!({
+                "id": id,
+                "scope": record.scope,
+                "created_at": record.created_at,
+                "expires_at": record.expires_at,
+                "revoked": record.revoked,
+                "valid": record.is_valid()
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(json!({ "keys": keys })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddApiKeyRequest {
+    /// The new key (rotate by adding a new key, then revoking the old one)
+    pub key: String,
+    /// `read`, `write` or `admin` (default: `read`)
+    pub scope: Option<String>,
+    /// Unix timestamp after which the key is rejected
+    pub expires_at: Option<u64>,
+    /// Alternative to `expires_at`: seconds from now
+    pub ttl_secs: Option<u64>,
+}
+
+/// Add (or replace) an API key at runtime.
+async fn add_api_key(
+    _auth: RequireScope<Admin>,
+    body: web::Json<AddApiKeyRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let key = body.key.trim();
+    if key.len() < 16 || key.contains(':') || key.contains(',') {
+        return Err(ApiError::bad_request(
+            "key must be at least 16 characters and contain no ':' or ','",
+        ));
+    }
+    let scope = match body.scope.as_deref() {
+        None => Scope::Read,
+        Some(raw) => Scope::parse(raw)
+            .ok_or_else(|| ApiError::bad_request(format!("unknown scope '{}'", raw)))?,
+    };
+    let expires_at = body.expires_at.or_else(|| {
+        body.ttl_secs.map(|ttl| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                .saturating_add(ttl)
+        })
+    });
+
+    auth::get_api_keys().add_api_key(key, scope, expires_at);
+    Ok(HttpResponse::Created().json(json!({
+        "id": auth::key_id(key),
+        "scope": scope,
+        "expires_at": expires_at
+    })))
+}
+
+/// Revoke an API key by id; it stays listed but is rejected from now on.
+async fn revoke_api_key(
+    _auth: RequireScope<Admin>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    if !auth::get_api_keys().revoke_by_id(&id) {
+        return Err(ApiError::not_found("unknown API key id"));
+    }
+    Ok(HttpResponse::Ok().json(json!({ "id": id, "revoked": true })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RebuildRelationshipsQuery {
+    /// Only rebuild pairs with a transfer at or after this unix timestamp
+    /// (default: rebuild everything)
+    pub since: Option<u64>,
+}
+
+/// Recompute relationship edges from stored transfer events, e.g. after a
+/// partial import left them out of step. Cached side-wallet results are dropped.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/rebuild-relationships",
+    tag = "admin",
+    params(RebuildRelationshipsQuery),
+    responses(
+        (status = 200, description = "Edges rebuilt (`rebuilt_edges`), `full` or `partial` `scope` and elapsed time"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 403, description = "Key lacks admin scope, or no API keys are configured", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn rebuild_relationships(
+    _auth: RequireScope<Admin>,
+    state: web::Data<ApiState>,
+    query: web::Query<RebuildRelationshipsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let since = query.since.unwrap_or(0);
+    let include_self_transfers = state.transfer_analytics.records_self_transfers();
+    let started = std::time::Instant::now();
+    let rebuilt = state
+        .db_manager
+        .rebuild_relationships_from_events(since, include_self_transfers)
+        .await?;
+    state.side_wallet_cache.clear().await;
+    tracing::info!(
+        "Rebuilt {} relationship edges (since={}) in {:?}",
+        rebuilt,
+        since,
+        started.elapsed()
+    );
+
+    Ok(HttpResponse::Ok().json(json!({
+        "rebuilt_edges": rebuilt,
+        "scope": if since == 0 { "full" } else { "partial" },
+        "since": since,
+        "self_transfers": include_self_transfers,
+        "elapsed_ms": started.elapsed().as_millis() as u64
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RetryFailuresQuery {
+    /// Max failed signatures retried (default: 50)
+    pub limit: Option<usize>,
+    /// Retry even if the backoff has not elapsed
+    pub force: Option<bool>,
+}
+
+/// Re-ingest signatures whose earlier ingestion failed; successes are removed
+/// from the dead-letter store, repeat failures wait longer before the next try.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/retry-failures",
+    tag = "admin",
+    params(RetryFailuresQuery),
+    responses(
+        (status = 200, description = "Retry `report`: pending, waiting and exhausted counts, signatures retried, `succeeded` and `failed`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 403, description = "Key lacks admin scope, or no API keys are configured", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn retry_failures(
+    _auth: RequireScope<Admin>,
+    state: web::Data<ApiState>,
+    query: web::Query<RetryFailuresQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let now = if query.force.unwrap_or(false) {
+        u64::MAX / 2
+    } else {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    };
+    let report = retry_ingestion_failures(&state, limit, now).await?;
+    if !report.succeeded.is_empty() {
+        state.side_wallet_cache.clear().await;
+    }
+    Ok(HttpResponse::Ok().json(json!({
+        "max_attempts": MAX_INGESTION_ATTEMPTS,
+        "report": report
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExhaustedFailuresQuery {
+    /// Max entries returned (default: 50)
+    pub limit: Option<usize>,
+    /// Entries skipped, newest failure first (default: 0)
+    pub offset: Option<usize>,
+}
+
+/// Failed signatures that ran out of retry attempts, with counts of every
+/// dead-letter state, so they can be inspected and ingested by hand.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/failures",
+    tag = "admin",
+    params(ExhaustedFailuresQuery),
+    responses(
+        (status = 200, description = "Exhausted failures (`exhausted`), most recently failed first, and due/waiting/exhausted `counts`"),
+        (status = 401, description = "Missing or invalid API key", body = ErrorBody),
+        (status = 403, description = "Key lacks admin scope, or no API keys are configured", body = ErrorBody),
+    ),
+    security(("api_key" = [])),
+)]
+async fn exhausted_failures(
+    _auth: RequireScope<Admin>,
+    state: web::Data<ApiState>,
+    query: web::Query<ExhaustedFailuresQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let counts = state
+        .db_manager
+        .count_ingestion_failures(MAX_INGESTION_ATTEMPTS, now)
+        .await?;
+    let exhausted = state
+        .db_manager
+        .get_exhausted_failures(MAX_INGESTION_ATTEMPTS, offset, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "max_attempts": MAX_INGESTION_ATTEMPTS,
+        "counts": counts,
+        "offset": offset,
+        "limit": limit,
+        "exhausted": exhausted
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::enhanced_parser::SolTransfer;
+    use crate::core::rpc_client::tests::mock_endpoint;
+    use crate::core::units::sol_to_lamports;
+    use std::collections::HashSet;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn now_epoch() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn sol_transfer(
+        from: &str,
+        to: &str,
+        amount_sol: f64,
+        instruction_index: usize,
+    ) -> SolTransfer {
+        SolTransfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount_lamports: sol_to_lamports(amount_sol),
+            amount_sol,
+            instruction_index,
+            transfer_type: "system".to_string(),
+        }
+    }
+
+    fn tx(
+        signature: &str,
+        slot: u64,
+        block_time: u64,
+        sol_transfers: Vec<SolTransfer>,
+    ) -> EnhancedTransaction {
+        EnhancedTransaction {
+            signature: signature.to_string(),
+            slot,
+            block_time: Some(block_time),
+            fee: 0,
+            success: true,
+            error: None,
+            compute_units_consumed: None,
+            compute_unit_price_micro_lamports: None,
+            priority_fee_lamports: None,
+            accounts: Vec::new(),
+            signers: Vec::new(),
+            writable_accounts: Vec::new(),
+            sol_transfers,
+            token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            stake_actions: Vec::new(),
+            mint_burn_events: Vec::new(),
+            nft_trades: Vec::new(),
+            balance_changes: Vec::new(),
+            programs_called: Vec::new(),
+            program_names: Vec::new(),
+            tx_type: TransactionType::Unknown,
+            is_versioned: false,
+            finalized: false,
+        }
+    }
+
+    /// `compute_side_wallets` parameters: depth 2, limit 25, 30 days, hubs terminal.
+    fn side_wallet_params(threshold: f64, direction: TraversalDirection) -> SideWalletParams {
+        SideWalletParams {
+            depth: 2,
+            threshold,
+            limit: 25,
+            lookback_days: 30,
+            cex_hops: false,
+            direction,
+            stop_at_hubs: true,
+        }
+    }
+
+    async fn test_state() -> ApiState {
+        test_state_with_rpc("http://localhost").await
+    }
+
+    async fn test_state_with_rpc(endpoint: &str) -> ApiState {
+        let db_manager = Arc::new(DatabaseManager::in_memory());
+        db_manager.init_schema().await.unwrap();
+
+        let rpc_client = Arc::new(SolanaRpcClient::new(endpoint.to_string()));
+        let tx_handler = Arc::new(TransactionHandler::new(Arc::clone(&rpc_client)));
+        let transfer_analytics = Arc::new(TransferAnalytics::new(Arc::clone(&db_manager)));
+        let graph_engine = Arc::new(GraphAnalysisEngine::new(Arc::clone(&db_manager)));
+        let anomaly_detector = Arc::new(AnomalyDetector::new(Arc::clone(&db_manager)));
+        let holdings = Arc::new(HoldingsTracker::new(Arc::clone(&rpc_client)));
+        let token_supply = Arc::new(TokenSupplyTracker::new(Arc::clone(&rpc_client)));
+        let exchange_registry = Arc::new(ExchangeRegistry::builtin());
+        let side_wallet_cache = Arc::new(SideWalletCache::with_ttl(Duration::from_secs(60)));
+
+        ApiState {
+            rpc_client,
+            tx_handler,
+            transfer_analytics,
+            db_manager,
+            graph_engine,
+            anomaly_detector,
+            holdings,
+            token_supply,
+            exchange_registry,
+            side_wallet_cache,
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_side_wallets_from_graph_edges() {
+        let state = test_state().await;
+        let now = now_epoch();
+
+        let main = "MAIN";
+        let side1 = "SIDE1";
+        let side2 = "SIDE2";
+
+        let mut transfers_a = Vec::new();
+        for i in 0..10 {
+            transfers_a.push(sol_transfer(main, side1, 1.0, i));
+        }
+        let mut transfers_b = Vec::new();
+        for i in 0..10 {
+            transfers_b.push(sol_transfer(side1, side2, 1.0, i));
+        }
+
+        let tx_a = tx("sig_main_side1", 1, now - 3600, transfers_a);
+        let tx_b = tx("sig_side1_side2", 2, now - 3500, transfers_b);
+
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx_a)
+            .await
+            .unwrap();
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx_b)
+            .await
+            .unwrap();
+
+        let candidates = compute_side_wallets(
+            &state,
+            main,
+            &side_wallet_params(0.10, TraversalDirection::Both),
+        )
+        .await
+        .unwrap()
+        .candidates;
+        let addrs: HashSet<String> = candidates.iter().map(|c| c.address.clone()).collect();
+
+        assert!(addrs.contains(side1), "expected {} in {:?}", side1, addrs);
+        assert!(addrs.contains(side2), "expected {} in {:?}", side2, addrs);
+    }
+
+    #[tokio::test]
+    async fn detects_cex_hop_withdrawal_recipient() {
+        let state = test_state().await;
+        let now = now_epoch();
+
+        let main = "MAIN";
+        let deposit = "CEX_DEPOSIT";
+        let hot = "CEX_HOT";
+        let recipient = "RECIPIENT";
+
+        let deposit_time = now - 2 * 86_400;
+        let sweep_time = deposit_time + 3600;
+        let withdraw_time = deposit_time + 86_400;
+
+        let tx_deposit = tx(
+            "sig_deposit",
+            10,
+            deposit_time,
+            vec![sol_transfer(main, deposit, 50.0, 0)],
+        );
+        let tx_sweep = tx(
+            "sig_sweep",
+            11,
+            sweep_time,
+            vec![sol_transfer(deposit, hot, 49.0, 0)],
+        );
+        let tx_withdraw = tx(
+            "sig_withdraw",
+            12,
+            withdraw_time,
+            vec![sol_transfer(hot, recipient, 45.0, 0)],
+        );
+
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx_deposit)
+            .await
+            .unwrap();
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx_sweep)
+            .await
+            .unwrap();
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx_withdraw)
+            .await
+            .unwrap();
+
+        let (cands, _bootstrap) = compute_cex_hops(&state, main, 30, 0, 10).await;
+        let cand = cands
+            .into_iter()
+            .find(|c| c.wallet == recipient)
+            .expect("expected cex-funded recipient");
+
+        assert!(cand.score > 0.1);
+        assert!(
+            cand.paths
+                .iter()
+                .any(|p| p.deposit_wallet == deposit && p.hot_wallet == hot),
+            "expected path via deposit/hot; got {:?}",
+            cand.paths
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_bootstrap_keeps_counters_and_events() {
+        let ok = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 1,
+            "blockTime": now_epoch() - 60,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": {
+                "accountKeys": [{ "pubkey": "FUNDER", "signer": true, "writable": true }],
+                "instructions": [{
+                    "programId": "11111111111111111111111111111111",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "FUNDER", "destination": "RECIPIENT", "lamports": 1_000_000_000u64
+                    } }
+                }]
+            } }
+        } });
+        let failed =
+            json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32009, "message": "skipped" } });
+        let (endpoint, _requests) = mock_endpoint(vec![
+            (200, ok.to_string()),
+            (200, ok.to_string()),
+            (200, ok.to_string()),
+            (200, failed.to_string()),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+
+        let signatures: Vec<String> = (0..5).map(|i| format!("sig_{}", i)).collect();
+        let mut stats = BootstrapStats {
+            signatures: signatures.len(),
+            ..BootstrapStats::new("FUNDER")
+        };
+        for (_, outcome) in ingest_signatures(&state, signatures).await {
+            stats.record(&outcome);
+        }
+
+        assert_eq!(stats.parsed_ok, 3);
+        assert_eq!(stats.parsed_failed, 2);
+        assert_eq!(stats.persisted_failed, 0);
+        assert_eq!(stats.new_transactions, 3);
+        assert_eq!(stats.updated_transactions, 0);
+        let events = state
+            .db_manager
+            .get_transfers_between("FUNDER", "RECIPIENT", None, 50)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn transaction_pages_thread_next_before_cursor() {
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        let page = |sigs: &[(&str, u64)]| {
+            let result: Vec<_> = sigs
+                .iter()
+                .map(|(sig, slot)| json!({ "signature": sig, "slot": slot, "blockTime": 1_700_000_000 + slot }))
+                .collect();
+            json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string()
+        };
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, page(&[("s5", 50), ("s4", 40)])),
+            (200, page(&[("s3", 30), ("s2", 20)])),
+            (200, page(&[("s1", 10)])),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+        let mut query = TransactionQuery {
+            limit: Some(2),
+            before: None,
+            until: None,
+            min_slot: None,
+            max_slot: Some(45),
+            min_block_time: None,
+            max_block_time: None,
+            tx_type: None,
+            commitment: None,
+        };
+
+        let first = fetch_transaction_page(&state, wallet, &query)
+            .await
+            .unwrap();
+        assert_eq!(first.transactions.len(), 1);
+        assert_eq!(first.transactions[0].signature, "s4");
+        assert_eq!(first.next_before.as_deref(), Some("s4"));
+
+        query.before = first.next_before;
+        let second = fetch_transaction_page(&state, wallet, &query)
+            .await
+            .unwrap();
+        assert_eq!(second.transactions.len(), 2);
+        assert_eq!(second.next_before.as_deref(), Some("s2"));
+
+        query.before = second.next_before;
+        query.commitment = Some(Commitment::Finalized);
+        let last = fetch_transaction_page(&state, wallet, &query)
+            .await
+            .unwrap();
+        assert_eq!(last.transactions[0].signature, "s1");
+        assert_eq!(last.next_before, None);
+
+        let requests = requests.lock().await;
+        let bodies: Vec<serde_json::Value> = requests
+            .iter()
+            .map(|b| serde_json::from_str(b).unwrap())
+            .collect();
+        assert!(bodies[0]["params"][1].get("before").is_none());
+        assert_eq!(bodies[1]["params"][1]["before"], "s4");
+        assert_eq!(bodies[2]["params"][1]["before"], "s2");
+        assert_eq!(bodies[1]["params"][1]["commitment"], "confirmed");
+        assert_eq!(bodies[2]["params"][1]["commitment"], "finalized");
+    }
+
+    #[tokio::test]
+    async fn metrics_gauges_follow_cache_inserts() {
+        let state = web::Data::new(test_state().await);
+        let idempotency = Arc::new(IdempotencyStore::new(Duration::from_secs(60), 100));
+        let gauges = Arc::new(Gauges::new());
+        assert!(gauges
+            .render()
+            .contains("onchain_beast_metrics_refreshed_at_seconds 0\n"));
+
+        let refresher = metrics::spawn_refresher(
+            Arc::clone(&gauges),
+            state.clone(),
+            Arc::clone(&idempotency),
+            Duration::from_millis(20),
+        );
+        let params = side_wallet_params(0.1, TraversalDirection::Both);
+        for wallet in ["W1", "W2", "W3"] {
+            state
+                .side_wallet_cache
+                .insert(wallet, &params, json!({}))
+                .await;
+        }
+
+        let line = "onchain_beast_cache_size{cache_type=\"side_wallet\"} 3\n";
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !gauges.render().contains(line) {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "{}",
+                gauges.render()
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        refresher.abort();
+
+        let text = gauges.render();
+        assert!(text.contains("# TYPE onchain_beast_cache_size gauge"));
+        assert!(text.contains("onchain_beast_cache_size{cache_type=\"transaction\"} 0\n"));
+        // The in-memory backend has no pool.
+        assert!(!text.contains("onchain_beast_db_pool_active"));
+    }
+
+    #[test]
+    fn side_wallets_ingests_only_when_bootstrap_or_cex_fetches_are_on() {
+        let query = |q: &str| {
+            web::Query::<SideWalletQuery>::from_query(q)
+                .unwrap()
+                .into_inner()
+        };
+        // Defaults follow the key: writers bootstrap, readers don't.
+        assert!(query("").ingests(true));
+        assert!(!query("").ingests(false));
+        assert!(query("bootstrap=false").ingests(true));
+        assert!(!query("bootstrap=false&cex_bootstrap_limit=0").ingests(true));
+        assert!(!query("bootstrap=false&cex_hops=false").ingests(true));
+        assert!(!query("bootstrap_limit=0&cex_hops=false").ingests(true));
+        assert!(query("bootstrap=true&cex_hops=false").ingests(false));
+    }
+
+    #[tokio::test]
+    async fn ingest_pages_back_to_the_until_signature() {
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        let page = |range: std::ops::Range<u64>| {
+            let result: Vec<_> = range
+                .map(|i| json!({ "signature": format!("s{}", i), "slot": 1_000 - i }))
+                .collect();
+            json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string()
+        };
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, page(0..INGEST_PAGE_SIZE)),
+            (200, page(INGEST_PAGE_SIZE..INGEST_PAGE_SIZE + 3)),
+            (200, page(0..2)),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+
+        let (signatures, truncated) = signatures_until(&state, wallet, Some("last"), 500)
+            .await
+            .unwrap();
+        assert_eq!(signatures.len() as u64, INGEST_PAGE_SIZE + 3);
+        assert!(!truncated);
+
+        let (signatures, truncated) = signatures_until(&state, wallet, None, 2).await.unwrap();
+        assert_eq!(signatures, ["s0", "s1"]);
+        assert!(truncated);
+
+        let requests = requests.lock().await;
+        let bodies: Vec<serde_json::Value> = requests
+            .iter()
+            .map(|b| serde_json::from_str(b).unwrap())
+            .collect();
+        assert_eq!(bodies.len(), 3);
+        assert_eq!(bodies[0]["params"][1]["until"], "last");
+        assert!(bodies[0]["params"][1].get("before").is_none());
+        assert_eq!(bodies[1]["params"][1]["until"], "last");
+        assert_eq!(
+            bodies[1]["params"][1]["before"],
+            format!("s{}", INGEST_PAGE_SIZE - 1)
+        );
+        assert_eq!(bodies[2]["params"][1]["limit"], 2);
+    }
+
+    #[tokio::test]
+    async fn traces_route_through_registry_exchange() {
+        let state = test_state().await;
+        let now = now_epoch();
+        let coinbase = "H8sMJSCQxfKiFTCfDR3DUMLPwcRbM61LGFJ8N4dK3WjS";
+
+        for (sig, slot, time, transfer) in [
+            (
+                "sig_src_cex",
+                1,
+                now - 7200,
+                sol_transfer("SRC", coinbase, 5.0, 0),
+            ),
+            (
+                "sig_cex_dst",
+                2,
+                now - 3600,
+                sol_transfer(coinbase, "DST", 4.8, 0),
+            ),
+            (
+                "sig_src_other",
+                3,
+                now - 3000,
+                sol_transfer("SRC", "OTHER", 1.0, 0),
+            ),
+        ] {
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(sig, slot, time, vec![transfer]))
+                .await
+                .unwrap();
+        }
+
+        let report = state
+            .graph_engine
+            .exchange_routes(
+                "SRC",
+                "DST",
+                &state.exchange_registry,
+                &ExchangeRouteOptions::default(),
+                None,
+                1_000,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.routes.len(), 1);
+        let route = &report.routes[0];
+        assert_eq!(route.wallets, vec!["SRC", coinbase, "DST"]);
+        assert_eq!(route.exchanges, vec!["Coinbase"]);
+        assert_eq!(route.hops[0].volume_sol, Some(5.0));
+        assert_eq!(route.hops[1].volume_sol, Some(4.8));
+        assert!(route.pass_through);
+        assert_eq!(route.delta_seconds, Some(3600));
+        assert_eq!(report.exchanges_detected, 1);
+    }
+
+    #[tokio::test]
+    async fn deep_health_reports_components_and_degrades_on_rpc_failure() {
+        let ok_body = r#"{"jsonrpc":"2.0","id":1,"result":"ok"}"#;
+        let (endpoint, _) = mock_endpoint(vec![(200, ok_body.to_string())]).await;
+        let (ready, report) = deep_health_report(&test_state_with_rpc(&endpoint).await).await;
+        assert!(ready);
+        assert_eq!(report["status"], "healthy");
+        assert_eq!(report["components"]["database"]["status"], "up");
+        assert_eq!(report["components"]["rpc"]["status"], "up");
+        assert!(report["components"]["rpc"]["latency_ms"].is_u64());
+
+        let error_body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"boom"}}"#;
+        let (endpoint, _) = mock_endpoint(vec![(500, error_body.to_string())]).await;
+        let (ready, report) = deep_health_report(&test_state_with_rpc(&endpoint).await).await;
+        assert!(ready);
+        assert_eq!(report["status"], "degraded");
+        assert_eq!(report["components"]["rpc"]["status"], "down");
+        assert!(report["components"]["rpc"]["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn dry_run_bootstrap_reads_stored_history_but_writes_nothing() {
+        let signatures = json!({ "jsonrpc": "2.0", "id": 1, "result": [
+            { "signature": "sig_dry", "slot": 1, "blockTime": now_epoch() - 60 }
+        ] });
+        let transfer = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 1,
+            "blockTime": now_epoch() - 60,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": {
+                "accountKeys": [{ "pubkey": "FUNDER", "signer": true, "writable": true }],
+                "instructions": [{
+                    "programId": "11111111111111111111111111111111",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "FUNDER", "destination": "RECIPIENT", "lamports": 1_000_000_000u64
+                    } }
+                }]
+            } }
+        } });
+        let (endpoint, _) = mock_endpoint(vec![
+            (200, signatures.to_string()),
+            (200, transfer.to_string()),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+        // History the real store already holds.
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx(
+                "sig_stored",
+                1,
+                now_epoch() - 3_600,
+                vec![sol_transfer("FUNDER", "EARLIER", 2.0, 0)],
+            ))
+            .await
+            .unwrap();
+        let scratch = state.with_scratch_storage("FUNDER", 2, 0).await.unwrap();
+        let connections = scratch
+            .db_manager
+            .get_wallet_connections("FUNDER")
+            .await
+            .unwrap();
+        assert_eq!(connections.len(), 1);
+
+        let stats = bootstrap_ingest_wallet(
+            &scratch,
+            "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU",
+            5,
+            0,
+        )
+        .await;
+        assert_eq!(stats.parsed_ok, 1);
+
+        let in_scratch = scratch
+            .db_manager
+            .get_transfers_between("FUNDER", "RECIPIENT", None, 50)
+            .await
+            .unwrap();
+        assert_eq!(in_scratch.len(), 1);
+
+        let persisted = state
+            .db_manager
+            .get_recent_transfer_events(None, 50)
+            .await
+            .unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].signature, "sig_stored");
+        assert!(state
+            .db_manager
+            .get_transaction_types(&["sig_dry".to_string()])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn bootstrap_ingestion_invalidates_cached_side_wallets() {
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        let signatures = json!({ "jsonrpc": "2.0", "id": 1, "result": [
+            { "signature": "sig_new", "slot": 1, "blockTime": now_epoch() - 60 }
+        ] });
+        let transfer = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 1,
+            "blockTime": now_epoch() - 60,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": { "accountKeys": [], "instructions": [] } }
+        } });
+        let (endpoint, _) = mock_endpoint(vec![
+            (200, signatures.to_string()),
+            (200, transfer.to_string()),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+        let params = SideWalletParams {
+            depth: 2,
+            threshold: 0.1,
+            limit: 15,
+            lookback_days: 30,
+            cex_hops: true,
+            direction: TraversalDirection::Both,
+            stop_at_hubs: true,
+        };
+        state
+            .side_wallet_cache
+            .insert(wallet, &params, json!({ "side_wallets": [] }))
+            .await;
+        assert!(state.side_wallet_cache.get(wallet, &params).await.is_some());
+
+        let stats = bootstrap_ingest_wallet(&state, wallet, 5, 0).await;
+        assert_eq!(stats.parsed_ok, 1);
+        assert!(state.side_wallet_cache.get(wallet, &params).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn funding_chain_follows_first_funders_to_exchange_and_cycles() {
+        let state = test_state().await;
+        let binance = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+        for (sig, time, transfer) in [
+            (
+                "sig_cex_origin",
+                1_000,
+                sol_transfer(binance, "ORIGIN", 100.0, 0),
+            ),
+            (
+                "sig_origin_mid",
+                2_000,
+                sol_transfer("ORIGIN", "MID", 50.0, 0),
+            ),
+            (
+                "sig_mid_target",
+                3_000,
+                sol_transfer("MID", "TARGET", 10.0, 0),
+            ),
+            // Later inbound transfers are not the first funder.
+            ("sig_late", 4_000, sol_transfer("LATE", "TARGET", 99.0, 0)),
+            // LOOP_A and LOOP_B first funded each other.
+            ("sig_ab", 5_000, sol_transfer("LOOP_A", "LOOP_B", 1.0, 0)),
+            ("sig_ba", 5_100, sol_transfer("LOOP_B", "LOOP_A", 1.0, 0)),
+        ] {
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(sig, time, time, vec![transfer]))
+                .await
+                .unwrap();
+        }
+
+        let funding = trace_funding_chain(&state, "TARGET", 10).await.unwrap();
+        let funders: Vec<&str> = funding.chain.iter().map(|h| h.funder.as_str()).collect();
+        assert_eq!(funders, vec!["MID", "ORIGIN", binance]);
+        assert_eq!(funding.chain[0].signature, "sig_mid_target");
+        assert_eq!(funding.chain[2].exchange.as_deref(), Some("Binance"));
+        assert_eq!(funding.stop_reason, "exchange");
+
+        let shallow = trace_funding_chain(&state, "TARGET", 1).await.unwrap();
+        assert_eq!(shallow.chain.len(), 1);
+        assert_eq!(shallow.stop_reason, "max_depth");
+
+        let looped = trace_funding_chain(&state, "LOOP_B", 10).await.unwrap();
+        let funders: Vec<&str> = looped.chain.iter().map(|h| h.funder.as_str()).collect();
+        assert_eq!(funders, vec!["LOOP_A", "LOOP_B"]);
+        assert_eq!(looped.stop_reason, "cycle");
+
+        let orphan = trace_funding_chain(&state, "LATE", 10).await.unwrap();
+        assert!(orphan.chain.is_empty());
+        assert_eq!(orphan.stop_reason, "no_funder");
+    }
+
+    #[tokio::test]
+    async fn flags_twenty_same_size_transfers_as_structuring() {
+        let state = test_state().await;
+        let now = now_epoch();
+
+        // 20 transfers of ~0.5 SOL ten minutes apart, plus unrelated amounts.
+        for i in 0..20u64 {
+            let amount = 0.5 + if i % 2 == 0 { 0.001 } else { -0.001 };
+            let transfer = sol_transfer("MIXER", &format!("DEST{}", i), amount, 0);
+            let sig = format!("sig_split_{}", i);
+            let time = now - 86_400 + i * 600;
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(&sig, i, time, vec![transfer]))
+                .await
+                .unwrap();
+        }
+        for (i, amount) in [3.2, 7.9, 12.0].into_iter().enumerate() {
+            let transfer = sol_transfer("MIXER", "OTHER", amount, 0);
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(
+                    &format!("sig_other_{}", i),
+                    100,
+                    now - 3_600,
+                    vec![transfer],
+                ))
+                .await
+                .unwrap();
+        }
+
+        let buckets = state
+            .db_manager
+            .get_amount_distribution("MIXER", None)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0].amount_sol, 0.5);
+        assert_eq!(buckets[0].count, 20);
+
+        let flagged = detect_structuring(&buckets, 5, 24 * 3600);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].amount_sol, 0.5);
+        assert_eq!(flagged[0].window_count, 20);
+        assert_eq!(flagged[0].confidence, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drain_flushes_write_of_cancelled_request() {
+        use futures::FutureExt;
+
+        let state = test_state().await;
+        let ingest = tx(
+            "sigDrain",
+            1,
+            now_epoch(),
+            vec![sol_transfer("W1", "W2", 3.0, 0)],
+        );
+
+        // Poll once so the write is spawned, then drop it as a cancelled request would.
+        let mut pending = Box::pin(state.transfer_analytics.analyze_transaction(&ingest));
+        assert!((&mut pending).now_or_never().is_none());
+        drop(pending);
+        assert_eq!(state.transfer_analytics.pending_writes(), 1);
+
+        let summary = state.transfer_analytics.drain(Duration::from_secs(5)).await;
+        assert_eq!(summary.pending, 1);
+        assert_eq!(summary.flushed, 1);
+        assert_eq!(summary.unfinished, 0);
+        assert_eq!(summary.completed_total, 1);
+        assert_eq!(summary.cancelled_total, 0);
+
+        let events = state
+            .db_manager
+            .get_transfer_events_for_wallet("W1", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].signature, "sigDrain");
+
+        // Nothing in flight: drain returns immediately.
+        let idle = state.transfer_analytics.drain(Duration::ZERO).await;
+        assert_eq!((idle.pending, idle.unfinished), (0, 0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_writes_started_on_actix_workers_survive_worker_shutdown() {
+        use futures::FutureExt;
+
+        // Built on this runtime, as `start_server` builds it on main's.
+        let db = Arc::new(DatabaseManager::in_memory());
+        let analytics = Arc::new(
+            TransferAnalytics::new(Arc::clone(&db)).with_runtime(tokio::runtime::Handle::current()),
+        );
+
+        // Stall the write so it is still running when the workers stop.
+        let lock = db.lock_memory().await;
+
+        let app_analytics = Arc::clone(&analytics);
+        let server = HttpServer::new(move || {
+            let analytics = Arc::clone(&app_analytics);
+            App::new().route(
+                "/ingest",
+                web::post().to(move || {
+                    let analytics = Arc::clone(&analytics);
+                    async move {
+                        let ingest = tx(
+                            "sigWorker",
+                            1,
+                            now_epoch(),
+                            vec![sol_transfer("W1", "W2", 1.0, 0)],
+                        );
+                        // Start the write and answer without waiting for it.
+                        let _ = analytics.analyze_transaction(&ingest).now_or_never();
+                        HttpResponse::Accepted().finish()
+                    }
+                }),
+            )
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        let running = tokio::spawn(server);
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/ingest", addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 202);
+        assert_eq!(analytics.pending_writes(), 1);
+
+        // Stopping the server shuts down the worker's runtime.
+        handle.stop(true).await;
+        running.await.unwrap().unwrap();
+        // The write outlived the worker: still in flight, not cancelled.
+        assert_eq!(analytics.pending_writes(), 1);
+        drop(lock);
+
+        let summary = analytics.drain(Duration::from_secs(5)).await;
+        assert_eq!(summary.unfinished, 0);
+        assert_eq!(summary.completed_total, 1);
+        assert_eq!(summary.cancelled_total, 0);
+
+        let events = db
+            .get_transfer_events_for_wallet("W1", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].signature, "sigWorker");
+    }
+
+    #[tokio::test]
+    async fn batch_parse_reports_per_signature_failures() {
+        let ok = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 1,
+            "blockTime": now_epoch() - 60,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": {
+                "accountKeys": [{ "pubkey": "PAYER", "signer": true, "writable": true }],
+                "instructions": [{
+                    "programId": "11111111111111111111111111111111",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "PAYER", "destination": "PAYEE", "lamports": 2_000_000_000u64
+                    } }
+                }]
+            } }
+        } });
+        let missing =
+            json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32009, "message": "skipped" } });
+        let (endpoint, _) = mock_endpoint(vec![
+            (200, ok.to_string()),
+            (200, ok.to_string()),
+            (200, missing.to_string()),
+        ])
+        .await;
+        let state = test_state_with_rpc(&endpoint).await;
+
+        let signatures: Vec<String> = ["b_0", "b_1", "b_2", "b_3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let results = parse_signatures(&state, signatures.clone(), true).await;
+
+        // Input order is kept whatever order the fetches complete in.
+        let order: Vec<_> = results.iter().map(|r| r.signature.as_str()).collect();
+        assert_eq!(order, ["b_0", "b_1", "b_2", "b_3"]);
+        let parsed = results.iter().filter(|r| r.transaction.is_some()).count();
+        let failed: Vec<_> = results.iter().filter(|r| r.error.is_some()).collect();
+        assert_eq!(parsed, 2);
+        assert_eq!(failed.len(), 2);
+        assert!(failed.iter().all(|r| r.stage == Some("parse")));
+
+        let events = state
+            .db_manager
+            .get_transfers_between("PAYER", "PAYEE", None, 50)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn similar_wallets_rank_behavioral_match_above_outlier() {
+        let state = test_state().await;
+        let day_start = (now_epoch() - 5 * 86_400) / 86_400 * 86_400;
+        // TARGET and TWIN send ~1 SOL three times a day around 14:00 UTC; WHALE
+        // receives 300 SOL once at 03:00.
+        let mut txs = Vec::new();
+        for day in 0..3u64 {
+            for i in 0..3u64 {
+                let t = day_start + day * 86_400 + 14 * 3_600 + i * 600;
+                txs.push(tx(
+                    &format!("t{}{}", day, i),
+                    t,
+                    t,
+                    vec![sol_transfer("TARGET", "SINK_A", 1.0, 0)],
+                ));
+                txs.push(tx(
+                    &format!("w{}{}", day, i),
+                    t,
+                    t + 60,
+                    vec![sol_transfer("TWIN", "SINK_B", 1.2, 0)],
+                ));
+            }
+        }
+        let t = day_start + 3 * 3_600;
+        txs.push(tx(
+            "whale",
+            t,
+            t,
+            vec![sol_transfer("EXCHANGE", "WHALE", 300.0, 0)],
+        ));
+        for t in &txs {
+            state
+                .transfer_analytics
+                .analyze_transaction(t)
+                .await
+                .unwrap();
+        }
+
+        let target = state
+            .db_manager
+            .get_behavioral_profile("TARGET", Some(day_start))
+            .await
+            .unwrap()
+            .unwrap();
+        let profiles = state
+            .db_manager
+            .get_all_behavioral_profiles(Some(day_start), 100)
+            .await
+            .unwrap();
+        let ranked = rank_similar_wallets(&target, profiles, 10);
+
+        let position = |w: &str| ranked.iter().position(|s| s.wallet == w).unwrap();
+        assert!(ranked.iter().all(|s| s.wallet != "TARGET"));
+        assert!(position("TWIN") < position("WHALE"));
+        assert!(ranked[position("TWIN")].similarity > 0.9);
+        assert!(ranked[position("WHALE")].similarity < 0.3);
+    }
+
+    #[tokio::test]
+    async fn side_wallet_bfs_follows_only_requested_direction() {
+        let state = test_state().await;
+        let now = now_epoch();
+        // ORIGIN -> FUNDER -> MAIN -> SINK
+        for (sig, offset, from, to) in [
+            ("sig_origin", 300, "ORIGIN", "FUNDER"),
+            ("sig_fund", 200, "FUNDER", "MAIN"),
+            ("sig_spend", 100, "MAIN", "SINK"),
+        ] {
+            let t = tx(sig, 1, now - offset, vec![sol_transfer(from, to, 5.0, 0)]);
+            state
+                .transfer_analytics
+                .analyze_transaction(&t)
+                .await
+                .unwrap();
+        }
+
+        let addresses = |direction| {
+            let state = &state;
+            async move {
+                let mut found: Vec<String> =
+                    compute_side_wallets(state, "MAIN", &side_wallet_params(0.01, direction))
+                        .await
+                        .unwrap()
+                        .candidates
+                        .into_iter()
+                        .map(|c| c.address)
+                        .collect();
+                found.sort();
+                found
+            }
+        };
+
+        assert_eq!(
+            addresses(TraversalDirection::Inbound).await,
+            ["FUNDER", "ORIGIN"]
+        );
+        assert_eq!(addresses(TraversalDirection::Outbound).await, ["SINK"]);
+        assert_eq!(
+            addresses(TraversalDirection::Both).await,
+            ["FUNDER", "ORIGIN", "SINK"]
+        );
+        assert!(TraversalDirection::parse(Some("sideways")).is_err());
+    }
+
+    #[tokio::test]
+    async fn self_transfers_do_not_create_relationship_edges() {
+        use crate::core::enhanced_parser::TokenTransfer;
+
+        let state = test_state().await;
+        let mut moves = tx(
+            "sig_self",
+            1,
+            now_epoch() - 60,
+            vec![
+                sol_transfer("OWNER", "OWNER", 4.0, 0),
+                sol_transfer("OWNER", "FRIEND", 1.0, 1),
+            ],
+        );
+        // Between two of OWNER's own token accounts.
+        moves.token_transfers.push(TokenTransfer {
+            mint: "MINT".to_string(),
+            from_token_account: "ATA_1".to_string(),
+            to_token_account: "ATA_2".to_string(),
+            from_owner: Some("OWNER".to_string()),
+            to_owner: Some("OWNER".to_string()),
+            amount: 500,
+            decimals: 0,
+            amount_ui: 500.0,
+            authority: "OWNER".to_string(),
+            instruction_index: 2,
+            transfer_type: "transfer".to_string(),
+            token_symbol: None,
+            token_name: None,
+            verified: None,
+        });
+        state
+            .transfer_analytics
+            .analyze_transaction(&moves)
+            .await
+            .unwrap();
+
+        let connections = state
+            .db_manager
+            .get_wallet_connections("OWNER")
+            .await
+            .unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].to_wallet, "FRIEND");
+        // The events themselves are still stored as evidence.
+        let events = state
+            .db_manager
+            .get_transfer_events_for_wallet("OWNER", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 3);
+
+        // Opting in records the self-loop.
+        TransferAnalytics::new(Arc::clone(&state.db_manager))
+            .with_self_transfers(true)
+            .analyze_transaction(&tx(
+                "sig_self_2",
+                2,
+                now_epoch() - 30,
+                vec![sol_transfer("OWNER", "OWNER", 1.0, 0)],
+            ))
+            .await
+            .unwrap();
+        let connections = state
+            .db_manager
+            .get_wallet_connections("OWNER")
+            .await
+            .unwrap();
+        assert!(connections
+            .iter()
+            .any(|c| c.from_wallet == "OWNER" && c.to_wallet == "OWNER"));
+    }
+
+    #[tokio::test]
+    async fn whale_stream_delivers_new_transfers_above_threshold() {
+        let state = test_state().await;
+        let frames =
+            whale_transfer_frames(state.transfer_analytics.subscribe_sol_transfers(), 50.0);
+        tokio::pin!(frames);
+
+        let ingest = tx(
+            "sig_whale",
+            1,
+            now_epoch(),
+            vec![
+                sol_transfer("SMALL", "X", 10.0, 0),
+                sol_transfer("WHALE", "Y", 250.0, 1),
+            ],
+        );
+        state
+            .transfer_analytics
+            .analyze_transaction(&ingest)
+            .await
+            .unwrap();
+        // Re-ingesting stores nothing new, so nothing is pushed again.
+        state
+            .transfer_analytics
+            .analyze_transaction(&ingest)
+            .await
+            .unwrap();
+        let later = tx(
+            "sig_whale_2",
+            2,
+            now_epoch(),
+            vec![sol_transfer("WHALE", "Z", 75.0, 0)],
+        );
+        state
+            .transfer_analytics
+            .analyze_transaction(&later)
+            .await
+            .unwrap();
+
+        let frame = |bytes: web::Bytes| String::from_utf8(bytes.to_vec()).unwrap();
+        let first = frame(frames.next().await.unwrap().unwrap());
+        let event: serde_json::Value =
+            serde_json::from_str(first.trim().strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(event["signature"], "sig_whale");
+        assert_eq!(event["from_wallet"], "WHALE");
+        assert_eq!(event["amount_sol"], 250.0);
+
+        let second = frame(frames.next().await.unwrap().unwrap());
+        assert!(second.contains("sig_whale_2"), "{}", second);
+    }
+
+    #[tokio::test]
+    async fn whale_stream_reports_lag_to_slow_consumers() {
+        let (sender, rx) = tokio::sync::broadcast::channel(2);
+        let event = |sig: &str| {
+            let t = tx(sig, 1, 1, vec![sol_transfer("A", "B", 500.0, 0)]);
+            sender
+                .send(TransferEvent::from_sol_transfer(&t, &t.sol_transfers[0], 0))
+                .unwrap();
+        };
+        for sig in ["e1", "e2", "e3", "e4", "e5"] {
+            event(sig);
+        }
+
+        let frames: Vec<_> = whale_transfer_frames(rx, 0.0).take(3).collect().await;
+        let frames: Vec<String> = frames
+            .into_iter()
+            .map(|f| String::from_utf8(f.unwrap().to_vec()).unwrap())
+            .collect();
+        assert_eq!(frames[0], "event: lag\ndata: {\"skipped\":3}\n\n");
+        assert!(frames[1].contains("e4"));
+        assert!(frames[2].contains("e5"));
+    }
+
+    #[tokio::test]
+    async fn wallet_risk_breakdown_explains_relay_score() {
+        let state = test_state().await;
+        let start = now_epoch() - 3 * 3_600;
+        let fund = tx(
+            "sig_relay_in",
+            1,
+            start,
+            vec![sol_transfer("SOURCE", "RELAY", 10.0, 0)],
+        );
+        state
+            .transfer_analytics
+            .analyze_transaction(&fund)
+            .await
+            .unwrap();
+        for i in 0..20u64 {
+            let t = start + 60 + i * 120;
+            let out = tx(
+                &format!("sig_relay_out_{}", i),
+                t,
+                t,
+                vec![sol_transfer("RELAY", &format!("DEST_{}", i), 0.5, 0)],
+            );
+            state
+                .transfer_analytics
+                .analyze_transaction(&out)
+                .await
+                .unwrap();
+        }
+
+        let risk = assess_wallet_risk(&state, "RELAY", RISK_SCORE_WINDOW_DAYS)
+            .await
+            .unwrap();
+        let factor = |name: &str| risk.factors.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(factor("structuring").signal, 1.0);
+        assert!((factor("pass_through").signal - 1.0).abs() < 1e-9);
+        assert!(factor("connections").signal > 0.5);
+        let sum: f64 = risk.factors.iter().map(|f| f.contribution).sum();
+        assert!((risk.score - sum).abs() < 1e-12);
+        assert!(risk.score > 0.8);
+
+        let quiet = assess_wallet_risk(&state, "SOURCE", RISK_SCORE_WINDOW_DAYS)
+            .await
+            .unwrap();
+        assert!(quiet.score < 0.2, "{:?}", quiet);
+
+        // Both scores are stored for the high-risk listing; other lookbacks aren't.
+        let short = assess_wallet_risk(&state, "DEST_0", 1).await.unwrap();
+        let listed = state
+            .db_manager
+            .get_high_risk_wallets(0.0, 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].wallet, "RELAY");
+        assert_eq!(listed[0].score, risk.score);
+        assert!(listed[0].computed_at >= start);
+        assert!(listed.iter().all(|s| s.wallet != "DEST_0"), "{:?}", short);
+    }
+
+    #[tokio::test]
+    async fn sandwiched_lists_only_swaps_where_wallet_is_victim() {
+        use crate::dex::SwapEvent;
+
+        let state = test_state().await;
+        let swap = |sig: &str, wallet: &str, slot: u64, (ti, ai), (to, ao)| SwapEvent {
+            signature: sig.to_string(),
+            event_index: 0,
+            slot,
+            block_time: Some(1_000 + slot),
+            wallet: wallet.to_string(),
+            dex_name: "Raydium V4".to_string(),
+            program_id: None,
+            pool_address: Some("POOL".to_string()),
+            token_in_mint: String::from(ti),
+            amount_in: ai,
+            token_out_mint: String::from(to),
+            amount_out: ao,
+        };
+        for s in [
+            swap("front", "BOT", 99, ("SOL", 20.0), ("MEME", 2_000.0)),
+            swap("victim", "USER", 100, ("SOL", 5.0), ("MEME", 420.0)),
+            swap("back", "BOT", 101, ("MEME", 2_000.0), ("SOL", 20.6)),
+            // USER's own later swap back out is not a sandwich of anyone.
+            swap("exit", "USER", 500, ("MEME", 420.0), ("SOL", 4.9)),
+            // Far from USER's swaps, so never fetched.
+            swap("far", "BOT", 900, ("SOL", 1.0), ("MEME", 100.0)),
+        ] {
+            state.db_manager.store_swap_event(&s).await.unwrap();
+        }
+
+        let (checked, found) = wallet_sandwiches(&state, "USER", None, 100).await.unwrap();
+        assert_eq!(checked, 2);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].victim_signature, "victim");
+        assert_eq!(found[0].attacker, "BOT");
+        assert!((found[0].victim_loss_estimate - 0.6).abs() < 1e-9);
+
+        let (_, bot) = wallet_sandwiches(&state, "BOT", None, 100).await.unwrap();
+        assert!(bot.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scoring_weights_shift_enriched_scores() {
+        let state = test_state().await;
+        let t = now_epoch() - 3_600;
+        for (i, to) in ["MAIN", "CAND"].into_iter().enumerate() {
+            let sig = format!("sig_fund_{}", to);
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(
+                    &sig,
+                    10 + i as u64,
+                    t + i as u64 * 600,
+                    vec![sol_transfer("FUNDER", to, 1.0, 0)],
+                ))
+                .await
+                .unwrap();
+        }
+
+        let candidate = || SideWalletCandidate {
+            address: "CAND".to_string(),
+            score: 0.2,
+            ..Default::default()
+        };
+        let enrich = |scoring: SideWalletScoringConfig| {
+            let state = &state;
+            async move {
+                let mut candidates = vec![candidate()];
+                enrich_candidates_with_event_signals(state, "MAIN", &mut candidates, 30, &scoring)
+                    .await;
+                candidates.remove(0)
+            }
+        };
+
+        let base = enrich(SideWalletScoringConfig::default()).await;
+        assert_eq!(base.shared_funders_count, 1);
+        let heavier = enrich(SideWalletScoringConfig {
+            shared_funder: 0.16,
+            ..Default::default()
+        })
+        .await;
+        assert!((heavier.score - base.score - 0.10).abs() < 1e-9);
+
+        let silent = enrich(SideWalletScoringConfig {
+            shared_funder: 0.0,
+            shared_counterparty: 0.0,
+            shared_mint: 0.0,
+            behavioral: 0.0,
+            same_block: 0.0,
+            temporal_overlap: 0.0,
+        })
+        .await;
+        assert_eq!(silent.score, 0.2);
+    }
+
+    #[tokio::test]
+    async fn bootstrap_pages_back_until_the_lookback_cutoff() {
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        let recent = now_epoch() - 3_600;
+        let page = |sigs: Vec<(String, u64)>| {
+            let result: Vec<_> = sigs
+                .into_iter()
+                .map(|(signature, t)| json!({ "signature": signature, "slot": 1, "blockTime": t }))
+                .collect();
+            json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string()
+        };
+        let first = page((0..50).map(|i| (format!("sig_{}", i), recent)).collect());
+        let second = page(vec![
+            ("sig_50".to_string(), recent),
+            ("sig_51".to_string(), 0),
+            ("sig_old".to_string(), 1_000),
+            ("sig_older".to_string(), 900),
+        ]);
+        let (endpoint, requests) = mock_endpoint(vec![(200, first), (200, second)]).await;
+        let state = test_state_with_rpc(&endpoint).await;
+
+        let since = now_epoch() - 86_400;
+        let signatures = signatures_since(&state, wallet, 60, since).await.unwrap();
+        assert_eq!(signatures.len(), 52);
+        assert_eq!(signatures.last().map(|s| s.as_str()), Some("sig_51"));
+        assert!(!signatures.iter().any(|s| s.starts_with("sig_old")));
+
+        let requests = requests.lock().await;
+        assert_eq!(requests.len(), 2);
+        let second_params: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        assert_eq!(second_params["params"][1]["before"], "sig_49");
+        assert_eq!(second_params["params"][1]["limit"], 10);
+    }
+
+    #[tokio::test]
+    async fn failed_ingestion_is_recorded_and_cleared_by_retry() {
+        let skipped =
+            json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32009, "message": "skipped" } });
+        let ok = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 1,
+            "blockTime": now_epoch() - 60,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": {
+                "accountKeys": [{ "pubkey": "PAYER", "signer": true, "writable": true }],
+                "instructions": [{
+                    "programId": "11111111111111111111111111111111",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "PAYER", "destination": "PAYEE", "lamports": 1_000_000_000u64
+                    } }
+                }]
+            } }
+        } });
+        let (endpoint, _) =
+            mock_endpoint(vec![(200, skipped.to_string()), (200, ok.to_string())]).await;
+        let state = test_state_with_rpc(&endpoint).await;
+
+        let results = ingest_signatures(&state, vec!["sig_gap".to_string()]).await;
+        assert!(matches!(results[0].1, IngestOutcome::ParseFailed(_)));
+        let pending = state
+            .db_manager
+            .get_pending_failures(8, u64::MAX, 10)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].signature, "sig_gap");
+        assert_eq!(pending[0].stage, "parse");
+
+        // Still backing off: nothing retried.
+        let report = retry_ingestion_failures(&state, 10, now_epoch())
+            .await
+            .unwrap();
+        assert_eq!((report.pending, report.waiting, report.retried), (1, 1, 0));
+
+        let report = retry_ingestion_failures(&state, 10, now_epoch() + 3_600)
+            .await
+            .unwrap();
+        assert_eq!(report.succeeded, vec!["sig_gap".to_string()]);
+        assert!(report.failed.is_empty());
+        assert!(state
+            .db_manager
+            .get_pending_failures(8, u64::MAX, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let events = state
+            .db_manager
+            .get_transfers_between("PAYER", "PAYEE", None, 50)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_investigate_reports_every_section_independently() {
+        let state = test_state().await;
+        let now = now_epoch();
+        for (i, to) in ["W2", "W2", "W3"].iter().enumerate() {
+            let ingest = tx(
+                &format!("sigInvestigate{}", i),
+                i as u64 + 1,
+                now - 3_600 * (i as u64 + 1),
+                vec![sol_transfer("W1", to, 2.0, 0)],
+            );
+            state
+                .transfer_analytics
+                .analyze_transaction(&ingest)
+                .await
+                .unwrap();
+        }
+
+        let report = investigate_wallet(&state, "W1", 30).await;
+        assert_eq!(report["wallet"], "W1");
+        let sections = report["sections"].as_object().unwrap();
+        for name in [
+            "holdings",
+            "profile",
+            "top_counterparties",
+            "side_wallets",
+            "patterns",
+            "risk",
+        ] {
+            assert!(sections[name]["status"].is_string(), "missing {}", name);
+        }
+
+        // No RPC behind the test state: holdings fail without taking the rest down.
+        assert_eq!(sections["holdings"]["status"], "error");
+        assert!(sections["holdings"]["error"].is_string());
+        assert_eq!(sections["profile"]["status"], "ok");
+        assert_eq!(sections["profile"]["data"]["total_transfers"], 3);
+        let counterparties = sections["top_counterparties"]["data"].as_array().unwrap();
+        assert_eq!(counterparties.len(), 2);
+        assert_eq!(sections["side_wallets"]["status"], "ok");
+        assert!(sections["patterns"]["data"]["structuring"].is_array());
+        assert!(sections["risk"]["data"]["risk_score"].is_number());
+    }
+
+    #[tokio::test]
+    async fn compare_finds_shared_funder_and_same_block_activity() {
+        let state = test_state().await;
+        let now = now_epoch();
+
+        // FUNDER seeds A and B in one slot; both then pay SHOP in another. C is
+        // unrelated.
+        for (sig, slot, time, transfer) in [
+            (
+                "sig_fund_a",
+                10,
+                now - 7200,
+                sol_transfer("FUNDER", "A", 5.0, 0),
+            ),
+            (
+                "sig_fund_b",
+                10,
+                now - 7200,
+                sol_transfer("FUNDER", "B", 5.0, 0),
+            ),
+            (
+                "sig_a_shop",
+                20,
+                now - 3600,
+                sol_transfer("A", "SHOP", 2.0, 0),
+            ),
+            (
+                "sig_b_shop",
+                20,
+                now - 3590,
+                sol_transfer("B", "SHOP", 2.0, 0),
+            ),
+            (
+                "sig_fund_c",
+                30,
+                now - 20 * 86_400,
+                sol_transfer("OTHER", "C", 1.0, 0),
+            ),
+        ] {
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(sig, slot, time, vec![transfer]))
+                .await
+                .unwrap();
+        }
+
+        let linked = compare_wallets(&state, "A", "B", 30).await.unwrap();
+        assert_eq!(linked.verdict, "likely_linked");
+        assert_eq!(linked.shared_funders.len(), 1);
+        assert_eq!(linked.shared_funders[0].wallet, "FUNDER");
+        // FUNDER is counted once, as a funder.
+        assert_eq!(linked.shared_counterparties, vec!["SHOP"]);
+        // Only the SHOP payments they signed share a block, not the funding.
+        assert_eq!(linked.temporal_overlap.same_block_count, 1);
+        assert!(linked.behavioral_similarity.is_some());
+        assert!(linked.reasons[0].starts_with("Shared inbound funder: FUNDER"));
+
+        let unrelated = compare_wallets(&state, "A", "C", 30).await.unwrap();
+        assert_ne!(unrelated.verdict, "likely_linked");
+        assert!(unrelated.shared_funders.is_empty());
+        assert!(unrelated.shared_counterparties.is_empty());
+        assert_eq!(unrelated.temporal_overlap.same_block_count, 0);
+    }
+
+    #[tokio::test]
+    async fn compare_counts_a_lone_shared_funder_once() {
+        let state = test_state().await;
+        let now = now_epoch();
+        // FUNDER pays X and Y in one transaction; neither does anything else.
+        state
+            .transfer_analytics
+            .analyze_transaction(&tx(
+                "sig_fund_both",
+                10,
+                now - 600,
+                vec![
+                    sol_transfer("FUNDER", "X", 5.0, 0),
+                    sol_transfer("FUNDER", "Y", 5.0, 1),
+                ],
+            ))
+            .await
+            .unwrap();
+
+        let comparison = compare_wallets(&state, "X", "Y", 30).await.unwrap();
+        assert_eq!(comparison.verdict, "possibly_linked");
+        assert_eq!(comparison.reasons.len(), 1);
+        assert!(comparison.shared_counterparties.is_empty());
+        assert_eq!(comparison.temporal_overlap.same_block_count, 0);
+    }
+
+    #[tokio::test]
+    async fn side_wallet_expansion_stops_at_registered_exchange() {
+        let state = test_state().await;
+        let now = now_epoch();
+        let binance = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+        for (sig, from, to) in [
+            ("sig_main_cex", "MAIN", binance),
+            ("sig_cex_user", binance, "CEX_USER"),
+            ("sig_main_side", "MAIN", "SIDE"),
+            ("sig_side_next", "SIDE", "SIDE_NEXT"),
+        ] {
+            let t = tx(sig, 1, now - 3600, vec![sol_transfer(from, to, 5.0, 0)]);
+            state
+                .transfer_analytics
+                .analyze_transaction(&t)
+                .await
+                .unwrap();
+        }
+
+        let params = side_wallet_params(0.01, TraversalDirection::Both);
+        let stopped = compute_side_wallets(&state, "MAIN", &params)
+            .await
+            .unwrap()
+            .candidates;
+        let exchange = stopped.iter().find(|c| c.address == binance).unwrap();
+        assert_eq!(exchange.hub.as_deref(), Some("Binance exchange"));
+        assert!(exchange.reasons[0].contains("not expanded"));
+        let found: HashSet<&str> = stopped.iter().map(|c| c.address.as_str()).collect();
+        assert!(found.contains("SIDE_NEXT"));
+        assert!(!found.contains("CEX_USER"), "{:?}", found);
+
+        let expanded = compute_side_wallets(
+            &state,
+            "MAIN",
+            &SideWalletParams {
+                stop_at_hubs: false,
+                ..params
+            },
+        )
+        .await
+        .unwrap()
+        .candidates;
+        assert!(expanded.iter().any(|c| c.address == "CEX_USER"));
+        assert!(expanded.iter().all(|c| c.hub.is_none()));
+    }
+    #[tokio::test]
+    async fn fresh_wallets_are_flagged_by_first_seen_time() {
+        let state = test_state().await;
+        let now = now_epoch();
+        for (sig, from, to, block_time) in [
+            ("sig_old_first", "FUNDER", "OLD", now - 60 * 86_400),
+            ("sig_main_old", "MAIN", "OLD", now - 3_600),
+            ("sig_main_new", "MAIN", "NEW", now - 86_400),
+        ] {
+            let t = tx(sig, 1, block_time, vec![sol_transfer(from, to, 5.0, 0)]);
+            state
+                .transfer_analytics
+                .analyze_transaction(&t)
+                .await
+                .unwrap();
+        }
+
+        let params = side_wallet_params(0.01, TraversalDirection::Both);
+        let candidates = compute_side_wallets(&state, "MAIN", &params)
+            .await
+            .unwrap()
+            .candidates;
+        let candidate = |addr: &str| candidates.iter().find(|c| c.address == addr).unwrap();
+        let new = candidate("NEW");
+        assert!(new.fresh_wallet);
+        assert!((new.age_days.unwrap() - 1.0).abs() < 0.01);
+        assert!(new.reasons.iter().any(|r| r.starts_with("Fresh wallet")));
+        let old = candidate("OLD");
+        assert!(!old.fresh_wallet);
+        assert!(old.age_days.unwrap() > 59.0);
+
+        // Age is all-time, so a lookback that misses OLD's first transfer still sees it.
+        let lookback_days = 2;
+        let fresh = |risk: &RiskAssessment| {
+            risk.factors
+                .iter()
+                .find(|f| f.name == "fresh_wallet")
+                .unwrap()
+                .signal
+        };
+        let new_risk = assess_wallet_risk(&state, "NEW", lookback_days)
+            .await
+            .unwrap();
+        assert!(fresh(&new_risk) > 0.8);
+        // OLD is not fresh itself; its only recent counterparty (MAIN) is, which
+        // raises the factor to half.
+        let old_risk = assess_wallet_risk(&state, "OLD", lookback_days)
+            .await
+            .unwrap();
+        assert_eq!(fresh(&old_risk), 0.5);
+        // MAIN is a day old and half its counterparties are fresh.
+        let main_risk = assess_wallet_risk(&state, "MAIN", lookback_days)
+            .await
+            .unwrap();
+        assert!(fresh(&main_risk) > 0.8);
+    }
+    #[tokio::test]
+    async fn price_history_reports_vwap_per_hour_bucket() {
+        use crate::dex::types::WSOL_MINT;
+        use crate::dex::SwapEvent;
+
+        let state = test_state().await;
+        let hour = 1_700_000_000 / 3_600 * 3_600;
+        let swap = |sig: &str, block_time: u64, (ti, ai), (to, ao)| SwapEvent {
+            signature: sig.to_string(),
+            event_index: 0,
+            slot: block_time,
+            block_time: Some(block_time),
+            wallet: "TRADER".to_string(),
+            dex_name: "Raydium V4".to_string(),
+            program_id: None,
+            pool_address: Some("POOL".to_string()),
+            token_in_mint: String::from(ti),
+            amount_in: ai,
+            token_out_mint: String::from(to),
+            amount_out: ao,
+        };
+        for s in [
+            // First hour: 100 MEME at 0.01, then 300 at 0.02.
+            swap("buy_1", hour + 60, (WSOL_MINT, 1.0), ("MEME", 100.0)),
+            swap("buy_2", hour + 1_200, (WSOL_MINT, 6.0), ("MEME", 300.0)),
+            // Second hour: sells at 0.03 and 0.015.
+            swap("sell_1", hour + 3_700, ("MEME", 100.0), (WSOL_MINT, 3.0)),
+            swap("sell_2", hour + 5_000, ("MEME", 200.0), (WSOL_MINT, 3.0)),
+            // Another pair of the same mint is not priced in SOL.
+            swap("usdc", hour + 3_800, ("USDC", 5.0), ("MEME", 50.0)),
+        ] {
+            state.db_manager.store_swap_event(&s).await.unwrap();
+        }
+
+        let (considered, buckets) = token_price_history(&state, "MEME", None, 1_000, 3_600)
+            .await
+            .unwrap();
+        assert_eq!(considered, 5);
+        assert_eq!(buckets.len(), 2);
+
+        let first = &buckets[0];
+        assert_eq!(first.bucket_start, hour);
+        assert_eq!((first.open, first.close), (0.01, 0.02));
+        assert_eq!((first.low, first.high), (0.01, 0.02));
+        assert_eq!((first.volume, first.volume_sol), (400.0, 7.0));
+        assert!((first.vwap - 7.0 / 400.0).abs() < 1e-12);
+
+        let second = &buckets[1];
+        assert_eq!(second.bucket_start, hour + 3_600);
+        assert_eq!(second.swaps, 2);
+        assert_eq!((second.open, second.close), (0.03, 0.015));
+        assert!((second.vwap - 6.0 / 300.0).abs() < 1e-12);
+    }
+    #[tokio::test]
+    async fn wallet_summary_is_cached_until_invalidated() {
+        let state = test_state().await;
+        let t = now_epoch() - 3_600;
+        for (sig, from, to, sol) in [
+            ("sig_sum_in", "FUNDER", "WALLET", 4.0),
+            ("sig_sum_out", "WALLET", "DEST", 1.5),
+        ] {
+            state
+                .transfer_analytics
+                .analyze_transaction(&tx(sig, 1, t, vec![sol_transfer(from, to, sol, 0)]))
+                .await
+                .unwrap();
+        }
+
+        let fresh = wallet_summary(&state, "WALLET").await.unwrap().unwrap();
+        assert_eq!(fresh["cached"], false);
+        assert_eq!(fresh["transaction_count"], 2);
+        assert_eq!(fresh["inbound_sol"], 4.0);
+        assert_eq!(fresh["outbound_sol"], 1.5);
+        assert_eq!(fresh["unique_counterparties"], 2);
+        assert_eq!(fresh["first_seen_epoch"], t);
+
+        let cached = wallet_summary(&state, "WALLET").await.unwrap().unwrap();
+        assert_eq!(cached["cached"], true);
+        state.side_wallet_cache.invalidate_wallet("WALLET").await;
+        let recomputed = wallet_summary(&state, "WALLET").await.unwrap().unwrap();
+        assert_eq!(recomputed["cached"], false);
+
+        assert!(wallet_summary(&state, "NOBODY").await.unwrap().is_none());
+    }
+    #[tokio::test]
+    async fn side_wallet_expansion_samples_a_huge_hub_within_the_graph_cap() {
+        use crate::graph::GraphCap;
+
+        let mut state = test_state().await;
+        state.graph_engine = Arc::new(
+            GraphAnalysisEngine::new(Arc::clone(&state.db_manager)).with_graph_cap(GraphCap {
+                max_nodes: 10,
+                max_edges: 60,
+            }),
+        );
+        let db = &state.db_manager;
+        db.store_wallet_relationship("MAIN", "HUB", 50.0, 0)
+            .await
+            .unwrap();
+        for i in 0..50_000 {
+            db.store_wallet_relationship("HUB", &format!("W{}", i), 1.0, 0)
+                .await
+                .unwrap();
+        }
+        db.store_wallet_relationship("SOLO", "FRIEND", 1.0, 0)
+            .await
+            .unwrap();
+
+        let params = side_wallet_params(0.0, TraversalDirection::Both);
+        let hub = compute_side_wallets(&state, "MAIN", &params).await.unwrap();
+        assert!(hub.sampled);
+        assert!(!hub.candidates.is_empty());
+        assert!(hub.candidates.len() <= 10, "{}", hub.candidates.len());
+        assert!(hub.candidates.iter().any(|c| c.address == "HUB"));
+
+        let solo = compute_side_wallets(&state, "SOLO", &params).await.unwrap();
+        assert!(!solo.sampled);
+        assert_eq!(solo.candidates.len(), 1);
     }
 }
@@ -2,24 +2,278 @@
 ///
 /// This approach uses Actix-web extractors instead of middleware,
 /// avoiding complex type system issues while providing clean auth.
-use actix_web::{dev::Payload, error::ErrorUnauthorized, Error, FromRequest, HttpRequest};
+///
+/// Keys are configured as `key`, `key:scope` or `key:scope:expires_at`, where scope
+/// is `read`, `write` or `admin` and `expires_at` is a unix timestamp. Plain keys
+/// get full (admin) scope for backward compatibility. Keys can be added and
+/// revoked at runtime; listings and revocation refer to a key by its `key_id`, so
+/// the secret never appears in a URL or a log line.
+///
+/// With no keys configured every route is public except the admin ones, which
+/// answer 403 until `API_KEYS` is set.
+use crate::api::responses::ApiError;
+use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
 use futures::future::{ready, Ready};
-use std::sync::OnceLock;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{LazyLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What an API key is allowed to do. Scopes are ordered: each includes the ones below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Query stored analysis.
+    Read,
+    /// Also trigger ingestion (bootstrap) against the RPC.
+    Write,
+    /// Everything, including key management.
+    Admin,
+}
+
+impl Scope {
+    pub fn parse(s: &str) -> Option<Scope> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// A configured API key.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiKeyRecord {
+    /// `None` if the configured scope was not recognized; such keys are known
+    /// but rejected with 403.
+    pub scope: Option<Scope>,
+    /// Unix timestamp after which the key is rejected.
+    pub expires_at: Option<u64>,
+    pub revoked: bool,
+    pub created_at: u64,
+}
+
+/// Public identifier of a key: the first 8 bytes of its SHA-256, in hex.
+pub fn key_id(key: &str) -> String {
+    Sha256::digest(key.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl ApiKeyRecord {
+    /// Not revoked and not expired.
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_at(now_epoch())
+    }
+
+    fn is_valid_at(&self, now: u64) -> bool {
+        !self.revoked && self.expires_at.is_none_or(|exp| now < exp)
+    }
+}
+
+/// Key store. Authentication is enabled once any key has been configured;
+/// revoked and expired keys stay in the store so revoking the last key does
+/// not open the API.
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    keys: RwLock<HashMap<String, ApiKeyRecord>>,
+}
+
+impl KeyStore {
+    /// Build from `key`, `key:scope` or `key:scope:expires_at` entries.
+    pub fn from_entries(entries: Vec<String>) -> Self {
+        let store = KeyStore::default();
+        store.load_entries(entries);
+        store
+    }
+
+    fn load_entries(&self, entries: Vec<String>) {
+        let now = now_epoch();
+        let mut keys = self.write();
+        for entry in entries {
+            let mut parts = entry.splitn(3, ':');
+            let key = parts.next().unwrap_or_default().trim().to_string();
+            if key.is_empty() {
+                continue;
+            }
+            let scope = match parts.next() {
+                Some(scope) => Scope::parse(scope),
+                None => Some(Scope::Admin),
+            };
+            let expires_at = parts.next().and_then(|e| e.trim().parse::<u64>().ok());
+            keys.insert(
+                key,
+                ApiKeyRecord {
+                    scope,
+                    expires_at,
+                    revoked: false,
+                    created_at: now,
+                },
+            );
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, ApiKeyRecord>> {
+        self.keys.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, ApiKeyRecord>> {
+        self.keys.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Add (or replace) a key.
+    pub fn add_api_key(&self, key: &str, scope: Scope, expires_at: Option<u64>) {
+        self.write().insert(
+            key.to_string(),
+            ApiKeyRecord {
+                scope: Some(scope),
+                expires_at,
+                revoked: false,
+                created_at: now_epoch(),
+            },
+        );
+    }
+
+    /// Revoke a key; returns false if it does not exist.
+    pub fn revoke_api_key(&self, key: &str) -> bool {
+        match self.write().get_mut(key) {
+            Some(record) => {
+                record.revoked = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Revoke the key whose `key_id` is `id`; returns false if there is none.
+    pub fn revoke_by_id(&self, id: &str) -> bool {
+        match self.write().iter_mut().find(|(key, _)| key_id(key) == id) {
+            Some((_, record)) => {
+                record.revoked = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the key exists, is not revoked and has not expired.
+    pub fn is_valid(&self, key: &str) -> bool {
+        let now = now_epoch();
+        self.read()
+            .get(key)
+            .is_some_and(|record| record.is_valid_at(now))
+    }
+
+    /// All keys as `key_id` and record, sorted by id.
+    pub fn list(&self) -> Vec<(String, ApiKeyRecord)> {
+        let mut out: Vec<(String, ApiKeyRecord)> = self
+            .read()
+            .iter()
+            .map(|(k, r)| (key_id(k), r.clone()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    /// Authorize a request carrying `header` for an operation needing `required`.
+    pub fn authorize(&self, header: Option<&str>, required: Scope) -> Result<ApiKey, ApiError> {
+        self.authorize_at(header, required, now_epoch())
+    }
+
+    fn authorize_at(
+        &self,
+        header: Option<&str>,
+        required: Scope,
+        now: u64,
+    ) -> Result<ApiKey, ApiError> {
+        let keys = self.read();
+        if keys.is_empty() {
+            // Key management and other admin routes stay off until keys are
+            // configured: otherwise anyone could mint the first key.
+            if required >= Scope::Admin {
+                return Err(ApiError::forbidden(
+                    "Admin routes are disabled: configure API_KEYS to enable them",
+                ));
+            }
+            return Ok(ApiKey {
+                key: "auth-disabled".to_string(),
+                scope: Scope::Write,
+            });
+        }
+
+        let Some(key) = header else {
+            // Missing API key
+            return Err(ApiError::unauthorized(
+                "Missing API key: include the 'X-API-Key' header in your request",
+            ));
+        };
+
+        let Some(record) = keys.get(key) else {
+            return Err(ApiError::unauthorized(
+                "Invalid API key: the provided API key is not valid",
+            ));
+        };
+        if record.revoked {
+            return Err(ApiError::unauthorized(
+                "Revoked API key: the provided API key has been revoked",
+            ));
+        }
+        if !record.is_valid_at(now) {
+            return Err(ApiError::unauthorized(format!(
+                "Expired API key: the provided API key expired at {}",
+                record.expires_at.unwrap_or_default()
+            )));
+        }
+
+        match record.scope {
+            None => Err(ApiError::forbidden(
+                "Unknown scope: the API key is configured with an unrecognized scope",
+            )),
+            Some(scope) if scope < required => Err(insufficient_scope(required)),
+            Some(scope) => Ok(ApiKey {
+                key: key.to_string(),
+                scope,
+            }),
+        }
+    }
+}
+
+fn insufficient_scope(required: Scope) -> ApiError {
+    ApiError::forbidden(format!(
+        "Insufficient scope: this operation requires {:?} scope",
+        required
+    ))
+}
+
+fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
 
 /// Global API keys configuration
-static API_KEYS: OnceLock<Vec<String>> = OnceLock::new();
+static API_KEYS: LazyLock<KeyStore> = LazyLock::new(KeyStore::default);
 
-/// Initialize API keys from configuration
+/// Initialize API keys from configuration (`key`, `key:scope` or `key:scope:expires_at` entries)
 pub fn init_api_keys(keys: Vec<String>) {
-    API_KEYS.get_or_init(|| keys);
+    API_KEYS.load_entries(keys);
 }
 
 /// Get configured API keys
-fn get_api_keys() -> &'static [String] {
-    match API_KEYS.get() {
-        Some(keys) => keys.as_slice(),
-        None => &[],
-    }
+pub fn get_api_keys() -> &'static KeyStore {
+    &API_KEYS
 }
 
 /// Check if authentication is enabled
@@ -27,7 +281,17 @@ pub fn is_auth_enabled() -> bool {
     !get_api_keys().is_empty()
 }
 
-/// Authenticated request - requires valid API key
+fn header_key(req: &HttpRequest) -> Option<&str> {
+    req.headers().get("X-API-Key").and_then(|v| v.to_str().ok())
+}
+
+/// Whether the request's key (or open access, with auth disabled) holds
+/// `required`, for middleware that runs before the handler's extractors.
+pub fn request_has_scope(req: &HttpRequest, required: Scope) -> bool {
+    get_api_keys().authorize(header_key(req), required).is_ok()
+}
+
+/// Authenticated request - requires valid API key (any scope)
 ///
 /// Usage in handlers:
 /// ```
@@ -40,45 +304,84 @@ pub fn is_auth_enabled() -> bool {
 /// }
 /// ```
 #[derive(Debug, Clone)]
-pub struct ApiKey(pub String);
+pub struct ApiKey {
+    pub key: String,
+    pub scope: Scope,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, required: Scope) -> bool {
+        self.scope >= required
+    }
+
+    /// 403 error for handlers that need more scope for part of a request.
+    pub fn require(&self, required: Scope) -> Result<(), ApiError> {
+        if self.has_scope(required) {
+            Ok(())
+        } else {
+            Err(insufficient_scope(required))
+        }
+    }
+}
 
 impl FromRequest for ApiKey {
     type Error = Error;
     type Future = Ready<Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        // If auth is disabled, allow all requests
-        if !is_auth_enabled() {
-            return ready(Ok(ApiKey("auth-disabled".to_string())));
-        }
+        ready(
+            get_api_keys()
+                .authorize(header_key(req), Scope::Read)
+                .map_err(Error::from),
+        )
+    }
+}
 
-        // Extract API key from header
-        let api_key = req
-            .headers()
-            .get("X-API-Key")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
-
-        match api_key {
-            Some(key) if get_api_keys().contains(&key) => {
-                // Valid API key
-                ready(Ok(ApiKey(key)))
-            }
-            Some(_) => {
-                // Invalid API key
-                ready(Err(ErrorUnauthorized(serde_json::json!({
-                    "error": "Invalid API key",
-                    "message": "The provided API key is not valid"
-                }))))
-            }
-            None => {
-                // Missing API key
-                ready(Err(ErrorUnauthorized(serde_json::json!({
-                    "error": "Missing API key",
-                    "message": "API key required. Include 'X-API-Key' header in your request"
-                }))))
-            }
-        }
+/// Marker for the scope a `RequireScope` extractor enforces.
+pub trait ScopeLevel {
+    const SCOPE: Scope;
+}
+
+pub struct Write;
+pub struct Admin;
+
+impl ScopeLevel for Write {
+    const SCOPE: Scope = Scope::Write;
+}
+
+impl ScopeLevel for Admin {
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// Authenticated request whose key must hold at least scope `S`.
+///
+/// ```
+/// use actix_web::HttpResponse;
+/// use onchain_beast::auth::{Admin, RequireScope};
+///
+/// async fn admin_handler(_auth: RequireScope<Admin>) -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+/// ```
+pub struct RequireScope<S: ScopeLevel> {
+    pub key: ApiKey,
+    _scope: PhantomData<S>,
+}
+
+impl<S: ScopeLevel> FromRequest for RequireScope<S> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            get_api_keys()
+                .authorize(header_key(req), S::SCOPE)
+                .map(|key| RequireScope {
+                    key,
+                    _scope: PhantomData,
+                })
+                .map_err(Error::from),
+        )
     }
 }
 
@@ -115,29 +418,35 @@ impl FromRequest for MaybeApiKey {
             return ready(Ok(MaybeApiKey(None)));
         }
 
-        // Extract API key from header
-        let api_key = req
-            .headers()
-            .get("X-API-Key")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
-
-        match api_key {
-            Some(key) if get_api_keys().contains(&key) => {
-                // Valid API key - authenticated
-                ready(Ok(MaybeApiKey(Some(ApiKey(key)))))
-            }
-            _ => {
-                // No key or invalid key - public access
-                ready(Ok(MaybeApiKey(None)))
-            }
-        }
+        // No key, invalid key or unknown scope - public access
+        ready(Ok(MaybeApiKey(
+            get_api_keys().authorize(header_key(req), Scope::Read).ok(),
+        )))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    fn store() -> KeyStore {
+        KeyStore::from_entries(vec![
+            "legacy".to_string(),
+            "reader:read".to_string(),
+            "writer:write".to_string(),
+            "boss:admin".to_string(),
+            "odd:superuser".to_string(),
+        ])
+    }
+
+    fn status(result: Result<ApiKey, ApiError>) -> StatusCode {
+        match result {
+            Ok(_) => StatusCode::OK,
+            Err(e) => e.status_code(),
+        }
+    }
 
     #[test]
     fn test_api_keys_initialization() {
@@ -148,4 +457,129 @@ mod tests {
         assert!(is_auth_enabled());
         assert_eq!(get_api_keys().len(), 2);
     }
+
+    #[test]
+    fn test_plain_keys_get_full_scope() {
+        let key = store().authorize(Some("legacy"), Scope::Admin).unwrap();
+        assert_eq!(key.scope, Scope::Admin);
+    }
+
+    #[test]
+    fn test_scope_hierarchy() {
+        let store = store();
+        assert_eq!(
+            status(store.authorize(Some("reader"), Scope::Read)),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(store.authorize(Some("reader"), Scope::Write)),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(store.authorize(Some("writer"), Scope::Write)),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(store.authorize(Some("writer"), Scope::Admin)),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(store.authorize(Some("boss"), Scope::Admin)),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_unknown_scope_is_forbidden() {
+        assert_eq!(
+            status(store().authorize(Some("odd"), Scope::Read)),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_missing_and_invalid_keys_are_unauthorized() {
+        let store = store();
+        assert_eq!(
+            status(store.authorize(None, Scope::Read)),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(store.authorize(Some("nope"), Scope::Read)),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn test_disabled_auth_allows_everything_but_admin() {
+        let store = KeyStore::default();
+        let key = store.authorize(None, Scope::Write).unwrap();
+        assert!(key.has_scope(Scope::Write));
+        assert!(!key.has_scope(Scope::Admin));
+        assert_eq!(
+            status(store.authorize(None, Scope::Admin)),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(store.authorize(Some("anything"), Scope::Admin)),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_keys_are_listed_and_revoked_by_id() {
+        let store = KeyStore::from_entries(vec!["first-secret-key:read".to_string()]);
+        let id = key_id("first-secret-key");
+        assert_eq!(id.len(), 16);
+        assert_ne!(id, key_id("other-secret-key"));
+
+        let listed = store.list();
+        assert_eq!(listed[0].0, id);
+        assert!(listed[0].1.is_valid());
+
+        assert!(!store.revoke_by_id("first-secret-key"));
+        assert!(store.revoke_by_id(&id));
+        assert!(!store.is_valid("first-secret-key"));
+    }
+
+    #[test]
+    fn test_key_rejected_after_expiry() {
+        let store = KeyStore::default();
+        store.add_api_key("temp", Scope::Read, Some(1_000));
+
+        assert_eq!(
+            status(store.authorize_at(Some("temp"), Scope::Read, 999)),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(store.authorize_at(Some("temp"), Scope::Read, 1_000)),
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(!store.is_valid("temp"));
+    }
+
+    #[test]
+    fn test_revoked_key_rejected_and_auth_stays_enabled() {
+        let store = KeyStore::from_entries(vec!["only:admin".to_string()]);
+        assert!(store.is_valid("only"));
+
+        assert!(store.revoke_api_key("only"));
+        assert!(!store.revoke_api_key("missing"));
+        assert!(!store.is_valid("only"));
+        assert_eq!(
+            status(store.authorize(Some("only"), Scope::Read)),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(store.authorize(None, Scope::Read)),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn test_expiry_parsed_from_entries() {
+        let store = KeyStore::from_entries(vec!["old:read:1".to_string()]);
+        assert!(!store.is_valid("old"));
+        assert_eq!(store.list()[0].1.expires_at, Some(1));
+    }
 }
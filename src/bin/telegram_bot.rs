@@ -3,10 +3,59 @@
 // Build: cargo build --release --bin telegram_bot
 // Run:   TELEGRAM_BOT_TOKEN=... ./target/release/telegram_bot
 
+use onchain_beast::core::is_valid_address;
+use onchain_beast::storage::{DatabaseManager, WatchEntry};
 use reqwest::Client;
 use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
 use teloxide::prelude::*;
-use teloxide::types::ParseMode;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+
+/// Candidates shown per message; "Next page" fetches the following slice.
+const PAGE_SIZE: usize = 5;
+/// Deepest page reachable via "Next page".
+const MAX_PAGE: usize = 9;
+/// Telegram rejects callback data longer than this many bytes.
+const MAX_CALLBACK_DATA: usize = 64;
+/// Most new signatures ingested per watched wallet on each poll.
+const WATCH_INGEST_LIMIT: usize = 1_000;
+/// Alerts sent per wallet per poll; the rest are summarised.
+const MAX_ALERTS_PER_POLL: usize = 5;
+
+/// What an inline button does when tapped.
+enum Callback {
+    /// Trace this wallet.
+    Track(String),
+    /// Show page `n` of the wallet's candidates.
+    Page(String, usize),
+}
+
+impl Callback {
+    /// `t:<wallet>` or `p:<page>:<wallet>`. Wallets are validated base58
+    /// addresses (at most 44 bytes), so the data stays under Telegram's limit;
+    /// anything that would not fit gets no button.
+    fn encode(&self) -> Option<String> {
+        let data = match self {
+            Callback::Track(wallet) => format!("t:{}", wallet),
+            Callback::Page(wallet, page) => format!("p:{}:{}", page, wallet),
+        };
+        (data.len() <= MAX_CALLBACK_DATA).then_some(data)
+    }
+
+    fn decode(data: &str) -> Option<Self> {
+        let (kind, rest) = data.split_once(':')?;
+        match kind {
+            "t" if looks_like_wallet(rest) => Some(Callback::Track(rest.to_string())),
+            "p" => {
+                let (page, wallet) = rest.split_once(':')?;
+                let page = page.parse::<usize>().ok()?.min(MAX_PAGE);
+                looks_like_wallet(wallet).then(|| Callback::Page(wallet.to_string(), page))
+            }
+            _ => None,
+        }
+    }
+}
 
 fn api_base() -> String {
     std::env::var("ONCHAIN_BEAST_API_BASE").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string())
@@ -19,10 +68,44 @@ fn api_key() -> Option<String> {
         .filter(|s| !s.is_empty())
 }
 
+fn database_url() -> String {
+    std::env::var("DATABASE_URL")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "memory".to_string())
+}
+
+/// How often watched wallets are polled (`WATCH_POLL_SECS`, default 120).
+fn watch_poll_interval() -> Duration {
+    let secs = std::env::var("WATCH_POLL_SECS")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or(120)
+        .max(15);
+    Duration::from_secs(secs)
+}
+
+/// Default alert threshold for `/watch` (`WATCH_MIN_SOL`, default 10).
+fn default_watch_min_sol() -> f64 {
+    std::env::var("WATCH_MIN_SOL")
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .unwrap_or(10.0)
+}
+
+/// Default lookback for `/compare` (`COMPARE_LOOKBACK_DAYS`, default 30).
+fn default_compare_lookback_days() -> u32 {
+    std::env::var("COMPARE_LOOKBACK_DAYS")
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok())
+        .unwrap_or(30)
+        .clamp(1, 365)
+}
+
 fn looks_like_wallet(s: &str) -> bool {
-    let s = s.trim();
-    let len = s.len();
-    (len == 44 || len == 32) && s.chars().all(|c| c.is_ascii_alphanumeric())
+    is_valid_address(s.trim())
 }
 
 fn short_addr(s: &str) -> String {
@@ -41,8 +124,14 @@ fn http_client() -> Client {
 }
 
 async fn get_json(url: &str) -> Result<Value, String> {
-    let client = http_client();
-    let mut req = client.get(url);
+    send_json(http_client().get(url)).await
+}
+
+async fn post_json(url: &str) -> Result<Value, String> {
+    send_json(http_client().post(url)).await
+}
+
+async fn send_json(mut req: reqwest::RequestBuilder) -> Result<Value, String> {
     if let Some(key) = api_key() {
         req = req.header("X-API-Key", key);
     }
@@ -87,16 +176,34 @@ async fn main() {
         .build()
         .expect("Failed to build teloxide reqwest client");
     let bot = Bot::with_client(bot_token, telegram_client);
-    let handler = Update::filter_message().endpoint(handle_message);
+
+    // The watchlist lives in the same database as the API (in-memory if DATABASE_URL is unset).
+    let db = match DatabaseManager::new(&database_url()).await {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            eprintln!("Failed to open database: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = db.init_schema().await {
+        eprintln!("Failed to initialise database schema: {}", e);
+        std::process::exit(1);
+    }
+    tokio::spawn(watch_loop(bot.clone(), db.clone()));
+
+    let handler = dptree::entry()
+        .branch(Update::filter_message().endpoint(handle_message))
+        .branch(Update::filter_callback_query().endpoint(handle_callback));
 
     Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![db])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
         .await;
 }
 
-async fn handle_message(bot: Bot, msg: Message) -> ResponseResult<()> {
+async fn handle_message(bot: Bot, msg: Message, db: Arc<DatabaseManager>) -> ResponseResult<()> {
     let Some(text) = msg.text() else {
         return Ok(());
     };
@@ -112,8 +219,14 @@ async fn handle_message(bot: Bot, msg: Message) -> ResponseResult<()> {
             let help = "<b>OnChain Beast</b>\n\n\
 <b>One feature:</b> Find likely side-wallets, including through CEX hops.\n\n\
 <b>Commands</b>\n\
-/track &lt;wallet&gt;  - trace side-wallets\n\n\
-Tip: you can also paste a wallet address directly.";
+/track &lt;wallet&gt;  - trace side-wallets\n\
+/stats &lt;wallet&gt;  - lifetime transfer summary\n\
+/watch &lt;wallet&gt; [min_sol]  - alert on large SOL transfers\n\
+/unwatch &lt;wallet&gt;  - stop alerts\n\
+/watchlist  - list watched wallets\n\
+/compare &lt;wallet&gt; &lt;wallet&gt; [days]  - how two wallets are linked\n\
+/highrisk [min_score]  - riskiest wallets assessed so far\n\n\
+Tip: you can also paste a wallet address directly, and tap a candidate to trace it next.";
 
             bot.send_message(msg.chat.id, help)
                 .parse_mode(ParseMode::Html)
@@ -125,11 +238,146 @@ Tip: you can also paste a wallet address directly.";
                     .await?;
                 return Ok(());
             }
-            track_wallet(&bot, msg.chat.id, arg).await?;
+            track_wallet(&bot, msg.chat.id, arg, 0).await?;
+        }
+        "/stats" => {
+            if !looks_like_wallet(arg) {
+                bot.send_message(msg.chat.id, "Usage: /stats <wallet_address>")
+                    .await?;
+                return Ok(());
+            }
+            let url = format!("{}/api/v1/wallet/{}/summary", api_base(), arg);
+            let reply = match get_json(&url).await {
+                Ok(v) => format_summary(&v),
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        "/watch" => {
+            let mut args = arg.split_whitespace();
+            let wallet = args.next().unwrap_or("");
+            let min_sol = match args.next().map(|v| v.parse::<f64>()) {
+                None => Some(default_watch_min_sol()),
+                Some(Ok(v)) if v.is_finite() && v >= 0.0 => Some(v),
+                Some(_) => None,
+            };
+            let (true, Some(min_sol)) = (looks_like_wallet(wallet), min_sol) else {
+                bot.send_message(msg.chat.id, "Usage: /watch <wallet_address> [min_sol]")
+                    .await?;
+                return Ok(());
+            };
+            let reply = match db.add_watch(msg.chat.id.0, wallet, min_sol).await {
+                Ok(true) => format!(
+                    "👀 Watching <code>{}</code> for transfers of at least {} SOL.",
+                    short_addr(wallet),
+                    min_sol
+                ),
+                Ok(false) => format!(
+                    "Updated <code>{}</code>: alerting on transfers of at least {} SOL.",
+                    short_addr(wallet),
+                    min_sol
+                ),
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        "/unwatch" => {
+            if !looks_like_wallet(arg) {
+                bot.send_message(msg.chat.id, "Usage: /unwatch <wallet_address>")
+                    .await?;
+                return Ok(());
+            }
+            let reply = match db.remove_watch(msg.chat.id.0, arg).await {
+                Ok(true) => format!("Stopped watching <code>{}</code>.", short_addr(arg)),
+                Ok(false) => format!("<code>{}</code> was not watched.", short_addr(arg)),
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        "/compare" => {
+            let mut args = arg.split_whitespace();
+            let (a, b) = (args.next().unwrap_or(""), args.next().unwrap_or(""));
+            let days = match args.next().map(|v| v.parse::<u32>()) {
+                None => Some(default_compare_lookback_days()),
+                Some(Ok(v)) if (1..=365).contains(&v) => Some(v),
+                Some(_) => None,
+            };
+            let (true, true, Some(days)) = (looks_like_wallet(a), looks_like_wallet(b), days)
+            else {
+                bot.send_message(msg.chat.id, "Usage: /compare <wallet_a> <wallet_b> [days]")
+                    .await?;
+                return Ok(());
+            };
+            let url = format!(
+                "{}/api/v1/compare?a={}&b={}&lookback_days={}",
+                api_base(),
+                a,
+                b,
+                days
+            );
+            let reply = match get_json(&url).await {
+                Ok(v) => format_comparison(&v),
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        "/highrisk" => {
+            let min_score = match arg {
+                "" => Some(0.5),
+                v => v.parse::<f64>().ok().filter(|s| (0.0..=1.0).contains(s)),
+            };
+            let Some(min_score) = min_score else {
+                bot.send_message(msg.chat.id, "Usage: /highrisk [min_score between 0 and 1]")
+                    .await?;
+                return Ok(());
+            };
+            let url = format!(
+                "{}/api/v1/analysis/high-risk-wallets?min_score={}&limit=10",
+                api_base(),
+                min_score
+            );
+            let reply = match get_json(&url).await {
+                Ok(v) => format_high_risk(&v),
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        "/watchlist" => {
+            let reply = match db.get_watchlist(msg.chat.id.0).await {
+                Ok(watches) if watches.is_empty() => {
+                    "No wallets watched. Use /watch &lt;wallet&gt;.".to_string()
+                }
+                Ok(watches) => {
+                    let mut lines = vec!["<b>Watchlist</b>".to_string()];
+                    for (i, w) in watches.iter().enumerate() {
+                        lines.push(format!(
+                            "{}. <code>{}</code> (≥ {} SOL)",
+                            i + 1,
+                            w.wallet,
+                            w.min_sol
+                        ));
+                    }
+                    lines.join("\n")
+                }
+                Err(e) => format!("❌ {}", e),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
         }
         _ => {
             if looks_like_wallet(text) {
-                track_wallet(&bot, msg.chat.id, text).await?;
+                track_wallet(&bot, msg.chat.id, text, 0).await?;
             } else {
                 bot.send_message(
                     msg.chat.id,
@@ -143,11 +391,36 @@ Tip: you can also paste a wallet address directly.";
     Ok(())
 }
 
-async fn track_wallet(bot: &Bot, chat_id: ChatId, wallet: &str) -> ResponseResult<()> {
+async fn handle_callback(bot: Bot, q: CallbackQuery) -> ResponseResult<()> {
+    // Stop the client-side spinner whatever the outcome.
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    let Some(chat_id) = q.message.as_ref().map(|m| m.chat().id) else {
+        return Ok(());
+    };
+    match q.data.as_deref().and_then(Callback::decode) {
+        Some(Callback::Track(wallet)) => track_wallet(&bot, chat_id, &wallet, 0).await,
+        Some(Callback::Page(wallet, page)) => track_wallet(&bot, chat_id, &wallet, page).await,
+        None => Ok(()),
+    }
+}
+
+async fn track_wallet(bot: &Bot, chat_id: ChatId, wallet: &str, page: usize) -> ResponseResult<()> {
+    // Ask for one extra candidate to know whether there is a next page. Later pages
+    // only re-read stored analysis; the first page ingests fresh transactions.
+    let limit = (page + 1) * PAGE_SIZE + 1;
+    let ingest = if page == 0 {
+        "bootstrap=true&bootstrap_limit=25&cex_bootstrap_limit=15"
+    } else {
+        "bootstrap=false&cex_bootstrap_limit=0"
+    };
     let url = format!(
-        "{}/api/v1/wallet/{}/side-wallets?bootstrap=true&bootstrap_limit=25&depth=2&threshold=0.10&limit=10&lookback_days=30&cex_hops=true&cex_bootstrap_limit=15",
+        "{}/api/v1/wallet/{}/side-wallets?{}&depth=2&threshold=0.10&limit={}&lookback_days=30&cex_hops={}&fields=address,score,wallet,paths",
         api_base(),
-        wallet
+        wallet,
+        ingest,
+        limit,
+        page == 0
     );
 
     let v = match get_json(&url).await {
@@ -170,14 +443,32 @@ async fn track_wallet(bot: &Bot, chat_id: ChatId, wallet: &str) -> ResponseResul
         .unwrap_or_default();
 
     let mut lines = Vec::new();
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = Vec::new();
+    let mut add_drill_down = |addr: &str| {
+        if let Some(data) = looks_like_wallet(addr)
+            .then(|| Callback::Track(addr.to_string()).encode())
+            .flatten()
+        {
+            buttons.push(vec![InlineKeyboardButton::callback(
+                format!("🔎 {}", short_addr(addr)),
+                data,
+            )]);
+        }
+    };
     lines.push(format!(
-        "<b>Trace</b> for <code>{}</code>",
-        short_addr(wallet)
+        "<b>Trace</b> for <code>{}</code>{}",
+        short_addr(wallet),
+        if page > 0 {
+            format!(" (page {})", page + 1)
+        } else {
+            String::new()
+        }
     ));
 
-    if !side_wallets.is_empty() {
+    let start = page * PAGE_SIZE;
+    if side_wallets.len() > start {
         lines.push("\n<b>Direct / Graph Candidates</b>".to_string());
-        for (i, item) in side_wallets.iter().take(5).enumerate() {
+        for (i, item) in side_wallets.iter().enumerate().skip(start).take(PAGE_SIZE) {
             let addr = item.get("address").and_then(|x| x.as_str()).unwrap_or("?");
             let score = item.get("score").and_then(|x| x.as_f64()).unwrap_or(0.0);
             lines.push(format!(
@@ -186,7 +477,10 @@ async fn track_wallet(bot: &Bot, chat_id: ChatId, wallet: &str) -> ResponseResul
                 short_addr(addr),
                 score
             ));
+            add_drill_down(addr);
         }
+    } else if page > 0 {
+        lines.push("\nNo more candidates.".to_string());
     } else {
         lines.push(
             "\n<b>Direct / Graph Candidates</b>\nNone yet (try again after ingesting more txs)."
@@ -219,14 +513,293 @@ async fn track_wallet(bot: &Bot, chat_id: ChatId, wallet: &str) -> ResponseResul
                 score,
                 via
             ));
+            add_drill_down(addr);
         }
         lines.push("\n<i>Note: CEX hops are probabilistic (exchanges pool funds).</i>".to_string());
     }
 
+    if side_wallets.len() > start + PAGE_SIZE && page < MAX_PAGE {
+        if let Some(data) = Callback::Page(wallet.to_string(), page + 1).encode() {
+            buttons.push(vec![InlineKeyboardButton::callback("Next page ▶", data)]);
+        }
+    }
+
     let msg = lines.join("\n");
     bot.send_message(chat_id, msg)
         .parse_mode(ParseMode::Html)
+        .reply_markup(InlineKeyboardMarkup::new(buttons))
         .await?;
 
     Ok(())
 }
+
+/// Render a `/compare` response as a one-line verdict followed by the evidence,
+/// e.g. "Likely linked: shared funder Abcd...wxyz, 42% temporal overlap".
+fn format_comparison(v: &Value) -> String {
+    let a = v.get("a").and_then(|x| x.as_str()).unwrap_or("?");
+    let b = v.get("b").and_then(|x| x.as_str()).unwrap_or("?");
+    let funders = v
+        .get("shared_funders")
+        .and_then(|x| x.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let counterparties = v
+        .get("shared_counterparties")
+        .and_then(|x| x.as_array())
+        .map(|x| x.len())
+        .unwrap_or(0);
+    let overlap = v.get("temporal_overlap");
+    let overlap_ratio = overlap
+        .and_then(|o| o.get("overlap_ratio"))
+        .and_then(|x| x.as_f64())
+        .unwrap_or(0.0);
+    let same_blocks = overlap
+        .and_then(|o| o.get("same_block_count"))
+        .and_then(|x| x.as_u64())
+        .unwrap_or(0);
+    let similarity = v.get("behavioral_similarity").and_then(|x| x.as_f64());
+
+    let mut evidence = Vec::new();
+    if let Some(funder) = funders
+        .first()
+        .and_then(|f| f.get("wallet"))
+        .and_then(|x| x.as_str())
+    {
+        evidence.push(format!("shared funder {}", short_addr(funder)));
+    }
+    if same_blocks > 0 {
+        evidence.push(format!("{} shared blocks", same_blocks));
+    }
+    if overlap_ratio > 0.0 {
+        evidence.push(format!("{:.0}% temporal overlap", overlap_ratio * 100.0));
+    }
+    if counterparties > 0 {
+        evidence.push(format!("{} shared counterparties", counterparties));
+    }
+    if let Some(s) = similarity {
+        evidence.push(format!("behavior similarity {:.2}", s));
+    }
+
+    let verdict = match v.get("verdict").and_then(|x| x.as_str()) {
+        Some("likely_linked") => "🔗 <b>Likely linked</b>",
+        Some("possibly_linked") => "🤔 <b>Possibly linked</b>",
+        _ => "<b>No link found</b>",
+    };
+    let mut lines = vec![format!(
+        "<b>Compare</b> <code>{}</code> ↔ <code>{}</code>",
+        short_addr(a),
+        short_addr(b)
+    )];
+    if evidence.is_empty() {
+        lines.push(verdict.to_string());
+    } else {
+        lines.push(format!("{}: {}", verdict, evidence.join(", ")));
+    }
+    if funders.len() > 1 {
+        let more: Vec<String> = funders
+            .iter()
+            .skip(1)
+            .filter_map(|f| f.get("wallet").and_then(|x| x.as_str()))
+            .map(|w| format!("<code>{}</code>", short_addr(w)))
+            .collect();
+        lines.push(format!("Other shared funders: {}", more.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Render a wallet `summary` response.
+fn format_summary(v: &Value) -> String {
+    let wallet = v.get("wallet").and_then(|x| x.as_str()).unwrap_or("?");
+    let num = |key: &str| v.get(key).and_then(|x| x.as_f64()).unwrap_or(0.0);
+    let count = |key: &str| v.get(key).and_then(|x| x.as_u64()).unwrap_or(0);
+    let epoch = |key: &str| match v.get(key).and_then(|x| x.as_u64()) {
+        Some(t) if t > 0 => t.to_string(),
+        _ => "unknown".to_string(),
+    };
+    let mints = |key: &str| {
+        v.get(key)
+            .and_then(|x| x.as_object())
+            .map(|m| m.len())
+            .unwrap_or(0)
+    };
+
+    [
+        format!("<b>Summary</b> <code>{}</code>", short_addr(wallet)),
+        format!(
+            "Transactions: {} ({} transfers)",
+            count("transaction_count"),
+            count("transfer_count")
+        ),
+        format!(
+            "SOL in: {:.4} / out: {:.4}",
+            num("inbound_sol"),
+            num("outbound_sol")
+        ),
+        format!(
+            "Tokens received: {} mints / sent: {} mints",
+            mints("inbound_token_ui_by_mint"),
+            mints("outbound_token_ui_by_mint")
+        ),
+        format!("Counterparties: {}", count("unique_counterparties")),
+        format!(
+            "First seen: {} / last seen: {}",
+            epoch("first_seen_epoch"),
+            epoch("last_seen_epoch")
+        ),
+    ]
+    .join("\n")
+}
+
+/// Render a `high-risk-wallets` response (`wallets` as `[address, score, computed_at]`).
+fn format_high_risk(v: &Value) -> String {
+    let wallets = v
+        .get("wallets")
+        .and_then(|x| x.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let min_score = v.get("min_score").and_then(|x| x.as_f64()).unwrap_or(0.0);
+    if wallets.is_empty() {
+        return format!(
+            "No wallets with a risk score of at least {:.2} yet. Scores are stored when a wallet's risk is assessed.",
+            min_score
+        );
+    }
+
+    let mut lines = vec![format!(
+        "<b>High-risk wallets</b> (score ≥ {:.2})",
+        min_score
+    )];
+    for (i, pair) in wallets.iter().enumerate() {
+        let addr = pair.get(0).and_then(|x| x.as_str()).unwrap_or("?");
+        let score = pair.get(1).and_then(|x| x.as_f64()).unwrap_or(0.0);
+        lines.push(format!("{}. <code>{}</code> ({:.2})", i + 1, addr, score));
+    }
+    if v.get("has_more").and_then(|x| x.as_bool()).unwrap_or(false) {
+        lines.push("…and more".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Poll every watched wallet on an interval and message chats about new large transfers.
+async fn watch_loop(bot: Bot, db: Arc<DatabaseManager>) {
+    let mut interval = tokio::time::interval(watch_poll_interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let watches = match db.get_all_watches().await {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("watchlist poll: {}", e);
+                continue;
+            }
+        };
+        for watch in watches {
+            if let Err(e) = poll_watch(&bot, &db, &watch).await {
+                log::warn!("watch poll failed for {}: {}", watch.wallet, e);
+            }
+        }
+    }
+}
+
+async fn poll_watch(bot: &Bot, db: &DatabaseManager, watch: &WatchEntry) -> Result<(), String> {
+    let base = api_base();
+    // The first poll only sets the baseline: alerts cover activity after /watch.
+    let Some(last) = &watch.last_signature else {
+        let page = get_json(&format!(
+            "{}/api/v1/wallet/{}/transactions?limit=1",
+            base, watch.wallet
+        ))
+        .await?;
+        let newest = page
+            .get("transactions")
+            .and_then(|x| x.as_array())
+            .and_then(|a| a.first())
+            .and_then(|s| s.get("signature"))
+            .and_then(|x| x.as_str());
+        return match newest {
+            Some(newest) => db
+                .set_watch_cursor(watch.chat_id, &watch.wallet, newest)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Ok(()),
+        };
+    };
+
+    // Ingest everything after the cursor and alert on the SOL transfers it returns.
+    let ingested = post_json(&format!(
+        "{}/api/v1/wallet/{}/ingest?until={}&limit={}",
+        base, watch.wallet, last, WATCH_INGEST_LIMIT
+    ))
+    .await?;
+    let Some(newest) = ingested.get("newest_signature").and_then(|x| x.as_str()) else {
+        return Ok(());
+    };
+    if ingested.get("truncated").and_then(|x| x.as_bool()) == Some(true) {
+        log::warn!(
+            "Watch {} had more than {} new signatures; older ones were skipped",
+            watch.wallet,
+            WATCH_INGEST_LIMIT
+        );
+    }
+
+    let failures = ingested
+        .get("failures")
+        .and_then(|x| x.as_array())
+        .map_or(0, |a| a.len());
+    if failures > 0 {
+        log::warn!(
+            "Watch {}: {} new signatures failed to ingest and won't be alerted on",
+            watch.wallet,
+            failures
+        );
+    }
+
+    let large: Vec<&Value> = ingested
+        .get("transfers")
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|t| {
+            t.get("amount_sol")
+                .and_then(|x| x.as_f64())
+                .is_some_and(|amount| amount >= watch.min_sol)
+        })
+        .collect();
+
+    if !large.is_empty() {
+        let mut lines = vec![format!(
+            "🚨 <b>Large transfers</b> for <code>{}</code>",
+            short_addr(&watch.wallet)
+        )];
+        for t in large.iter().take(MAX_ALERTS_PER_POLL) {
+            let amount = t.get("amount_sol").and_then(|x| x.as_f64()).unwrap_or(0.0);
+            let from = t.get("from_wallet").and_then(|x| x.as_str()).unwrap_or("?");
+            let to = t.get("to_wallet").and_then(|x| x.as_str()).unwrap_or("?");
+            let line = if from == watch.wallet {
+                format!(
+                    "➡️ sent {:.2} SOL to <code>{}</code>",
+                    amount,
+                    short_addr(to)
+                )
+            } else {
+                format!(
+                    "⬅️ received {:.2} SOL from <code>{}</code>",
+                    amount,
+                    short_addr(from)
+                )
+            };
+            lines.push(line);
+        }
+        if large.len() > MAX_ALERTS_PER_POLL {
+            lines.push(format!("…and {} more", large.len() - MAX_ALERTS_PER_POLL));
+        }
+        bot.send_message(ChatId(watch.chat_id), lines.join("\n"))
+            .parse_mode(ParseMode::Html)
+            .await
+            .map_err(|e| format!("Failed to send alert: {}", e))?;
+    }
+
+    db.set_watch_cursor(watch.chat_id, &watch.wallet, newest)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,67 @@
+use crate::core::errors::{BeastError, BeastResult};
+
+/// Decoded length of a Solana address (an ed25519 public key).
+pub const ADDRESS_BYTES: usize = 32;
+
+/// Check that `address` is base58 that decodes to exactly `ADDRESS_BYTES` bytes.
+/// Cheap enough to run before every RPC call, which would otherwise spend quota
+/// only to reject the address itself.
+pub fn validate_address(address: &str) -> BeastResult<()> {
+    // 32 bytes never encode to more than 44 characters; skip decoding anything longer.
+    if address.is_empty() || address.len() > 44 {
+        return Err(BeastError::InvalidAddress(format!(
+            "expected 32-44 base58 characters, got {}",
+            address.len()
+        )));
+    }
+    let bytes = bs58::decode(address)
+        .into_vec()
+        .map_err(|e| BeastError::InvalidAddress(format!("not base58 ({})", e)))?;
+    if bytes.len() != ADDRESS_BYTES {
+        return Err(BeastError::InvalidAddress(format!(
+            "decodes to {} bytes, expected {}",
+            bytes.len(),
+            ADDRESS_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// `validate_address` as a predicate.
+pub fn is_valid_address(address: &str) -> bool {
+    validate_address(address).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_real_addresses() {
+        for address in [
+            "11111111111111111111111111111111",
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        ] {
+            assert!(validate_address(address).is_ok(), "{}", address);
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_length_and_non_base58() {
+        // Valid base58, but 31 and 33 bytes.
+        let short = bs58::encode([7u8; 31]).into_string();
+        let long = bs58::encode([7u8; 33]).into_string();
+        for address in [short.as_str(), long.as_str(), "", "abc"] {
+            assert!(!is_valid_address(address), "{}", address);
+        }
+
+        // 0, O, I and l are not in the base58 alphabet.
+        let err = validate_address("0PjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap_err();
+        assert!(matches!(err, BeastError::InvalidAddress(_)));
+        assert!(err.to_string().contains("not base58"));
+        assert!(!is_valid_address(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDtIl"
+        ));
+    }
+}
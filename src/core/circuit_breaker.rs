@@ -0,0 +1,183 @@
+/// Circuit breaker guarding outbound RPC calls
+///
+/// After `failure_threshold` consecutive failures the breaker opens and calls fail
+/// fast. Once `reset_timeout` has elapsed a single half-open probe is let through;
+/// its outcome either closes the breaker again or re-opens it. A probe that never
+/// reports back (its future was dropped by a request timeout) expires after
+/// another `reset_timeout`, and the next call becomes the probe.
+use crate::core::env::env_parse_in;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+pub struct RpcCircuitBreaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the current half-open probe was admitted.
+    probe_started_at: Option<Instant>,
+    trips: u64,
+}
+
+impl RpcCircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            inner: Mutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started_at: None,
+                trips: 0,
+            }),
+        }
+    }
+
+    /// Build from `RPC_CB_FAILURE_THRESHOLD` (default 5) and `RPC_CB_RESET_SECS` (default 30).
+    pub fn from_env() -> Self {
+        let failure_threshold = env_parse_in("RPC_CB_FAILURE_THRESHOLD", 5, 1..=1_000);
+        let reset_secs = env_parse_in("RPC_CB_RESET_SECS", 30, 1..=3_600);
+
+        Self::new(failure_threshold, Duration::from_secs(reset_secs))
+    }
+
+    /// Returns true if a call may proceed. Moves Open -> HalfOpen once the reset
+    /// timeout has elapsed, admitting exactly one probe; a probe outstanding for
+    /// longer than the reset timeout is given up and replaced.
+    pub fn allow_request(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let expired =
+            |since: Option<Instant>| since.is_none_or(|t| t.elapsed() >= self.reset_timeout);
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::HalfOpen => {
+                if expired(inner.probe_started_at) {
+                    inner.probe_started_at = Some(Instant::now());
+                    tracing::info!("RPC circuit probe never reported back; sending another");
+                    true
+                } else {
+                    false
+                }
+            }
+            CircuitState::Open => {
+                if expired(inner.opened_at) {
+                    inner.state = CircuitState::HalfOpen;
+                    inner.probe_started_at = Some(Instant::now());
+                    tracing::info!("RPC circuit half-open; sending probe");
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.state != CircuitState::Closed {
+            tracing::info!("RPC circuit closed");
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_started_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+
+        let trip = match inner.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::Open => false,
+        };
+        if trip {
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+            inner.probe_started_at = None;
+            inner.trips = inner.trips.saturating_add(1);
+            tracing::warn!(
+                "RPC circuit opened after {} consecutive failures",
+                inner.consecutive_failures
+            );
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).state
+    }
+
+    /// Number of times the breaker has tripped open.
+    pub fn trips(&self) -> u64 {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).trips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_recovers_via_probe() {
+        let breaker = RpcCircuitBreaker::new(3, Duration::from_millis(20));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allow_request());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.allow_request());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // Only a single probe is admitted.
+        assert!(!breaker.allow_request());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.trips(), 1);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = RpcCircuitBreaker::new(1, Duration::from_millis(10));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(breaker.allow_request());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.trips(), 2);
+    }
+
+    #[test]
+    fn test_abandoned_probe_expires_after_reset_timeout() {
+        let breaker = RpcCircuitBreaker::new(1, Duration::from_millis(20));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(30));
+        // The probe is admitted, then its caller goes away without reporting.
+        assert!(breaker.allow_request());
+        assert!(!breaker.allow_request());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.allow_request());
+        assert!(!breaker.allow_request());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -1,8 +1,12 @@
+use crate::core::env::{env_flag, env_parse_in};
 use crate::core::errors::{BeastError, Result};
+use crate::core::units::{lamports_delta_to_sol, lamports_to_sol};
+use crate::dex::{nft, DexDecoder, DexPrograms, NftMarketplaces, NftTrade, SwapEvent};
 /// Enhanced Transaction Parser - Extracts SOL and Token Transfers
 /// Parses Solana transactions to extract fund flows and transfer details
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
 // ============================================================================
 // DATA STRUCTURES
@@ -17,6 +21,14 @@ pub struct EnhancedTransaction {
     pub success: bool,
     pub error: Option<String>,
 
+    // Compute budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_units_consumed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_unit_price_micro_lamports: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_fee_lamports: Option<u64>,
+
     // Account analysis
     pub accounts: Vec<String>,
     pub signers: Vec<String>,
@@ -26,6 +38,22 @@ pub struct EnhancedTransaction {
     pub sol_transfers: Vec<SolTransfer>,
     pub token_transfers: Vec<TokenTransfer>,
 
+    // DEX swaps (net per fee payer)
+    #[serde(default)]
+    pub swaps: Vec<SwapEvent>,
+
+    // Stake program actions
+    #[serde(default)]
+    pub stake_actions: Vec<StakeAction>,
+
+    // Token mints and burns
+    #[serde(default)]
+    pub mint_burn_events: Vec<MintBurnEvent>,
+
+    // NFT marketplace sales
+    #[serde(default)]
+    pub nft_trades: Vec<NftTrade>,
+
     // Balance changes
     pub balance_changes: Vec<BalanceChange>,
 
@@ -36,6 +64,10 @@ pub struct EnhancedTransaction {
     // Classification
     pub tx_type: TransactionType,
     pub is_versioned: bool,
+
+    /// Read at `finalized` commitment, so no fork can roll it back.
+    #[serde(default)]
+    pub finalized: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,7 +77,7 @@ pub struct SolTransfer {
     pub amount_lamports: u64,
     pub amount_sol: f64,
     pub instruction_index: usize,
-    pub transfer_type: String, // "system", "inner", "balance_change"
+    pub transfer_type: String, // "system", "inner", "balance_change", "balance_fan_out"
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +105,29 @@ pub struct TokenTransfer {
     pub verified: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintBurnEvent {
+    pub mint: String,
+    /// Token account credited (mint) or debited (burn).
+    pub account: String,
+    pub owner: Option<String>,
+    pub amount: u64,
+    pub decimals: u8,
+    pub kind: String, // "mint", "burn"
+    pub instruction_index: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeAction {
+    pub action: String, // "delegate", "deactivate", "withdraw", "split"
+    pub stake_account: String,
+    /// Vote account delegated to (`delegate` only).
+    pub validator: Option<String>,
+    /// Lamports moved (`withdraw` and `split` only).
+    pub amount_lamports: Option<u64>,
+    pub instruction_index: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BalanceChange {
     pub account: String,
@@ -89,6 +144,7 @@ pub enum TransactionType {
     TokenSwap,
     TokenMint,
     NFTTrade,
+    Staking,
     DeFiInteraction,
     ProgramCall,
     Unknown,
@@ -98,12 +154,81 @@ pub enum TransactionType {
 // ENHANCED TRANSACTION PARSER
 // ============================================================================
 
+/// Compute units a transaction may use when it sets no explicit limit.
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+const MAX_TRANSACTION_COMPUTE_UNITS: u64 = 1_400_000;
+
+/// Maps an address lookup table to the addresses it stores.
+type LookupResolver<'a> = dyn Fn(&str) -> Option<Vec<String>> + 'a;
+
+/// Token account -> owner, remembered from the token balances of earlier
+/// transactions so a later one that lacks them (e.g. a close or a transfer out
+/// of an emptied account) still gets its owner.
+///
+/// Bounded by keeping two generations of at most half the capacity each: when
+/// the current one fills up it replaces the previous one, dropping the
+/// accounts that have not been seen since.
+pub struct TokenOwnerCache {
+    max_entries: usize,
+    generations: RwLock<(HashMap<String, String>, HashMap<String, String>)>,
+}
+
+impl TokenOwnerCache {
+    /// Holds at most `max_entries` accounts; 0 disables the cache.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            generations: RwLock::new((HashMap::new(), HashMap::new())),
+        }
+    }
+
+    pub fn get(&self, account: &str) -> Option<String> {
+        let generations = self.generations.read().unwrap_or_else(|e| e.into_inner());
+        generations
+            .0
+            .get(account)
+            .or_else(|| generations.1.get(account))
+            .cloned()
+    }
+
+    pub fn insert(&self, account: &str, owner: &str) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut generations = self.generations.write().unwrap_or_else(|e| e.into_inner());
+        let (current, previous) = &mut *generations;
+        if current.get(account).is_some_and(|o| o == owner) {
+            return;
+        }
+        if current.len() >= self.max_entries.div_ceil(2) {
+            *previous = std::mem::take(current);
+        }
+        current.insert(account.to_string(), owner.to_string());
+    }
+
+    pub fn len(&self) -> usize {
+        let generations = self.generations.read().unwrap_or_else(|e| e.into_inner());
+        generations.0.len()
+            + generations
+                .1
+                .keys()
+                .filter(|k| !generations.0.contains_key(*k))
+                .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 pub struct EnhancedTransactionParser {
     // Known program IDs
     system_program: String,
     token_program: String,
     token_2022_program: String,
     associated_token_program: String,
+    stake_program: String,
+    compute_budget_program: String,
 
     // DEX programs
     raydium_v4: String,
@@ -113,28 +238,91 @@ pub struct EnhancedTransactionParser {
     // NFT programs
     metaplex: String,
     magic_eden: String,
+
+    dex_decoder: DexDecoder,
+
+    /// Attribute one unexplained decrease to several smaller increases (airdrops).
+    infer_fan_out: bool,
+
+    /// Owners of token accounts seen in earlier transactions.
+    owner_cache: Arc<TokenOwnerCache>,
 }
 
 impl EnhancedTransactionParser {
+    /// Fan-out inference is off unless `INFER_FAN_OUT_TRANSFERS=true`. The owner
+    /// cache holds `TOKEN_OWNER_CACHE_SIZE` accounts (default 100000; 0 disables it).
     pub fn new() -> Self {
         EnhancedTransactionParser {
             system_program: "11111111111111111111111111111111".to_string(),
             token_program: "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP".to_string(),
             token_2022_program: "TokenzQdBNbJPPzh6txJjTpWp8QJKhdfuqJW65PfQG".to_string(),
             associated_token_program: "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL".to_string(),
+            stake_program: "Stake11111111111111111111111111111111111111".to_string(),
+            compute_budget_program: "ComputeBudget111111111111111111111111111111".to_string(),
             raydium_v4: "675kPX9MHTjS2zt1qfmKe2LdPsyAtg5w6qcCX6qX8W8S".to_string(),
             orca_whirlpool: "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc".to_string(),
             jupiter_v6: "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string(),
             metaplex: "metaqbxxUerdq28cj1RbAWVQGDiVQB5d5owY8c4DUr".to_string(),
             magic_eden: "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K".to_string(),
+            dex_decoder: DexDecoder::new(),
+            infer_fan_out: env_flag("INFER_FAN_OUT_TRANSFERS", false),
+            owner_cache: Arc::new(TokenOwnerCache::new(env_parse_in(
+                "TOKEN_OWNER_CACHE_SIZE",
+                100_000,
+                0..=10_000_000,
+            ))),
         }
     }
 
+    pub fn with_fan_out_inference(mut self, enabled: bool) -> Self {
+        self.infer_fan_out = enabled;
+        self
+    }
+
+    /// Share `cache` with other parsers (e.g. every clone of a handler).
+    pub fn with_owner_cache(mut self, cache: Arc<TokenOwnerCache>) -> Self {
+        self.owner_cache = cache;
+        self
+    }
+
+    pub fn owner_cache(&self) -> Arc<TokenOwnerCache> {
+        Arc::clone(&self.owner_cache)
+    }
+
     /// Parse a full transaction from raw RPC data
+    ///
+    /// Accounts loaded through address lookup tables are taken from
+    /// `meta.loadedAddresses` when the RPC node provides them.
     pub fn parse(
         &self,
         raw_data: &serde_json::Value,
         signature: String,
+    ) -> Result<EnhancedTransaction> {
+        self.parse_inner(raw_data, signature, None)
+    }
+
+    /// Parse a versioned transaction, expanding its address lookup tables with
+    /// `resolver`, which maps a lookup table address to the addresses it stores.
+    ///
+    /// If any table cannot be resolved the parser falls back to
+    /// `meta.loadedAddresses` (or static keys only) rather than misaligning indices.
+    pub fn parse_with_lookups<F>(
+        &self,
+        raw_data: &serde_json::Value,
+        signature: String,
+        resolver: F,
+    ) -> Result<EnhancedTransaction>
+    where
+        F: Fn(&str) -> Option<Vec<String>>,
+    {
+        self.parse_inner(raw_data, signature, Some(&resolver))
+    }
+
+    fn parse_inner(
+        &self,
+        raw_data: &serde_json::Value,
+        signature: String,
+        resolver: Option<&LookupResolver>,
     ) -> Result<EnhancedTransaction> {
         let slot = raw_data.get("slot").and_then(|s| s.as_u64()).unwrap_or(0);
 
@@ -167,9 +355,18 @@ impl EnhancedTransactionParser {
             .ok_or_else(|| BeastError::RpcError("No message field".to_string()))?;
 
         // Extract accounts
-        let accounts = self.extract_accounts(message)?;
+        let mut accounts = self.extract_accounts(message)?;
         let signers = self.extract_signers(message, &accounts)?;
-        let writable_accounts = self.extract_writable_accounts(message, &accounts)?;
+        let mut writable_accounts = self.extract_writable_accounts(message, &accounts)?;
+
+        // Looked-up accounts follow the static keys: all writable, then all readonly.
+        if let Some((loaded_writable, loaded_readonly)) =
+            self.resolve_lookup_accounts(message, meta, resolver)
+        {
+            writable_accounts.extend(loaded_writable.iter().cloned());
+            accounts.extend(loaded_writable);
+            accounts.extend(loaded_readonly);
+        }
 
         // Extract balances for balance change detection
         let pre_balances = self.extract_balances(meta.get("preBalances"));
@@ -191,11 +388,44 @@ impl EnhancedTransactionParser {
         let mut sol_transfers =
             self.extract_sol_transfers_from_instructions(instructions, &accounts);
 
+        // Stake program actions; withdrawals also move SOL out of the stake account.
+        // They count as explicit so balance inference doesn't emit them again.
+        let (stake_actions, stake_withdrawals) =
+            self.extract_stake_actions(instructions, &accounts);
+        sol_transfers.extend(stake_withdrawals);
+
         // Add SOL transfers from balance changes (catches inner instructions)
-        sol_transfers.extend(self.extract_sol_transfers_from_balances(&balance_changes));
+        let mut inferred =
+            self.extract_sol_transfers_from_balances(&balance_changes, &sol_transfers);
+        if self.infer_fan_out {
+            let fan_out =
+                self.infer_fan_out_transfers(&balance_changes, &sol_transfers, &inferred, fee);
+            inferred.extend(fan_out);
+        }
+        sol_transfers.extend(inferred);
 
-        // Extract token transfers from parsed instructions
+        // Extract token transfers, mints and burns from parsed instructions
         let token_transfers = self.extract_token_transfers(instructions, &accounts, meta)?;
+        let mint_burn_events = self.extract_mint_burn_events(instructions, &accounts, meta);
+
+        // Compute budget: consumed units and the priority fee bid
+        let compute_units_consumed = meta.get("computeUnitsConsumed").and_then(|c| c.as_u64());
+        let (compute_unit_limit, compute_unit_price_micro_lamports) =
+            self.extract_compute_budget(instructions, &accounts);
+        let priority_fee_lamports = compute_unit_price_micro_lamports.map(|price| {
+            let limit = compute_unit_limit.unwrap_or_else(|| {
+                let budgeted = instructions
+                    .iter()
+                    .filter(|instr| {
+                        self.instruction_program_id(instr, &accounts).as_deref()
+                            != Some(self.compute_budget_program.as_str())
+                    })
+                    .count() as u64;
+                (budgeted * DEFAULT_INSTRUCTION_COMPUTE_UNITS).min(MAX_TRANSACTION_COMPUTE_UNITS)
+            });
+            // Micro-lamports per unit, rounded up to whole lamports.
+            ((price as u128 * limit as u128).div_ceil(1_000_000)) as u64
+        });
 
         // Extract program IDs
         let programs_called = self.extract_program_ids(instructions, &accounts);
@@ -205,31 +435,49 @@ impl EnhancedTransactionParser {
             .collect();
 
         // Determine transaction type
-        let tx_type =
-            self.determine_transaction_type(&programs_called, &sol_transfers, &token_transfers);
+        let tx_type = self.determine_transaction_type(
+            &programs_called,
+            &sol_transfers,
+            &token_transfers,
+            &stake_actions,
+            &mint_burn_events,
+        );
 
         // Check if versioned
         let is_versioned =
             message.get("version").is_some() || message.get("addressTableLookups").is_some();
 
-        Ok(EnhancedTransaction {
+        let mut tx = EnhancedTransaction {
             signature,
             slot,
             block_time,
             fee,
             success,
             error,
+            compute_units_consumed,
+            compute_unit_price_micro_lamports,
+            priority_fee_lamports,
             accounts,
             signers,
             writable_accounts,
             sol_transfers,
             token_transfers,
+            swaps: Vec::new(),
+            stake_actions,
+            mint_burn_events,
+            nft_trades: Vec::new(),
             balance_changes,
             programs_called,
             program_names,
             tx_type,
             is_versioned,
-        })
+            finalized: false,
+        };
+
+        tx.swaps = self.dex_decoder.extract_swaps(raw_data, &tx);
+        tx.nft_trades = nft::extract_nft_trades(&tx);
+
+        Ok(tx)
     }
 
     // ========================================================================
@@ -253,11 +501,107 @@ impl EnhancedTransactionParser {
             .collect())
     }
 
+    /// Addresses loaded through lookup tables as `(writable, readonly)`.
+    ///
+    /// jsonParsed messages already list looked-up keys in `accountKeys`, so
+    /// nothing is returned for them.
+    fn resolve_lookup_accounts(
+        &self,
+        message: &serde_json::Value,
+        meta: &serde_json::Value,
+        resolver: Option<&LookupResolver>,
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let lookups = message
+            .get("addressTableLookups")
+            .and_then(|l| l.as_array())
+            .filter(|l| !l.is_empty())?;
+
+        let already_expanded = message
+            .get("accountKeys")
+            .and_then(|k| k.as_array())
+            .map(|keys| {
+                keys.iter()
+                    .any(|k| k.get("source").and_then(|s| s.as_str()) == Some("lookupTable"))
+            })
+            .unwrap_or(false);
+        if already_expanded {
+            return None;
+        }
+
+        if let Some(resolver) = resolver {
+            match self.expand_lookups(lookups, resolver) {
+                Some(expanded) => return Some(expanded),
+                None => tracing::warn!(
+                    "Could not resolve all address lookup tables; falling back to loadedAddresses"
+                ),
+            }
+        }
+
+        let loaded = meta.get("loadedAddresses")?;
+        let list = |key: &str| -> Vec<String> {
+            loaded
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Some((list("writable"), list("readonly")))
+    }
+
+    fn expand_lookups(
+        &self,
+        lookups: &[serde_json::Value],
+        resolver: &LookupResolver,
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let mut writable = Vec::new();
+        let mut readonly = Vec::new();
+
+        for lookup in lookups {
+            let table_key = lookup.get("accountKey")?.as_str()?;
+            let table = resolver(table_key)?;
+
+            for (field, out) in [
+                ("writableIndexes", &mut writable),
+                ("readonlyIndexes", &mut readonly),
+            ] {
+                let indexes = lookup
+                    .get(field)
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                for idx in indexes {
+                    out.push(table.get(idx.as_u64()? as usize)?.clone());
+                }
+            }
+        }
+
+        Some((writable, readonly))
+    }
+
     fn extract_signers(
         &self,
         message: &serde_json::Value,
         accounts: &[String],
     ) -> Result<Vec<String>> {
+        // `jsonParsed` messages carry no header; signers are flagged per account key.
+        if message.get("header").is_none() {
+            let keys = message
+                .get("accountKeys")
+                .and_then(|k| k.as_array())
+                .cloned()
+                .unwrap_or_default();
+            return Ok(keys
+                .iter()
+                .filter(|k| k.get("signer").and_then(|s| s.as_bool()) == Some(true))
+                .filter_map(|k| k.get("pubkey").and_then(|p| p.as_str()))
+                .map(|s| s.to_string())
+                .collect());
+        }
+
         let num_required = message
             .get("header")
             .and_then(|h| h.get("numRequiredSignatures"))
@@ -337,7 +681,7 @@ impl EnhancedTransactionParser {
                 }
 
                 let change_lamports = post as i64 - pre as i64;
-                let change_sol = change_lamports as f64 / 1_000_000_000.0;
+                let change_sol = lamports_delta_to_sol(change_lamports.into());
 
                 Some(BalanceChange {
                     account: account.clone(),
@@ -410,7 +754,7 @@ impl EnhancedTransactionParser {
             let from = info.get("source")?.as_str()?.to_string();
             let to = info.get("destination")?.as_str()?.to_string();
             let amount_lamports = info.get("lamports")?.as_u64()?;
-            let amount_sol = amount_lamports as f64 / 1_000_000_000.0;
+            let amount_sol = lamports_to_sol(amount_lamports);
 
             Some(SolTransfer {
                 from,
@@ -425,41 +769,232 @@ impl EnhancedTransactionParser {
         }
     }
 
+    /// Pair each balance increase with every decrease of the same size. Pairs that
+    /// repeat a transfer in `explicit` (same from, to and lamports) are skipped so a
+    /// parsed system transfer is not counted twice.
     fn extract_sol_transfers_from_balances(
         &self,
         balance_changes: &[BalanceChange],
+        explicit: &[SolTransfer],
     ) -> Vec<SolTransfer> {
-        // Group balance changes to find transfers
         // This is simplified - in production would need more sophisticated matching
-        let increases: Vec<_> = balance_changes
+        let mut decreases_by_amount: HashMap<u64, Vec<&BalanceChange>> = HashMap::new();
+        for dec in balance_changes.iter().filter(|bc| bc.change_lamports < 0) {
+            decreases_by_amount
+                .entry(dec.change_lamports.unsigned_abs())
+                .or_default()
+                .push(dec);
+        }
+
+        // Each explicit transfer cancels at most one inferred duplicate.
+        let mut already_seen: HashMap<(&str, &str, u64), usize> = HashMap::new();
+        for t in explicit {
+            *already_seen
+                .entry((t.from.as_str(), t.to.as_str(), t.amount_lamports))
+                .or_default() += 1;
+        }
+
+        let mut transfers = Vec::new();
+
+        for inc in balance_changes.iter().filter(|bc| bc.change_lamports > 0) {
+            let amount = inc.change_lamports.unsigned_abs();
+            for dec in decreases_by_amount.get(&amount).into_iter().flatten() {
+                let key = (dec.account.as_str(), inc.account.as_str(), amount);
+                if let Some(remaining) = already_seen.get_mut(&key).filter(|n| **n > 0) {
+                    *remaining -= 1;
+                    continue;
+                }
+                transfers.push(SolTransfer {
+                    from: dec.account.clone(),
+                    to: inc.account.clone(),
+                    amount_lamports: amount,
+                    amount_sol: inc.change_sol.abs(),
+                    instruction_index: 999, // Inner instruction
+                    transfer_type: "balance_change".to_string(),
+                });
+            }
+        }
+
+        transfers
+    }
+
+    /// One-to-many distributions: when exactly one decrease is left unexplained and
+    /// at least two unexplained increases sum to it, less at most the fee, the
+    /// sender is credited with a transfer to each recipient. Decreases no larger
+    /// than the fee belong to a separate fee payer and are ignored.
+    fn infer_fan_out_transfers(
+        &self,
+        balance_changes: &[BalanceChange],
+        explicit: &[SolTransfer],
+        matched: &[SolTransfer],
+        fee: u64,
+    ) -> Vec<SolTransfer> {
+        // Accounts already covered by a parsed or exact-amount transfer.
+        let explained: HashSet<&str> = explicit
             .iter()
-            .filter(|bc| bc.change_lamports > 0)
+            .chain(matched)
+            .flat_map(|t| [t.from.as_str(), t.to.as_str()])
+            .collect();
+        let unexplained: Vec<&BalanceChange> = balance_changes
+            .iter()
+            .filter(|bc| !explained.contains(bc.account.as_str()))
             .collect();
 
-        let decreases: Vec<_> = balance_changes
+        let senders: Vec<&BalanceChange> = unexplained
             .iter()
-            .filter(|bc| bc.change_lamports < 0)
+            .copied()
+            .filter(|bc| bc.change_lamports < 0 && bc.change_lamports.unsigned_abs() > fee)
+            .collect();
+        let recipients: Vec<&BalanceChange> = unexplained
+            .iter()
+            .copied()
+            .filter(|bc| bc.change_lamports > 0)
             .collect();
 
-        let mut transfers = Vec::new();
+        let [sender] = senders.as_slice() else {
+            return Vec::new();
+        };
+        if recipients.len() < 2 {
+            return Vec::new();
+        }
+        let sent = sender.change_lamports.unsigned_abs();
+        let received: u64 = recipients.iter().map(|r| r.change_lamports as u64).sum();
+        if received > sent || sent - received > fee {
+            return Vec::new();
+        }
+
+        recipients
+            .iter()
+            .map(|r| SolTransfer {
+                from: sender.account.clone(),
+                to: r.account.clone(),
+                amount_lamports: r.change_lamports as u64,
+                amount_sol: r.change_sol,
+                instruction_index: 999, // Inner instruction
+                transfer_type: "balance_fan_out".to_string(),
+            })
+            .collect()
+    }
+
+    // ========================================================================
+    // COMPUTE BUDGET
+    // ========================================================================
+
+    /// `(unit limit, unit price in micro-lamports)` from ComputeBudget instructions.
+    ///
+    /// The RPC does not parse ComputeBudget instructions, so the base58 `data` is
+    /// decoded: discriminator 2 is `setComputeUnitLimit(u32)` and 3 is
+    /// `setComputeUnitPrice(u64)`, both little-endian.
+    fn extract_compute_budget(
+        &self,
+        instructions: &[serde_json::Value],
+        accounts: &[String],
+    ) -> (Option<u64>, Option<u64>) {
+        let mut limit = None;
+        let mut price = None;
+
+        for instr in instructions {
+            if self.instruction_program_id(instr, accounts).as_deref()
+                != Some(self.compute_budget_program.as_str())
+            {
+                continue;
+            }
+            let Some(data) = instr
+                .get("data")
+                .and_then(|d| d.as_str())
+                .and_then(|d| bs58::decode(d).into_vec().ok())
+            else {
+                continue;
+            };
+
+            match data.split_first() {
+                Some((2, rest)) if rest.len() >= 4 => {
+                    let bytes: [u8; 4] = rest[..4].try_into().unwrap_or_default();
+                    limit = Some(u32::from_le_bytes(bytes) as u64);
+                }
+                Some((3, rest)) if rest.len() >= 8 => {
+                    let bytes: [u8; 8] = rest[..8].try_into().unwrap_or_default();
+                    price = Some(u64::from_le_bytes(bytes));
+                }
+                _ => {}
+            }
+        }
+
+        (limit, price)
+    }
+
+    // ========================================================================
+    // STAKE EXTRACTION
+    // ========================================================================
+
+    fn extract_stake_actions(
+        &self,
+        instructions: &[serde_json::Value],
+        accounts: &[String],
+    ) -> (Vec<StakeAction>, Vec<SolTransfer>) {
+        let mut actions = Vec::new();
+        let mut withdrawals = Vec::new();
 
-        // Match increases to decreases
-        for inc in &increases {
-            for dec in &decreases {
-                if inc.change_lamports.abs() == dec.change_lamports.abs() {
-                    transfers.push(SolTransfer {
-                        from: dec.account.clone(),
-                        to: inc.account.clone(),
-                        amount_lamports: inc.change_lamports.unsigned_abs(),
-                        amount_sol: inc.change_sol.abs(),
-                        instruction_index: 999, // Inner instruction
-                        transfer_type: "balance_change".to_string(),
+        for (idx, instr) in instructions.iter().enumerate() {
+            if self.instruction_program_id(instr, accounts).as_deref()
+                != Some(self.stake_program.as_str())
+            {
+                continue;
+            }
+            let Some(parsed) = instr.get("parsed") else {
+                continue;
+            };
+            let Some(action) = self.parse_stake_action(parsed, idx) else {
+                continue;
+            };
+
+            if action.action == "withdraw" {
+                let destination = parsed
+                    .get("info")
+                    .and_then(|i| i.get("destination"))
+                    .and_then(|d| d.as_str());
+                if let (Some(to), Some(amount_lamports)) = (destination, action.amount_lamports) {
+                    withdrawals.push(SolTransfer {
+                        from: action.stake_account.clone(),
+                        to: to.to_string(),
+                        amount_lamports,
+                        amount_sol: lamports_to_sol(amount_lamports),
+                        instruction_index: idx,
+                        transfer_type: "stake_withdraw".to_string(),
                     });
                 }
             }
+            actions.push(action);
         }
 
-        transfers
+        (actions, withdrawals)
+    }
+
+    fn parse_stake_action(
+        &self,
+        parsed: &serde_json::Value,
+        instruction_index: usize,
+    ) -> Option<StakeAction> {
+        let action = parsed.get("type")?.as_str()?;
+        if !matches!(action, "delegate" | "deactivate" | "withdraw" | "split") {
+            return None;
+        }
+
+        let info = parsed.get("info")?;
+        let stake_account = info.get("stakeAccount")?.as_str()?.to_string();
+        let validator = info
+            .get("voteAccount")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let amount_lamports = info.get("lamports").and_then(|l| l.as_u64());
+
+        Some(StakeAction {
+            action: action.to_string(),
+            stake_account,
+            validator,
+            amount_lamports,
+            instruction_index,
+        })
     }
 
     // ========================================================================
@@ -499,6 +1034,7 @@ impl EnhancedTransactionParser {
                         .and_then(|v| v.as_u64())
                         .unwrap_or(0) as u8;
 
+                    self.owner_cache.insert(account, owner);
                     map.insert(account.clone(), (mint, owner.to_string(), decimals));
                 }
             }
@@ -507,6 +1043,19 @@ impl EnhancedTransactionParser {
         map
     }
 
+    /// Owner of `account` from this transaction's token balances, else from the
+    /// owner cache.
+    fn token_account_owner(
+        &self,
+        account: &str,
+        token_account_meta: &HashMap<String, (String, String, u8)>,
+    ) -> Option<String> {
+        token_account_meta
+            .get(account)
+            .map(|(_, o, _)| o.clone())
+            .or_else(|| self.owner_cache.get(account))
+    }
+
     fn extract_token_transfers(
         &self,
         instructions: &[serde_json::Value],
@@ -517,8 +1066,7 @@ impl EnhancedTransactionParser {
 
         let token_account_meta = self.build_token_account_meta_map(meta, accounts);
 
-        // Extract from main instructions
-        for (idx, instr) in instructions.iter().enumerate() {
+        for (idx, instr) in self.outer_and_inner_instructions(instructions, meta) {
             if let Some(transfer) = self.extract_token_transfer_from_instruction(
                 instr,
                 accounts,
@@ -529,7 +1077,18 @@ impl EnhancedTransactionParser {
             }
         }
 
-        // Extract from inner instructions
+        Ok(transfers)
+    }
+
+    /// Main instructions with their index, followed by inner instructions with
+    /// the index encoded as `outer * 1000 + inner`.
+    fn outer_and_inner_instructions<'a>(
+        &self,
+        instructions: &'a [serde_json::Value],
+        meta: &'a serde_json::Value,
+    ) -> Vec<(usize, &'a serde_json::Value)> {
+        let mut all: Vec<(usize, &serde_json::Value)> = instructions.iter().enumerate().collect();
+
         if let Some(inner_instructions) = meta.get("innerInstructions").and_then(|i| i.as_array()) {
             for inner_group in inner_instructions {
                 if let Some(inner_instrs) =
@@ -540,21 +1099,83 @@ impl EnhancedTransactionParser {
                         .and_then(|i| i.as_u64())
                         .unwrap_or(0) as usize;
 
-                    for (inner_idx, inner_instr) in inner_instrs.iter().enumerate() {
-                        if let Some(transfer) = self.extract_token_transfer_from_instruction(
-                            inner_instr,
-                            accounts,
-                            &token_account_meta,
-                            outer_idx * 1000 + inner_idx, // Encode as inner instruction
-                        ) {
-                            transfers.push(transfer);
-                        }
-                    }
+                    all.extend(
+                        inner_instrs
+                            .iter()
+                            .enumerate()
+                            .map(|(inner_idx, instr)| (outer_idx * 1000 + inner_idx, instr)),
+                    );
                 }
             }
         }
 
-        Ok(transfers)
+        all
+    }
+
+    fn extract_mint_burn_events(
+        &self,
+        instructions: &[serde_json::Value],
+        accounts: &[String],
+        meta: &serde_json::Value,
+    ) -> Vec<MintBurnEvent> {
+        let token_account_meta = self.build_token_account_meta_map(meta, accounts);
+
+        self.outer_and_inner_instructions(instructions, meta)
+            .into_iter()
+            .filter_map(|(idx, instr)| {
+                let program_id = self.instruction_program_id(instr, accounts)?;
+                if program_id != self.token_program && program_id != self.token_2022_program {
+                    return None;
+                }
+                self.parse_mint_burn(instr.get("parsed")?, &token_account_meta, idx)
+            })
+            .collect()
+    }
+
+    fn parse_mint_burn(
+        &self,
+        parsed: &serde_json::Value,
+        token_account_meta: &HashMap<String, (String, String, u8)>,
+        instruction_index: usize,
+    ) -> Option<MintBurnEvent> {
+        let kind = match parsed.get("type")?.as_str()? {
+            "mintTo" | "mintToChecked" => "mint",
+            "burn" | "burnChecked" => "burn",
+            _ => return None,
+        };
+        let info = parsed.get("info")?;
+
+        let account = info.get("account")?.as_str()?.to_string();
+        let known = token_account_meta.get(&account);
+        let mint = info
+            .get("mint")
+            .and_then(|m| m.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| known.map(|(m, _, _)| m.clone()))?;
+
+        // Unchecked variants carry a raw `amount`; checked ones a `tokenAmount`.
+        let token_amount = info.get("tokenAmount");
+        let amount = info
+            .get("amount")
+            .or_else(|| token_amount.and_then(|t| t.get("amount")))
+            .and_then(|a| a.as_str())
+            .and_then(|a| a.parse::<u64>().ok())?;
+        let decimals = token_amount
+            .and_then(|t| t.get("decimals"))
+            .and_then(|d| d.as_u64())
+            .map(|d| d as u8)
+            .or_else(|| known.map(|(_, _, d)| *d))
+            .unwrap_or(0);
+
+        Some(MintBurnEvent {
+            mint,
+            owner: self.token_account_owner(&account, token_account_meta),
+            account,
+            amount,
+            decimals,
+            kind: kind.to_string(),
+            instruction_index,
+        })
     }
 
     fn extract_token_transfer_from_instruction(
@@ -601,14 +1222,16 @@ impl EnhancedTransactionParser {
             .and_then(|a| a.as_str())
             .and_then(|s| s.parse::<u64>().ok())?;
 
-        let (from_mint, from_owner, from_decimals) = token_account_meta
+        let (from_mint, from_decimals) = token_account_meta
             .get(&from_token_account)
-            .map(|(m, o, d)| (m.clone(), Some(o.clone()), *d))
-            .unwrap_or_else(|| ("unknown".to_string(), None, 0));
-        let (to_mint, to_owner, to_decimals) = token_account_meta
+            .map(|(m, _, d)| (m.clone(), *d))
+            .unwrap_or_else(|| ("unknown".to_string(), 0));
+        let (to_mint, to_decimals) = token_account_meta
             .get(&to_token_account)
-            .map(|(m, o, d)| (m.clone(), Some(o.clone()), *d))
-            .unwrap_or_else(|| ("unknown".to_string(), None, 0));
+            .map(|(m, _, d)| (m.clone(), *d))
+            .unwrap_or_else(|| ("unknown".to_string(), 0));
+        let from_owner = self.token_account_owner(&from_token_account, token_account_meta);
+        let to_owner = self.token_account_owner(&to_token_account, token_account_meta);
 
         let mint = if from_mint != "unknown" {
             from_mint
@@ -677,12 +1300,8 @@ impl EnhancedTransactionParser {
             .get("amount")
             .and_then(|a| a.as_str())
             .and_then(|s| s.parse::<u64>().ok())?;
-        let from_owner = token_account_meta
-            .get(&from_token_account)
-            .map(|(_, o, _)| o.clone());
-        let to_owner = token_account_meta
-            .get(&to_token_account)
-            .map(|(_, o, _)| o.clone());
+        let from_owner = self.token_account_owner(&from_token_account, token_account_meta);
+        let to_owner = self.token_account_owner(&to_token_account, token_account_meta);
         let amount_ui = token_amount
             .get("uiAmount")
             .and_then(|a| a.as_f64())
@@ -729,16 +1348,23 @@ impl EnhancedTransactionParser {
             p if p == self.token_program => "SPL Token".to_string(),
             p if p == self.token_2022_program => "Token 2022".to_string(),
             p if p == self.associated_token_program => "Associated Token".to_string(),
+            p if p == self.stake_program => "Stake Program".to_string(),
+            p if p == self.compute_budget_program => "Compute Budget".to_string(),
             p if p == self.raydium_v4 => "Raydium V4".to_string(),
             p if p == self.orca_whirlpool => "Orca Whirlpool".to_string(),
             p if p == self.jupiter_v6 => "Jupiter V6".to_string(),
             p if p == self.metaplex => "Metaplex".to_string(),
             p if p == self.magic_eden => "Magic Eden".to_string(),
-            _ => format!(
-                "{}...{}",
-                &program_id[..8],
-                &program_id[program_id.len() - 8..]
-            ),
+            _ => DexPrograms::name(program_id)
+                .or_else(|| NftMarketplaces::name(program_id))
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| {
+                    format!(
+                        "{}...{}",
+                        &program_id[..8],
+                        &program_id[program_id.len() - 8..]
+                    )
+                }),
         }
     }
 
@@ -751,21 +1377,36 @@ impl EnhancedTransactionParser {
         programs: &[String],
         sol_transfers: &[SolTransfer],
         token_transfers: &[TokenTransfer],
+        stake_actions: &[StakeAction],
+        mint_burn_events: &[MintBurnEvent],
     ) -> TransactionType {
+        // Freshly minted supply outranks the program that triggered it
+        if mint_burn_events.iter().any(|ev| ev.kind == "mint") {
+            return TransactionType::TokenMint;
+        }
+
         // Check for DEX interactions first
         for program in programs {
             if program.contains(&self.raydium_v4)
                 || program.contains(&self.orca_whirlpool)
                 || program.contains(&self.jupiter_v6)
+                || DexPrograms::name(program).is_some()
             {
                 return TransactionType::TokenSwap;
             }
 
-            if program.contains(&self.metaplex) || program.contains(&self.magic_eden) {
+            if program.contains(&self.metaplex)
+                || program.contains(&self.magic_eden)
+                || NftMarketplaces::name(program).is_some()
+            {
                 return TransactionType::NFTTrade;
             }
         }
 
+        if !stake_actions.is_empty() {
+            return TransactionType::Staking;
+        }
+
         // Check transfer types
         if !token_transfers.is_empty() {
             return TransactionType::TokenTransfer;
@@ -807,4 +1448,536 @@ mod tests {
         assert_eq!(changes[0].change_lamports, -500);
         assert_eq!(changes[1].change_lamports, 500);
     }
+
+    /// v0 transaction whose token transfer destination is loaded through an ALT.
+    fn v0_fixture() -> serde_json::Value {
+        serde_json::json!({
+            "slot": 260_000_000u64,
+            "blockTime": 1_710_000_000u64,
+            "version": 0,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 2_039_280u64, 1u64, 2_039_280u64, 1u64],
+                "postBalances": [999_995_000u64, 2_039_280u64, 1u64, 2_039_280u64, 1u64],
+                "preTokenBalances": [
+                    { "accountIndex": 1, "mint": "MintA", "owner": "Sender",
+                      "uiTokenAmount": { "amount": "1000", "decimals": 2 } },
+                    { "accountIndex": 3, "mint": "MintA", "owner": "Receiver",
+                      "uiTokenAmount": { "amount": "0", "decimals": 2 } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 1, "mint": "MintA", "owner": "Sender",
+                      "uiTokenAmount": { "amount": "500", "decimals": 2 } },
+                    { "accountIndex": 3, "mint": "MintA", "owner": "Receiver",
+                      "uiTokenAmount": { "amount": "500", "decimals": 2 } }
+                ]
+            },
+            "transaction": {
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 1
+                    },
+                    "accountKeys": [
+                        "Sender",
+                        "SenderAta",
+                        "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP"
+                    ],
+                    "instructions": [{
+                        "programIdIndex": 2,
+                        "accounts": [1, 3, 0],
+                        "parsed": {
+                            "type": "transfer",
+                            "info": {
+                                "source": "SenderAta",
+                                "destination": "ReceiverAta",
+                                "authority": "Sender",
+                                "amount": "500"
+                            }
+                        }
+                    }],
+                    "addressTableLookups": [{
+                        "accountKey": "LookupTable1",
+                        "writableIndexes": [2],
+                        "readonlyIndexes": [0]
+                    }]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_transfer_resolves_only_after_alt_expansion() {
+        let parser =
+            EnhancedTransactionParser::new().with_owner_cache(Arc::new(TokenOwnerCache::new(0)));
+        let raw = v0_fixture();
+
+        let unresolved = parser.parse(&raw, "sig_v0".to_string()).unwrap();
+        assert!(unresolved.is_versioned);
+        assert_eq!(unresolved.accounts.len(), 3);
+        assert_eq!(unresolved.token_transfers[0].to_owner, None);
+
+        let resolver = |table: &str| {
+            (table == "LookupTable1").then(|| {
+                vec![
+                    "ReadonlyProgram".to_string(),
+                    "Unused".to_string(),
+                    "ReceiverAta".to_string(),
+                ]
+            })
+        };
+        let tx = parser
+            .parse_with_lookups(&raw, "sig_v0".to_string(), resolver)
+            .unwrap();
+
+        assert_eq!(
+            tx.accounts,
+            vec![
+                "Sender",
+                "SenderAta",
+                "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                "ReceiverAta",
+                "ReadonlyProgram",
+            ]
+        );
+        assert!(tx.writable_accounts.contains(&"ReceiverAta".to_string()));
+        let transfer = &tx.token_transfers[0];
+        assert_eq!(transfer.to_owner.as_deref(), Some("Receiver"));
+        assert_eq!(transfer.mint, "MintA");
+        assert!((transfer.amount_ui - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_loaded_addresses_used_without_resolver() {
+        let parser = EnhancedTransactionParser::new();
+        let mut raw = v0_fixture();
+        raw["meta"]["loadedAddresses"] = serde_json::json!({
+            "writable": ["ReceiverAta"],
+            "readonly": ["ReadonlyProgram"]
+        });
+
+        // An unknown table falls back to the node-provided addresses.
+        let tx = parser
+            .parse_with_lookups(&raw, "sig_v0".to_string(), |_| None)
+            .unwrap();
+        assert_eq!(tx.accounts.len(), 5);
+        assert_eq!(tx.token_transfers[0].to_owner.as_deref(), Some("Receiver"));
+    }
+
+    /// jsonParsed transaction with a single Stake program instruction, paid for
+    /// by a separate fee payer so Staker's balance moves by exactly 3 SOL.
+    fn stake_fixture(instruction: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "slot": 270_000_000u64,
+            "blockTime": 1_715_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 10_000_000_000u64, 5_002_282_880u64, 1u64],
+                "postBalances": [999_995_000u64, 13_000_000_000u64, 2_002_282_880u64, 1u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "FeePayer", "signer": true, "writable": true },
+                        { "pubkey": "Staker", "signer": true, "writable": true },
+                        { "pubkey": "StakeAcct", "signer": false, "writable": true },
+                        { "pubkey": "Stake11111111111111111111111111111111111111", "signer": false, "writable": false }
+                    ],
+                    "instructions": [instruction]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_stake_delegate_is_classified_as_staking() {
+        let parser = EnhancedTransactionParser::new();
+        let raw = stake_fixture(serde_json::json!({
+            "programId": "Stake11111111111111111111111111111111111111",
+            "parsed": {
+                "type": "delegate",
+                "info": {
+                    "stakeAccount": "StakeAcct",
+                    "voteAccount": "ValidatorVote",
+                    "stakeAuthority": "Staker"
+                }
+            }
+        }));
+
+        let tx = parser.parse(&raw, "sig_delegate".to_string()).unwrap();
+
+        assert_eq!(tx.tx_type, TransactionType::Staking);
+        assert_eq!(tx.stake_actions.len(), 1);
+        let action = &tx.stake_actions[0];
+        assert_eq!(action.action, "delegate");
+        assert_eq!(action.stake_account, "StakeAcct");
+        assert_eq!(action.validator.as_deref(), Some("ValidatorVote"));
+        assert_eq!(action.amount_lamports, None);
+        assert!(tx.program_names.contains(&"Stake Program".to_string()));
+    }
+
+    #[test]
+    fn test_stake_withdraw_emits_sol_transfer() {
+        let parser = EnhancedTransactionParser::new();
+        let raw = stake_fixture(serde_json::json!({
+            "programId": "Stake11111111111111111111111111111111111111",
+            "parsed": {
+                "type": "withdraw",
+                "info": {
+                    "stakeAccount": "StakeAcct",
+                    "destination": "Staker",
+                    "lamports": 3_000_000_000u64,
+                    "withdrawAuthority": "Staker"
+                }
+            }
+        }));
+
+        let tx = parser.parse(&raw, "sig_withdraw".to_string()).unwrap();
+
+        assert_eq!(tx.tx_type, TransactionType::Staking);
+        assert_eq!(tx.stake_actions[0].action, "withdraw");
+        assert_eq!(tx.stake_actions[0].amount_lamports, Some(3_000_000_000));
+        // The balance change matches the withdrawal exactly; it is one transfer.
+        assert_eq!(tx.sol_transfers.len(), 1);
+        let withdrawal = &tx.sol_transfers[0];
+        assert_eq!(withdrawal.transfer_type, "stake_withdraw");
+        assert_eq!(withdrawal.from, "StakeAcct");
+        assert_eq!(withdrawal.to, "Staker");
+        assert!((withdrawal.amount_sol - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_system_transfer_is_not_double_counted_from_balances() {
+        let parser = EnhancedTransactionParser::new();
+        // FeePayer pays the fee; Alice sends Bob 1 SOL (explicit and in balances),
+        // Carol sends Dave 0.5 SOL through an inner instruction (balances only).
+        let raw = serde_json::json!({
+            "slot": 280_000_000u64,
+            "blockTime": 1_720_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [
+                    1_000_000_000u64, 3_000_000_000u64, 0u64,
+                    2_000_000_000u64, 0u64, 1u64
+                ],
+                "postBalances": [
+                    999_995_000u64, 2_000_000_000u64, 1_000_000_000u64,
+                    1_500_000_000u64, 500_000_000u64, 1u64
+                ]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "FeePayer", "signer": true, "writable": true },
+                        { "pubkey": "Alice", "signer": true, "writable": true },
+                        { "pubkey": "Bob", "signer": false, "writable": true },
+                        { "pubkey": "Carol", "signer": true, "writable": true },
+                        { "pubkey": "Dave", "signer": false, "writable": true },
+                        { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": "11111111111111111111111111111111",
+                        "parsed": {
+                            "type": "transfer",
+                            "info": { "source": "Alice", "destination": "Bob", "lamports": 1_000_000_000u64 }
+                        }
+                    }]
+                }
+            }
+        });
+
+        let tx = parser.parse(&raw, "sig_dupe".to_string()).unwrap();
+
+        let summary: Vec<(&str, &str, &str)> = tx
+            .sol_transfers
+            .iter()
+            .map(|t| (t.from.as_str(), t.to.as_str(), t.transfer_type.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Alice", "Bob", "system"),
+                ("Carol", "Dave", "balance_change")
+            ]
+        );
+    }
+
+    /// Disperser paying the fee and sending 1, 2 and 3 SOL in one inner instruction.
+    fn fan_out_fixture() -> serde_json::Value {
+        serde_json::json!({
+            "slot": 281_000_000u64,
+            "blockTime": 1_720_100_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [10_000_000_000u64, 0u64, 0u64, 0u64],
+                "postBalances": [3_999_995_000u64, 1_000_000_000u64, 2_000_000_000u64, 3_000_000_000u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Disperser", "signer": true, "writable": true },
+                        { "pubkey": "R1", "signer": false, "writable": true },
+                        { "pubkey": "R2", "signer": false, "writable": true },
+                        { "pubkey": "R3", "signer": false, "writable": true }
+                    ],
+                    "instructions": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_fan_out_is_inferred_only_when_enabled() {
+        let raw = fan_out_fixture();
+
+        let off = EnhancedTransactionParser::new()
+            .with_fan_out_inference(false)
+            .parse(&raw, "sig_fan".to_string())
+            .unwrap();
+        assert!(off.sol_transfers.is_empty());
+
+        let on = EnhancedTransactionParser::new()
+            .with_fan_out_inference(true)
+            .parse(&raw, "sig_fan".to_string())
+            .unwrap();
+        let summary: Vec<(&str, &str, u64)> = on
+            .sol_transfers
+            .iter()
+            .map(|t| (t.from.as_str(), t.to.as_str(), t.amount_lamports))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Disperser", "R1", 1_000_000_000),
+                ("Disperser", "R2", 2_000_000_000),
+                ("Disperser", "R3", 3_000_000_000)
+            ]
+        );
+        assert!(on
+            .sol_transfers
+            .iter()
+            .all(|t| t.transfer_type == "balance_fan_out"));
+
+        // Recipients getting more than the sender lost is not a distribution.
+        let mut short = raw.clone();
+        short["meta"]["postBalances"][3] = serde_json::json!(3_100_000_000u64);
+        let rejected = EnhancedTransactionParser::new()
+            .with_fan_out_inference(true)
+            .parse(&short, "sig_fan".to_string())
+            .unwrap();
+        assert!(rejected.sol_transfers.is_empty());
+    }
+
+    #[test]
+    fn test_compute_budget_and_priority_fee() {
+        let parser = EnhancedTransactionParser::new();
+        let compute_budget = "ComputeBudget111111111111111111111111111111";
+        let mut limit_data = vec![2u8];
+        limit_data.extend_from_slice(&300_000u32.to_le_bytes());
+        let mut price_data = vec![3u8];
+        price_data.extend_from_slice(&25_000u64.to_le_bytes());
+
+        let raw = serde_json::json!({
+            "slot": 275_000_000u64,
+            "blockTime": 1_716_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 12_500,
+                "computeUnitsConsumed": 142_000u64,
+                "preBalances": [1_000_000_000u64, 1u64],
+                "postBalances": [999_987_500u64, 1u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Bot", "signer": true, "writable": true },
+                        { "pubkey": compute_budget, "signer": false, "writable": false }
+                    ],
+                    "instructions": [
+                        { "programId": compute_budget, "accounts": [],
+                          "data": bs58::encode(&limit_data).into_string() },
+                        { "programId": compute_budget, "accounts": [],
+                          "data": bs58::encode(&price_data).into_string() }
+                    ]
+                }
+            }
+        });
+
+        let tx = parser.parse(&raw, "sig_cb".to_string()).unwrap();
+
+        assert_eq!(tx.compute_units_consumed, Some(142_000));
+        assert_eq!(tx.compute_unit_price_micro_lamports, Some(25_000));
+        // 25_000 micro-lamports * 300_000 units = 7_500 lamports
+        assert_eq!(tx.priority_fee_lamports, Some(7_500));
+
+        let plain = parser.parse(&v0_fixture(), "sig_v0".to_string()).unwrap();
+        assert_eq!(plain.priority_fee_lamports, None);
+        let json = serde_json::to_value(&plain).unwrap();
+        assert!(json.get("priority_fee_lamports").is_none());
+    }
+
+    /// jsonParsed transaction with one SPL Token instruction against `TokenAcct`.
+    fn token_fixture(instruction: serde_json::Value, pre: &str, post: &str) -> serde_json::Value {
+        let balance = |amount: &str| {
+            serde_json::json!([{ "accountIndex": 1, "mint": "MemeMint", "owner": "Holder",
+                "uiTokenAmount": { "amount": amount, "decimals": 6 } }])
+        };
+        serde_json::json!({
+            "slot": 276_000_000u64,
+            "blockTime": 1_717_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 2_039_280u64, 1u64],
+                "postBalances": [999_995_000u64, 2_039_280u64, 1u64],
+                "preTokenBalances": balance(pre),
+                "postTokenBalances": balance(post)
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Holder", "signer": true, "writable": true },
+                        { "pubkey": "TokenAcct", "signer": false, "writable": true },
+                        { "pubkey": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP", "signer": false, "writable": false }
+                    ],
+                    "instructions": [instruction]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_mint_to_is_extracted_and_classified() {
+        let parser = EnhancedTransactionParser::new();
+        let raw = token_fixture(
+            serde_json::json!({
+                "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                "parsed": { "type": "mintTo", "info": {
+                    "mint": "MemeMint", "account": "TokenAcct",
+                    "mintAuthority": "Holder", "amount": "1000000000"
+                } }
+            }),
+            "0",
+            "1000000000",
+        );
+
+        let tx = parser.parse(&raw, "sig_mint".to_string()).unwrap();
+
+        assert_eq!(tx.tx_type, TransactionType::TokenMint);
+        assert_eq!(tx.mint_burn_events.len(), 1);
+        let ev = &tx.mint_burn_events[0];
+        assert_eq!(ev.kind, "mint");
+        assert_eq!(ev.mint, "MemeMint");
+        assert_eq!(ev.account, "TokenAcct");
+        assert_eq!(ev.owner.as_deref(), Some("Holder"));
+        assert_eq!(ev.amount, 1_000_000_000);
+        assert_eq!(ev.decimals, 6);
+    }
+
+    #[test]
+    fn test_burn_checked_is_extracted() {
+        let parser = EnhancedTransactionParser::new();
+        let raw = token_fixture(
+            serde_json::json!({
+                "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                "parsed": { "type": "burnChecked", "info": {
+                    "mint": "MemeMint", "account": "TokenAcct", "authority": "Holder",
+                    "tokenAmount": { "amount": "250000", "decimals": 6, "uiAmount": 0.25 }
+                } }
+            }),
+            "1000000",
+            "750000",
+        );
+
+        let tx = parser.parse(&raw, "sig_burn".to_string()).unwrap();
+
+        assert_ne!(tx.tx_type, TransactionType::TokenMint);
+        assert_eq!(tx.mint_burn_events.len(), 1);
+        let ev = &tx.mint_burn_events[0];
+        assert_eq!(ev.kind, "burn");
+        assert_eq!(ev.amount, 250_000);
+        assert_eq!(ev.owner.as_deref(), Some("Holder"));
+    }
+
+    #[test]
+    fn test_owner_from_earlier_transaction_fills_missing_balance() {
+        let parser =
+            EnhancedTransactionParser::new().with_owner_cache(Arc::new(TokenOwnerCache::new(8)));
+        let tx = |balances: serde_json::Value| {
+            serde_json::json!({
+                "slot": 1u64,
+                "blockTime": 1_000u64,
+                "meta": {
+                    "err": null, "fee": 0,
+                    "preBalances": [], "postBalances": [],
+                    "preTokenBalances": balances.clone(),
+                    "postTokenBalances": balances
+                },
+                "transaction": { "message": {
+                    "accountKeys": ["Sender", "SenderAta", "ReceiverAta"],
+                    "instructions": [{
+                        "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                        "parsed": { "type": "transfer", "info": {
+                            "source": "SenderAta", "destination": "ReceiverAta",
+                            "authority": "Sender", "amount": "5"
+                        } }
+                    }]
+                } }
+            })
+        };
+
+        // First transaction carries both token balances.
+        let first = parser
+            .parse(
+                &tx(serde_json::json!([
+                    { "accountIndex": 1, "mint": "MintA", "owner": "Sender",
+                      "uiTokenAmount": { "amount": "10", "decimals": 0 } },
+                    { "accountIndex": 2, "mint": "MintA", "owner": "Receiver",
+                      "uiTokenAmount": { "amount": "0", "decimals": 0 } }
+                ])),
+                "sig1".to_string(),
+            )
+            .unwrap();
+        assert_eq!(
+            first.token_transfers[0].to_owner.as_deref(),
+            Some("Receiver")
+        );
+
+        // The second one has none; owners come from the cache.
+        let second = parser
+            .parse(&tx(serde_json::json!([])), "sig2".to_string())
+            .unwrap();
+        let transfer = &second.token_transfers[0];
+        assert_eq!(transfer.from_owner.as_deref(), Some("Sender"));
+        assert_eq!(transfer.to_owner.as_deref(), Some("Receiver"));
+        assert_eq!(transfer.mint, "unknown");
+
+        // A parser without the shared cache cannot resolve them.
+        let fresh =
+            EnhancedTransactionParser::new().with_owner_cache(Arc::new(TokenOwnerCache::new(8)));
+        let unresolved = fresh
+            .parse(&tx(serde_json::json!([])), "sig2".to_string())
+            .unwrap();
+        assert_eq!(unresolved.token_transfers[0].from_owner, None);
+    }
+
+    #[test]
+    fn test_owner_cache_is_bounded() {
+        let cache = TokenOwnerCache::new(4);
+        for i in 0..10 {
+            cache.insert(&format!("ata{}", i), "owner");
+        }
+        assert!(cache.len() <= 4);
+        assert_eq!(cache.get("ata9").as_deref(), Some("owner"));
+        assert_eq!(cache.get("ata0"), None);
+
+        let disabled = TokenOwnerCache::new(0);
+        disabled.insert("ata", "owner");
+        assert!(disabled.is_empty());
+    }
 }
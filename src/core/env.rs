@@ -0,0 +1,134 @@
+//! Environment settings - the one parser behind every numeric, boolean and enum
+//! override read from the environment.
+//!
+//! An unset or blank variable silently falls back to its default. A value that
+//! doesn't parse, or that the setting rejects, is logged and ignored; one out of
+//! range is logged and clamped. A typo is visible in the logs instead of quietly
+//! running on the default, and never stops the process.
+
+use std::fmt::{Debug, Display};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Trimmed value of `var`, or `None` when it is unset or blank.
+fn raw(var: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// `var` parsed as `T`, or `default` when unset or unparseable.
+pub fn env_parse<T>(var: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    env_parse_where(var, default, |_| true, "")
+}
+
+/// Like `env_parse`, but a parsed value failing `accept` is also ignored;
+/// `expected` describes valid values in the warning (e.g. "a positive number").
+pub fn env_parse_where<T>(var: &str, default: T, accept: impl Fn(&T) -> bool, expected: &str) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(raw) = raw(var) else {
+        return default;
+    };
+    match raw.parse::<T>() {
+        Ok(value) if accept(&value) => value,
+        Ok(_) => {
+            tracing::warn!("Ignoring {}={:?}: expected {}", var, raw, expected);
+            default
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring {}={:?}: {}", var, raw, e);
+            default
+        }
+    }
+}
+
+/// Like `env_parse`, with the value clamped into `range`.
+pub fn env_parse_in<T>(var: &str, default: T, range: RangeInclusive<T>) -> T
+where
+    T: FromStr + PartialOrd + Copy + Debug,
+    T::Err: Display,
+{
+    let value = env_parse(var, default);
+    let (min, max) = (*range.start(), *range.end());
+    if value < min || value > max {
+        let clamped = if value < min { min } else { max };
+        tracing::warn!(
+            "{}={:?} is outside {:?}..={:?}; using {:?}",
+            var,
+            value,
+            min,
+            max,
+            clamped
+        );
+        return clamped;
+    }
+    value
+}
+
+/// Boolean `var`: `1`/`true`/`yes` or `0`/`false`/`no` (any case), else `default`.
+pub fn env_flag(var: &str, default: bool) -> bool {
+    let Some(raw) = raw(var) else {
+        return default;
+    };
+    match raw.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => true,
+        "0" | "false" | "no" => false,
+        _ => {
+            tracing::warn!("Ignoring {}={:?}: expected true or false", var, raw);
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test sets its own variables: tests share the process environment.
+    #[test]
+    fn test_unset_blank_and_invalid_values_fall_back_to_the_default() {
+        assert_eq!(env_parse("ENV_TEST_UNSET", 7u64), 7);
+        std::env::set_var("ENV_TEST_BLANK", "  ");
+        assert_eq!(env_parse("ENV_TEST_BLANK", 7u64), 7);
+        std::env::set_var("ENV_TEST_GARBAGE", "seven");
+        assert_eq!(env_parse("ENV_TEST_GARBAGE", 7u64), 7);
+        std::env::set_var("ENV_TEST_VALID", " 42 ");
+        assert_eq!(env_parse("ENV_TEST_VALID", 7u64), 42);
+    }
+
+    #[test]
+    fn test_rejected_values_fall_back_and_out_of_range_values_clamp() {
+        std::env::set_var("ENV_TEST_NEGATIVE", "-0.5");
+        assert_eq!(
+            env_parse_where(
+                "ENV_TEST_NEGATIVE",
+                0.1,
+                |w: &f64| *w >= 0.0,
+                "a non-negative number"
+            ),
+            0.1
+        );
+        std::env::set_var("ENV_TEST_HIGH", "5000");
+        assert_eq!(env_parse_in("ENV_TEST_HIGH", 60u64, 1..=3_600), 3_600);
+        std::env::set_var("ENV_TEST_LOW", "0");
+        assert_eq!(env_parse_in("ENV_TEST_LOW", 60u64, 1..=3_600), 1);
+    }
+
+    #[test]
+    fn test_flags_accept_common_spellings() {
+        std::env::set_var("ENV_TEST_FLAG_ON", "YES");
+        assert!(env_flag("ENV_TEST_FLAG_ON", false));
+        std::env::set_var("ENV_TEST_FLAG_OFF", "0");
+        assert!(!env_flag("ENV_TEST_FLAG_OFF", true));
+        std::env::set_var("ENV_TEST_FLAG_ODD", "maybe");
+        assert!(env_flag("ENV_TEST_FLAG_ODD", true));
+    }
+}
@@ -8,7 +8,7 @@ pub enum BeastError {
     #[error("Database Error: {0}")]
     DatabaseError(String),
 
-    #[error("Invalid wallet address: {0}")]
+    #[error("Invalid Solana address: {0}")]
     InvalidAddress(String),
 
     #[error("Analysis failed: {0}")]
@@ -23,6 +23,10 @@ pub enum BeastError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// The transaction is `slots_missing` slots short of the required depth.
+    #[error("Not enough confirmations: {message}")]
+    Unconfirmed { message: String, slots_missing: u64 },
+
     #[error("Network error: {0}")]
     NetworkError(String),
 
@@ -1,8 +1,13 @@
+pub mod address;
+pub mod circuit_breaker;
 pub mod enhanced_parser;
+pub mod env;
 pub mod errors;
 pub mod rpc_client;
+pub mod units;
 
+pub use address::{is_valid_address, validate_address};
 pub use enhanced_parser::{
-    EnhancedTransaction, EnhancedTransactionParser, SolTransfer, TokenTransfer,
+    EnhancedTransaction, EnhancedTransactionParser, SolTransfer, TokenOwnerCache, TokenTransfer,
 };
 pub use rpc_client::SolanaRpcClient;
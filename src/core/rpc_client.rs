@@ -1,17 +1,120 @@
 /// Solana RPC Client wrapper for blockchain interactions
+use crate::core::address::validate_address;
+use crate::core::circuit_breaker::{CircuitState, RpcCircuitBreaker};
+use crate::core::env::{env_parse, env_parse_in};
 use crate::core::errors::{BeastError, Result};
+use crate::core::units::amount_to_ui;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Instant};
 
+/// Used when neither `SOLANA_RPC_ENDPOINT` nor `RPC_ENDPOINTS` is set.
+pub const DEFAULT_RPC_ENDPOINT: &str = "https://api.mainnet-beta.solana.com";
+
+/// Max pubkeys accepted by a single `getMultipleAccounts` call.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Base delay for exponential retry backoff.
+const RETRY_BASE_MS: u64 = 200;
+/// Upper bound for a single backoff sleep.
+const RETRY_MAX_BACKOFF_MS: u64 = 5_000;
+/// Upper bound honoured for a server-provided `Retry-After`.
+const RETRY_AFTER_MAX: Duration = Duration::from_secs(30);
+/// JSON-RPC error codes that signal rate limiting (HTTP-style 429 and the
+/// provider-specific `-32005`).
+const RATE_LIMIT_RPC_CODES: [i32; 2] = [429, -32005];
+
+/// Token programs whose accounts are included in holdings snapshots.
+const TOKEN_PROGRAMS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+    "TokenzQdBNbJPPzh6txJjTpWp8QJKhdfuqJW65PfQG",
+];
+
+/// How settled the state read over RPC must be: `processed` is newest but can be
+/// rolled back, `finalized` is reorg-safe but lags, `confirmed` sits in between.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+impl std::str::FromStr for Commitment {
+    type Err = String;
+
+    fn from_str(raw: &str) -> std::result::Result<Self, String> {
+        Self::parse(raw)
+    }
+}
+
+impl Commitment {
+    pub fn parse(raw: &str) -> std::result::Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "processed" => Ok(Self::Processed),
+            "confirmed" => Ok(Self::Confirmed),
+            "finalized" => Ok(Self::Finalized),
+            other => Err(format!(
+                "unknown commitment '{}' (expected processed, confirmed or finalized)",
+                other
+            )),
+        }
+    }
+
+    /// `RPC_COMMITMENT`, or `confirmed` when unset or unrecognised (logged).
+    pub fn from_env() -> Self {
+        env_parse("RPC_COMMITMENT", Self::default())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+
+    /// For methods the RPC only serves at `confirmed` or above
+    /// (`getSignaturesForAddress`, `getTransaction`).
+    fn at_least_confirmed(self) -> Self {
+        match self {
+            Self::Processed => Self::Confirmed,
+            other => other,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SolanaRpcClient {
-    endpoint: String,
+    endpoints: Arc<Vec<RpcEndpoint>>,
+    /// Round-robin cursor into `endpoints`.
+    next_endpoint: Arc<AtomicUsize>,
     http_client: reqwest::Client,
-    rate_limiter: Arc<RateLimiter>,
     max_retries: usize,
+    retries: Arc<AtomicU64>,
+    /// Commitment sent with account and signature reads.
+    commitment: Commitment,
+}
+
+/// One upstream RPC URL with its own rate limiter and circuit breaker.
+struct RpcEndpoint {
+    url: String,
+    rate_limiter: RateLimiter,
+    circuit_breaker: Arc<RpcCircuitBreaker>,
+}
+
+/// Health snapshot of a pooled endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub state: CircuitState,
+    pub trips: u64,
 }
 
 struct RateLimiter {
@@ -43,6 +146,46 @@ impl RateLimiter {
 
 impl SolanaRpcClient {
     pub fn new(endpoint: String) -> Self {
+        Self::with_circuit_breaker(endpoint, Arc::new(RpcCircuitBreaker::from_env()))
+    }
+
+    /// Client over `RPC_ENDPOINTS` (comma-separated) plus `SOLANA_RPC_ENDPOINT`,
+    /// falling back to the public mainnet RPC when neither is set.
+    pub fn from_env() -> Self {
+        let mut endpoints: Vec<String> = Vec::new();
+        let single = std::env::var("SOLANA_RPC_ENDPOINT").unwrap_or_default();
+        let pooled = std::env::var("RPC_ENDPOINTS").unwrap_or_default();
+        for url in std::iter::once(single.as_str()).chain(pooled.split(',')) {
+            let url = url.trim();
+            if !url.is_empty() && !endpoints.iter().any(|e| e == url) {
+                endpoints.push(url.to_string());
+            }
+        }
+        Self::new_pool(endpoints)
+    }
+
+    /// Client that round-robins requests across `endpoints`, failing over to the
+    /// next endpoint on transport errors, HTTP 429 and 5xx. Each endpoint gets its
+    /// own circuit breaker and is skipped while its circuit is open.
+    pub fn new_pool(endpoints: Vec<String>) -> Self {
+        let mut endpoints: Vec<(String, Arc<RpcCircuitBreaker>)> = endpoints
+            .into_iter()
+            .map(|url| (url, Arc::new(RpcCircuitBreaker::from_env())))
+            .collect();
+        if endpoints.is_empty() {
+            endpoints.push((
+                DEFAULT_RPC_ENDPOINT.to_string(),
+                Arc::new(RpcCircuitBreaker::from_env()),
+            ));
+        }
+        Self::with_endpoints(endpoints)
+    }
+
+    pub fn with_circuit_breaker(endpoint: String, circuit_breaker: Arc<RpcCircuitBreaker>) -> Self {
+        Self::with_endpoints(vec![(endpoint, circuit_breaker)])
+    }
+
+    fn with_endpoints(endpoints: Vec<(String, Arc<RpcCircuitBreaker>)>) -> Self {
         // `reqwest::Client::new()` can read system proxy configuration on macOS.
         // In sandboxed environments this can panic (SystemConfiguration returning NULL),
         // so we explicitly disable proxy auto-detection.
@@ -53,130 +196,168 @@ impl SolanaRpcClient {
             .build()
             .expect("Failed to build reqwest client");
 
-        let min_interval_ms = std::env::var("RPC_MIN_INTERVAL_MS")
-            .ok()
-            .and_then(|s| s.trim().parse::<u64>().ok())
-            .unwrap_or(120);
+        let min_interval_ms = env_parse("RPC_MIN_INTERVAL_MS", 120u64);
+        let max_retries = env_parse_in("RPC_MAX_RETRIES", 5, 1..=15);
 
-        let max_retries = std::env::var("RPC_MAX_RETRIES")
-            .ok()
-            .and_then(|s| s.trim().parse::<usize>().ok())
-            .unwrap_or(5)
-            .clamp(1, 15);
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, circuit_breaker)| RpcEndpoint {
+                url,
+                rate_limiter: RateLimiter::new(Duration::from_millis(min_interval_ms)),
+                circuit_breaker,
+            })
+            .collect();
 
         SolanaRpcClient {
-            endpoint,
+            endpoints: Arc::new(endpoints),
+            next_endpoint: Arc::new(AtomicUsize::new(0)),
             http_client,
-            rate_limiter: Arc::new(RateLimiter::new(Duration::from_millis(min_interval_ms))),
             max_retries,
+            retries: Arc::new(AtomicU64::new(0)),
+            commitment: Commitment::from_env(),
         }
     }
 
-    /// Get account information from Solana blockchain
-    pub async fn get_account_info(&self, address: &str) -> Result<AccountInfo> {
-        // Validate Solana address format
-        if address.len() != 44 && address.len() != 32 {
-            return Err(BeastError::InvalidAddress(format!(
-                "Invalid Solana address length: {}",
-                address.len()
-            )));
+    /// The same client (sharing endpoints, limits and breakers) reading at `commitment`.
+    pub fn with_commitment(&self, commitment: Commitment) -> Self {
+        Self {
+            commitment,
+            ..self.clone()
         }
+    }
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getAccountInfo",
-            "params": [address, { "encoding": "jsonParsed" }]
-        });
+    pub fn commitment(&self) -> Commitment {
+        self.commitment
+    }
 
-        self.rate_limiter.acquire().await;
-        match self
-            .http_client
-            .post(&self.endpoint)
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(response) => match response.json::<RpcResponse<AccountData>>().await {
-                Ok(rpc_response) => {
-                    if let Some(data) = rpc_response.result {
-                        Ok(AccountInfo {
-                            address: address.to_string(),
-                            balance: data.value.lamports,
-                            owner: data.value.owner,
-                            executable: data.value.executable,
-                            rent_epoch: data.value.rent_epoch,
-                        })
-                    } else {
-                        Err(BeastError::RpcError("Account not found".to_string()))
-                    }
-                }
-                Err(e) => Err(BeastError::RpcError(format!(
-                    "Failed to parse account info response: {}",
-                    e
-                ))),
-            },
-            Err(e) => Err(BeastError::RpcError(format!(
-                "Failed to get account info: {}",
-                e
-            ))),
-        }
+    /// Number of retried RPC attempts since the client was created.
+    pub fn retry_count(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
     }
 
-    /// Get transaction signatures for a wallet
-    pub async fn get_signatures(
+    /// Sleep before retry `attempt` (0-based): the server's `Retry-After` if it
+    /// sent one, otherwise exponential backoff with full jitter.
+    async fn wait_before_retry(&self, method: &str, attempt: usize, retry_after: Option<Duration>) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        let delay = retry_after.unwrap_or_else(|| backoff_delay(attempt));
+        tracing::debug!(
+            "retrying {} (attempt {}) in {}ms",
+            method,
+            attempt + 2,
+            delay.as_millis()
+        );
+        sleep(delay).await;
+    }
+
+    /// Circuit breaker of the first (primary) endpoint.
+    pub fn circuit_breaker(&self) -> &Arc<RpcCircuitBreaker> {
+        &self.endpoints[0].circuit_breaker
+    }
+
+    /// Circuit state of every endpoint in the pool.
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|e| EndpointHealth {
+                url: e.url.clone(),
+                state: e.circuit_breaker.state(),
+                trips: e.circuit_breaker.trips(),
+            })
+            .collect()
+    }
+
+    /// Send a JSON-RPC request through the endpoint pool.
+    ///
+    /// Starts at the next endpoint in round-robin order and skips endpoints whose
+    /// circuit is open. Transport errors and 5xx responses count as failures for
+    /// that endpoint's breaker; they and HTTP 429 fail over to the next endpoint.
+    /// Throttling is not an outage, so 429 leaves the breaker alone and is left
+    /// to the retry backoff. The last endpoint's outcome is returned as the
+    /// inner result. The outer
+    /// error is returned when every circuit is open (fail fast, no retry).
+    async fn send(
         &self,
-        address: &str,
-        limit: u64,
-    ) -> Result<Vec<TransactionSignature>> {
-        // Validate Solana address format
-        if address.len() != 44 && address.len() != 32 {
-            return Err(BeastError::InvalidAddress(format!(
-                "Invalid Solana address length: {}",
-                address.len()
-            )));
+        body: &serde_json::Value,
+    ) -> Result<std::result::Result<reqwest::Response, reqwest::Error>> {
+        let count = self.endpoints.len();
+        let start = self.next_endpoint.fetch_add(1, Ordering::Relaxed) % count;
+        let mut last_failure = None;
+
+        for offset in 0..count {
+            let endpoint = &self.endpoints[(start + offset) % count];
+            if !endpoint.circuit_breaker.allow_request() {
+                continue;
+            }
+
+            endpoint.rate_limiter.acquire().await;
+            let result = self.http_client.post(&endpoint.url).json(body).send().await;
+
+            let (failed, throttled) = match &result {
+                Ok(resp) => (
+                    resp.status().is_server_error(),
+                    resp.status().as_u16() == 429,
+                ),
+                Err(_) => (true, false),
+            };
+            if !failed && !throttled {
+                endpoint.circuit_breaker.record_success();
+                return Ok(result);
+            }
+
+            if failed {
+                endpoint.circuit_breaker.record_failure();
+            }
+            if count > 1 {
+                tracing::debug!("RPC endpoint {} failed; trying next", endpoint.url);
+            }
+            last_failure = Some(result);
         }
 
-        let body = serde_json::json!({
+        last_failure.ok_or_else(|| BeastError::RpcError("circuit open".to_string()))
+    }
+
+    /// Call any JSON-RPC method through the endpoint pool and return its `result`
+    /// (`Value::Null` when the node returned none).
+    ///
+    /// Transport errors, HTTP 429 and rate-limit error codes are retried with
+    /// backoff (honouring `Retry-After`); other HTTP and JSON-RPC errors are returned
+    /// as `BeastError::RpcError`.
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let mut body = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "getSignaturesForAddress",
-            "params": [address, { "limit": limit.min(1000) }]
+            "method": method
         });
+        if !params.is_null() {
+            body["params"] = params;
+        }
 
-        for attempt in 0..self.max_retries {
-            self.rate_limiter.acquire().await;
-
-            let resp = match self
-                .http_client
-                .post(&self.endpoint)
-                .json(&body)
-                .send()
-                .await
-            {
+        let mut attempt = 0;
+        loop {
+            let retries_left = attempt + 1 < self.max_retries;
+            let resp = match self.send(&body).await? {
                 Ok(r) => r,
+                Err(e) if retries_left => {
+                    tracing::debug!("{} request failed: {}", method, e);
+                    self.wait_before_retry(method, attempt, None).await;
+                    attempt += 1;
+                    continue;
+                }
                 Err(e) => {
-                    if attempt + 1 < self.max_retries {
-                        sleep(Duration::from_millis(200 * (attempt as u64 + 1))).await;
-                        continue;
-                    }
-                    return Err(BeastError::RpcError(format!(
-                        "Failed to get signatures: {}",
-                        e
-                    )));
+                    return Err(BeastError::RpcError(format!("{} failed: {}", method, e)));
                 }
             };
 
             let status = resp.status();
+            let retry_after = parse_retry_after(resp.headers());
             let text = resp.text().await.map_err(|e| {
-                BeastError::RpcError(format!("Failed to read signatures response: {}", e))
+                BeastError::RpcError(format!("Failed to read {} response: {}", method, e))
             })?;
 
             if !status.is_success() {
-                if status.as_u16() == 429 && attempt + 1 < self.max_retries {
-                    // Exponential backoff capped at ~3s.
-                    let backoff = (250_u64 << attempt.min(4)).min(3_000);
-                    sleep(Duration::from_millis(backoff)).await;
+                if status.as_u16() == 429 && retries_left {
+                    self.wait_before_retry(method, attempt, retry_after).await;
+                    attempt += 1;
                     continue;
                 }
                 return Err(BeastError::RpcError(format!(
@@ -186,15 +367,15 @@ impl SolanaRpcClient {
                 )));
             }
 
-            let rpc_response: RpcResponse<Vec<SignatureData>> = serde_json::from_str(&text)
+            let rpc_response: RpcResponse<serde_json::Value> = serde_json::from_str(&text)
                 .map_err(|e| {
-                    BeastError::RpcError(format!("Failed to parse signatures response: {}", e))
+                    BeastError::RpcError(format!("Failed to parse {} response: {}", method, e))
                 })?;
 
             if let Some(err) = rpc_response.error {
-                if err.code == 429 && attempt + 1 < self.max_retries {
-                    let backoff = (250_u64 << attempt.min(4)).min(3_000);
-                    sleep(Duration::from_millis(backoff)).await;
+                if RATE_LIMIT_RPC_CODES.contains(&err.code) && retries_left {
+                    self.wait_before_retry(method, attempt, retry_after).await;
+                    attempt += 1;
                     continue;
                 }
                 return Err(BeastError::RpcError(format!(
@@ -203,20 +384,291 @@ impl SolanaRpcClient {
                 )));
             }
 
-            let sigs = rpc_response.result.unwrap_or_default();
-            let signatures = sigs
-                .into_iter()
-                .map(|sig_data| TransactionSignature {
-                    signature: sig_data.signature,
-                    slot: sig_data.slot,
-                    block_time: sig_data.block_time.unwrap_or(0),
-                    memo: sig_data.memo,
+            return Ok(rpc_response.result.unwrap_or(serde_json::Value::Null));
+        }
+    }
+
+    /// `call` with the `result` deserialized into `T` (`T::default()` when null).
+    async fn call_typed<T: serde::de::DeserializeOwned + Default>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        match self.call(method, params).await? {
+            serde_json::Value::Null => Ok(T::default()),
+            value => serde_json::from_value(value).map_err(|e| {
+                BeastError::RpcError(format!("Failed to parse {} response: {}", method, e))
+            }),
+        }
+    }
+
+    /// Get account information from Solana blockchain
+    pub async fn get_account_info(&self, address: &str) -> Result<AccountInfo> {
+        validate_address(address)?;
+
+        let data: AccountData = self
+            .call_typed(
+                "getAccountInfo",
+                serde_json::json!([address, {
+                    "encoding": "jsonParsed",
+                    "commitment": self.commitment.as_str()
+                }]),
+            )
+            .await?;
+        let value = data
+            .value
+            .ok_or_else(|| BeastError::RpcError("Account not found".to_string()))?;
+
+        Ok(AccountInfo {
+            address: address.to_string(),
+            balance: value.lamports,
+            owner: value.owner,
+            executable: value.executable,
+            rent_epoch: value.rent_epoch,
+        })
+    }
+
+    /// Get account information for many addresses, batched into
+    /// `getMultipleAccounts` calls of at most 100 pubkeys.
+    ///
+    /// Results preserve input order; accounts that do not exist are `None`.
+    pub async fn get_multiple_accounts(
+        &self,
+        addresses: &[&str],
+    ) -> Result<Vec<Option<AccountInfo>>> {
+        for address in addresses {
+            validate_address(address)?;
+        }
+
+        let mut out = Vec::with_capacity(addresses.len());
+        for chunk in address_chunks(addresses) {
+            let values = self
+                .call_typed::<MultipleAccountsData>(
+                    "getMultipleAccounts",
+                    serde_json::json!([chunk, {
+                        "encoding": "base64",
+                        "dataSlice": { "offset": 0, "length": 0 },
+                        "commitment": self.commitment.as_str()
+                    }]),
+                )
+                .await?
+                .value;
+            if values.len() != chunk.len() {
+                return Err(BeastError::RpcError(format!(
+                    "getMultipleAccounts returned {} accounts for {} addresses",
+                    values.len(),
+                    chunk.len()
+                )));
+            }
+
+            out.extend(chunk.iter().zip(values).map(|(address, value)| {
+                value.map(|v| AccountInfo {
+                    address: address.to_string(),
+                    balance: v.lamports,
+                    owner: v.owner,
+                    executable: v.executable,
+                    rent_epoch: v.rent_epoch,
                 })
-                .collect();
-            return Ok(signatures);
+            }));
+        }
+
+        Ok(out)
+    }
+
+    /// Get the token accounts owned by a wallet (SPL Token and Token-2022).
+    pub async fn get_token_accounts_by_owner(&self, owner: &str) -> Result<Vec<TokenHolding>> {
+        validate_address(owner)?;
+
+        let mut holdings = Vec::new();
+        for program_id in TOKEN_PROGRAMS {
+            let accounts: TokenAccountsData = self
+                .call_typed(
+                    "getTokenAccountsByOwner",
+                    serde_json::json!([owner, { "programId": program_id }, {
+                        "encoding": "jsonParsed",
+                        "commitment": self.commitment.as_str()
+                    }]),
+                )
+                .await?;
+
+            for entry in accounts.value {
+                let info = &entry.account.data["parsed"]["info"];
+                let Some(mint) = info["mint"].as_str() else {
+                    continue;
+                };
+                let token_amount = &info["tokenAmount"];
+                let amount = token_amount["amount"]
+                    .as_str()
+                    .and_then(|a| a.parse::<u64>().ok())
+                    .unwrap_or(0);
+                let decimals = token_amount["decimals"].as_u64().unwrap_or(0) as u8;
+
+                holdings.push(TokenHolding {
+                    mint: mint.to_string(),
+                    token_account: entry.pubkey,
+                    amount,
+                    decimals,
+                    ui_amount: amount_to_ui(amount, decimals),
+                });
+            }
         }
 
-        Ok(vec![])
+        Ok(holdings)
+    }
+
+    /// Resolve an SPL token account's mint, owner and decimals.
+    ///
+    /// Returns `None` for accounts that do not exist (e.g. closed) or are not token accounts.
+    pub async fn get_token_account(&self, address: &str) -> Result<Option<TokenAccountMeta>> {
+        validate_address(address)?;
+
+        let data: TokenAccountInfoData = self
+            .call_typed(
+                "getAccountInfo",
+                serde_json::json!([address, {
+                    "encoding": "jsonParsed",
+                    "commitment": self.commitment.as_str()
+                }]),
+            )
+            .await?;
+
+        let Some(account) = data.value else {
+            return Ok(None);
+        };
+        let info = &account.data["parsed"]["info"];
+        let (Some(mint), Some(owner)) = (info["mint"].as_str(), info["owner"].as_str()) else {
+            return Ok(None);
+        };
+        Ok(Some(TokenAccountMeta {
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            decimals: info["tokenAmount"]["decimals"].as_u64().unwrap_or(0) as u8,
+        }))
+    }
+
+    /// Estimated production time of a slot (unix seconds); `None` when the node
+    /// has no time for it.
+    pub async fn get_block_time(&self, slot: u64) -> Result<Option<u64>> {
+        let result = self.call("getBlockTime", serde_json::json!([slot])).await?;
+        Ok(result.as_u64())
+    }
+
+    /// Current slot at `confirmed` commitment.
+    pub async fn get_slot(&self) -> Result<u64> {
+        let result = self
+            .call(
+                "getSlot",
+                serde_json::json!([{ "commitment": "confirmed" }]),
+            )
+            .await?;
+        result
+            .as_u64()
+            .ok_or_else(|| BeastError::RpcError(format!("Unexpected getSlot result: {}", result)))
+    }
+
+    /// Total supply of an SPL token mint.
+    pub async fn get_token_supply(&self, mint: &str) -> Result<TokenSupply> {
+        validate_address(mint)?;
+
+        let result = self
+            .call(
+                "getTokenSupply",
+                serde_json::json!([mint, { "commitment": self.commitment.as_str() }]),
+            )
+            .await?;
+        let value = &result["value"];
+        let amount = value["amount"]
+            .as_str()
+            .and_then(|a| a.parse::<u64>().ok())
+            .ok_or_else(|| BeastError::RpcError(format!("No supply returned for {}", mint)))?;
+        let decimals = value["decimals"].as_u64().unwrap_or(0) as u8;
+
+        Ok(TokenSupply {
+            mint: mint.to_string(),
+            amount,
+            decimals,
+            ui_amount: amount_to_ui(amount, decimals),
+        })
+    }
+
+    /// Distinct owners holding a non-zero balance of `mint`, across SPL Token and
+    /// Token-2022. Scans every token account of the mint (`getProgramAccounts`), so
+    /// this is slow and often disabled on public RPCs.
+    pub async fn count_token_holders(&self, mint: &str) -> Result<usize> {
+        validate_address(mint)?;
+
+        let mut owners = std::collections::HashSet::new();
+        for program_id in TOKEN_PROGRAMS {
+            let accounts: Vec<TokenAccountEntry> = self
+                .call_typed(
+                    "getProgramAccounts",
+                    serde_json::json!([program_id, {
+                        "encoding": "jsonParsed",
+                        "filters": [{ "memcmp": { "offset": 0, "bytes": mint } }],
+                        "commitment": self.commitment.as_str()
+                    }]),
+                )
+                .await?;
+            for entry in accounts {
+                let info = &entry.account.data["parsed"]["info"];
+                let held = info["tokenAmount"]["amount"]
+                    .as_str()
+                    .is_some_and(|a| a != "0");
+                if let (true, Some(owner)) = (held, info["owner"].as_str()) {
+                    owners.insert(owner.to_string());
+                }
+            }
+        }
+
+        Ok(owners.len())
+    }
+
+    /// Get transaction signatures for a wallet
+    pub async fn get_signatures(
+        &self,
+        address: &str,
+        limit: u64,
+    ) -> Result<Vec<TransactionSignature>> {
+        self.get_signatures_page(address, limit, None, None).await
+    }
+
+    /// Get a page of transaction signatures (newest first), starting strictly before
+    /// the `before` signature and stopping at the `until` signature.
+    pub async fn get_signatures_page(
+        &self,
+        address: &str,
+        limit: u64,
+        before: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<TransactionSignature>> {
+        validate_address(address)?;
+
+        let mut config = serde_json::json!({
+            "limit": limit.min(1000),
+            "commitment": self.commitment.at_least_confirmed().as_str()
+        });
+        if let Some(before) = before {
+            config["before"] = serde_json::json!(before);
+        }
+        if let Some(until) = until {
+            config["until"] = serde_json::json!(until);
+        }
+
+        let sigs: Vec<SignatureData> = self
+            .call_typed(
+                "getSignaturesForAddress",
+                serde_json::json!([address, config]),
+            )
+            .await?;
+        Ok(sigs
+            .into_iter()
+            .map(|sig_data| TransactionSignature {
+                signature: sig_data.signature,
+                slot: sig_data.slot,
+                block_time: sig_data.block_time.unwrap_or(0),
+                memo: sig_data.memo,
+            })
+            .collect())
     }
 
     /// Get full transaction details with enhanced data
@@ -230,7 +682,7 @@ impl SolanaRpcClient {
                 {
                     "encoding": "jsonParsed",
                     "maxSupportedTransactionVersion": 0,
-                    "commitment": "confirmed"
+                    "commitment": self.commitment.at_least_confirmed().as_str()
                 }
             ]
         });
@@ -238,19 +690,12 @@ impl SolanaRpcClient {
         // The public Solana RPC can occasionally return `result: null` for very recent
         // transactions and frequently rate-limits (`429`). We retry with backoff.
         for attempt in 0..self.max_retries {
-            self.rate_limiter.acquire().await;
-
-            let resp = match self
-                .http_client
-                .post(&self.endpoint)
-                .json(&body)
-                .send()
-                .await
-            {
+            let resp = match self.send(&body).await? {
                 Ok(r) => r,
                 Err(e) => {
                     if attempt + 1 < self.max_retries {
-                        sleep(Duration::from_millis(200 * (attempt as u64 + 1))).await;
+                        self.wait_before_retry("getTransaction", attempt, None)
+                            .await;
                         continue;
                     }
                     return Err(BeastError::RpcError(format!(
@@ -261,6 +706,7 @@ impl SolanaRpcClient {
             };
 
             let status = resp.status();
+            let retry_after = parse_retry_after(resp.headers());
             let text = resp
                 .text()
                 .await
@@ -268,8 +714,8 @@ impl SolanaRpcClient {
 
             if !status.is_success() {
                 if status.as_u16() == 429 && attempt + 1 < self.max_retries {
-                    let backoff = (250_u64 << attempt.min(4)).min(3_000);
-                    sleep(Duration::from_millis(backoff)).await;
+                    self.wait_before_retry("getTransaction", attempt, retry_after)
+                        .await;
                     continue;
                 }
                 return Err(BeastError::RpcError(format!(
@@ -286,9 +732,9 @@ impl SolanaRpcClient {
 
             if let Some(err) = rpc_response.error {
                 // Surface the real JSON-RPC error instead of masking it as "not found".
-                if err.code == 429 && attempt + 1 < self.max_retries {
-                    let backoff = (250_u64 << attempt.min(4)).min(3_000);
-                    sleep(Duration::from_millis(backoff)).await;
+                if RATE_LIMIT_RPC_CODES.contains(&err.code) && attempt + 1 < self.max_retries {
+                    self.wait_before_retry("getTransaction", attempt, retry_after)
+                        .await;
                     continue;
                 }
                 return Err(BeastError::RpcError(format!(
@@ -318,7 +764,8 @@ impl SolanaRpcClient {
             }
 
             if attempt + 1 < self.max_retries {
-                sleep(Duration::from_millis(200 * (attempt as u64 + 1))).await;
+                self.wait_before_retry("getTransaction", attempt, None)
+                    .await;
                 continue;
             }
 
@@ -336,14 +783,7 @@ impl SolanaRpcClient {
             "method": "getHealth"
         });
 
-        self.rate_limiter.acquire().await;
-        match self
-            .http_client
-            .post(&self.endpoint)
-            .json(&body)
-            .send()
-            .await
-        {
+        match self.send(&body).await? {
             Ok(response) => match response.json::<serde_json::Value>().await {
                 Ok(value) => {
                     let result = value.get("result").and_then(|r| r.as_str());
@@ -357,38 +797,13 @@ impl SolanaRpcClient {
 
     /// Get current cluster info
     pub async fn get_cluster_info(&self) -> Result<ClusterInfo> {
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getClusterNodes"
-        });
-
-        self.rate_limiter.acquire().await;
-        match self
-            .http_client
-            .post(&self.endpoint)
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(response) => match response.json::<RpcResponse<Vec<NodeInfo>>>().await {
-                Ok(rpc_response) => {
-                    let nodes = rpc_response.result.unwrap_or_default();
-                    Ok(ClusterInfo {
-                        total_nodes: nodes.len() as u64,
-                        endpoint: self.endpoint.clone(),
-                    })
-                }
-                Err(e) => Err(BeastError::RpcError(format!(
-                    "Failed to parse cluster info: {}",
-                    e
-                ))),
-            },
-            Err(e) => Err(BeastError::RpcError(format!(
-                "Failed to get cluster info: {}",
-                e
-            ))),
-        }
+        let nodes: Vec<NodeInfo> = self
+            .call_typed("getClusterNodes", serde_json::Value::Null)
+            .await?;
+        Ok(ClusterInfo {
+            total_nodes: nodes.len() as u64,
+            endpoint: self.endpoints[0].url.clone(),
+        })
     }
 }
 
@@ -401,7 +816,36 @@ pub struct AccountInfo {
     pub rent_epoch: u64,
 }
 
-#[derive(Debug, Clone)]
+/// A token balance held by a wallet.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenHolding {
+    pub mint: String,
+    pub token_account: String,
+    /// Raw amount in base units.
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: f64,
+}
+
+/// Total supply of a token mint.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenSupply {
+    pub mint: String,
+    /// Raw supply in base units.
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: f64,
+}
+
+/// Mint, owner and decimals of an SPL token account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccountMeta {
+    pub mint: String,
+    pub owner: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct TransactionSignature {
     pub signature: String,
     pub slot: u64,
@@ -443,7 +887,34 @@ struct RpcError {
 
 #[derive(Debug, Deserialize, Default, Clone)]
 struct AccountData {
-    value: AccountValue,
+    value: Option<AccountValue>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+struct MultipleAccountsData {
+    value: Vec<Option<AccountValue>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TokenAccountsData {
+    value: Vec<TokenAccountEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenAccountEntry {
+    pubkey: String,
+    account: TokenAccountValue,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TokenAccountInfoData {
+    value: Option<TokenAccountValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenAccountValue {
+    #[serde(default)]
+    data: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -451,9 +922,15 @@ struct AccountValue {
     lamports: u64,
     owner: String,
     executable: bool,
+    #[serde(default, rename = "rentEpoch")]
     rent_epoch: u64,
 }
 
+/// Split addresses into `getMultipleAccounts`-sized batches.
+fn address_chunks<'a, 'b>(addresses: &'a [&'b str]) -> std::slice::Chunks<'a, &'b str> {
+    addresses.chunks(MAX_MULTIPLE_ACCOUNTS)
+}
+
 #[derive(Debug, Deserialize)]
 struct SignatureData {
     signature: String,
@@ -477,3 +954,480 @@ struct TransactionData {
 
 #[derive(Debug, Deserialize)]
 struct NodeInfo {}
+
+/// Exponential backoff with full jitter: uniform in `[0, min(cap, base * 2^attempt)]`.
+fn backoff_delay(attempt: usize) -> Duration {
+    let ceiling = (RETRY_BASE_MS << attempt.min(10)).min(RETRY_MAX_BACKOFF_MS);
+    // A freshly keyed `RandomState` is a cheap source of randomness without pulling in `rand`.
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % (ceiling + 1))
+}
+
+/// `Retry-After` in delta-seconds form, capped at [`RETRY_AFTER_MAX`]. HTTP-date
+/// values are ignored (fall back to backoff).
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let secs = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(RETRY_AFTER_MAX))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal HTTP endpoint that replays canned `(status, body)` responses in order
+    /// (repeating the last one) and records each request body.
+    pub(crate) async fn mock_endpoint(
+        responses: Vec<(u16, String)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+
+        tokio::spawn(async move {
+            let mut served = 0usize;
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body = loop {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break String::new();
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    let Some(split) = text.find("\r\n\r\n") else {
+                        continue;
+                    };
+                    let content_length = text[..split]
+                        .lines()
+                        .find_map(|l| {
+                            let (k, v) = l.split_once(':')?;
+                            k.eq_ignore_ascii_case("content-length")
+                                .then(|| v.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if buf.len() >= split + 4 + content_length {
+                        break text[split + 4..].to_string();
+                    }
+                };
+                seen.lock().await.push(body);
+
+                let (status, payload) = responses[served.min(responses.len() - 1)].clone();
+                served += 1;
+                let resp = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    payload.len(),
+                    payload
+                );
+                let _ = socket.write_all(resp.as_bytes()).await;
+            }
+        });
+
+        (format!("http://{}", addr), requests)
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_on_failures_and_closes_after_probe() {
+        let error_body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"boom"}}"#;
+        let ok_body = r#"{"jsonrpc":"2.0","id":1,"result":"ok"}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (500, error_body.to_string()),
+            (500, error_body.to_string()),
+            (200, ok_body.to_string()),
+        ])
+        .await;
+
+        let breaker = Arc::new(RpcCircuitBreaker::new(2, Duration::from_millis(100)));
+        let client = SolanaRpcClient::with_circuit_breaker(endpoint, Arc::clone(&breaker));
+
+        assert!(!client.health_check().await.unwrap());
+        assert!(!client.health_check().await.unwrap());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Fails fast without reaching the endpoint.
+        let err = client.health_check().await.unwrap_err();
+        assert!(err.to_string().contains("circuit open"));
+        assert_eq!(requests.lock().await.len(), 2);
+
+        // After the reset timeout a half-open probe succeeds and closes the circuit.
+        sleep(Duration::from_millis(150)).await;
+        assert!(client.health_check().await.unwrap());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(requests.lock().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pool_fails_over_from_rate_limited_endpoint() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":429,"message":"Too many requests"}}"#;
+        let ok_body = r#"{"jsonrpc":"2.0","id":1,"result":[{"signature":"sig1","slot":7,"blockTime":1700000000}]}"#;
+        let (limited, limited_requests) =
+            mock_endpoint(vec![(429, rate_limited.to_string())]).await;
+        let (healthy, healthy_requests) = mock_endpoint(vec![(200, ok_body.to_string())]).await;
+
+        let client = SolanaRpcClient::with_endpoints(vec![
+            (
+                limited.clone(),
+                Arc::new(RpcCircuitBreaker::new(2, Duration::from_secs(30))),
+            ),
+            (
+                healthy,
+                Arc::new(RpcCircuitBreaker::new(2, Duration::from_secs(30))),
+            ),
+        ]);
+
+        for _ in 0..4 {
+            let signatures = client
+                .get_signatures("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU", 10)
+                .await
+                .unwrap();
+            assert_eq!(signatures[0].signature, "sig1");
+        }
+
+        // Failover happens before any retry is consumed.
+        assert_eq!(client.retry_count(), 0);
+        assert_eq!(healthy_requests.lock().await.len(), 4);
+        // Round-robin hits the limited endpoint on calls 1 and 3. Throttling is
+        // not an outage: its circuit stays closed and it keeps its turn.
+        assert_eq!(limited_requests.lock().await.len(), 2);
+        let health = client.endpoint_health();
+        assert_eq!(health[0].url, limited);
+        assert_eq!(health[0].state, CircuitState::Closed);
+        assert_eq!(health[0].trips, 0);
+        assert_eq!(health[1].state, CircuitState::Closed);
+
+        client
+            .get_signatures("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU", 10)
+            .await
+            .unwrap();
+        assert_eq!(limited_requests.lock().await.len(), 3);
+    }
+
+    #[test]
+    fn test_address_chunks_split_at_100() {
+        let owned: Vec<String> = (0..250u32)
+            .map(|i| {
+                let mut key = [1u8; 32];
+                key[..4].copy_from_slice(&i.to_be_bytes());
+                bs58::encode(key).into_string()
+            })
+            .collect();
+        let addresses: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
+
+        let chunks: Vec<&[&str]> = address_chunks(&addresses).collect();
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![100, 100, 50]
+        );
+        assert_eq!(chunks.concat(), addresses);
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_accounts_preserves_order() {
+        // Every batch reports the first account as missing and the rest as funded.
+        let value: Vec<serde_json::Value> = (0..100)
+            .map(|i| {
+                if i == 0 {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::json!({
+                        "lamports": 1_000u64 + i,
+                        "owner": "11111111111111111111111111111111",
+                        "executable": false,
+                        "rentEpoch": 0
+                    })
+                }
+            })
+            .collect();
+        let full = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": value } });
+        let tail =
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": &value[..50] } });
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, full.to_string()),
+            (200, full.to_string()),
+            (200, tail.to_string()),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint);
+
+        let owned: Vec<String> = (0..250u32)
+            .map(|i| {
+                let mut key = [1u8; 32];
+                key[..4].copy_from_slice(&i.to_be_bytes());
+                bs58::encode(key).into_string()
+            })
+            .collect();
+        let addresses: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
+        let accounts = client.get_multiple_accounts(&addresses).await.unwrap();
+
+        assert_eq!(requests.lock().await.len(), 3);
+        assert_eq!(accounts.len(), 250);
+        assert!(accounts[100].is_none());
+        let second = accounts[101].as_ref().unwrap();
+        assert_eq!(second.address, addresses[101]);
+        assert_eq!(second.balance, 1_001);
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_accounts_retries_a_rate_limited_batch() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"Node is behind"}}"#;
+        let ok = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":[null,{"lamports":7,"owner":"11111111111111111111111111111111","executable":false,"rentEpoch":0}]}}"#;
+        let (endpoint, requests) =
+            mock_endpoint(vec![(429, rate_limited.to_string()), (200, ok.to_string())]).await;
+        let client = SolanaRpcClient::new(endpoint);
+
+        let accounts = client
+            .get_multiple_accounts(&[
+                "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU",
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(requests.lock().await.len(), 2);
+        assert_eq!(client.retry_count(), 1);
+        assert!(accounts[0].is_none());
+        assert_eq!(accounts[1].as_ref().unwrap().balance, 7);
+    }
+
+    #[tokio::test]
+    async fn test_get_token_accounts_by_owner_parses_both_programs() {
+        let spl = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [{
+            "pubkey": "UsdcAta111111111111111111111111111111111111",
+            "account": { "data": { "parsed": { "info": {
+                "mint": "EPjFWJd5AufSYXvKdSpVN9rEdLZ4ScjjL4x2KxUcYQ7f",
+                "tokenAmount": { "amount": "2500000", "decimals": 6, "uiAmount": 2.5 }
+            } } } }
+        }] } });
+        let token_2022 =
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [] } });
+        let (endpoint, requests) =
+            mock_endpoint(vec![(200, spl.to_string()), (200, token_2022.to_string())]).await;
+        let client = SolanaRpcClient::new(endpoint);
+
+        let holdings = client
+            .get_token_accounts_by_owner("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU")
+            .await
+            .unwrap();
+
+        assert_eq!(requests.lock().await.len(), 2);
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].amount, 2_500_000);
+        assert_eq!(holdings[0].decimals, 6);
+        assert!((holdings[0].ui_amount - 2.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_get_token_accounts_by_owner_retries_each_program() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":429,"message":"Too many requests"}}"#;
+        let empty = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":[]}}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, empty.to_string()),
+            (429, rate_limited.to_string()),
+            (200, empty.to_string()),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint);
+
+        let holdings = client
+            .get_token_accounts_by_owner("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU")
+            .await
+            .unwrap();
+
+        assert!(holdings.is_empty());
+        assert_eq!(client.retry_count(), 1);
+        let requests = requests.lock().await;
+        assert_eq!(requests.len(), 3);
+        let program = |i: usize| {
+            let sent: serde_json::Value = serde_json::from_str(&requests[i]).unwrap();
+            sent["params"][1]["programId"].as_str().unwrap().to_string()
+        };
+        assert_eq!(program(0), TOKEN_PROGRAMS[0]);
+        assert_eq!(program(1), TOKEN_PROGRAMS[1]);
+        assert_eq!(program(2), TOKEN_PROGRAMS[1]);
+    }
+
+    #[tokio::test]
+    async fn test_reads_send_the_client_commitment() {
+        let supply =
+            r#"{"jsonrpc":"2.0","id":1,"result":{"value":{"amount":"1000","decimals":3}}}"#;
+        let signatures = r#"{"jsonrpc":"2.0","id":1,"result":[]}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, supply.to_string()),
+            (200, supply.to_string()),
+            (200, signatures.to_string()),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint);
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        assert_eq!(client.commitment(), Commitment::Confirmed);
+        client.get_token_supply(mint).await.unwrap();
+        let finalized = client.with_commitment(Commitment::Finalized);
+        finalized.get_token_supply(mint).await.unwrap();
+        // getSignaturesForAddress has no `processed` view; it is raised to `confirmed`.
+        client
+            .with_commitment(Commitment::Processed)
+            .get_signatures(mint, 5)
+            .await
+            .unwrap();
+
+        let sent: Vec<serde_json::Value> = requests
+            .lock()
+            .await
+            .iter()
+            .map(|body| serde_json::from_str(body).unwrap())
+            .collect();
+        assert_eq!(sent[0]["params"][1]["commitment"], "confirmed");
+        assert_eq!(sent[1]["params"][1]["commitment"], "finalized");
+        assert_eq!(sent[2]["method"], "getSignaturesForAddress");
+        assert_eq!(sent[2]["params"][1]["commitment"], "confirmed");
+        assert_eq!(client.commitment(), Commitment::Confirmed);
+
+        assert_eq!(Commitment::parse(" Processed "), Ok(Commitment::Processed));
+        assert!(Commitment::parse("max").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_retry_until_success() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":429,"message":"Too many requests"}}"#;
+        let ok_body = r#"{"jsonrpc":"2.0","id":1,"result":[{"signature":"sig1","slot":7,"blockTime":1700000000}]}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (429, rate_limited.to_string()),
+            (429, rate_limited.to_string()),
+            (200, ok_body.to_string()),
+        ])
+        .await;
+        let breaker = Arc::new(RpcCircuitBreaker::new(10, Duration::from_secs(30)));
+        let client = SolanaRpcClient::with_circuit_breaker(endpoint, breaker);
+
+        let signatures = client
+            .get_signatures("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU", 10)
+            .await
+            .unwrap();
+
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].signature, "sig1");
+        assert_eq!(requests.lock().await.len(), 3);
+        assert_eq!(client.retry_count(), 2);
+    }
+
+    #[test]
+    fn test_retry_after_and_backoff_bounds() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, "2".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(2)));
+        headers.insert(reqwest::header::RETRY_AFTER, "3600".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(RETRY_AFTER_MAX));
+
+        for attempt in 0..20 {
+            let ceiling = (RETRY_BASE_MS << attempt.min(10)).min(RETRY_MAX_BACKOFF_MS);
+            assert!(backoff_delay(attempt) <= Duration::from_millis(ceiling));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_page_threads_cursors() {
+        let ok_body = r#"{"jsonrpc":"2.0","id":1,"result":[]}"#;
+        let (endpoint, requests) = mock_endpoint(vec![(200, ok_body.to_string())]).await;
+        let client = SolanaRpcClient::new(endpoint);
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+        client.get_signatures(wallet, 5).await.unwrap();
+        client
+            .get_signatures_page(wallet, 5, Some("sigB"), Some("sigU"))
+            .await
+            .unwrap();
+
+        let requests = requests.lock().await;
+        let plain: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        assert!(plain["params"][1].get("before").is_none());
+        let paged: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        assert_eq!(paged["params"][1]["before"], "sigB");
+        assert_eq!(paged["params"][1]["until"], "sigU");
+        assert_eq!(paged["params"][1]["limit"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_generic_call_returns_result_and_surfaces_errors() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"Node is behind"}}"#;
+        let supply = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":{"amount":"1000","decimals":6}}}"#;
+        let invalid =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid param"}}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, rate_limited.to_string()),
+            (200, supply.to_string()),
+            (200, invalid.to_string()),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint);
+
+        let result = client
+            .call(
+                "getTokenSupply",
+                serde_json::json!(["EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["value"]["decimals"], 6);
+        assert_eq!(client.retry_count(), 1);
+
+        let sent: serde_json::Value = serde_json::from_str(&requests.lock().await[1]).unwrap();
+        assert_eq!(sent["method"], "getTokenSupply");
+        assert_eq!(
+            sent["params"][0],
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+        );
+
+        let err = client
+            .call("getSlot", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("-32602"), "{}", err);
+        let sent: serde_json::Value = serde_json::from_str(&requests.lock().await[2]).unwrap();
+        assert!(sent.get("params").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_account_info_retries_rate_limits_and_reports_missing_accounts() {
+        let rate_limited =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":429,"message":"Too many requests"}}"#;
+        let funded = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":{"lamports":5000,"owner":"11111111111111111111111111111111","executable":false,"rentEpoch":3}}}"#;
+        let missing = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":null}}"#;
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, rate_limited.to_string()),
+            (200, funded.to_string()),
+            (200, missing.to_string()),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint);
+        let address = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+        let account = client.get_account_info(address).await.unwrap();
+        assert_eq!(account.balance, 5_000);
+        assert_eq!(account.rent_epoch, 3);
+        assert_eq!(client.retry_count(), 1);
+
+        let err = client.get_account_info(address).await.unwrap_err();
+        assert!(err.to_string().contains("Account not found"), "{}", err);
+        assert_eq!(requests.lock().await.len(), 3);
+    }
+}
@@ -0,0 +1,140 @@
+/// Decimal places of native SOL.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Lamports in one SOL.
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Raw token `amount` in UI units for a mint with `decimals` decimal places.
+///
+/// The whole and fractional parts are converted separately, so the result is
+/// the closest `f64` to the whole part plus the fraction instead of the rounding
+/// of a single `amount as f64` on amounts above 2^53.
+pub fn amount_to_ui(amount: u64, decimals: u8) -> f64 {
+    scale_down(amount as u128, decimals)
+}
+
+/// Signed raw `delta` (a balance change) in UI units; see `amount_to_ui`.
+pub fn delta_to_ui(delta: i128, decimals: u8) -> f64 {
+    let ui = scale_down(delta.unsigned_abs(), decimals);
+    if delta < 0 {
+        -ui
+    } else {
+        ui
+    }
+}
+
+/// Lamports in SOL.
+pub fn lamports_to_sol(lamports: u64) -> f64 {
+    amount_to_ui(lamports, SOL_DECIMALS)
+}
+
+/// Signed lamport change in SOL.
+pub fn lamports_delta_to_sol(delta: i128) -> f64 {
+    delta_to_ui(delta, SOL_DECIMALS)
+}
+
+/// SOL in lamports, rounded to the nearest lamport. Saturates: negative and NaN
+/// amounts give 0, amounts beyond `u64::MAX` lamports give `u64::MAX`.
+pub fn sol_to_lamports(sol: f64) -> u64 {
+    // `as` saturates float-to-int casts and maps NaN to 0.
+    (sol * LAMPORTS_PER_SOL as f64).round() as u64
+}
+
+fn scale_down(amount: u128, decimals: u8) -> f64 {
+    match 10u128.checked_pow(decimals as u32) {
+        Some(scale) => (amount / scale) as f64 + (amount % scale) as f64 / scale as f64,
+        None => amount as f64 / 10f64.powi(decimals as i32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random values (an LCG) for the property checks.
+    fn samples(seed: u64, count: usize) -> impl Iterator<Item = u64> {
+        (0..count).scan(seed, |state, _| {
+            *state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            Some(*state)
+        })
+    }
+
+    #[test]
+    fn test_known_conversions() {
+        assert_eq!(lamports_to_sol(0), 0.0);
+        assert_eq!(lamports_to_sol(1), 1e-9);
+        assert_eq!(lamports_to_sol(LAMPORTS_PER_SOL), 1.0);
+        assert_eq!(lamports_to_sol(2_039_280), 0.00203928);
+        assert_eq!(lamports_delta_to_sol(-1_500_000_000), -1.5);
+        assert_eq!(amount_to_ui(1_234_567, 6), 1.234567);
+        assert_eq!(amount_to_ui(42, 0), 42.0);
+        assert_eq!(delta_to_ui(-5, 1), -0.5);
+
+        assert_eq!(sol_to_lamports(1.5), 1_500_000_000);
+        assert_eq!(sol_to_lamports(0.1), 100_000_000);
+        assert_eq!(sol_to_lamports(-1.0), 0);
+        assert_eq!(sol_to_lamports(f64::NAN), 0);
+        assert_eq!(sol_to_lamports(f64::INFINITY), u64::MAX);
+        assert_eq!(sol_to_lamports(1e30), u64::MAX);
+    }
+
+    #[test]
+    fn test_whole_part_survives_amounts_beyond_f64_precision() {
+        // `u64::MAX as f64 / 1e9` rounds before dividing; the split keeps the
+        // whole part exact.
+        let amount = u64::MAX;
+        let ui = amount_to_ui(amount, 9);
+        assert_eq!(ui.trunc(), (amount / 1_000_000_000) as f64);
+        assert_eq!(amount_to_ui(u64::MAX, 30), u64::MAX as f64 / 1e30);
+        assert!(amount_to_ui(1, 255).is_finite());
+    }
+
+    #[test]
+    fn test_lamports_round_trip_exactly_below_2_pow_50() {
+        // Up to 2^50 lamports (about 1.1 million SOL) the SOL value is within
+        // half a lamport, so it rounds back to the same count.
+        let max = 1u64 << 50;
+        for lamports in samples(7, 10_000).map(|v| v % max).chain([
+            0,
+            1,
+            999_999_999,
+            LAMPORTS_PER_SOL,
+            max - 1,
+        ]) {
+            let sol = lamports_to_sol(lamports);
+            assert_eq!(sol_to_lamports(sol), lamports, "{} lamports", lamports);
+            assert_eq!(lamports_delta_to_sol(-(lamports as i128)), -sol);
+        }
+    }
+
+    #[test]
+    fn test_lamports_round_trip_within_relative_precision_above_2_pow_50() {
+        for lamports in samples(11, 10_000).map(|v| v | (1 << 50)) {
+            let back = sol_to_lamports(lamports_to_sol(lamports));
+            let error = back.abs_diff(lamports) as f64 / lamports as f64;
+            assert!(error <= f64::EPSILON, "{} -> {}", lamports, back);
+        }
+    }
+
+    #[test]
+    fn test_token_amounts_are_monotonic_and_scale_by_decimals() {
+        for (i, amount) in samples(13, 5_000).enumerate() {
+            let decimals = (i % 19) as u8;
+            let amount = amount >> (i % 64);
+            let ui = amount_to_ui(amount, decimals);
+            assert!(ui >= 0.0 && ui.is_finite());
+            assert!(amount_to_ui(amount.saturating_add(1), decimals) >= ui);
+            // One more decimal place is a tenth of the value.
+            let expected = ui / 10.0;
+            let tenth = amount_to_ui(amount, decimals + 1);
+            assert!(
+                (tenth - expected).abs() <= expected * 4.0 * f64::EPSILON,
+                "{} at {} decimals",
+                amount,
+                decimals
+            );
+        }
+    }
+}
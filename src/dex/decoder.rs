@@ -0,0 +1,373 @@
+/// DEX Decoder - Extracts net swaps from parsed transactions
+///
+/// Swaps are derived from the fee payer's balance deltas (native SOL plus
+/// pre/post token balances), which works uniformly across AMMs and aggregators
+/// without decoding each program's instruction layout.
+use crate::core::enhanced_parser::EnhancedTransaction;
+use crate::core::units::{delta_to_ui, lamports_delta_to_sol};
+use crate::dex::nft::NftMarketplaces;
+use crate::dex::types::{DexPrograms, SwapEvent, WSOL_MINT};
+use crate::dex::{meteora, phoenix, pumpfun};
+use std::collections::HashMap;
+
+/// Programs that never act as a swap venue on their own.
+const CORE_PROGRAMS: &[&str] = &[
+    "11111111111111111111111111111111",
+    "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+    "TokenzQdBNbJPPzh6txJjTpWp8QJKhdfuqJW65PfQG",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    "ComputeBudget111111111111111111111111111111",
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+];
+
+/// SOL movements smaller than this (ATA rent, tips) are not treated as a swap leg.
+const MIN_SOL_LEG_LAMPORTS: i128 = 10_000_000;
+
+/// Dex name used when a swap is inferred from transfers through an unknown program.
+pub const INFERRED_DEX_NAME: &str = "INFERRED";
+
+pub struct DexDecoder;
+
+impl DexDecoder {
+    pub fn new() -> Self {
+        DexDecoder
+    }
+
+    /// Extract the net swap executed by the fee payer, if any.
+    pub fn extract_swaps(
+        &self,
+        raw_data: &serde_json::Value,
+        tx: &EnhancedTransaction,
+    ) -> Vec<SwapEvent> {
+        if !tx.success {
+            return Vec::new();
+        }
+        let Some(wallet) = tx.signers.first() else {
+            return Vec::new();
+        };
+        // NFT sales are reported as `NftTrade`s, not as token swaps.
+        if NftMarketplaces::ALL
+            .iter()
+            .any(|p| tx.programs_called.iter().any(|c| c == *p))
+        {
+            return Vec::new();
+        }
+
+        let known = DexPrograms::ALL
+            .iter()
+            .find(|p| tx.programs_called.iter().any(|c| c == *p));
+
+        let swap = match known.copied() {
+            Some(DexPrograms::METEORA_DLMM) => meteora::decode(raw_data, tx, wallet),
+            Some(DexPrograms::PHOENIX) => phoenix::decode(raw_data, tx, wallet),
+            Some(DexPrograms::PUMP_FUN) => pumpfun::decode(raw_data, tx, wallet),
+            Some(program_id) => balance_delta_swap(raw_data, tx, wallet, program_id),
+            None => self.infer_from_transfers(tx, wallet),
+        };
+
+        swap.into_iter().collect()
+    }
+
+    /// Fallback for unknown programs: pair what the wallet sent with what it received.
+    fn infer_from_transfers(&self, tx: &EnhancedTransaction, wallet: &str) -> Option<SwapEvent> {
+        let mut venues: Vec<&String> = tx
+            .programs_called
+            .iter()
+            .filter(|p| !CORE_PROGRAMS.contains(&p.as_str()))
+            .collect();
+        if venues.is_empty() {
+            return None;
+        }
+        venues.sort();
+
+        let mut by_asset: HashMap<String, f64> = HashMap::new();
+        for t in &tx.token_transfers {
+            if t.mint == "unknown" {
+                continue;
+            }
+            if t.from_owner.as_deref() == Some(wallet) {
+                *by_asset.entry(t.mint.clone()).or_insert(0.0) -= t.amount_ui;
+            }
+            if t.to_owner.as_deref() == Some(wallet) {
+                *by_asset.entry(t.mint.clone()).or_insert(0.0) += t.amount_ui;
+            }
+        }
+
+        let mut sol_lamports: i128 = 0;
+        for t in &tx.sol_transfers {
+            // Balance-inferred transfers restate instruction-level ones.
+            if matches!(
+                t.transfer_type.as_str(),
+                "balance_change" | "balance_fan_out"
+            ) {
+                continue;
+            }
+            if t.from == wallet {
+                sol_lamports -= t.amount_lamports as i128;
+            }
+            if t.to == wallet {
+                sol_lamports += t.amount_lamports as i128;
+            }
+        }
+        if sol_lamports.abs() >= MIN_SOL_LEG_LAMPORTS {
+            *by_asset.entry(WSOL_MINT.to_string()).or_insert(0.0) +=
+                lamports_delta_to_sol(sol_lamports);
+        }
+
+        let legs = by_asset.into_iter().filter(|(_, d)| *d != 0.0).collect();
+        build_swap(
+            tx,
+            wallet,
+            INFERRED_DEX_NAME,
+            venues.first().map(|p| p.to_string()),
+            legs,
+        )
+    }
+}
+
+impl Default for DexDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode a swap routed through a known DEX program from the wallet's balance deltas.
+///
+/// Deltas are net over the whole transaction, so multi-hop routes and order-book
+/// fills against several makers collapse into a single event.
+pub(crate) fn balance_delta_swap(
+    raw_data: &serde_json::Value,
+    tx: &EnhancedTransaction,
+    wallet: &str,
+    program_id: &str,
+) -> Option<SwapEvent> {
+    let meta = raw_data.get("meta")?;
+    let mut token_deltas = wallet_token_deltas(meta, wallet);
+
+    // Native SOL delta, excluding the network fee paid by the fee payer.
+    let mut sol_delta: i128 = tx
+        .balance_changes
+        .iter()
+        .filter(|bc| bc.account == wallet)
+        .map(|bc| bc.change_lamports as i128)
+        .sum();
+    sol_delta += tx.fee as i128;
+    if let Some((wsol_delta, _)) = token_deltas.remove(WSOL_MINT) {
+        sol_delta += wsol_delta;
+    }
+
+    let mut legs: Vec<(String, f64)> = token_deltas
+        .into_iter()
+        .filter(|(_, (delta, _))| *delta != 0)
+        .map(|(mint, (delta, decimals))| (mint, delta_to_ui(delta, decimals)))
+        .collect();
+    if sol_delta.abs() >= MIN_SOL_LEG_LAMPORTS {
+        legs.push((WSOL_MINT.to_string(), lamports_delta_to_sol(sol_delta)));
+    }
+
+    let dex_name = DexPrograms::name(program_id).unwrap_or("Unknown DEX");
+    build_swap(tx, wallet, dex_name, Some(program_id.to_string()), legs)
+}
+
+/// Account list of the first instruction (outer, then inner) invoking `program_id`.
+///
+/// Handles both `jsonParsed` (pubkey strings) and `json` (account indexes) encodings.
+pub(crate) fn instruction_accounts(
+    raw_data: &serde_json::Value,
+    tx: &EnhancedTransaction,
+    program_id: &str,
+) -> Option<Vec<String>> {
+    let outer = raw_data
+        .pointer("/transaction/message/instructions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten();
+    let inner = raw_data
+        .pointer("/meta/innerInstructions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get("instructions").and_then(|v| v.as_array()))
+        .flatten();
+
+    let ix = outer.chain(inner).find(|ix| {
+        let pid = ix.get("programId").and_then(|p| p.as_str()).or_else(|| {
+            ix.get("programIdIndex")
+                .and_then(|i| i.as_u64())
+                .and_then(|i| tx.accounts.get(i as usize))
+                .map(|s| s.as_str())
+        });
+        pid == Some(program_id)
+    })?;
+
+    ix.get("accounts")?
+        .as_array()?
+        .iter()
+        .map(|a| match a {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => tx.accounts.get(n.as_u64()? as usize).cloned(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Net raw token deltas per mint for token accounts owned by `wallet`.
+fn wallet_token_deltas(meta: &serde_json::Value, wallet: &str) -> HashMap<String, (i128, u8)> {
+    let mut out: HashMap<String, (i128, u8)> = HashMap::new();
+
+    for (key, sign) in [("preTokenBalances", -1_i128), ("postTokenBalances", 1_i128)] {
+        let Some(entries) = meta.get(key).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for entry in entries {
+            if entry.get("owner").and_then(|v| v.as_str()) != Some(wallet) {
+                continue;
+            }
+            let Some(mint) = entry.get("mint").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let ui = entry.get("uiTokenAmount");
+            let amount = ui
+                .and_then(|v| v.get("amount"))
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<i128>().ok())
+                .unwrap_or(0);
+            let decimals = ui
+                .and_then(|v| v.get("decimals"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as u8;
+
+            let e = out.entry(mint.to_string()).or_insert((0, decimals));
+            e.0 += sign * amount;
+        }
+    }
+
+    out
+}
+
+/// Pick the sold and bought legs (preferring token legs over SOL) and build the event.
+fn build_swap(
+    tx: &EnhancedTransaction,
+    wallet: &str,
+    dex_name: &str,
+    program_id: Option<String>,
+    legs: Vec<(String, f64)>,
+) -> Option<SwapEvent> {
+    let pick = |sold: bool| {
+        legs.iter()
+            .filter(|(_, d)| if sold { *d < 0.0 } else { *d > 0.0 })
+            .min_by(|a, b| {
+                (a.0 == WSOL_MINT).cmp(&(b.0 == WSOL_MINT)).then_with(|| {
+                    b.1.abs()
+                        .partial_cmp(&a.1.abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+            })
+            .cloned()
+    };
+
+    let (token_in_mint, amount_in) = pick(true)?;
+    let (token_out_mint, amount_out) = pick(false)?;
+    if token_in_mint == token_out_mint {
+        return None;
+    }
+
+    Some(SwapEvent {
+        signature: tx.signature.clone(),
+        event_index: 0,
+        slot: tx.slot,
+        block_time: tx.block_time,
+        wallet: wallet.to_string(),
+        dex_name: dex_name.to_string(),
+        program_id,
+        pool_address: None,
+        token_in_mint,
+        amount_in: amount_in.abs(),
+        token_out_mint,
+        amount_out,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::core::enhanced_parser::{EnhancedTransaction, EnhancedTransactionParser};
+    use crate::dex::types::{DexPrograms, WSOL_MINT};
+    use serde_json::json;
+
+    pub(crate) const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    pub(crate) const USDC: &str = "EPjFWJd5AufSYXvKdSpVN9rEdLZ4ScjjL4x2KxUcYQ7f";
+
+    /// Parse a jsonParsed transaction where `WALLET` calls `program_id` with
+    /// `ix_accounts`, moving SOL from `sol_pre` to `sol_post` (fee 5000) and its
+    /// USDC balance from `usdc_pre` to `usdc_post` (raw, 6 decimals).
+    pub(crate) fn parse_swap_fixture(
+        program_id: &str,
+        ix_accounts: &[&str],
+        sol_pre: u64,
+        sol_post: u64,
+        usdc_pre: u64,
+        usdc_post: u64,
+    ) -> EnhancedTransaction {
+        let raw = json!({
+            "slot": 250_000_000u64,
+            "blockTime": 1_700_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [sol_pre, 1_000_000u64, 1u64],
+                "postBalances": [sol_post, 1_000_000u64, 1u64],
+                "preTokenBalances": [{
+                    "accountIndex": 1,
+                    "mint": USDC,
+                    "owner": WALLET,
+                    "uiTokenAmount": { "amount": usdc_pre.to_string(), "decimals": 6 }
+                }],
+                "postTokenBalances": [{
+                    "accountIndex": 1,
+                    "mint": USDC,
+                    "owner": WALLET,
+                    "uiTokenAmount": { "amount": usdc_post.to_string(), "decimals": 6 }
+                }]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": WALLET, "signer": true, "writable": true },
+                        { "pubkey": "UsdcAta111111111111111111111111111111111111", "signer": false, "writable": true },
+                        { "pubkey": program_id, "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": program_id,
+                        "accounts": ix_accounts,
+                        "data": ""
+                    }]
+                }
+            }
+        });
+
+        EnhancedTransactionParser::new()
+            .parse(&raw, "sig_swap".to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decodes_raydium_sol_to_usdc_swap() {
+        let tx = parse_swap_fixture(
+            DexPrograms::RAYDIUM_V4,
+            &[],
+            3_000_005_000,
+            2_000_000_000,
+            5_000_000,
+            155_000_000,
+        );
+
+        assert_eq!(tx.swaps.len(), 1);
+        let swap = &tx.swaps[0];
+        assert_eq!(swap.wallet, WALLET);
+        assert_eq!(swap.dex_name, "Raydium V4");
+        assert_eq!(swap.token_in_mint, WSOL_MINT);
+        assert!((swap.amount_in - 1.0).abs() < 1e-9);
+        assert_eq!(swap.token_out_mint, USDC);
+        assert!((swap.amount_out - 150.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,52 @@
+/// Meteora DLMM swaps
+///
+/// A DLMM swap may cross several bins, but the wallet's net balance deltas
+/// already reflect the total, so amounts come from the shared delta decoder and
+/// this module only attributes the pool.
+use crate::core::enhanced_parser::EnhancedTransaction;
+use crate::dex::decoder::{balance_delta_swap, instruction_accounts};
+use crate::dex::types::{DexPrograms, SwapEvent};
+
+/// Position of the `lb_pair` (pool) account in the DLMM `swap` instruction.
+const LB_PAIR_INDEX: usize = 0;
+
+pub fn decode(
+    raw_data: &serde_json::Value,
+    tx: &EnhancedTransaction,
+    wallet: &str,
+) -> Option<SwapEvent> {
+    let mut swap = balance_delta_swap(raw_data, tx, wallet, DexPrograms::METEORA_DLMM)?;
+    swap.pool_address = instruction_accounts(raw_data, tx, DexPrograms::METEORA_DLMM)
+        .and_then(|accounts| accounts.get(LB_PAIR_INDEX).cloned());
+    Some(swap)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dex::decoder::tests::{parse_swap_fixture, USDC, WALLET};
+    use crate::dex::types::{DexPrograms, WSOL_MINT};
+
+    #[test]
+    fn test_decodes_meteora_usdc_to_sol_swap() {
+        let tx = parse_swap_fixture(
+            DexPrograms::METEORA_DLMM,
+            &["LbPair1111111111111111111111111111111111111", WALLET],
+            1_000_005_000,
+            3_000_000_000,
+            400_000_000,
+            100_000_000,
+        );
+
+        assert_eq!(tx.swaps.len(), 1);
+        let swap = &tx.swaps[0];
+        assert_eq!(swap.dex_name, "Meteora DLMM");
+        assert_eq!(
+            swap.pool_address.as_deref(),
+            Some("LbPair1111111111111111111111111111111111111")
+        );
+        assert_eq!(swap.token_in_mint, USDC);
+        assert!((swap.amount_in - 300.0).abs() < 1e-9);
+        assert_eq!(swap.token_out_mint, WSOL_MINT);
+        assert!((swap.amount_out - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,11 @@
+/// DEX swap extraction
+pub mod decoder;
+pub mod meteora;
+pub mod nft;
+pub mod phoenix;
+pub mod pumpfun;
+pub mod types;
+
+pub use decoder::DexDecoder;
+pub use nft::{NftMarketplaces, NftTrade};
+pub use types::{DexPrograms, SwapEvent};
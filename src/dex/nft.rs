@@ -0,0 +1,231 @@
+/// NFT marketplace sales (Magic Eden, Tensor)
+///
+/// A sale moves a single 0-decimal token from the seller to the buyer; the
+/// price is the SOL the seller received. Marketplace fees and royalties are
+/// paid out of the buyer's side, so this is the seller's net proceeds.
+use crate::core::enhanced_parser::EnhancedTransaction;
+use crate::core::units::lamports_delta_to_sol;
+use serde::{Deserialize, Serialize};
+
+/// A decoded NFT sale.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NftTrade {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<u64>,
+    pub marketplace: String,
+    pub program_id: String,
+    pub mint: String,
+    pub seller: String,
+    pub buyer: String,
+    pub price_sol: f64,
+}
+
+/// Known NFT marketplace program IDs.
+pub struct NftMarketplaces;
+
+impl NftMarketplaces {
+    pub const MAGIC_EDEN_V2: &'static str = "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K";
+    pub const TENSOR_SWAP: &'static str = "TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN";
+    pub const TENSOR_MARKETPLACE: &'static str = "TCMPhJdwDryooaGtiocG1u3xcYbRpiJzb283XfCZsDp";
+
+    pub const ALL: &'static [&'static str] = &[
+        Self::MAGIC_EDEN_V2,
+        Self::TENSOR_SWAP,
+        Self::TENSOR_MARKETPLACE,
+    ];
+
+    /// Human-readable marketplace name for a program ID, if known.
+    pub fn name(program_id: &str) -> Option<&'static str> {
+        match program_id {
+            Self::MAGIC_EDEN_V2 => Some("Magic Eden"),
+            Self::TENSOR_SWAP => Some("Tensor Swap"),
+            Self::TENSOR_MARKETPLACE => Some("Tensor"),
+            _ => None,
+        }
+    }
+}
+
+/// Extract NFT sales from a transaction routed through a known marketplace.
+pub fn extract_nft_trades(tx: &EnhancedTransaction) -> Vec<NftTrade> {
+    if !tx.success {
+        return Vec::new();
+    }
+    let Some(program_id) = NftMarketplaces::ALL
+        .iter()
+        .find(|p| tx.programs_called.iter().any(|c| c == *p))
+    else {
+        return Vec::new();
+    };
+    let marketplace = NftMarketplaces::name(program_id).unwrap_or_default();
+
+    let mut trades: Vec<NftTrade> = Vec::new();
+    for t in &tx.token_transfers {
+        if t.amount != 1 || t.decimals != 0 || t.mint == "unknown" {
+            continue;
+        }
+        let (Some(seller), Some(buyer)) = (t.from_owner.as_deref(), t.to_owner.as_deref()) else {
+            continue;
+        };
+        if seller == buyer || trades.iter().any(|trade| trade.mint == t.mint) {
+            continue;
+        }
+
+        let Some(price_sol) = seller_proceeds_sol(tx, seller) else {
+            continue;
+        };
+
+        trades.push(NftTrade {
+            signature: tx.signature.clone(),
+            slot: tx.slot,
+            block_time: tx.block_time,
+            marketplace: marketplace.to_string(),
+            program_id: program_id.to_string(),
+            mint: t.mint.clone(),
+            seller: seller.to_string(),
+            buyer: buyer.to_string(),
+            price_sol,
+        });
+    }
+
+    trades
+}
+
+/// SOL the seller gained, adding back the network fee if the seller paid it
+/// (accepted bids are signed by the seller).
+fn seller_proceeds_sol(tx: &EnhancedTransaction, seller: &str) -> Option<f64> {
+    let mut lamports: i64 = tx
+        .balance_changes
+        .iter()
+        .filter(|bc| bc.account == seller)
+        .map(|bc| bc.change_lamports)
+        .sum();
+    if tx.signers.first().map(|s| s.as_str()) == Some(seller) {
+        lamports += tx.fee as i64;
+    }
+    (lamports > 0).then(|| lamports_delta_to_sol(lamports.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::enhanced_parser::EnhancedTransactionParser;
+    use serde_json::json;
+
+    const BUYER: &str = "BuyerWa11et1111111111111111111111111111111";
+    const SELLER: &str = "Se11erWa11et111111111111111111111111111111";
+    const NFT_MINT: &str = "NftMint111111111111111111111111111111111111";
+
+    /// Magic Eden v2 `executeSale` in the `getTransaction` (jsonParsed) shape,
+    /// hand-built rather than captured (addresses and amounts are placeholders;
+    /// the transfer authority is the marketplace's program-as-signer PDA): the
+    /// buyer pays 12.5 SOL (plus rent for its new token account), the seller nets
+    /// 11.875 SOL after 5% in fees and royalties, and the NFT moves between the
+    /// two associated token accounts. Replace with a captured sale when one is
+    /// at hand.
+    fn magic_eden_sale() -> serde_json::Value {
+        let token_balance = |index: u64, owner: &str, amount: &str| {
+            json!({
+                "accountIndex": index,
+                "mint": NFT_MINT,
+                "owner": owner,
+                "uiTokenAmount": { "amount": amount, "decimals": 0 }
+            })
+        };
+        json!({
+            "slot": 245_000_000u64,
+            "blockTime": 1_706_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 10_000,
+                "preBalances": [20_000_000_000u64, 1_000_000_000u64, 2_039_280u64, 0u64, 5_000_000_000u64, 1u64, 1u64],
+                "postBalances": [7_487_950_720u64, 12_875_000_000u64, 2_039_280u64, 2_039_280u64, 5_625_000_000u64, 1u64, 1u64],
+                "preTokenBalances": [token_balance(2, SELLER, "1")],
+                "postTokenBalances": [token_balance(3, BUYER, "1")],
+                "innerInstructions": [{
+                    "index": 0,
+                    "instructions": [{
+                        "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                        "parsed": { "type": "transfer", "info": {
+                            "source": "Se11erNftAta1111111111111111111111111111111",
+                            "destination": "BuyerNftAta11111111111111111111111111111111",
+                            "authority": "1NoC7LWDVaPx6zWPvHgqaoNYVFDyP5vnyqLDZA8uW5D",
+                            "amount": "1"
+                        } }
+                    }]
+                }]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": BUYER, "signer": true, "writable": true },
+                        { "pubkey": SELLER, "signer": false, "writable": true },
+                        { "pubkey": "Se11erNftAta1111111111111111111111111111111", "signer": false, "writable": true },
+                        { "pubkey": "BuyerNftAta11111111111111111111111111111111", "signer": false, "writable": true },
+                        { "pubkey": "RoyaltyAndFees1111111111111111111111111111", "signer": false, "writable": true },
+                        { "pubkey": "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", "signer": false, "writable": false },
+                        { "pubkey": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP", "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K",
+                        "accounts": [BUYER, SELLER],
+                        "data": "3UjLyJvuY4jcKW2p"
+                    }]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_decodes_magic_eden_sale() {
+        let parser = EnhancedTransactionParser::new();
+        let tx = parser
+            .parse(&magic_eden_sale(), "me_sale".to_string())
+            .unwrap();
+
+        assert_eq!(tx.nft_trades.len(), 1);
+        let trade = &tx.nft_trades[0];
+        assert_eq!(trade.marketplace, "Magic Eden");
+        assert_eq!(trade.mint, NFT_MINT);
+        assert_eq!(trade.seller, SELLER);
+        assert_eq!(trade.buyer, BUYER);
+        assert!((trade.price_sol - 11.875).abs() < 1e-9);
+        assert!(tx.swaps.is_empty());
+    }
+
+    #[test]
+    fn test_decodes_sale_listing_both_token_accounts_before_and_after() {
+        // Nodes usually report the seller's emptied account and the buyer's
+        // account on both sides of the sale rather than only where it holds 1.
+        let balance = |index: u64, owner: &str, amount: &str| {
+            json!({
+                "accountIndex": index,
+                "mint": NFT_MINT,
+                "owner": owner,
+                "uiTokenAmount": { "amount": amount, "decimals": 0 }
+            })
+        };
+        let mut raw = magic_eden_sale();
+        raw["meta"]["preTokenBalances"] = json!([balance(2, SELLER, "1"), balance(3, BUYER, "0")]);
+        raw["meta"]["postTokenBalances"] = json!([balance(2, SELLER, "0"), balance(3, BUYER, "1")]);
+        let tx = EnhancedTransactionParser::new()
+            .parse(&raw, "me_sale_full".to_string())
+            .unwrap();
+
+        assert_eq!(tx.nft_trades.len(), 1);
+        assert_eq!(tx.nft_trades[0].seller, SELLER);
+        assert_eq!(tx.nft_trades[0].buyer, BUYER);
+        assert!((tx.nft_trades[0].price_sol - 11.875).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ignores_transactions_without_marketplace() {
+        let mut raw = magic_eden_sale();
+        raw["transaction"]["message"]["instructions"][0]["programId"] =
+            json!("11111111111111111111111111111111");
+        let tx = EnhancedTransactionParser::new()
+            .parse(&raw, "plain".to_string())
+            .unwrap();
+        assert!(extract_nft_trades(&tx).is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+/// Phoenix order-book swaps
+///
+/// A Phoenix `Swap` can fill against many resting orders in one instruction.
+/// Rather than decoding each fill from the event log, the wallet's net balance
+/// deltas yield a single event carrying the net amounts.
+use crate::core::enhanced_parser::EnhancedTransaction;
+use crate::dex::decoder::{balance_delta_swap, instruction_accounts};
+use crate::dex::types::{DexPrograms, SwapEvent};
+
+/// Position of the market account in the Phoenix `Swap` instruction
+/// (after the program and log authority).
+const MARKET_INDEX: usize = 2;
+
+pub fn decode(
+    raw_data: &serde_json::Value,
+    tx: &EnhancedTransaction,
+    wallet: &str,
+) -> Option<SwapEvent> {
+    let mut swap = balance_delta_swap(raw_data, tx, wallet, DexPrograms::PHOENIX)?;
+    swap.pool_address = instruction_accounts(raw_data, tx, DexPrograms::PHOENIX)
+        .and_then(|accounts| accounts.get(MARKET_INDEX).cloned());
+    Some(swap)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dex::decoder::tests::{parse_swap_fixture, USDC, WALLET};
+    use crate::dex::types::{DexPrograms, WSOL_MINT};
+
+    #[test]
+    fn test_decodes_phoenix_fill_as_single_net_swap() {
+        let tx = parse_swap_fixture(
+            DexPrograms::PHOENIX,
+            &[
+                DexPrograms::PHOENIX,
+                "PhoenixLogAuthority111111111111111111111111",
+                "PhoenixMarket11111111111111111111111111111",
+                WALLET,
+            ],
+            5_000_005_000,
+            2_500_000_000,
+            0,
+            375_250_000,
+        );
+
+        assert_eq!(tx.swaps.len(), 1);
+        let swap = &tx.swaps[0];
+        assert_eq!(swap.dex_name, "Phoenix");
+        assert_eq!(
+            swap.pool_address.as_deref(),
+            Some("PhoenixMarket11111111111111111111111111111")
+        );
+        assert_eq!(swap.token_in_mint, WSOL_MINT);
+        assert!((swap.amount_in - 2.5).abs() < 1e-9);
+        assert_eq!(swap.token_out_mint, USDC);
+        assert!((swap.amount_out - 375.25).abs() < 1e-9);
+    }
+}
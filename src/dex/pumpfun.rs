@@ -0,0 +1,222 @@
+/// pump.fun bonding-curve trades
+///
+/// `buy` and `sell` move tokens between the curve's token account and the
+/// user's, with SOL as the other leg. Sells pay out by debiting the curve
+/// account directly (no system transfer), so the SOL leg is read from the
+/// bonding curve's lamport delta for both directions.
+use crate::core::enhanced_parser::EnhancedTransaction;
+use crate::core::units::{amount_to_ui, lamports_to_sol};
+use crate::dex::decoder::instruction_accounts;
+use crate::dex::types::{DexPrograms, SwapEvent, WSOL_MINT};
+
+/// Account positions shared by the `buy` and `sell` instructions.
+const MINT_INDEX: usize = 2;
+const BONDING_CURVE_INDEX: usize = 3;
+const ASSOCIATED_USER_INDEX: usize = 5;
+
+/// pump.fun tokens are minted with 6 decimals.
+const PUMP_TOKEN_DECIMALS: u8 = 6;
+
+pub fn decode(
+    raw_data: &serde_json::Value,
+    tx: &EnhancedTransaction,
+    wallet: &str,
+) -> Option<SwapEvent> {
+    let accounts = instruction_accounts(raw_data, tx, DexPrograms::PUMP_FUN)?;
+    let mint = accounts.get(MINT_INDEX)?;
+    let bonding_curve = accounts.get(BONDING_CURVE_INDEX)?;
+    let user_token_account = accounts.get(ASSOCIATED_USER_INDEX)?;
+
+    let mut token_delta: f64 = 0.0;
+    for t in &tx.token_transfers {
+        let decimals = if t.decimals > 0 {
+            t.decimals
+        } else {
+            PUMP_TOKEN_DECIMALS
+        };
+        let amount = amount_to_ui(t.amount, decimals);
+        if &t.to_token_account == user_token_account {
+            token_delta += amount;
+        }
+        if &t.from_token_account == user_token_account {
+            token_delta -= amount;
+        }
+    }
+
+    let curve_lamports: i64 = tx
+        .balance_changes
+        .iter()
+        .filter(|bc| &bc.account == bonding_curve)
+        .map(|bc| bc.change_lamports)
+        .sum();
+    let sol_amount = lamports_to_sol(curve_lamports.unsigned_abs());
+
+    if token_delta == 0.0 || sol_amount == 0.0 {
+        return None;
+    }
+
+    let (token_in_mint, amount_in, token_out_mint, amount_out) = if token_delta > 0.0 {
+        (WSOL_MINT.to_string(), sol_amount, mint.clone(), token_delta)
+    } else {
+        (
+            mint.clone(),
+            -token_delta,
+            WSOL_MINT.to_string(),
+            sol_amount,
+        )
+    };
+
+    Some(SwapEvent {
+        signature: tx.signature.clone(),
+        event_index: 0,
+        slot: tx.slot,
+        block_time: tx.block_time,
+        wallet: wallet.to_string(),
+        dex_name: DexPrograms::name(DexPrograms::PUMP_FUN)?.to_string(),
+        program_id: Some(DexPrograms::PUMP_FUN.to_string()),
+        pool_address: Some(bonding_curve.clone()),
+        token_in_mint,
+        amount_in,
+        token_out_mint,
+        amount_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::enhanced_parser::{EnhancedTransaction, EnhancedTransactionParser};
+    use crate::dex::types::{DexPrograms, WSOL_MINT};
+    use serde_json::json;
+
+    const USER: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const USER_ATA: &str = "UserAta11111111111111111111111111111111111";
+    const CURVE: &str = "BondingCurve111111111111111111111111111111";
+    const CURVE_ATA: &str = "CurveAta1111111111111111111111111111111111";
+    const MINT: &str = "MemeMint1111111111111111111111111111111pump";
+
+    /// A pump.fun trade moving `tokens` (raw, 6 decimals) between the curve and
+    /// the user, with the curve's lamports going from `curve_pre` to `curve_post`.
+    fn trade(
+        buy: bool,
+        tokens: u64,
+        user_pre: u64,
+        user_post: u64,
+        curve_pre: u64,
+        curve_post: u64,
+    ) -> EnhancedTransaction {
+        let (source, destination) = if buy {
+            (CURVE_ATA, USER_ATA)
+        } else {
+            (USER_ATA, CURVE_ATA)
+        };
+        let (user_tokens_pre, user_tokens_post) = if buy { (0, tokens) } else { (tokens, 0) };
+        let token_balance = |index: u64, owner: &str, amount: u64| {
+            json!({
+                "accountIndex": index,
+                "mint": MINT,
+                "owner": owner,
+                "uiTokenAmount": { "amount": amount.to_string(), "decimals": 6 }
+            })
+        };
+
+        let raw = json!({
+            "slot": 280_000_000u64,
+            "blockTime": 1_720_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [user_pre, 2_039_280u64, curve_pre, 2_039_280u64, 1u64, 1u64],
+                "postBalances": [user_post, 2_039_280u64, curve_post, 2_039_280u64, 1u64, 1u64],
+                "preTokenBalances": [
+                    token_balance(1, USER, user_tokens_pre),
+                    token_balance(3, CURVE, 1_000_000_000_000)
+                ],
+                "postTokenBalances": [
+                    token_balance(1, USER, user_tokens_post),
+                    token_balance(3, CURVE, if buy { 1_000_000_000_000 - tokens } else { 1_000_000_000_000 + tokens })
+                ],
+                "innerInstructions": [{
+                    "index": 0,
+                    "instructions": [{
+                        "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                        "parsed": {
+                            "type": "transfer",
+                            "info": {
+                                "source": source,
+                                "destination": destination,
+                                "authority": if buy { CURVE } else { USER },
+                                "amount": tokens.to_string()
+                            }
+                        }
+                    }]
+                }]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": USER, "signer": true, "writable": true },
+                        { "pubkey": USER_ATA, "signer": false, "writable": true },
+                        { "pubkey": CURVE, "signer": false, "writable": true },
+                        { "pubkey": CURVE_ATA, "signer": false, "writable": true },
+                        { "pubkey": MINT, "signer": false, "writable": false },
+                        { "pubkey": DexPrograms::PUMP_FUN, "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": DexPrograms::PUMP_FUN,
+                        "accounts": [
+                            "PumpGlobal111111111111111111111111111111111",
+                            "PumpFeeRecipient11111111111111111111111111",
+                            MINT, CURVE, CURVE_ATA, USER_ATA, USER
+                        ],
+                        "data": ""
+                    }]
+                }
+            }
+        });
+
+        EnhancedTransactionParser::new()
+            .parse(&raw, "sig_pump".to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decodes_pumpfun_buy() {
+        let tx = trade(
+            true,
+            35_000_000_000,
+            2_000_000_000,
+            1_494_995_000,
+            30_000_000_000,
+            30_500_000_000,
+        );
+
+        assert_eq!(tx.swaps.len(), 1);
+        let swap = &tx.swaps[0];
+        assert_eq!(swap.dex_name, "pump.fun");
+        assert_eq!(swap.pool_address.as_deref(), Some(CURVE));
+        assert_eq!(swap.token_in_mint, WSOL_MINT);
+        assert!((swap.amount_in - 0.5).abs() < 1e-9);
+        assert_eq!(swap.token_out_mint, MINT);
+        assert!((swap.amount_out - 35_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decodes_pumpfun_sell() {
+        let tx = trade(
+            false,
+            10_000_000_000,
+            1_000_000_000,
+            1_119_995_000,
+            30_000_000_000,
+            29_880_000_000,
+        );
+
+        assert_eq!(tx.swaps.len(), 1);
+        let swap = &tx.swaps[0];
+        assert_eq!(swap.dex_name, "pump.fun");
+        assert_eq!(swap.token_in_mint, MINT);
+        assert!((swap.amount_in - 10_000.0).abs() < 1e-9);
+        assert_eq!(swap.token_out_mint, WSOL_MINT);
+        assert!((swap.amount_out - 0.12).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,61 @@
+/// DEX swap types and known program IDs
+use serde::{Deserialize, Serialize};
+
+/// Wrapped SOL mint. Native SOL legs are reported under this mint so swaps
+/// always carry a comparable pair of mints.
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Net swap executed by a wallet within a single transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SwapEvent {
+    pub signature: String,
+    pub event_index: i32,
+    pub slot: u64,
+    pub block_time: Option<u64>,
+    /// Wallet that executed the swap (fee payer).
+    pub wallet: String,
+    pub dex_name: String,
+    pub program_id: Option<String>,
+    pub pool_address: Option<String>,
+    /// Asset the wallet gave up.
+    pub token_in_mint: String,
+    pub amount_in: f64,
+    /// Asset the wallet received.
+    pub token_out_mint: String,
+    pub amount_out: f64,
+}
+
+/// Known DEX program IDs.
+pub struct DexPrograms;
+
+impl DexPrograms {
+    pub const RAYDIUM_V4: &'static str = "675kPX9MHTjS2zt1qfmKe2LdPsyAtg5w6qcCX6qX8W8S";
+    pub const ORCA_WHIRLPOOL: &'static str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+    pub const JUPITER_V6: &'static str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+    pub const METEORA_DLMM: &'static str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+    pub const PHOENIX: &'static str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jKCPBhqS4Y";
+    pub const PUMP_FUN: &'static str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+    /// All known DEX programs, aggregators first so routed swaps are attributed to them.
+    pub const ALL: &'static [&'static str] = &[
+        Self::JUPITER_V6,
+        Self::RAYDIUM_V4,
+        Self::ORCA_WHIRLPOOL,
+        Self::METEORA_DLMM,
+        Self::PHOENIX,
+        Self::PUMP_FUN,
+    ];
+
+    /// Human-readable DEX name for a program ID, if known.
+    pub fn name(program_id: &str) -> Option<&'static str> {
+        match program_id {
+            Self::RAYDIUM_V4 => Some("Raydium V4"),
+            Self::ORCA_WHIRLPOOL => Some("Orca Whirlpool"),
+            Self::JUPITER_V6 => Some("Jupiter V6"),
+            Self::METEORA_DLMM => Some("Meteora DLMM"),
+            Self::PHOENIX => Some("Phoenix"),
+            Self::PUMP_FUN => Some("pump.fun"),
+            _ => None,
+        }
+    }
+}
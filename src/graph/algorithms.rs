@@ -0,0 +1,429 @@
+/// Graph Algorithms - Centrality and clustering over `WalletGraph`
+use crate::graph::wallet_graph::WalletGraph;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// PageRank stops once the L1 change between iterations drops below this.
+pub const PAGERANK_EPSILON: f64 = 1e-9;
+
+/// Upper bound on local-moving passes during community detection.
+const MAX_COMMUNITY_PASSES: usize = 100;
+
+pub struct GraphAlgorithms;
+
+impl GraphAlgorithms {
+    /// Weighted PageRank. Each wallet passes its score to the wallets it sends
+    /// to, in proportion to `WalletEdge::weight`; wallets that never send spread
+    /// their score uniformly. Scores sum to 1.0.
+    ///
+    /// Runs at most `iterations` rounds, stopping early on convergence.
+    pub fn pagerank(graph: &WalletGraph, damping: f64, iterations: usize) -> HashMap<String, f64> {
+        let nodes: Vec<&String> = graph.nodes().collect();
+        let n = nodes.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+        let damping = damping.clamp(0.0, 1.0);
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.as_str(), i))
+            .collect();
+
+        let mut out_weight = vec![0.0_f64; n];
+        let mut links: Vec<(usize, usize, f64)> = Vec::with_capacity(graph.edge_count());
+        for edge in graph.edges() {
+            let (Some(&from), Some(&to)) =
+                (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+            else {
+                continue;
+            };
+            let w = edge.weight();
+            if w <= 0.0 {
+                continue;
+            }
+            out_weight[from] += w;
+            links.push((from, to, w));
+        }
+
+        let base = (1.0 - damping) / n as f64;
+        let mut rank = vec![1.0 / n as f64; n];
+
+        for _ in 0..iterations {
+            let dangling: f64 = (0..n)
+                .filter(|&i| out_weight[i] == 0.0)
+                .map(|i| rank[i])
+                .sum();
+
+            let mut next = vec![base + damping * dangling / n as f64; n];
+            for &(from, to, w) in &links {
+                next[to] += damping * rank[from] * w / out_weight[from];
+            }
+
+            let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if delta < PAGERANK_EPSILON {
+                break;
+            }
+        }
+
+        let total: f64 = rank.iter().sum();
+        nodes
+            .into_iter()
+            .zip(rank)
+            .map(|(w, r)| (w.clone(), if total > 0.0 { r / total } else { 0.0 }))
+            .collect()
+    }
+}
+
+impl GraphAlgorithms {
+    /// Betweenness centrality (Brandes): the share of shortest directed transfer
+    /// paths between other wallets that pass through each wallet, normalised to
+    /// 0.0 - 1.0. Paths are counted in hops, so wallets that relay funds between
+    /// otherwise unconnected wallets (mixers, layering hops) score highest.
+    pub fn betweenness_centrality(graph: &WalletGraph) -> HashMap<String, f64> {
+        Self::betweenness_centrality_sampled(graph, usize::MAX)
+    }
+
+    /// `betweenness_centrality` from at most `max_sources` source wallets, spread
+    /// evenly over the sorted wallet list and scaled up to estimate the full score.
+    /// Costs O(`max_sources` * edges) instead of O(wallets * edges).
+    pub fn betweenness_centrality_sampled(
+        graph: &WalletGraph,
+        max_sources: usize,
+    ) -> HashMap<String, f64> {
+        let nodes: Vec<&String> = graph.nodes().collect();
+        let n = nodes.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.as_str(), i))
+            .collect();
+
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for edge in graph.edges() {
+            if let (Some(&from), Some(&to)) =
+                (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+            {
+                successors[from].push(to);
+            }
+        }
+
+        let sample = max_sources.clamp(1, n);
+        let mut centrality = vec![0.0_f64; n];
+        let mut stack: Vec<usize> = Vec::with_capacity(n);
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0_f64; n];
+        let mut hops = vec![usize::MAX; n];
+        let mut dependency = vec![0.0_f64; n];
+
+        for source in (0..sample).map(|i| i * n / sample) {
+            stack.clear();
+            predecessors.iter_mut().for_each(Vec::clear);
+            paths.fill(0.0);
+            hops.fill(usize::MAX);
+            dependency.fill(0.0);
+
+            paths[source] = 1.0;
+            hops[source] = 0;
+            queue.push_back(source);
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                for &w in &successors[v] {
+                    if hops[w] == usize::MAX {
+                        hops[w] = hops[v] + 1;
+                        queue.push_back(w);
+                    }
+                    if hops[w] == hops[v] + 1 {
+                        paths[w] += paths[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            while let Some(w) = stack.pop() {
+                for &v in &predecessors[w] {
+                    dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+                }
+                if w != source {
+                    centrality[w] += dependency[w];
+                }
+            }
+        }
+
+        let scale = if n > 2 {
+            (n as f64 / sample as f64) / ((n - 1) * (n - 2)) as f64
+        } else {
+            0.0
+        };
+        nodes
+            .into_iter()
+            .zip(centrality)
+            .map(|(w, c)| (w.clone(), (c * scale).min(1.0)))
+            .collect()
+    }
+}
+
+impl GraphAlgorithms {
+    /// Modularity-based community detection (the local-moving phase of Louvain).
+    ///
+    /// Edges are treated as undirected with weight `WalletEdge::weight` summed over
+    /// both directions. Each wallet repeatedly joins the neighbouring community with
+    /// the highest modularity gain until no wallet moves. Wallets are visited in
+    /// sorted order so results are deterministic.
+    ///
+    /// Communities are returned largest first; members are sorted.
+    pub fn detect_communities(graph: &WalletGraph) -> Vec<Vec<String>> {
+        let nodes: Vec<&String> = graph.nodes().collect();
+        let n = nodes.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.as_str(), i))
+            .collect();
+
+        // Undirected adjacency with merged weights.
+        let mut adjacency: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); n];
+        for edge in graph.edges() {
+            let (Some(&a), Some(&b)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+            else {
+                continue;
+            };
+            let w = edge.weight();
+            if w <= 0.0 {
+                continue;
+            }
+            *adjacency[a].entry(b).or_insert(0.0) += w;
+            *adjacency[b].entry(a).or_insert(0.0) += w;
+        }
+
+        let strength: Vec<f64> = adjacency.iter().map(|adj| adj.values().sum()).collect();
+        let two_m: f64 = strength.iter().sum();
+
+        let mut community: Vec<usize> = (0..n).collect();
+        if two_m > 0.0 {
+            let mut total: Vec<f64> = strength.clone();
+
+            for _ in 0..MAX_COMMUNITY_PASSES {
+                let mut moved = false;
+                for i in 0..n {
+                    if strength[i] == 0.0 {
+                        continue;
+                    }
+                    let current = community[i];
+                    total[current] -= strength[i];
+
+                    let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+                    for (&j, &w) in &adjacency[i] {
+                        *links.entry(community[j]).or_insert(0.0) += w;
+                    }
+
+                    let gain = |c: usize, k_in: f64| k_in - total[c] * strength[i] / two_m;
+                    let mut best = current;
+                    let mut best_gain = gain(current, links.get(&current).copied().unwrap_or(0.0));
+                    for (&c, &k_in) in &links {
+                        let g = gain(c, k_in);
+                        if g > best_gain + 1e-12 {
+                            best = c;
+                            best_gain = g;
+                        }
+                    }
+
+                    total[best] += strength[i];
+                    if best != current {
+                        community[i] = best;
+                        moved = true;
+                    }
+                }
+                if !moved {
+                    break;
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (i, c) in community.into_iter().enumerate() {
+            groups.entry(c).or_default().push(nodes[i].clone());
+        }
+        let mut out: Vec<Vec<String>> = groups.into_values().collect();
+        for group in &mut out {
+            group.sort();
+        }
+        out.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TransferEvent;
+
+    #[test]
+    fn test_pagerank_favors_hub_and_sums_to_one() {
+        let mut graph = WalletGraph::new();
+        for spoke in ["a", "b", "c", "d"] {
+            graph.add_transfer(spoke, "hub", 1.0);
+        }
+        graph.add_transfer("hub", "a", 1.0);
+
+        let scores = GraphAlgorithms::pagerank(&graph, 0.85, 100);
+        let total: f64 = scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let hub = scores["hub"];
+        assert!(scores.iter().all(|(w, s)| w == "hub" || *s < hub));
+        assert!(scores["a"] > scores["b"]);
+    }
+
+    #[test]
+    fn test_pagerank_weights_heavier_edges() {
+        let mut graph = WalletGraph::new();
+        graph.add_transfer("src", "light", 0.1);
+        for _ in 0..5 {
+            graph.add_transfer("src", "heavy", 100.0);
+        }
+
+        let scores = GraphAlgorithms::pagerank(&graph, 0.85, 50);
+        assert!(scores["heavy"] > scores["light"]);
+    }
+
+    #[test]
+    fn test_communities_split_on_bridge_edge() {
+        let mut graph = WalletGraph::new();
+        let left = ["a1", "a2", "a3", "a4"];
+        let right = ["b1", "b2", "b3", "b4"];
+        for cluster in [left, right] {
+            for (i, from) in cluster.iter().enumerate() {
+                for to in &cluster[i + 1..] {
+                    graph.add_transfer(from, to, 1.0);
+                    graph.add_transfer(to, from, 1.0);
+                }
+            }
+        }
+        graph.add_transfer("a4", "b1", 1.0);
+
+        let communities = GraphAlgorithms::detect_communities(&graph);
+        assert_eq!(communities.len(), 2, "got {:?}", communities);
+        assert_eq!(communities[0], left.to_vec());
+        assert_eq!(communities[1], right.to_vec());
+    }
+
+    #[test]
+    fn test_pruned_graph_drops_dust_edges() {
+        let mut graph = WalletGraph::new();
+        for _ in 0..3 {
+            graph.add_transfer("whale", "hub", 50.0);
+        }
+        graph.add_transfer("hub", "trader", 5.0);
+        graph.add_transfer("hub", "trader", 5.0);
+        graph.add_transfer("duster", "hub", 0.000_001);
+
+        let pruned = graph.pruned(0.01, 2);
+        assert!(pruned.edge("duster", "hub").is_none());
+        assert!(!pruned.contains("duster"));
+        assert!(pruned.edge("whale", "hub").is_some());
+        assert!(pruned.edge("hub", "trader").is_some());
+        assert_eq!(pruned.edge_count(), 2);
+
+        // A single-transfer edge is dropped by the count threshold alone.
+        assert!(graph.pruned(0.0, 2).edge("duster", "hub").is_none());
+
+        // The unpruned graph is untouched.
+        assert_eq!(graph.edge_count(), 3);
+        assert!(!GraphAlgorithms::pagerank(&pruned, 0.85, 50).contains_key("duster"));
+    }
+
+    #[test]
+    fn test_token_flows_weight_edges_only_when_enabled() {
+        let event = |from: &str, to: &str, sol: Option<f64>, tokens: Option<f64>| TransferEvent {
+            signature: format!("{}-{}", from, to),
+            event_index: 0,
+            slot: 1,
+            block_time: Some(1),
+            kind: if tokens.is_some() { "token" } else { "sol" }.to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: tokens.map(|_| "USDC".to_string()),
+            amount_sol: sol,
+            token_amount_ui: tokens,
+            token_amount: None,
+            token_decimals: None,
+        };
+        let events = vec![
+            event("src", "stable", None, Some(250_000.0)),
+            event("src", "dust", Some(0.01), None),
+        ];
+
+        let sol_only = WalletGraph::from_transfer_events(&events);
+        let stable = sol_only.edge("src", "stable").unwrap();
+        assert!(stable.token_volume_by_mint.is_empty());
+        assert_eq!(stable.weight(), 1.0);
+        // Without token weighting a SOL threshold drops the token-only edge.
+        assert!(sol_only.pruned(0.001, 1).edge("src", "stable").is_none());
+
+        let weighted = WalletGraph::from_transfer_events_with_tokens(&events);
+        let stable = weighted.edge("src", "stable").unwrap();
+        assert_eq!(stable.amount, 0.0);
+        assert_eq!(stable.token_volume_by_mint["USDC"], 250_000.0);
+        assert!(stable.weight() > 10.0);
+        let pruned = weighted.pruned(0.1, 1);
+        assert!(pruned.edge("src", "stable").is_some());
+        assert!(pruned.edge("src", "dust").is_none());
+
+        let scores = GraphAlgorithms::pagerank(&weighted, 0.85, 50);
+        assert!(scores["stable"] > scores["dust"]);
+    }
+
+    #[test]
+    fn test_token_volume_is_weighted_per_mint() {
+        let mut graph = WalletGraph::new();
+        graph.add_token_transfer("a", "b", "USDC", 1_000.0);
+        graph.add_token_transfer("a", "b", "BONK", 1_000.0);
+        graph.add_token_transfer("a", "c", "USDC", 2_000.0);
+        graph.add_token_transfer("a", "c", "USDC", 0.0);
+
+        let b = graph.edge("a", "b").unwrap();
+        assert_eq!(b.token_volume_by_mint.len(), 2);
+        assert!((b.weight() - (2.0 + 2.0 * 1_000f64.ln_1p())).abs() < 1e-12);
+        let c = graph.edge("a", "c").unwrap();
+        assert!((c.weight() - (2.0 + 2_000f64.ln_1p())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_betweenness_peaks_at_bottleneck() {
+        // Three senders reach three receivers only through "mixer".
+        let mut graph = WalletGraph::new();
+        for sender in ["a1", "a2", "a3"] {
+            graph.add_transfer(sender, "mixer", 1.0);
+        }
+        for receiver in ["b1", "b2", "b3"] {
+            graph.add_transfer("mixer", receiver, 1.0);
+        }
+        graph.add_transfer("a1", "a2", 1.0);
+        graph.add_transfer("b1", "b2", 1.0);
+
+        let scores = GraphAlgorithms::betweenness_centrality(&graph);
+        assert_eq!(scores.len(), 7);
+        let mixer = scores["mixer"];
+        assert!(scores.iter().all(|(w, s)| w == "mixer" || *s < mixer));
+        // The 9 sender->receiver pairs out of the 6 * 5 ordered pairs of other
+        // wallets; a1->a2 and b1->b2 are direct, so nothing else relays.
+        assert!((mixer - 9.0 / 30.0).abs() < 1e-12);
+        assert_eq!(scores["a3"], 0.0);
+
+        let sampled = GraphAlgorithms::betweenness_centrality_sampled(&graph, 3);
+        let top = sampled
+            .iter()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        assert_eq!(top.0, "mixer");
+    }
+}
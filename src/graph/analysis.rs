@@ -0,0 +1,298 @@
+/// Graph Analysis Engine - Builds wallet graphs from storage and runs graph algorithms
+use crate::core::errors::{BeastError, BeastResult};
+use crate::graph::algorithms::GraphAlgorithms;
+use crate::graph::exchange_routes::{self, ExchangeRouteOptions, ExchangeRouteReport};
+use crate::graph::wallet_graph::{GraphCap, WalletGraph};
+use crate::modules::ExchangeRegistry;
+use crate::storage::DatabaseManager;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Run a CPU-heavy graph computation on the blocking pool so it doesn't stall
+/// the async workers serving other requests.
+async fn run_blocking<T, F>(f: F) -> BeastResult<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| BeastError::AnalysisFailed(format!("Graph computation failed: {}", e)))
+}
+
+/// Community assignment for a wallet and the members of its community.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletCluster {
+    pub wallet: String,
+    /// Community id, or `None` if the wallet has no transfers in the window.
+    pub community_id: Option<usize>,
+    pub community_count: usize,
+    pub members: Vec<ClusterMember>,
+    /// The transfer graph exceeded `graph_cap` and only its heaviest edges were used.
+    pub sampled: bool,
+    pub graph_cap: GraphCap,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMember {
+    pub wallet: String,
+    pub community_id: usize,
+    /// Current SOL balance, filled in by callers with RPC access.
+    pub balance_sol: Option<f64>,
+    /// Investigator labels, filled in by callers.
+    pub labels: Vec<String>,
+}
+
+/// Parameters for `GraphAnalysisEngine::network_centrality`.
+#[derive(Debug, Clone, Copy)]
+pub struct CentralityOptions {
+    /// PageRank damping factor.
+    pub damping: f64,
+    /// Max PageRank iterations.
+    pub iterations: usize,
+    /// Max source wallets for betweenness (see `betweenness_centrality_sampled`).
+    pub betweenness_sources: usize,
+}
+
+/// PageRank and betweenness of one wallet in the recent-transfer graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletCentrality {
+    pub wallet: String,
+    /// PageRank score.
+    pub score: f64,
+    /// Share of shortest transfer paths between other wallets passing through this one.
+    pub betweenness: f64,
+}
+
+/// Centrality of every wallet in the recent-transfer graph, highest PageRank first.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkCentrality {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Source wallets betweenness was computed from (all nodes unless sampled).
+    pub betweenness_sources: usize,
+    /// The transfer graph exceeded `graph_cap` and only its heaviest edges were used.
+    pub sampled: bool,
+    pub graph_cap: GraphCap,
+    pub wallets: Vec<WalletCentrality>,
+}
+
+pub struct GraphAnalysisEngine {
+    db_manager: Arc<DatabaseManager>,
+    graph_cap: GraphCap,
+}
+
+impl GraphAnalysisEngine {
+    /// Graphs are capped at `GraphCap::from_env`.
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            db_manager,
+            graph_cap: GraphCap::from_env(),
+        }
+    }
+
+    pub fn with_graph_cap(mut self, graph_cap: GraphCap) -> Self {
+        self.graph_cap = graph_cap;
+        self
+    }
+
+    pub fn graph_cap(&self) -> GraphCap {
+        self.graph_cap
+    }
+
+    /// Build a wallet graph from recent transfer events, sampled down to the
+    /// graph cap when larger.
+    pub async fn build_graph(
+        &self,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+    ) -> BeastResult<WalletGraph> {
+        self.load_graph(since_epoch, event_limit, false).await
+    }
+
+    async fn load_graph(
+        &self,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+        token_weight: bool,
+    ) -> BeastResult<WalletGraph> {
+        let events = self
+            .db_manager
+            .get_recent_transfer_events(since_epoch, event_limit)
+            .await?;
+        let graph = if token_weight {
+            WalletGraph::from_transfer_events_with_tokens(&events)
+        } else {
+            WalletGraph::from_transfer_events(&events)
+        };
+        Ok(graph.capped(self.graph_cap))
+    }
+
+    /// `build_graph` without edges below `min_sol` SOL or `min_tx_count` transfers
+    /// (see `WalletGraph::pruned`). With `token_weight`, token volume also weights
+    /// edges (see `WalletGraph::from_transfer_events_with_tokens`).
+    pub async fn build_graph_pruned(
+        &self,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+        min_sol: f64,
+        min_tx_count: u32,
+        token_weight: bool,
+    ) -> BeastResult<WalletGraph> {
+        let graph = self
+            .load_graph(since_epoch, event_limit, token_weight)
+            .await?;
+        if min_sol <= 0.0 && min_tx_count <= 1 {
+            return Ok(graph);
+        }
+        Ok(graph.pruned(min_sol, min_tx_count))
+    }
+
+    /// PageRank and betweenness over the `build_graph_pruned` graph. Betweenness is
+    /// sampled from `options.betweenness_sources` wallets when the graph is larger.
+    /// Both run on the blocking pool.
+    pub async fn network_centrality(
+        &self,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+        min_sol: f64,
+        min_tx_count: u32,
+        token_weight: bool,
+        options: CentralityOptions,
+    ) -> BeastResult<NetworkCentrality> {
+        let graph = self
+            .build_graph_pruned(
+                since_epoch,
+                event_limit,
+                min_sol,
+                min_tx_count,
+                token_weight,
+            )
+            .await?;
+        let (graph, pagerank, mut betweenness) = run_blocking(move || {
+            let pagerank = GraphAlgorithms::pagerank(&graph, options.damping, options.iterations);
+            let betweenness = GraphAlgorithms::betweenness_centrality_sampled(
+                &graph,
+                options.betweenness_sources,
+            );
+            (graph, pagerank, betweenness)
+        })
+        .await?;
+
+        let mut wallets: Vec<WalletCentrality> = pagerank
+            .into_iter()
+            .map(|(wallet, score)| WalletCentrality {
+                betweenness: betweenness.remove(&wallet).unwrap_or(0.0),
+                wallet,
+                score,
+            })
+            .collect();
+        wallets.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.wallet.cmp(&b.wallet))
+        });
+
+        Ok(NetworkCentrality {
+            nodes: graph.node_count(),
+            edges: graph.edge_count(),
+            betweenness_sources: options
+                .betweenness_sources
+                .clamp(1, graph.node_count().max(1)),
+            sampled: graph.is_sampled(),
+            graph_cap: self.graph_cap,
+            wallets,
+        })
+    }
+
+    /// Graph of recent transfers restricted to wallets within `depth` hops of `wallet`.
+    pub async fn wallet_neighborhood(
+        &self,
+        wallet: &str,
+        depth: usize,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+    ) -> BeastResult<WalletGraph> {
+        let graph = self.build_graph(since_epoch, event_limit).await?;
+        Ok(graph.neighborhood(wallet, depth))
+    }
+
+    /// Detect communities over recent transfers and return the one containing `wallet`.
+    /// Edges below `min_sol` SOL or `min_tx_count` transfers are ignored; with
+    /// `token_weight`, token volume also weights edges. Community detection runs on
+    /// the blocking pool.
+    pub async fn wallet_cluster(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+        min_sol: f64,
+        min_tx_count: u32,
+        token_weight: bool,
+    ) -> BeastResult<WalletCluster> {
+        let graph = self
+            .build_graph_pruned(
+                since_epoch,
+                event_limit,
+                min_sol,
+                min_tx_count,
+                token_weight,
+            )
+            .await?;
+        let (graph, communities) = run_blocking(move || {
+            let communities = GraphAlgorithms::detect_communities(&graph);
+            (graph, communities)
+        })
+        .await?;
+
+        let community_id = communities
+            .iter()
+            .position(|c| c.iter().any(|w| w == wallet));
+        let members = community_id
+            .map(|id| {
+                communities[id]
+                    .iter()
+                    .map(|w| ClusterMember {
+                        wallet: w.clone(),
+                        community_id: id,
+                        balance_sol: None,
+                        labels: Vec::new(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(WalletCluster {
+            wallet: wallet.to_string(),
+            community_id,
+            community_count: communities.len(),
+            members,
+            sampled: graph.is_sampled(),
+            graph_cap: self.graph_cap,
+        })
+    }
+
+    /// Routes from `source` to `destination` through exchange wallets over recent transfers.
+    pub async fn exchange_routes(
+        &self,
+        source: &str,
+        destination: &str,
+        registry: &ExchangeRegistry,
+        options: &ExchangeRouteOptions,
+        since_epoch: Option<u64>,
+        event_limit: usize,
+    ) -> BeastResult<ExchangeRouteReport> {
+        let events = self
+            .db_manager
+            .get_recent_transfer_events(since_epoch, event_limit)
+            .await?;
+        Ok(exchange_routes::trace_exchange_routes(
+            &events,
+            registry,
+            source,
+            destination,
+            options,
+        ))
+    }
+}
@@ -0,0 +1,480 @@
+/// Exchange routes - source→destination paths that pass through known exchange wallets
+use crate::graph::wallet_graph::WalletGraph;
+use crate::modules::ExchangeRegistry;
+use crate::storage::TransferEvent;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExchangeRouteOptions {
+    /// Maximum number of transfer hops in a graph route.
+    pub max_hops: usize,
+    pub max_routes: usize,
+    /// How long after a deposit a matching withdrawal may occur to count as a pass-through.
+    pub window_secs: i64,
+    /// Allowed relative difference between deposit and withdrawal amounts.
+    pub amount_tolerance: f64,
+}
+
+impl Default for ExchangeRouteOptions {
+    fn default() -> Self {
+        Self {
+            max_hops: 4,
+            max_routes: 20,
+            window_secs: 72 * 3600,
+            amount_tolerance: 0.10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteHop {
+    pub from: String,
+    pub to: String,
+    /// Aggregated SOL volume on the edge; `None` for the inferred hop inside an exchange.
+    pub volume_sol: Option<f64>,
+    pub transaction_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeRoute {
+    pub wallets: Vec<String>,
+    /// Exchange names in the order the route passes through them.
+    pub exchanges: Vec<String>,
+    pub hops: Vec<RouteHop>,
+    /// A deposit from the source was followed by a similar withdrawal to the destination.
+    pub pass_through: bool,
+    /// Seconds between the matched deposit and withdrawal, for pass-through routes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeRouteReport {
+    pub source: String,
+    pub destination: String,
+    pub routes: Vec<ExchangeRoute>,
+    /// Distinct exchanges across all routes.
+    pub exchanges_detected: usize,
+}
+
+/// Deposit/withdrawal pair matched through the same exchange.
+struct PassThrough {
+    deposit_wallet: String,
+    withdrawal_wallet: String,
+    exchange: String,
+    delta_seconds: i64,
+}
+
+/// Find routes from `source` to `destination` through registry wallets: simple
+/// graph paths of up to `max_hops` edges with an exchange in the middle, plus
+/// deposit→withdrawal pairs on the same exchange that match in amount and time.
+pub fn trace_exchange_routes(
+    events: &[TransferEvent],
+    registry: &ExchangeRegistry,
+    source: &str,
+    destination: &str,
+    options: &ExchangeRouteOptions,
+) -> ExchangeRouteReport {
+    let graph = WalletGraph::from_transfer_events(events);
+    let pass_throughs = match_pass_throughs(events, registry, source, destination, options);
+
+    let mut used = vec![false; pass_throughs.len()];
+    let mut routes: Vec<ExchangeRoute> = graph_paths(&graph, source, destination, options)
+        .into_iter()
+        .filter_map(|wallets| {
+            let exchanges = exchanges_on(&wallets, registry);
+            if exchanges.is_empty() {
+                return None;
+            }
+            let hops = wallets
+                .windows(2)
+                .map(|pair| observed_hop(&graph, &pair[0], &pair[1]))
+                .collect();
+            // A route is a pass-through when its first hop is a matched deposit,
+            // its last hop the matching withdrawal, and it stays on that exchange
+            // in between.
+            let matched = pass_throughs
+                .iter()
+                .position(|p| carries_pass_through(&wallets, p, registry));
+            if let Some(i) = matched {
+                used[i] = true;
+            }
+            let matched = matched.map(|i| &pass_throughs[i]);
+            Some(ExchangeRoute {
+                wallets,
+                exchanges,
+                hops,
+                pass_through: matched.is_some(),
+                delta_seconds: matched.map(|p| p.delta_seconds),
+            })
+        })
+        .collect();
+
+    for (p, _) in pass_throughs.iter().zip(&used).filter(|(_, used)| !**used) {
+        if routes.len() >= options.max_routes {
+            break;
+        }
+        // Not on any graph route (too many hops, or no edge inside the exchange).
+        let mut wallets = vec![source.to_string(), p.deposit_wallet.clone()];
+        let mut hops = vec![observed_hop(&graph, source, &p.deposit_wallet)];
+        if p.deposit_wallet != p.withdrawal_wallet {
+            wallets.push(p.withdrawal_wallet.clone());
+            hops.push(match graph.edge(&p.deposit_wallet, &p.withdrawal_wallet) {
+                Some(_) => observed_hop(&graph, &p.deposit_wallet, &p.withdrawal_wallet),
+                None => RouteHop {
+                    from: p.deposit_wallet.clone(),
+                    to: p.withdrawal_wallet.clone(),
+                    volume_sol: None,
+                    transaction_count: 0,
+                },
+            });
+        }
+        wallets.push(destination.to_string());
+        hops.push(observed_hop(&graph, &p.withdrawal_wallet, destination));
+        routes.push(ExchangeRoute {
+            wallets,
+            exchanges: vec![p.exchange.clone()],
+            hops,
+            pass_through: true,
+            delta_seconds: Some(p.delta_seconds),
+        });
+    }
+    routes.truncate(options.max_routes);
+
+    let exchanges_detected = routes
+        .iter()
+        .flat_map(|r| r.exchanges.iter())
+        .collect::<HashSet<_>>()
+        .len();
+
+    ExchangeRouteReport {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        routes,
+        exchanges_detected,
+    }
+}
+
+fn observed_hop(graph: &WalletGraph, from: &str, to: &str) -> RouteHop {
+    let edge = graph.edge(from, to);
+    RouteHop {
+        from: from.to_string(),
+        to: to.to_string(),
+        volume_sol: Some(edge.map(|e| e.amount).unwrap_or(0.0)),
+        transaction_count: edge.map(|e| e.transaction_count).unwrap_or(0),
+    }
+}
+
+/// Whether `wallets` starts with `p`'s deposit, ends with its withdrawal and only
+/// visits wallets of `p`'s exchange in between.
+fn carries_pass_through(wallets: &[String], p: &PassThrough, registry: &ExchangeRegistry) -> bool {
+    let n = wallets.len();
+    n >= 3
+        && wallets[1] == p.deposit_wallet
+        && wallets[n - 2] == p.withdrawal_wallet
+        && wallets[1..n - 1].iter().all(|w| {
+            registry
+                .identify_exchange(w)
+                .is_some_and(|info| info.exchange == p.exchange)
+        })
+}
+
+/// Exchange names of the intermediate wallets, collapsing consecutive repeats.
+fn exchanges_on(wallets: &[String], registry: &ExchangeRegistry) -> Vec<String> {
+    let mut exchanges: Vec<String> = Vec::new();
+    for wallet in &wallets[1..wallets.len().saturating_sub(1)] {
+        if let Some(info) = registry.identify_exchange(wallet) {
+            if exchanges.last() != Some(&info.exchange) {
+                exchanges.push(info.exchange.clone());
+            }
+        }
+    }
+    exchanges
+}
+
+/// Simple directed paths from `source` to `destination`, shortest first.
+fn graph_paths(
+    graph: &WalletGraph,
+    source: &str,
+    destination: &str,
+    options: &ExchangeRouteOptions,
+) -> Vec<Vec<String>> {
+    let mut adjacency: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for edge in graph.edges() {
+        adjacency.entry(&edge.from).or_default().insert(&edge.to);
+    }
+
+    let mut paths = Vec::new();
+    let mut stack = vec![source];
+    let mut budget = MAX_PATH_VISITS;
+    collect_paths(
+        &adjacency,
+        destination,
+        options.max_hops,
+        &mut stack,
+        &mut paths,
+        &mut budget,
+    );
+    paths.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    paths
+}
+
+/// Bound on the paths kept; callers only keep `max_routes` anyway.
+const MAX_PATHS: usize = 1_000;
+
+/// Bound on wallets visited by the path search. Dense graphs have exponentially
+/// many simple paths that never reach the destination, so counting found paths
+/// alone doesn't bound the work.
+const MAX_PATH_VISITS: usize = 50_000;
+
+fn collect_paths<'a>(
+    adjacency: &HashMap<&'a str, BTreeSet<&'a str>>,
+    destination: &str,
+    max_hops: usize,
+    stack: &mut Vec<&'a str>,
+    paths: &mut Vec<Vec<String>>,
+    budget: &mut usize,
+) {
+    let Some(&current) = stack.last() else {
+        return;
+    };
+    if stack.len() > max_hops {
+        return;
+    }
+    for &next in adjacency.get(current).into_iter().flatten() {
+        if paths.len() >= MAX_PATHS || *budget == 0 {
+            return;
+        }
+        if stack.contains(&next) {
+            continue;
+        }
+        *budget -= 1;
+        stack.push(next);
+        if next == destination {
+            paths.push(stack.iter().map(|w| w.to_string()).collect());
+        } else {
+            collect_paths(adjacency, destination, max_hops, stack, paths, budget);
+        }
+        stack.pop();
+    }
+}
+
+fn event_amount(ev: &TransferEvent) -> Option<f64> {
+    match ev.kind.as_str() {
+        "sol" => ev.amount_sol,
+        _ => ev.token_amount_ui,
+    }
+    .filter(|a| a.is_finite() && *a > 0.0)
+}
+
+/// Pair each deposit from `source` into an exchange with the closest later
+/// withdrawal of a similar amount (same asset) from that exchange to `destination`.
+fn match_pass_throughs(
+    events: &[TransferEvent],
+    registry: &ExchangeRegistry,
+    source: &str,
+    destination: &str,
+    options: &ExchangeRouteOptions,
+) -> Vec<PassThrough> {
+    let exchange_of = |wallet: Option<&str>| {
+        wallet
+            .and_then(|w| registry.identify_exchange(w))
+            .map(|info| info.exchange.as_str())
+    };
+
+    let deposits = events.iter().filter(|ev| {
+        ev.from_wallet.as_deref() == Some(source) && exchange_of(ev.to_wallet.as_deref()).is_some()
+    });
+    let withdrawals: Vec<&TransferEvent> = events
+        .iter()
+        .filter(|ev| {
+            ev.to_wallet.as_deref() == Some(destination)
+                && exchange_of(ev.from_wallet.as_deref()).is_some()
+        })
+        .collect();
+
+    let mut best: HashMap<(String, String), PassThrough> = HashMap::new();
+    for dep in deposits {
+        let (Some(dep_time), Some(dep_amount)) = (dep.block_time, event_amount(dep)) else {
+            continue;
+        };
+        let Some(exchange) = exchange_of(dep.to_wallet.as_deref()) else {
+            continue;
+        };
+        for w in &withdrawals {
+            let (Some(w_time), Some(w_amount)) = (w.block_time, event_amount(w)) else {
+                continue;
+            };
+            let delta_seconds = w_time - dep_time;
+            if delta_seconds < 0
+                || delta_seconds > options.window_secs
+                || w.kind != dep.kind
+                || w.mint != dep.mint
+                || exchange_of(w.from_wallet.as_deref()) != Some(exchange)
+                || (w_amount - dep_amount).abs() / dep_amount > options.amount_tolerance
+            {
+                continue;
+            }
+            let deposit_wallet = dep.to_wallet.clone().unwrap_or_default();
+            let withdrawal_wallet = w.from_wallet.clone().unwrap_or_default();
+            let key = (deposit_wallet.clone(), withdrawal_wallet.clone());
+            if best
+                .get(&key)
+                .is_none_or(|existing| delta_seconds < existing.delta_seconds)
+            {
+                best.insert(
+                    key,
+                    PassThrough {
+                        deposit_wallet,
+                        withdrawal_wallet,
+                        exchange: exchange.to_string(),
+                        delta_seconds,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut matches: Vec<PassThrough> = best.into_values().collect();
+    matches.sort_by(|a, b| {
+        a.delta_seconds
+            .cmp(&b.delta_seconds)
+            .then_with(|| a.deposit_wallet.cmp(&b.deposit_wallet))
+            .then_with(|| a.withdrawal_wallet.cmp(&b.withdrawal_wallet))
+    });
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINANCE_1: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const BINANCE_2: &str = "5tzFkiKscXHK5ZXCGbXZxdw7gTjjD1mBwuoFbhUvuAi9";
+
+    fn sol_event(sig: &str, from: &str, to: &str, amount: f64, time: i64) -> TransferEvent {
+        TransferEvent {
+            signature: sig.to_string(),
+            event_index: 0,
+            slot: time,
+            block_time: Some(time),
+            kind: "sol".to_string(),
+            transfer_type: "system".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: None,
+            amount_sol: Some(amount),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        }
+    }
+
+    #[test]
+    fn test_pass_through_without_direct_link() {
+        let events = vec![
+            sol_event("dep", "SRC", BINANCE_1, 10.0, 1_000),
+            sol_event("wd", BINANCE_2, "DST", 9.5, 4_600),
+            // Too different in size to be the same funds.
+            sol_event("wd_other", BINANCE_2, "DST", 3.0, 2_000),
+        ];
+
+        let report = trace_exchange_routes(
+            &events,
+            &ExchangeRegistry::builtin(),
+            "SRC",
+            "DST",
+            &ExchangeRouteOptions::default(),
+        );
+
+        assert_eq!(report.routes.len(), 1);
+        let route = &report.routes[0];
+        assert!(route.pass_through);
+        assert_eq!(route.delta_seconds, Some(3_600));
+        assert_eq!(route.wallets, vec!["SRC", BINANCE_1, BINANCE_2, "DST"]);
+        assert_eq!(route.exchanges, vec!["Binance"]);
+        assert_eq!(route.hops[1].volume_sol, None);
+        assert_eq!(report.exchanges_detected, 1);
+    }
+
+    #[test]
+    fn test_pass_through_over_edge_between_exchange_wallets() {
+        let events = vec![
+            sol_event("dep", "SRC", BINANCE_1, 10.0, 1_000),
+            sol_event("sweep", BINANCE_1, BINANCE_2, 500.0, 2_000),
+            sol_event("wd", BINANCE_2, "DST", 9.8, 4_600),
+        ];
+
+        for max_hops in [2, 4] {
+            let options = ExchangeRouteOptions {
+                max_hops,
+                ..ExchangeRouteOptions::default()
+            };
+            let report = trace_exchange_routes(
+                &events,
+                &ExchangeRegistry::builtin(),
+                "SRC",
+                "DST",
+                &options,
+            );
+
+            assert_eq!(report.routes.len(), 1, "max_hops {}", max_hops);
+            let route = &report.routes[0];
+            assert!(route.pass_through);
+            assert_eq!(route.delta_seconds, Some(3_600));
+            assert_eq!(route.wallets, vec!["SRC", BINANCE_1, BINANCE_2, "DST"]);
+            // The sweep is observed, not inferred.
+            assert_eq!(route.hops[1].volume_sol, Some(500.0));
+        }
+    }
+
+    #[test]
+    fn test_dense_graph_search_is_bounded() {
+        // Every wallet pays every other: billions of simple paths within 8 hops,
+        // none of which reach DST.
+        let wallets: Vec<String> = (0..30).map(|i| format!("W{:02}", i)).collect();
+        let mut events = vec![sol_event("dep", "SRC", BINANCE_1, 1.0, 1_000)];
+        for (i, from) in wallets.iter().enumerate() {
+            events.push(sol_event(&format!("in{}", i), BINANCE_1, from, 1.0, 1_000));
+            for (j, to) in wallets.iter().enumerate() {
+                if i != j {
+                    events.push(sol_event(&format!("e{}_{}", i, j), from, to, 1.0, 1_000));
+                }
+            }
+        }
+        let options = ExchangeRouteOptions {
+            max_hops: 8,
+            ..ExchangeRouteOptions::default()
+        };
+
+        let report = trace_exchange_routes(
+            &events,
+            &ExchangeRegistry::builtin(),
+            "SRC",
+            "DST",
+            &options,
+        );
+
+        assert!(report.routes.is_empty());
+    }
+
+    #[test]
+    fn test_withdrawal_outside_window_is_not_matched() {
+        let events = vec![
+            sol_event("dep", "SRC", BINANCE_1, 10.0, 1_000),
+            sol_event("wd", BINANCE_2, "DST", 10.0, 1_000 + 10 * 86_400),
+        ];
+
+        let report = trace_exchange_routes(
+            &events,
+            &ExchangeRegistry::builtin(),
+            "SRC",
+            "DST",
+            &ExchangeRouteOptions::default(),
+        );
+
+        assert!(report.routes.is_empty());
+        assert_eq!(report.exchanges_detected, 0);
+    }
+}
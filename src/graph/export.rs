@@ -0,0 +1,173 @@
+/// Graph export - GraphML (Gephi) and DOT (Graphviz) serialization of wallet graphs
+use crate::graph::wallet_graph::WalletGraph;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Per-wallet attributes written alongside the graph structure.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeAttributes {
+    pub risk_score: f64,
+    pub is_exchange: bool,
+}
+
+/// Wallets missing from the attribute map get `NodeAttributes::default()`.
+pub type NodeAttributeMap = HashMap<String, NodeAttributes>;
+
+fn xml_escape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn dot_escape(raw: &str) -> String {
+    raw.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl WalletGraph {
+    /// Serialize as GraphML with `risk_score`/`is_exchange` node attributes and
+    /// `amount`/`transaction_count` edge attributes.
+    pub fn to_graphml(&self, attributes: &NodeAttributeMap) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str("  <key id=\"risk_score\" for=\"node\" attr.name=\"risk_score\" attr.type=\"double\"><default>0</default></key>\n");
+        out.push_str("  <key id=\"is_exchange\" for=\"node\" attr.name=\"is_exchange\" attr.type=\"boolean\"><default>false</default></key>\n");
+        out.push_str(
+            "  <key id=\"amount\" for=\"edge\" attr.name=\"amount\" attr.type=\"double\"/>\n",
+        );
+        out.push_str("  <key id=\"transaction_count\" for=\"edge\" attr.name=\"transaction_count\" attr.type=\"int\"/>\n");
+        out.push_str("  <graph id=\"wallets\" edgedefault=\"directed\">\n");
+
+        for node in self.nodes() {
+            let attrs = attributes.get(node).copied().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"risk_score\">{}</data><data key=\"is_exchange\">{}</data></node>",
+                xml_escape(node),
+                attrs.risk_score,
+                attrs.is_exchange
+            );
+        }
+        for edge in sorted_edges(self) {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"amount\">{}</data><data key=\"transaction_count\">{}</data></edge>",
+                xml_escape(&edge.from),
+                xml_escape(&edge.to),
+                edge.amount,
+                edge.transaction_count
+            );
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Serialize as a Graphviz digraph; exchanges are drawn as boxes.
+    pub fn to_dot(&self, attributes: &NodeAttributeMap) -> String {
+        let mut out = String::from("digraph wallets {\n");
+        for node in self.nodes() {
+            let attrs = attributes.get(node).copied().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "  \"{}\" [risk_score={}, is_exchange={}, shape={}];",
+                dot_escape(node),
+                attrs.risk_score,
+                attrs.is_exchange,
+                if attrs.is_exchange { "box" } else { "ellipse" }
+            );
+        }
+        for edge in sorted_edges(self) {
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [amount={}, transaction_count={}];",
+                dot_escape(&edge.from),
+                dot_escape(&edge.to),
+                edge.amount,
+                edge.transaction_count
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Edges in a stable order so exports are reproducible.
+fn sorted_edges(graph: &WalletGraph) -> Vec<&crate::graph::WalletEdge> {
+    let mut edges: Vec<_> = graph.edges().collect();
+    edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> WalletGraph {
+        let mut graph = WalletGraph::new();
+        graph.add_transfer("A", "B", 1.5);
+        graph.add_transfer("A", "B", 0.5);
+        graph.add_transfer("B", "<C&\"D\">", 3.0);
+        graph
+    }
+
+    #[test]
+    fn test_graphml_contains_nodes_edges_and_escapes_ids() {
+        let mut attrs = NodeAttributeMap::new();
+        attrs.insert(
+            "B".to_string(),
+            NodeAttributes {
+                risk_score: 0.75,
+                is_exchange: true,
+            },
+        );
+
+        let xml = sample_graph().to_graphml(&attrs);
+
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+        assert!(xml.contains("id=\"&lt;C&amp;&quot;D&quot;&gt;\""));
+        assert!(!xml.contains("<C&"));
+        assert!(xml.contains(
+            "<node id=\"B\"><data key=\"risk_score\">0.75</data><data key=\"is_exchange\">true</data></node>"
+        ));
+        assert!(
+            xml.contains("<data key=\"amount\">2</data><data key=\"transaction_count\">2</data>")
+        );
+    }
+
+    #[test]
+    fn test_dot_contains_nodes_edges_and_escapes_ids() {
+        let dot = sample_graph().to_dot(&NodeAttributeMap::new());
+
+        assert!(dot.starts_with("digraph wallets {"));
+        assert_eq!(dot.matches(" -> ").count(), 2);
+        assert_eq!(dot.matches("shape=").count(), 3);
+        assert!(dot.contains("\"B\" -> \"<C&\\\"D\\\">\" [amount=3, transaction_count=1];"));
+    }
+
+    #[test]
+    fn test_neighborhood_limits_depth() {
+        let mut graph = sample_graph();
+        graph.add_transfer("X", "A", 1.0);
+        graph.add_transfer("Y", "Z", 1.0);
+
+        let sub = graph.neighborhood("A", 1);
+        assert_eq!(sub.node_count(), 3);
+        assert!(sub.contains("X") && sub.contains("B"));
+        assert!(!sub.contains("Y"));
+        assert_eq!(sub.edge_count(), 2);
+        assert_eq!(graph.neighborhood("missing", 3).node_count(), 0);
+    }
+}
@@ -0,0 +1,15 @@
+/// Graph module - Wallet transfer graph and graph algorithms
+pub mod algorithms;
+pub mod analysis;
+pub mod exchange_routes;
+pub mod export;
+pub mod wallet_graph;
+
+pub use algorithms::GraphAlgorithms;
+pub use analysis::{
+    CentralityOptions, ClusterMember, GraphAnalysisEngine, NetworkCentrality, WalletCentrality,
+    WalletCluster,
+};
+pub use exchange_routes::{ExchangeRoute, ExchangeRouteOptions, ExchangeRouteReport, RouteHop};
+pub use export::{NodeAttributeMap, NodeAttributes};
+pub use wallet_graph::{GraphCap, WalletEdge, WalletGraph};
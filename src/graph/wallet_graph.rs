@@ -0,0 +1,319 @@
+/// Wallet Graph - Directed, weighted graph of wallet-to-wallet transfers
+use crate::core::env::env_parse_where;
+use crate::storage::TransferEvent;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Aggregated transfers from one wallet to another.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletEdge {
+    pub from: String,
+    pub to: String,
+    /// Total SOL moved along this edge.
+    pub amount: f64,
+    /// Mint -> total token amount (UI units) moved along this edge. Only recorded
+    /// in graphs built with token weighting; empty otherwise.
+    pub token_volume_by_mint: BTreeMap<String, f64>,
+    pub transaction_count: u32,
+}
+
+impl WalletEdge {
+    /// Edge weight used by the graph algorithms: one unit per transfer plus a
+    /// log-scaled SOL volume term and one per mint, so a single large transfer
+    /// cannot dwarf sustained activity and units of different mints never add up.
+    pub fn weight(&self) -> f64 {
+        self.transaction_count as f64
+            + self.amount.max(0.0).ln_1p()
+            + self
+                .token_volume_by_mint
+                .values()
+                .map(|ui| ui.max(0.0).ln_1p())
+                .sum::<f64>()
+    }
+}
+
+/// Largest graph the analyses build; larger ones are sampled down to it (see
+/// `WalletGraph::capped`). Side-wallet expansion applies the same limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GraphCap {
+    pub max_nodes: usize,
+    pub max_edges: usize,
+}
+
+impl Default for GraphCap {
+    fn default() -> Self {
+        Self {
+            max_nodes: 20_000,
+            max_edges: 50_000,
+        }
+    }
+}
+
+impl GraphCap {
+    /// Defaults overridden by `GRAPH_MAX_NODES` and `GRAPH_MAX_EDGES` (positive).
+    pub fn from_env() -> Self {
+        let limit = |var: &str, default: usize| {
+            env_parse_where(var, default, |n: &usize| *n > 0, "a positive number")
+        };
+        let defaults = Self::default();
+        Self {
+            max_nodes: limit("GRAPH_MAX_NODES", defaults.max_nodes),
+            max_edges: limit("GRAPH_MAX_EDGES", defaults.max_edges),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WalletGraph {
+    nodes: BTreeSet<String>,
+    edges: HashMap<(String, String), WalletEdge>,
+    /// Edges were dropped to fit a `GraphCap`.
+    sampled: bool,
+}
+
+impl WalletGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a graph from stored transfer events; events without both endpoints
+    /// and self-transfers are skipped. Edges are weighted by SOL volume only.
+    pub fn from_transfer_events(events: &[TransferEvent]) -> Self {
+        Self::from_events(events, false)
+    }
+
+    /// `from_transfer_events`, also weighting edges by token volume so token-only
+    /// flows (e.g. stablecoins) count towards centrality.
+    pub fn from_transfer_events_with_tokens(events: &[TransferEvent]) -> Self {
+        Self::from_events(events, true)
+    }
+
+    fn from_events(events: &[TransferEvent], token_weight: bool) -> Self {
+        let mut graph = Self::new();
+        for ev in events {
+            let (Some(from), Some(to)) = (ev.from_wallet.as_deref(), ev.to_wallet.as_deref())
+            else {
+                continue;
+            };
+            let tokens = match (token_weight, ev.mint.as_deref(), ev.token_ui_amount()) {
+                (true, Some(mint), Some(ui)) => Some((mint, ui)),
+                _ => None,
+            };
+            graph.record(from, to, ev.amount_sol.unwrap_or(0.0), tokens);
+        }
+        graph
+    }
+
+    /// Record a single transfer, aggregating into the existing edge.
+    pub fn add_transfer(&mut self, from: &str, to: &str, amount: f64) {
+        self.record(from, to, amount, None);
+    }
+
+    /// Record a single transfer of `ui_amount` of `mint`, aggregating into the existing edge.
+    pub fn add_token_transfer(&mut self, from: &str, to: &str, mint: &str, ui_amount: f64) {
+        self.record(from, to, 0.0, Some((mint, ui_amount)));
+    }
+
+    fn record(&mut self, from: &str, to: &str, amount: f64, tokens: Option<(&str, f64)>) {
+        if from == to {
+            return;
+        }
+        self.nodes.insert(from.to_string());
+        self.nodes.insert(to.to_string());
+
+        let edge = self
+            .edges
+            .entry((from.to_string(), to.to_string()))
+            .or_insert_with(|| WalletEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                amount: 0.0,
+                token_volume_by_mint: BTreeMap::new(),
+                transaction_count: 0,
+            });
+        edge.amount += amount;
+        if let Some((mint, ui)) = tokens {
+            *edge
+                .token_volume_by_mint
+                .entry(mint.to_string())
+                .or_insert(0.0) += ui;
+        }
+        edge.transaction_count = edge.transaction_count.saturating_add(1);
+    }
+
+    /// Wallets in the graph, in sorted order.
+    pub fn nodes(&self) -> impl Iterator<Item = &String> {
+        self.nodes.iter()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &WalletEdge> {
+        self.edges.values()
+    }
+
+    pub fn edge(&self, from: &str, to: &str) -> Option<&WalletEdge> {
+        self.edges.get(&(from.to_string(), to.to_string()))
+    }
+
+    pub fn contains(&self, wallet: &str) -> bool {
+        self.nodes.contains(wallet)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Whether this graph, or the graph it was derived from, was cut down by `capped`.
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    /// The graph if it fits `cap`; otherwise a sample keeping the heaviest edges
+    /// (by `WalletEdge::weight`) that fit both limits, marked `is_sampled`.
+    pub fn capped(self, cap: GraphCap) -> WalletGraph {
+        if self.nodes.len() <= cap.max_nodes && self.edges.len() <= cap.max_edges {
+            return self;
+        }
+        let mut edges: Vec<WalletEdge> = self.edges.into_values().collect();
+        edges.sort_by(|a, b| {
+            b.weight()
+                .partial_cmp(&a.weight())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.from.cmp(&b.from))
+                .then_with(|| a.to.cmp(&b.to))
+        });
+
+        let mut out = WalletGraph {
+            sampled: true,
+            ..WalletGraph::new()
+        };
+        for edge in edges {
+            if out.edges.len() >= cap.max_edges {
+                break;
+            }
+            let new_nodes = [&edge.from, &edge.to]
+                .into_iter()
+                .filter(|w| !out.nodes.contains(*w))
+                .count();
+            if out.nodes.len() + new_nodes > cap.max_nodes {
+                continue;
+            }
+            out.nodes.insert(edge.from.clone());
+            out.nodes.insert(edge.to.clone());
+            out.edges.insert((edge.from.clone(), edge.to.clone()), edge);
+        }
+        out
+    }
+
+    /// Copy without edges that moved less than `min_sol` SOL or carried fewer than
+    /// `min_tx_count` transfers, and without the wallets left with no edges, so
+    /// dust does not skew the graph algorithms. Edges with recorded token volume
+    /// (graphs built with token weighting) are exempt from `min_sol`, which has no
+    /// meaning for token amounts; without token weighting, token-only edges carry
+    /// no SOL and are dropped by any positive `min_sol`.
+    pub fn pruned(&self, min_sol: f64, min_tx_count: u32) -> WalletGraph {
+        let mut out = WalletGraph {
+            sampled: self.sampled,
+            ..WalletGraph::new()
+        };
+        for (key, edge) in &self.edges {
+            let below_sol = edge.amount < min_sol && edge.token_volume_by_mint.is_empty();
+            if below_sol || edge.transaction_count < min_tx_count {
+                continue;
+            }
+            out.nodes.insert(edge.from.clone());
+            out.nodes.insert(edge.to.clone());
+            out.edges.insert(key.clone(), edge.clone());
+        }
+        out
+    }
+
+    /// Subgraph of wallets within `depth` hops of `wallet` (ignoring direction),
+    /// with every edge between them.
+    pub fn neighborhood(&self, wallet: &str, depth: usize) -> WalletGraph {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (from, to) in self.edges.keys() {
+            adjacency.entry(from).or_default().push(to);
+            adjacency.entry(to).or_default().push(from);
+        }
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue = VecDeque::new();
+        if self.contains(wallet) {
+            seen.insert(wallet);
+            queue.push_back((wallet, 0usize));
+        }
+        while let Some((current, hops)) = queue.pop_front() {
+            if hops >= depth {
+                continue;
+            }
+            for next in adjacency.get(current).into_iter().flatten() {
+                if seen.insert(next) {
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+
+        let mut sub = WalletGraph {
+            sampled: self.sampled,
+            ..WalletGraph::new()
+        };
+        sub.nodes = seen.iter().map(|w| w.to_string()).collect();
+        for ((from, to), edge) in &self.edges {
+            if seen.contains(from.as_str()) && seen.contains(to.as_str()) {
+                sub.edges.insert((from.clone(), to.clone()), edge.clone());
+            }
+        }
+        sub
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capped_keeps_heaviest_edges_of_a_huge_hub() {
+        let mut graph = WalletGraph::new();
+        for i in 0..50_000u32 {
+            graph.add_transfer("HUB", &format!("W{}", i), 1.0);
+        }
+        // A few busy counterparties outweigh the one-off ones.
+        for _ in 0..5 {
+            graph.add_transfer("HUB", "W7", 1.0);
+            graph.add_transfer("W42", "HUB", 1.0);
+        }
+        assert_eq!(graph.edge_count(), 50_001);
+
+        let cap = GraphCap {
+            max_nodes: 100,
+            max_edges: 1_000,
+        };
+        let sampled = graph.clone().capped(cap);
+        assert!(sampled.is_sampled());
+        assert_eq!(sampled.node_count(), 100);
+        // HUB plus 99 counterparties; W42 has an edge each way.
+        assert_eq!(sampled.edge_count(), 100);
+        assert!(sampled.edge("HUB", "W7").is_some());
+        assert!(sampled.edge("W42", "HUB").is_some());
+        // Derived graphs stay marked.
+        assert!(sampled.pruned(0.0, 1).is_sampled());
+        assert!(sampled.neighborhood("HUB", 1).is_sampled());
+
+        let edge_capped = graph.clone().capped(GraphCap {
+            max_nodes: 100_000,
+            max_edges: 1_000,
+        });
+        assert_eq!(edge_capped.edge_count(), 1_000);
+
+        let whole = graph.capped(GraphCap {
+            max_nodes: 100_000,
+            max_edges: 100_000,
+        });
+        assert!(!whole.is_sampled());
+        assert_eq!(whole.edge_count(), 50_001);
+    }
+}
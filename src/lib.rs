@@ -1,5 +1,8 @@
 pub mod api;
 pub mod auth;
 pub mod core;
+pub mod dex;
+pub mod graph;
+pub mod middleware;
 pub mod modules;
 pub mod storage;
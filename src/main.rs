@@ -1,16 +1,15 @@
 use onchain_beast::api;
 use onchain_beast::auth;
+use onchain_beast::core::env::env_parse;
 use onchain_beast::core::rpc_client::SolanaRpcClient;
 use onchain_beast::storage::DatabaseManager;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
-    let rpc_endpoint = std::env::var("SOLANA_RPC_ENDPOINT")
-        .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
-
     let database_url = std::env::var("DATABASE_URL")
         .ok()
         .map(|s| s.trim().to_string())
@@ -31,7 +30,28 @@ async fn main() -> anyhow::Result<()> {
     let db_manager = Arc::new(DatabaseManager::new(&database_url).await?);
     db_manager.init_schema().await?;
 
-    let rpc_client = Arc::new(SolanaRpcClient::new(rpc_endpoint));
+    // SOLANA_RPC_ENDPOINT and/or a comma-separated RPC_ENDPOINTS pool.
+    let rpc_client = Arc::new(SolanaRpcClient::from_env());
+
+    // Optional background job filling in block times the RPC omitted at ingestion.
+    let backfill_secs = env_parse("BLOCK_TIME_BACKFILL_SECS", 0u64);
+    if backfill_secs > 0 {
+        let db_manager = Arc::clone(&db_manager);
+        let rpc_client = Arc::clone(&rpc_client);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(backfill_secs.max(60)));
+            loop {
+                interval.tick().await;
+                match db_manager.backfill_block_times(&rpc_client, 200).await {
+                    Ok(stats) if stats.slots_checked > 0 => {
+                        tracing::info!("Block time backfill: {:?}", stats)
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Block time backfill failed: {}", e),
+                }
+            }
+        });
+    }
 
     // Render (and some other PaaS) provide a required `PORT` env var. Prefer it if set.
     // When running locally, default to 127.0.0.1:8080 unless overridden via API_HOST/API_PORT.
@@ -0,0 +1,2 @@
+/// HTTP middleware shared by the API server
+pub mod timeout;
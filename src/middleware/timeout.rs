@@ -0,0 +1,189 @@
+/// Request timeouts - overall time budget per request, with longer budgets for
+/// routes that ingest over RPC
+///
+/// A request over budget is answered with `504` and the uniform error body
+/// (code `TIMEOUT`); its handler future is dropped. Ingestion writes already
+/// started keep running in their own tasks (see `TransferAnalytics`).
+use crate::api::responses::{ApiError, RequestId};
+use crate::core::env::env_parse_in;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::HttpMessage;
+use std::time::Duration;
+
+/// Side-wallet search; bootstraps unless told not to or the key is read-only.
+pub(crate) const SIDE_WALLETS_ROUTE: &str = "/api/v1/wallet/{address}/side-wallets";
+
+/// Routes that may bootstrap (fetch and parse transactions over RPC) and get
+/// the longer budget.
+pub(crate) const BOOTSTRAP_ROUTES: &[&str] = &[
+    SIDE_WALLETS_ROUTE,
+    "/api/v1/parse/batch",
+    "/api/v1/wallet/{address}/ingest",
+];
+
+/// Default budget plus per-route overrides.
+#[derive(Debug, Clone)]
+pub struct RequestTimeouts {
+    default: Duration,
+    /// Route patterns (`{name}` matches one path segment) with their budget.
+    overrides: Vec<(String, Duration)>,
+}
+
+impl RequestTimeouts {
+    /// Budgets come from `REQUEST_TIMEOUT_SECS` (default 60) and, for bootstrap
+    /// routes, `BOOTSTRAP_REQUEST_TIMEOUT_SECS` (default 300).
+    pub fn from_env() -> Self {
+        let secs = |var: &str, default: u64| env_parse_in(var, default, 1..=3_600);
+        let bootstrap = Duration::from_secs(secs("BOOTSTRAP_REQUEST_TIMEOUT_SECS", 300));
+        BOOTSTRAP_ROUTES.iter().fold(
+            Self::new(Duration::from_secs(secs("REQUEST_TIMEOUT_SECS", 60))),
+            |timeouts, route| timeouts.with_override(route, bootstrap),
+        )
+    }
+
+    pub fn new(default: Duration) -> Self {
+        Self {
+            default,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Use `timeout` for paths matching `pattern` (e.g. `/api/v1/wallet/{address}/cluster`).
+    pub fn with_override(mut self, pattern: &str, timeout: Duration) -> Self {
+        self.overrides.push((pattern.to_string(), timeout));
+        self
+    }
+
+    /// Budget for a request path: the first matching override, else the default.
+    pub fn timeout_for(&self, path: &str) -> Duration {
+        self.overrides
+            .iter()
+            .find(|(pattern, _)| pattern_matches(pattern, path))
+            .map(|(_, timeout)| *timeout)
+            .unwrap_or(self.default)
+    }
+}
+
+/// Whether `path` matches a route `pattern` (`{name}` matches one path segment).
+pub(crate) fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim_end_matches('/').split('/');
+    let mut path = path.trim_end_matches('/').split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s))
+                if p == s || (p.starts_with('{') && p.ends_with('}') && !s.is_empty()) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Answer with `504 TIMEOUT` when the rest of the chain runs past the route's
+/// budget. Wrap it inside `request_id_middleware` so the error carries the id.
+pub async fn timeout_middleware(
+    timeouts: &RequestTimeouts,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let budget = timeouts.timeout_for(req.path());
+    let route = format!("{} {}", req.method(), req.path());
+    let request_id = req.extensions().get::<RequestId>().cloned();
+
+    match tokio::time::timeout(budget, next.call(req)).await {
+        Ok(res) => res,
+        Err(_) => {
+            tracing::warn!("{} timed out after {:?}", route, budget);
+            let mut err = ApiError::timeout(format!(
+                "request exceeded its {}s time budget",
+                budget.as_secs_f64()
+            ));
+            if let Some(RequestId(id)) = request_id {
+                err = err.with_request_id(id);
+            }
+            Err(err.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::responses::{request_id_middleware, REQUEST_ID_HEADER};
+    use actix_web::http::StatusCode;
+    use actix_web::{middleware, test, web, App, HttpResponse};
+    use std::sync::Arc;
+
+    async fn slow() -> HttpResponse {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_overrides_match_route_patterns() {
+        let timeouts = RequestTimeouts::new(Duration::from_secs(60)).with_override(
+            "/api/v1/wallet/{address}/side-wallets",
+            Duration::from_secs(300),
+        );
+
+        assert_eq!(
+            timeouts.timeout_for("/api/v1/wallet/W1/side-wallets"),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            timeouts.timeout_for("/api/v1/wallet/W1/side-wallets/"),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            timeouts.timeout_for("/api/v1/wallet/W1/cluster"),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            timeouts.timeout_for("/api/v1/wallet//side-wallets"),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_slow_handler_gets_504_with_request_id_unless_overridden() {
+        let timeouts = Arc::new(
+            RequestTimeouts::new(Duration::from_millis(50))
+                .with_override("/slow/{id}/bootstrap", Duration::from_secs(5)),
+        );
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware::from_fn(move |req, next| {
+                    let timeouts = Arc::clone(&timeouts);
+                    async move { timeout_middleware(&timeouts, req, next).await }
+                }))
+                .wrap(middleware::from_fn(request_id_middleware))
+                .route("/slow", web::get().to(slow))
+                .route("/slow/{id}/bootstrap", web::get().to(slow)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/slow")
+            .insert_header((REQUEST_ID_HEADER, "req-slow"))
+            .to_request();
+        // Middleware errors are rendered by the server, as `error_response` does here.
+        let resp = test::try_call_service(&app, req)
+            .await
+            .err()
+            .expect("slow request should time out")
+            .error_response();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "req-slow");
+        let bytes = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "TIMEOUT");
+        assert_eq!(body["error"]["request_id"], "req-slow");
+
+        let req = test::TestRequest::get()
+            .uri("/slow/7/bootstrap")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::core::errors::BeastResult;
+use crate::storage::{BehavioralProfile, DatabaseManager};
+use serde::Serialize;
+use std::sync::Arc;
+
+type MetricFn = fn(&BehavioralProfile) -> f64;
+
+/// A metric on which a wallet sits far above the population baseline.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyTrigger {
+    /// "transfers_per_day" | "sol_volume_per_day"
+    pub metric: String,
+    pub value: f64,
+    pub z_score: f64,
+    pub population_mean: f64,
+    pub population_stddev: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusualWallet {
+    pub wallet: String,
+    pub max_z_score: f64,
+    pub triggers: Vec<AnomalyTrigger>,
+}
+
+/// Statistical anomaly detection: flags wallets whose daily transfer activity is
+/// more than `z_threshold` standard deviations above the population mean.
+pub struct AnomalyDetector {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl AnomalyDetector {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager }
+    }
+
+    /// Scan behavioral profiles in the window and return unusual wallets,
+    /// strongest first.
+    pub async fn detect(
+        &self,
+        since_epoch: Option<u64>,
+        profile_limit: usize,
+        z_threshold: f64,
+    ) -> BeastResult<(usize, Vec<UnusualWallet>)> {
+        let profiles = self
+            .db_manager
+            .get_all_behavioral_profiles(since_epoch, profile_limit)
+            .await?;
+        Ok((
+            profiles.len(),
+            Self::unusual_wallets(&profiles, z_threshold),
+        ))
+    }
+
+    /// Flag profiles whose per-day transfer count or SOL volume exceeds the z threshold.
+    pub fn unusual_wallets(profiles: &[BehavioralProfile], z_threshold: f64) -> Vec<UnusualWallet> {
+        let metrics: [(&str, MetricFn); 2] = [
+            ("transfers_per_day", |p| p.avg_tx_per_day),
+            ("sol_volume_per_day", |p| {
+                p.avg_tx_per_day * p.avg_sol_per_tx
+            }),
+        ];
+
+        let baselines: Vec<(f64, f64)> = metrics
+            .iter()
+            .map(|(_, f)| mean_stddev(profiles.iter().map(f)))
+            .collect();
+
+        let mut out: Vec<UnusualWallet> = profiles
+            .iter()
+            .filter_map(|p| {
+                let triggers: Vec<AnomalyTrigger> = metrics
+                    .iter()
+                    .zip(&baselines)
+                    .filter_map(|((name, f), &(mean, stddev))| {
+                        if stddev <= f64::EPSILON {
+                            return None;
+                        }
+                        let value = f(p);
+                        let z_score = (value - mean) / stddev;
+                        (z_score > z_threshold).then(|| AnomalyTrigger {
+                            metric: name.to_string(),
+                            value,
+                            z_score,
+                            population_mean: mean,
+                            population_stddev: stddev,
+                        })
+                    })
+                    .collect();
+
+                let max_z_score = triggers.iter().map(|t| t.z_score).fold(f64::MIN, f64::max);
+                (!triggers.is_empty()).then(|| UnusualWallet {
+                    wallet: p.wallet.clone(),
+                    max_z_score,
+                    triggers,
+                })
+            })
+            .collect();
+
+        out.sort_by(|a, b| {
+            b.max_z_score
+                .partial_cmp(&a.max_z_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.wallet.cmp(&b.wallet))
+        });
+        out
+    }
+}
+
+/// Population mean and standard deviation.
+fn mean_stddev(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::enhanced_parser::{EnhancedTransaction, SolTransfer, TransactionType};
+
+    fn sol_tx(signature: &str, block_time: u64, from: &str, to: &str) -> EnhancedTransaction {
+        EnhancedTransaction {
+            signature: signature.to_string(),
+            slot: 1,
+            block_time: Some(block_time),
+            fee: 0,
+            success: true,
+            error: None,
+            compute_units_consumed: None,
+            compute_unit_price_micro_lamports: None,
+            priority_fee_lamports: None,
+            accounts: Vec::new(),
+            signers: Vec::new(),
+            writable_accounts: Vec::new(),
+            sol_transfers: vec![SolTransfer {
+                from: from.to_string(),
+                to: to.to_string(),
+                amount_lamports: 1_000_000_000,
+                amount_sol: 1.0,
+                instruction_index: 0,
+                transfer_type: "system".to_string(),
+            }],
+            token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            stake_actions: Vec::new(),
+            mint_burn_events: Vec::new(),
+            nft_trades: Vec::new(),
+            balance_changes: Vec::new(),
+            programs_called: Vec::new(),
+            program_names: Vec::new(),
+            tx_type: TransactionType::SystemTransfer,
+            is_versioned: false,
+            finalized: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flags_only_the_outlier() {
+        let db = Arc::new(DatabaseManager::in_memory());
+        let t = 1_700_000_000;
+
+        // A ring of 20 ordinary wallets, each sending twice to its neighbour.
+        let ring: Vec<String> = (0..20).map(|i| format!("W{:02}", i)).collect();
+        for (i, from) in ring.iter().enumerate() {
+            let to = &ring[(i + 1) % ring.len()];
+            for k in 0..2 {
+                let tx = sol_tx(&format!("ring_{}_{}", i, k), t, from, to);
+                db.store_sol_transfer_event(&tx, &tx.sol_transfers[0], 0)
+                    .await
+                    .unwrap();
+            }
+        }
+        // One wallet spraying 60 transfers across the ring.
+        for k in 0..60 {
+            let tx = sol_tx(&format!("spray_{}", k), t, "OUTLIER", &ring[k % ring.len()]);
+            db.store_sol_transfer_event(&tx, &tx.sol_transfers[0], 0)
+                .await
+                .unwrap();
+        }
+
+        let detector = AnomalyDetector::new(db);
+        let (scanned, unusual) = detector.detect(None, 1000, 3.0).await.unwrap();
+
+        assert_eq!(scanned, 21);
+        assert_eq!(unusual.len(), 1, "got {:?}", unusual);
+        assert_eq!(unusual[0].wallet, "OUTLIER");
+        assert!(unusual[0]
+            .triggers
+            .iter()
+            .any(|t| t.metric == "transfers_per_day" && t.z_score > 3.0));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::core::errors::{BeastError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A known centralized-exchange wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExchangeInfo {
+    pub address: String,
+    /// Exchange name, e.g. "Binance".
+    pub exchange: String,
+    /// "hot", "deposit" or "cold".
+    #[serde(default = "default_wallet_type")]
+    pub wallet_type: String,
+}
+
+fn default_wallet_type() -> String {
+    "hot".to_string()
+}
+
+/// Publicly labelled exchange hot wallets shipped with the binary.
+const BUILTIN_EXCHANGES: &[(&str, &str)] = &[
+    ("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "Binance"),
+    ("5tzFkiKscXHK5ZXCGbXZxdw7gTjjD1mBwuoFbhUvuAi9", "Binance"),
+    ("H8sMJSCQxfKiFTCfDR3DUMLPwcRbM61LGFJ8N4dK3WjS", "Coinbase"),
+    ("FWznbcNXWQuHTawe9RxvQ2LdCENssh12dsznf4RiouN5", "Kraken"),
+    ("5VCwKtCXgCJ6kit5FybXjvriW3xELsFDhYrPSqtJNmcD", "OKX"),
+    ("AC5RDfQFmDS1deWZos921JfqscXdByf8BKHs5ACWjtW2", "Bybit"),
+];
+
+/// Address → exchange lookup used to label CEX hops and graph nodes.
+#[derive(Debug, Clone)]
+pub struct ExchangeRegistry {
+    entries: HashMap<String, ExchangeInfo>,
+}
+
+impl ExchangeRegistry {
+    /// Built-in list, extended (and overridden per address) by the JSON file at
+    /// `EXCHANGE_REGISTRY_PATH` if set. A file that cannot be loaded is logged
+    /// and ignored.
+    pub fn from_env() -> Self {
+        let mut registry = Self::builtin();
+        if let Some(path) = std::env::var("EXCHANGE_REGISTRY_PATH")
+            .ok()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+        {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| BeastError::ConfigError(format!("{}: {}", path, e)))
+                .and_then(|json| registry.merge_json(&json))
+            {
+                Ok(added) => tracing::info!("Loaded {} exchange addresses from {}", added, path),
+                Err(e) => tracing::warn!("Ignoring exchange registry override: {}", e),
+            }
+        }
+        registry
+    }
+
+    pub fn builtin() -> Self {
+        let entries = BUILTIN_EXCHANGES
+            .iter()
+            .map(|(address, exchange)| {
+                (
+                    address.to_string(),
+                    ExchangeInfo {
+                        address: address.to_string(),
+                        exchange: exchange.to_string(),
+                        wallet_type: default_wallet_type(),
+                    },
+                )
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Merge a JSON array of `{ "address", "exchange", "wallet_type"? }` entries,
+    /// replacing existing entries for the same address. Returns how many were read.
+    pub fn merge_json(&mut self, json: &str) -> Result<usize> {
+        let overrides: Vec<ExchangeInfo> = serde_json::from_str(json)
+            .map_err(|e| BeastError::ConfigError(format!("Invalid exchange registry: {}", e)))?;
+        let count = overrides.len();
+        for info in overrides {
+            self.entries.insert(info.address.clone(), info);
+        }
+        Ok(count)
+    }
+
+    pub fn identify_exchange(&self, address: &str) -> Option<&ExchangeInfo> {
+        self.entries.get(address)
+    }
+
+    pub fn is_exchange(&self, address: &str) -> bool {
+        self.entries.contains_key(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for ExchangeRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifies_known_binance_hot_wallet() {
+        let registry = ExchangeRegistry::builtin();
+        let info = registry
+            .identify_exchange("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM")
+            .unwrap();
+        assert_eq!(info.exchange, "Binance");
+        assert_eq!(info.wallet_type, "hot");
+        assert!(registry
+            .identify_exchange("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU")
+            .is_none());
+    }
+
+    #[test]
+    fn test_json_overrides_and_extends_builtin() {
+        let mut registry = ExchangeRegistry::builtin();
+        let before = registry.len();
+        let added = registry
+            .merge_json(
+                r#"[
+                    { "address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "exchange": "Binance", "wallet_type": "cold" },
+                    { "address": "DepositAddr1111111111111111111111111111111", "exchange": "Gate.io", "wallet_type": "deposit" }
+                ]"#,
+            )
+            .unwrap();
+
+        assert_eq!(added, 2);
+        assert_eq!(registry.len(), before + 1);
+        assert_eq!(
+            registry
+                .identify_exchange("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM")
+                .unwrap()
+                .wallet_type,
+            "cold"
+        );
+        assert!(registry.is_exchange("DepositAddr1111111111111111111111111111111"));
+        assert!(registry.merge_json("{not json").is_err());
+    }
+}
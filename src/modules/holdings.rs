@@ -0,0 +1,153 @@
+use crate::core::env::env_parse_in;
+use crate::core::errors::Result;
+use crate::core::rpc_client::{Commitment, TokenHolding};
+use crate::core::units::lamports_to_sol;
+use crate::core::SolanaRpcClient;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Point-in-time SOL and token balances for a wallet.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletHoldings {
+    pub wallet: String,
+    pub sol_balance: f64,
+    pub tokens: Vec<TokenHolding>,
+    pub fetched_at_epoch: u64,
+}
+
+/// Holdings snapshots fetched over RPC, cached briefly per wallet.
+pub struct HoldingsTracker {
+    rpc_client: Arc<SolanaRpcClient>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, (Instant, WalletHoldings)>>,
+}
+
+impl HoldingsTracker {
+    /// Cache TTL comes from `HOLDINGS_CACHE_TTL_SECS` (default 30).
+    pub fn new(rpc_client: Arc<SolanaRpcClient>) -> Self {
+        let ttl_secs = env_parse_in("HOLDINGS_CACHE_TTL_SECS", 30u64, 0..=3_600);
+        Self::with_ttl(rpc_client, Duration::from_secs(ttl_secs))
+    }
+
+    pub fn with_ttl(rpc_client: Arc<SolanaRpcClient>, ttl: Duration) -> Self {
+        Self {
+            rpc_client,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Current holdings for `wallet`; empty token accounts are omitted.
+    pub async fn get_holdings(&self, wallet: &str) -> Result<WalletHoldings> {
+        {
+            let cache = self.cache.read().await;
+            if let Some((at, holdings)) = cache.get(wallet) {
+                if at.elapsed() < self.ttl {
+                    return Ok(holdings.clone());
+                }
+            }
+        }
+
+        let holdings = fetch_holdings(&self.rpc_client, wallet).await?;
+
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        cache.insert(wallet.to_string(), (Instant::now(), holdings.clone()));
+
+        Ok(holdings)
+    }
+
+    /// Holdings read at `commitment`. Only reads at the client's own commitment
+    /// are cached.
+    pub async fn get_holdings_at(
+        &self,
+        wallet: &str,
+        commitment: Commitment,
+    ) -> Result<WalletHoldings> {
+        if commitment == self.rpc_client.commitment() {
+            return self.get_holdings(wallet).await;
+        }
+        fetch_holdings(&self.rpc_client.with_commitment(commitment), wallet).await
+    }
+}
+
+async fn fetch_holdings(rpc_client: &SolanaRpcClient, wallet: &str) -> Result<WalletHoldings> {
+    let sol_lamports = rpc_client
+        .get_multiple_accounts(&[wallet])
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|a| a.balance)
+        .sum::<u64>();
+
+    let mut tokens: Vec<TokenHolding> = rpc_client
+        .get_token_accounts_by_owner(wallet)
+        .await?
+        .into_iter()
+        .filter(|h| h.amount > 0)
+        .collect();
+    tokens.sort_by(|a, b| {
+        b.ui_amount
+            .partial_cmp(&a.ui_amount)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.mint.cmp(&b.mint))
+    });
+
+    Ok(WalletHoldings {
+        wallet: wallet.to_string(),
+        sol_balance: lamports_to_sol(sol_lamports),
+        tokens,
+        fetched_at_epoch: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rpc_client::tests::mock_endpoint;
+
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    #[tokio::test]
+    async fn test_holdings_include_sol_and_are_cached() {
+        let accounts = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [{
+            "lamports": 1_500_000_000u64,
+            "owner": "11111111111111111111111111111111",
+            "executable": false,
+            "rentEpoch": 0
+        }] } });
+        let tokens = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [
+            { "pubkey": "AtaA", "account": { "data": { "parsed": { "info": {
+                "mint": "MintA", "tokenAmount": { "amount": "0", "decimals": 6 } } } } } },
+            { "pubkey": "AtaB", "account": { "data": { "parsed": { "info": {
+                "mint": "MintB", "tokenAmount": { "amount": "4200", "decimals": 2 } } } } } }
+        ] } });
+        let empty = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [] } });
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, accounts.to_string()),
+            (200, tokens.to_string()),
+            (200, empty.to_string()),
+        ])
+        .await;
+
+        let tracker = HoldingsTracker::with_ttl(
+            Arc::new(SolanaRpcClient::new(endpoint)),
+            Duration::from_secs(60),
+        );
+        let holdings = tracker.get_holdings(WALLET).await.unwrap();
+
+        assert!((holdings.sol_balance - 1.5).abs() < 1e-9);
+        assert_eq!(holdings.tokens.len(), 1);
+        assert_eq!(holdings.tokens[0].mint, "MintB");
+        assert!((holdings.tokens[0].ui_amount - 42.0).abs() < 1e-9);
+
+        tracker.get_holdings(WALLET).await.unwrap();
+        assert_eq!(requests.lock().await.len(), 3);
+    }
+}
@@ -1,5 +1,29 @@
+pub mod anomaly_detector;
+pub mod exchange_registry;
+pub mod holdings;
+pub mod price_history;
+pub mod risk;
+pub mod sandwich;
+pub mod side_wallet_scoring;
+pub mod similarity;
+pub mod structuring;
+pub mod token_supply;
 pub mod transaction_handler;
 pub mod transfer_analytics;
 
+pub use anomaly_detector::AnomalyDetector;
+pub use exchange_registry::{ExchangeInfo, ExchangeRegistry};
+pub use holdings::HoldingsTracker;
+pub use price_history::{price_history, PriceBucket};
+pub use risk::{
+    assess_risk, freshness, RiskAssessment, RiskFactor, RiskWeights, WalletAge, FRESH_WALLET_DAYS,
+};
+pub use sandwich::{detect_sandwiches, SandwichEvent, SANDWICH_SLOT_WINDOW};
+pub use side_wallet_scoring::{
+    ignored_mints_from_env, terminal_accounts_from_env, SideWalletScoringConfig,
+};
+pub use similarity::{behavioral_similarity, rank_similar_wallets, SimilarWallet};
+pub use structuring::{detect_structuring, detect_structuring_filtered, StructuringFlag};
+pub use token_supply::TokenSupplyTracker;
 pub use transaction_handler::TransactionHandler;
 pub use transfer_analytics::TransferAnalytics;
@@ -0,0 +1,131 @@
+use crate::dex::types::WSOL_MINT;
+use crate::dex::SwapEvent;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// SOL-denominated price candle for one time bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceBucket {
+    /// Unix time the bucket starts at (a multiple of the bucket length).
+    pub bucket_start: u64,
+    /// SOL per token of the first and last swap in the bucket.
+    pub open: f64,
+    pub close: f64,
+    pub high: f64,
+    pub low: f64,
+    /// Volume-weighted price: SOL traded over tokens traded.
+    pub vwap: f64,
+    /// Tokens bought and sold against SOL.
+    pub volume: f64,
+    pub volume_sol: f64,
+    pub swaps: u64,
+}
+
+/// Price of `mint` in SOL per `bucket_secs` bucket, from swaps pairing it with
+/// SOL in either direction (SOL spent per token bought, or SOL received per token
+/// sold). Swaps against other mints, without a block time or with a zero amount
+/// are skipped. Oldest bucket first.
+pub fn price_history(swaps: &[SwapEvent], mint: &str, bucket_secs: u64) -> Vec<PriceBucket> {
+    let bucket_secs = bucket_secs.max(1);
+    let mut trades: Vec<(&SwapEvent, u64, f64, f64)> = swaps
+        .iter()
+        .filter_map(|swap| {
+            let block_time = swap.block_time?;
+            let (tokens, sol) = if swap.token_in_mint == mint && swap.token_out_mint == WSOL_MINT {
+                (swap.amount_in, swap.amount_out)
+            } else if swap.token_in_mint == WSOL_MINT && swap.token_out_mint == mint {
+                (swap.amount_out, swap.amount_in)
+            } else {
+                return None;
+            };
+            (tokens > 0.0 && sol > 0.0).then_some((swap, block_time, tokens, sol))
+        })
+        .collect();
+    trades.sort_by(|(a, a_time, ..), (b, b_time, ..)| {
+        a_time
+            .cmp(b_time)
+            .then_with(|| a.slot.cmp(&b.slot))
+            .then_with(|| a.signature.cmp(&b.signature))
+            .then_with(|| a.event_index.cmp(&b.event_index))
+    });
+
+    let mut buckets: BTreeMap<u64, PriceBucket> = BTreeMap::new();
+    for (_, block_time, tokens, sol) in trades {
+        let price = sol / tokens;
+        let start = block_time - block_time % bucket_secs;
+        let bucket = buckets.entry(start).or_insert(PriceBucket {
+            bucket_start: start,
+            open: price,
+            close: price,
+            high: price,
+            low: price,
+            vwap: 0.0,
+            volume: 0.0,
+            volume_sol: 0.0,
+            swaps: 0,
+        });
+        bucket.close = price;
+        bucket.high = bucket.high.max(price);
+        bucket.low = bucket.low.min(price);
+        bucket.volume += tokens;
+        bucket.volume_sol += sol;
+        bucket.swaps += 1;
+    }
+
+    buckets
+        .into_values()
+        .map(|mut bucket| {
+            bucket.vwap = bucket.volume_sol / bucket.volume;
+            bucket
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(
+        signature: &str,
+        block_time: Option<u64>,
+        (token_in, amount_in): (&str, f64),
+        (token_out, amount_out): (&str, f64),
+    ) -> SwapEvent {
+        SwapEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot: block_time.unwrap_or(0),
+            block_time,
+            wallet: "TRADER".to_string(),
+            dex_name: "Raydium V4".to_string(),
+            program_id: None,
+            pool_address: Some("POOL".to_string()),
+            token_in_mint: token_in.to_string(),
+            amount_in,
+            token_out_mint: token_out.to_string(),
+            amount_out,
+        }
+    }
+
+    #[test]
+    fn test_buys_and_sells_price_the_same_way_and_other_pairs_are_skipped() {
+        let swaps = vec![
+            // Newest first, as stored swaps are listed.
+            swap("sell", Some(7_300), ("MEME", 200.0), (WSOL_MINT, 3.0)),
+            swap("buy", Some(7_250), (WSOL_MINT, 1.0), ("MEME", 100.0)),
+            swap("usdc", Some(7_260), ("USDC", 5.0), ("MEME", 1_000.0)),
+            swap("untimed", None, (WSOL_MINT, 1.0), ("MEME", 1.0)),
+            swap("empty", Some(7_270), (WSOL_MINT, 0.0), ("MEME", 10.0)),
+        ];
+
+        let buckets = price_history(&swaps, "MEME", 3_600);
+        assert_eq!(buckets.len(), 1);
+        let b = &buckets[0];
+        assert_eq!(b.bucket_start, 7_200);
+        assert_eq!(b.swaps, 2);
+        assert_eq!((b.open, b.close), (0.01, 0.015));
+        assert_eq!((b.low, b.high), (0.01, 0.015));
+        assert_eq!((b.volume, b.volume_sol), (300.0, 4.0));
+        assert!((b.vwap - 4.0 / 300.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::core::env::env_parse_where;
+use crate::modules::StructuringFlag;
+use crate::storage::CounterpartyNetFlow;
+use serde::Serialize;
+
+/// Relative weight of each risk factor; normalised to sum to 1 when scoring.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RiskWeights {
+    pub structuring: f64,
+    pub pass_through: f64,
+    pub connections: f64,
+    pub fresh_wallet: f64,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            structuring: 0.45,
+            pass_through: 0.35,
+            connections: 0.20,
+            fresh_wallet: 0.10,
+        }
+    }
+}
+
+impl RiskWeights {
+    /// Defaults overridden by `RISK_WEIGHT_STRUCTURING`, `RISK_WEIGHT_PASS_THROUGH`,
+    /// `RISK_WEIGHT_CONNECTIONS` and `RISK_WEIGHT_FRESH_WALLET` (non-negative numbers).
+    pub fn from_env() -> Self {
+        let weight = |var: &str, default: f64| {
+            env_parse_where(
+                var,
+                default,
+                |w: &f64| w.is_finite() && *w >= 0.0,
+                "a non-negative number",
+            )
+        };
+        let defaults = Self::default();
+        Self {
+            structuring: weight("RISK_WEIGHT_STRUCTURING", defaults.structuring),
+            pass_through: weight("RISK_WEIGHT_PASS_THROUGH", defaults.pass_through),
+            connections: weight("RISK_WEIGHT_CONNECTIONS", defaults.connections),
+            fresh_wallet: weight("RISK_WEIGHT_FRESH_WALLET", defaults.fresh_wallet),
+        }
+    }
+
+    fn normalized(self) -> Self {
+        let total = self.structuring + self.pass_through + self.connections + self.fresh_wallet;
+        if total <= 0.0 {
+            return Self::default();
+        }
+        Self {
+            structuring: self.structuring / total,
+            pass_through: self.pass_through / total,
+            connections: self.connections / total,
+            fresh_wallet: self.fresh_wallet / total,
+        }
+    }
+}
+
+/// One input to the risk score: `contribution = signal * weight`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskFactor {
+    pub name: &'static str,
+    /// Strength of the signal, 0.0 - 1.0.
+    pub signal: f64,
+    /// Normalised weight (all weights sum to 1).
+    pub weight: f64,
+    pub contribution: f64,
+    pub detail: String,
+}
+
+/// Explainable wallet risk: `score` is the sum of the factor contributions.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskAssessment {
+    pub score: f64,
+    pub factors: Vec<RiskFactor>,
+}
+
+/// Counterparty count at which the breadth signal saturates.
+const CONNECTIONS_SATURATION: f64 = 200.0;
+
+/// Wallets first seen less than this many days ago count as freshly created.
+pub const FRESH_WALLET_DAYS: f64 = 7.0;
+
+/// How fresh a wallet first seen `age_days` ago is: 1.0 when brand new, falling
+/// linearly to 0.0 at `FRESH_WALLET_DAYS`.
+pub fn freshness(age_days: f64) -> f64 {
+    (1.0 - age_days.max(0.0) / FRESH_WALLET_DAYS).clamp(0.0, 1.0)
+}
+
+/// Age inputs to the `fresh_wallet` factor.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WalletAge {
+    /// Days since the wallet's first stored transfer; `None` if it has none.
+    pub age_days: Option<f64>,
+    /// Share of its counterparties that are fresh wallets, 0.0 - 1.0.
+    pub fresh_counterparty_share: f64,
+}
+
+/// Combine structuring flags and per-counterparty flows into a weighted risk score.
+///
+/// - `structuring`: strongest structuring flag's confidence (bursts of same-size transfers)
+/// - `pass_through`: how closely SOL out matches SOL in (relaying, as mixers and
+///   peel chains do), `min(in, out) / max(in, out)`
+/// - `connections`: number of counterparties on a log scale, saturating at 200
+/// - `fresh_wallet`: the wallet's own `freshness`, or half the share of fresh
+///   counterparties if higher, so a cluster of new wallets adds a little risk
+pub fn assess_risk(
+    structuring: &[StructuringFlag],
+    flows: &[CounterpartyNetFlow],
+    age: WalletAge,
+    weights: RiskWeights,
+) -> RiskAssessment {
+    let weights = weights.normalized();
+
+    let structuring_signal = structuring
+        .iter()
+        .map(|f| f.confidence)
+        .fold(0.0_f64, f64::max);
+
+    let inbound: f64 = flows.iter().map(|f| f.inbound_sol).sum();
+    let outbound: f64 = flows.iter().map(|f| f.outbound_sol).sum();
+    let pass_through_signal = if inbound > 0.0 && outbound > 0.0 {
+        inbound.min(outbound) / inbound.max(outbound)
+    } else {
+        0.0
+    };
+
+    let connections_signal =
+        ((flows.len() as f64).ln_1p() / CONNECTIONS_SATURATION.ln_1p()).min(1.0);
+
+    let fresh_signal = age
+        .age_days
+        .map(freshness)
+        .unwrap_or(0.0)
+        .max(0.5 * age.fresh_counterparty_share.clamp(0.0, 1.0));
+
+    let factor = |name, signal: f64, weight: f64, detail| RiskFactor {
+        name,
+        signal,
+        weight,
+        contribution: signal * weight,
+        detail,
+    };
+    let factors = vec![
+        factor(
+            "structuring",
+            structuring_signal,
+            weights.structuring,
+            format!("{} structuring pattern(s) flagged", structuring.len()),
+        ),
+        factor(
+            "pass_through",
+            pass_through_signal,
+            weights.pass_through,
+            format!("{:.3} SOL in, {:.3} SOL out", inbound, outbound),
+        ),
+        factor(
+            "connections",
+            connections_signal,
+            weights.connections,
+            format!("{} counterparties", flows.len()),
+        ),
+        factor(
+            "fresh_wallet",
+            fresh_signal,
+            weights.fresh_wallet,
+            format!(
+                "first seen {}; {:.0}% of counterparties fresh",
+                age.age_days
+                    .map(|d| format!("{:.1} days ago", d))
+                    .unwrap_or_else(|| "never".to_string()),
+                age.fresh_counterparty_share * 100.0
+            ),
+        ),
+    ];
+
+    RiskAssessment {
+        score: factors.iter().map(|f| f.contribution).sum(),
+        factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(counterparty: &str, inbound_sol: f64, outbound_sol: f64) -> CounterpartyNetFlow {
+        CounterpartyNetFlow {
+            counterparty: counterparty.to_string(),
+            transfer_count: 1,
+            inbound_sol,
+            outbound_sol,
+            net_sol: inbound_sol - outbound_sol,
+            inbound_token_ui_by_mint: Default::default(),
+            outbound_token_ui_by_mint: Default::default(),
+            net_token_ui_by_mint: Default::default(),
+        }
+    }
+
+    fn flag(confidence: f64) -> StructuringFlag {
+        StructuringFlag {
+            amount_sol: 0.5,
+            transfer_count: 8,
+            total_sol: 4.0,
+            window_count: 8,
+            window_start: 0,
+            window_end: 3_600,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_breakdown_sums_to_score() {
+        let flows = [flow("IN", 10.0, 0.0), flow("OUT", 0.0, 9.5)];
+        let risk = assess_risk(
+            &[flag(0.8), flag(0.6)],
+            &flows,
+            WalletAge::default(),
+            RiskWeights::default(),
+        );
+
+        let sum: f64 = risk.factors.iter().map(|f| f.contribution).sum();
+        assert!((risk.score - sum).abs() < 1e-12);
+        let weights: f64 = risk.factors.iter().map(|f| f.weight).sum();
+        assert!((weights - 1.0).abs() < 1e-12);
+        assert_eq!(risk.factors[0].signal, 0.8);
+        assert!((risk.factors[1].signal - 0.95).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_weights_are_normalised_and_quiet_wallet_scores_zero() {
+        let weights = RiskWeights {
+            structuring: 2.0,
+            pass_through: 0.0,
+            connections: 0.0,
+            fresh_wallet: 0.0,
+        };
+        let risk = assess_risk(
+            &[flag(0.5)],
+            &[flow("IN", 1.0, 1.0)],
+            WalletAge::default(),
+            weights,
+        );
+        assert!((risk.score - 0.5).abs() < 1e-12);
+
+        let quiet = assess_risk(&[], &[], WalletAge::default(), RiskWeights::default());
+        assert_eq!(quiet.score, 0.0);
+    }
+
+    #[test]
+    fn test_fresh_wallet_factor() {
+        let fresh = |age: WalletAge| {
+            assess_risk(&[], &[], age, RiskWeights::default())
+                .factors
+                .into_iter()
+                .find(|f| f.name == "fresh_wallet")
+                .unwrap()
+                .signal
+        };
+        let aged = |days| WalletAge {
+            age_days: Some(days),
+            fresh_counterparty_share: 0.0,
+        };
+
+        assert!((fresh(aged(1.0)) - 6.0 / 7.0).abs() < 1e-12);
+        assert_eq!(fresh(aged(30.0)), 0.0);
+        assert_eq!(fresh(WalletAge::default()), 0.0);
+        // An old wallet among fresh ones is raised only slightly.
+        let in_fresh_cluster = WalletAge {
+            age_days: Some(30.0),
+            fresh_counterparty_share: 0.6,
+        };
+        assert!((fresh(in_fresh_cluster) - 0.3).abs() < 1e-12);
+    }
+}
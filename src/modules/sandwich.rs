@@ -0,0 +1,234 @@
+use crate::dex::SwapEvent;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Largest slot distance between the front-run, the victim and the back-run.
+pub const SANDWICH_SLOT_WINDOW: u64 = 1;
+
+/// Largest relative difference between what the front-run bought and what the
+/// back-run sold for the two to count as one round trip.
+const ROUND_TRIP_TOLERANCE: f64 = 0.05;
+
+/// A swap that was bought in front of and sold into by another wallet.
+#[derive(Debug, Clone, Serialize)]
+pub struct SandwichEvent {
+    pub victim: String,
+    pub attacker: String,
+    pub pool: String,
+    pub slot: u64,
+    pub victim_signature: String,
+    pub front_run_signature: String,
+    pub back_run_signature: String,
+    /// Swaps caught between the same front-run and back-run, this one included.
+    pub victim_count: usize,
+    /// Mint the victim paid with; the loss is denominated in it.
+    pub loss_mint: String,
+    /// Victim's share of the attacker's round-trip profit (back-run proceeds minus
+    /// front-run cost), split across the victims by how much each swapped in.
+    pub victim_loss_estimate: f64,
+}
+
+/// Sandwich detection: a wallet front-runs when it swaps a pool and, at most
+/// `2 * SANDWICH_SLOT_WINDOW` slots later, swaps back about what it bought (within
+/// `ROUND_TRIP_TOLERANCE`), ending with more of the input mint than it started
+/// with. Other wallets' swaps in the front-run's direction between the two legs,
+/// at most `SANDWICH_SLOT_WINDOW` slots from each, are its victims. Order inside a
+/// slot is not recorded, so same-slot legs count as before/after. Each swap is a
+/// leg or victim of at most one sandwich, the most profitable. Largest loss first.
+pub fn detect_sandwiches(swaps: &[SwapEvent]) -> Vec<SandwichEvent> {
+    let mut by_pool: HashMap<&str, Vec<&SwapEvent>> = HashMap::new();
+    for swap in swaps {
+        if let Some(pool) = swap.pool_address.as_deref() {
+            by_pool.entry(pool).or_default().push(swap);
+        }
+    }
+
+    let mut out = Vec::new();
+    for (pool, pool_swaps) in by_pool {
+        out.extend(pool_sandwiches(pool, &pool_swaps));
+    }
+
+    out.sort_by(|a, b| {
+        b.victim_loss_estimate
+            .partial_cmp(&a.victim_loss_estimate)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.victim_signature.cmp(&b.victim_signature))
+    });
+    out
+}
+
+/// Whether `back` sells about what `front` bought, back into the mint it paid with.
+fn is_round_trip(front: &SwapEvent, back: &SwapEvent) -> bool {
+    back.wallet == front.wallet
+        && back.signature != front.signature
+        && back.slot >= front.slot
+        && back.slot - front.slot <= 2 * SANDWICH_SLOT_WINDOW
+        && back.token_in_mint == front.token_out_mint
+        && back.token_out_mint == front.token_in_mint
+        && front.amount_out > 0.0
+        && (back.amount_in - front.amount_out).abs() <= ROUND_TRIP_TOLERANCE * front.amount_out
+}
+
+/// Sandwiches in one pool, taking profitable round trips best first.
+fn pool_sandwiches(pool: &str, pool_swaps: &[&SwapEvent]) -> Vec<SandwichEvent> {
+    let mut round_trips: Vec<(&SwapEvent, &SwapEvent, f64)> = Vec::new();
+    for front in pool_swaps {
+        for back in pool_swaps.iter().filter(|back| is_round_trip(front, back)) {
+            let profit = back.amount_out - front.amount_in;
+            if profit > 0.0 {
+                round_trips.push((front, back, profit));
+            }
+        }
+    }
+    round_trips.sort_by(|a, b| {
+        b.2.partial_cmp(&a.2)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.signature.cmp(&b.0.signature))
+            .then_with(|| a.1.signature.cmp(&b.1.signature))
+    });
+
+    let mut taken: HashSet<&str> = HashSet::new();
+    let mut out = Vec::new();
+    for (front, back, profit) in round_trips {
+        if taken.contains(front.signature.as_str()) || taken.contains(back.signature.as_str()) {
+            continue;
+        }
+        let victims: Vec<&SwapEvent> = pool_swaps
+            .iter()
+            .copied()
+            .filter(|v| {
+                v.wallet != front.wallet
+                    && !taken.contains(v.signature.as_str())
+                    && v.token_in_mint == front.token_in_mint
+                    && v.token_out_mint == front.token_out_mint
+                    && v.slot >= front.slot
+                    && v.slot - front.slot <= SANDWICH_SLOT_WINDOW
+                    && back.slot >= v.slot
+                    && back.slot - v.slot <= SANDWICH_SLOT_WINDOW
+            })
+            .collect();
+        if victims.is_empty() {
+            continue;
+        }
+
+        taken.insert(&front.signature);
+        taken.insert(&back.signature);
+        let total_in: f64 = victims.iter().map(|v| v.amount_in.max(0.0)).sum();
+        for victim in &victims {
+            taken.insert(&victim.signature);
+            let share = if total_in > 0.0 {
+                victim.amount_in.max(0.0) / total_in
+            } else {
+                1.0 / victims.len() as f64
+            };
+            out.push(SandwichEvent {
+                victim: victim.wallet.clone(),
+                attacker: front.wallet.clone(),
+                pool: pool.to_string(),
+                slot: victim.slot,
+                victim_signature: victim.signature.clone(),
+                front_run_signature: front.signature.clone(),
+                back_run_signature: back.signature.clone(),
+                victim_count: victims.len(),
+                loss_mint: victim.token_in_mint.clone(),
+                victim_loss_estimate: profit * share,
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(
+        signature: &str,
+        wallet: &str,
+        slot: u64,
+        (token_in, amount_in): (&str, f64),
+        (token_out, amount_out): (&str, f64),
+    ) -> SwapEvent {
+        SwapEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot,
+            block_time: Some(slot),
+            wallet: wallet.to_string(),
+            dex_name: "Raydium V4".to_string(),
+            program_id: None,
+            pool_address: Some("POOL".to_string()),
+            token_in_mint: token_in.to_string(),
+            amount_in,
+            token_out_mint: token_out.to_string(),
+            amount_out,
+        }
+    }
+
+    #[test]
+    fn test_front_victim_back_triple_in_one_slot_is_flagged() {
+        let swaps = vec![
+            swap("front", "BOT", 100, ("SOL", 10.0), ("MEME", 1_000.0)),
+            swap("victim", "USER", 100, ("SOL", 5.0), ("MEME", 450.0)),
+            swap("back", "BOT", 100, ("MEME", 1_000.0), ("SOL", 10.4)),
+            // Same pool, unrelated trader two slots later.
+            swap("late", "OTHER", 102, ("SOL", 1.0), ("MEME", 90.0)),
+        ];
+
+        let found = detect_sandwiches(&swaps);
+        assert_eq!(found.len(), 1);
+        let s = &found[0];
+        assert_eq!(s.victim, "USER");
+        assert_eq!(s.attacker, "BOT");
+        assert_eq!(s.pool, "POOL");
+        assert_eq!(s.slot, 100);
+        assert_eq!(s.front_run_signature, "front");
+        assert_eq!(s.back_run_signature, "back");
+        assert_eq!(s.loss_mint, "SOL");
+        assert!((s.victim_loss_estimate - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unprofitable_or_distant_round_trips_are_ignored() {
+        let losing = vec![
+            swap("front", "BOT", 100, ("SOL", 10.0), ("MEME", 1_000.0)),
+            swap("victim", "USER", 100, ("SOL", 5.0), ("MEME", 450.0)),
+            swap("back", "BOT", 100, ("MEME", 1_000.0), ("SOL", 9.9)),
+        ];
+        assert!(detect_sandwiches(&losing).is_empty());
+
+        let distant = vec![
+            swap("front", "BOT", 90, ("SOL", 10.0), ("MEME", 1_000.0)),
+            swap("victim", "USER", 100, ("SOL", 5.0), ("MEME", 450.0)),
+            swap("back", "BOT", 100, ("MEME", 1_000.0), ("SOL", 10.4)),
+        ];
+        assert!(detect_sandwiches(&distant).is_empty());
+
+        // Selling back a fraction of the front-run is not a round trip, however
+        // much SOL it brings in.
+        let partial = vec![
+            swap("front", "BOT", 100, ("SOL", 10.0), ("MEME", 1_000.0)),
+            swap("victim", "USER", 100, ("SOL", 5.0), ("MEME", 450.0)),
+            swap("back", "BOT", 100, ("MEME", 200.0), ("SOL", 10.4)),
+        ];
+        assert!(detect_sandwiches(&partial).is_empty());
+    }
+
+    #[test]
+    fn test_profit_is_split_across_victims_by_size() {
+        let swaps = vec![
+            swap("front", "BOT", 100, ("SOL", 10.0), ("MEME", 1_000.0)),
+            swap("small", "USER1", 100, ("SOL", 1.0), ("MEME", 90.0)),
+            swap("large", "USER2", 100, ("SOL", 3.0), ("MEME", 260.0)),
+            swap("back", "BOT", 100, ("MEME", 990.0), ("SOL", 10.8)),
+        ];
+
+        let found = detect_sandwiches(&swaps);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|s| s.victim_count == 2));
+        assert_eq!(found[0].victim_signature, "large");
+        assert!((found[0].victim_loss_estimate - 0.6).abs() < 1e-9);
+        assert_eq!(found[1].victim_signature, "small");
+        assert!((found[1].victim_loss_estimate - 0.2).abs() < 1e-9);
+    }
+}
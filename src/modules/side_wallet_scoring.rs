@@ -0,0 +1,130 @@
+use crate::core::env::env_parse_where;
+use crate::dex::types::{DexPrograms, WSOL_MINT};
+use serde::Serialize;
+
+/// Mints too common to link wallets: wrapped SOL, USDC, USDT.
+pub const DEFAULT_IGNORED_MINTS: &[&str] = &[
+    WSOL_MINT,
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+];
+
+/// Program accounts that touch countless unrelated wallets: System, SPL Token,
+/// Token-2022 and Associated Token Account programs. The known DEX programs are
+/// added by `terminal_accounts_from_env`.
+pub const DEFAULT_TERMINAL_PROGRAMS: &[&str] = &[
+    "11111111111111111111111111111111",
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+];
+
+/// Score bumps applied to side-wallet candidates once event evidence is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SideWalletScoringConfig {
+    /// Per shared inbound funder (up to 3).
+    pub shared_funder: f64,
+    /// Per shared counterparty (up to 5).
+    pub shared_counterparty: f64,
+    /// Per shared uncommon token mint (up to 3).
+    pub shared_mint: f64,
+    /// Times behavioral similarity, when it is above 0.65.
+    pub behavioral: f64,
+    /// Scaled by shared blocks (full weight at 5).
+    pub same_block: f64,
+    /// Times the activity-window overlap ratio, when above 15% and no block is shared.
+    pub temporal_overlap: f64,
+}
+
+impl Default for SideWalletScoringConfig {
+    fn default() -> Self {
+        Self {
+            shared_funder: 0.06,
+            shared_counterparty: 0.03,
+            shared_mint: 0.04,
+            behavioral: 0.12,
+            same_block: 0.08,
+            temporal_overlap: 0.10,
+        }
+    }
+}
+
+impl SideWalletScoringConfig {
+    /// Defaults overridden by `SIDE_WALLET_WEIGHT_SHARED_FUNDER`,
+    /// `SIDE_WALLET_WEIGHT_SHARED_COUNTERPARTY`, `SIDE_WALLET_WEIGHT_SHARED_MINT`,
+    /// `SIDE_WALLET_WEIGHT_BEHAVIORAL`, `SIDE_WALLET_WEIGHT_SAME_BLOCK` and
+    /// `SIDE_WALLET_WEIGHT_TEMPORAL_OVERLAP` (non-negative numbers).
+    pub fn from_env() -> Self {
+        let weight = |var: &str, default: f64| {
+            env_parse_where(
+                var,
+                default,
+                |w: &f64| w.is_finite() && *w >= 0.0,
+                "a non-negative number",
+            )
+        };
+        let defaults = Self::default();
+        Self {
+            shared_funder: weight("SIDE_WALLET_WEIGHT_SHARED_FUNDER", defaults.shared_funder),
+            shared_counterparty: weight(
+                "SIDE_WALLET_WEIGHT_SHARED_COUNTERPARTY",
+                defaults.shared_counterparty,
+            ),
+            shared_mint: weight("SIDE_WALLET_WEIGHT_SHARED_MINT", defaults.shared_mint),
+            behavioral: weight("SIDE_WALLET_WEIGHT_BEHAVIORAL", defaults.behavioral),
+            same_block: weight("SIDE_WALLET_WEIGHT_SAME_BLOCK", defaults.same_block),
+            temporal_overlap: weight(
+                "SIDE_WALLET_WEIGHT_TEMPORAL_OVERLAP",
+                defaults.temporal_overlap,
+            ),
+        }
+    }
+
+    /// Bump for shared funders, shared counterparties and shared mints.
+    pub fn shared_evidence_bump(&self, funders: u32, counterparties: u32, mints: u32) -> f64 {
+        self.shared_funder * funders.min(3) as f64
+            + self.shared_counterparty * counterparties.min(5) as f64
+            + self.shared_mint * mints.min(3) as f64
+    }
+
+    /// Bump for activity in the same blocks (`same_block_count > 0`).
+    pub fn same_block_bump(&self, same_block_count: u32) -> f64 {
+        self.same_block * (same_block_count.min(5) as f64 * 0.2)
+    }
+}
+
+/// Accounts the side-wallet expansion never expands through:
+/// `SIDE_WALLET_TERMINAL_ACCOUNTS` (comma-separated, replaces the list; empty
+/// disables it) or `DEFAULT_TERMINAL_PROGRAMS` plus the known DEX programs.
+pub fn terminal_accounts_from_env() -> Vec<String> {
+    match std::env::var("SIDE_WALLET_TERMINAL_ACCOUNTS") {
+        Ok(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(_) => DEFAULT_TERMINAL_PROGRAMS
+            .iter()
+            .chain(DexPrograms::ALL)
+            .map(|a| a.to_string())
+            .collect(),
+    }
+}
+
+/// Mints ignored for shared-mint evidence: `SHARED_MINT_IGNORE` (comma-separated,
+/// replaces the list; empty ignores nothing) or `DEFAULT_IGNORED_MINTS`.
+pub fn ignored_mints_from_env() -> Vec<String> {
+    match std::env::var("SHARED_MINT_IGNORE") {
+        Ok(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(_) => DEFAULT_IGNORED_MINTS
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+    }
+}
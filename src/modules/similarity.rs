@@ -0,0 +1,194 @@
+use crate::storage::BehavioralProfile;
+use serde::Serialize;
+
+/// A wallet whose transaction patterns resemble the target's.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarWallet {
+    pub wallet: String,
+    /// 0.0 - 1.0, see `behavioral_similarity`.
+    pub similarity: f64,
+    pub profile: BehavioralProfile,
+}
+
+/// Similarity of two wallets' transaction patterns (0.0 - 1.0): average SOL
+/// per transfer, transfers per day and counterparty count on a log scale, how
+/// close their most active UTC hours are, and how alike their inbound share of
+/// transfers is (two pure receivers match, a receiver and a sender do not).
+pub fn behavioral_similarity(profile_a: &BehavioralProfile, profile_b: &BehavioralProfile) -> f64 {
+    // 1. Average SOL amount similarity (normalize by log scale)
+    let avg_sol_sim = if profile_a.avg_sol_per_tx > 0.0 && profile_b.avg_sol_per_tx > 0.0 {
+        let ratio = (profile_a.avg_sol_per_tx / profile_b.avg_sol_per_tx)
+            .max(profile_b.avg_sol_per_tx / profile_a.avg_sol_per_tx);
+        let log_ratio = ratio.ln().abs();
+        (-log_ratio / 2.0).exp()
+    } else {
+        0.5
+    };
+
+    // 2. Transaction frequency similarity (tx per day)
+    let freq_sim = if profile_a.avg_tx_per_day > 0.0 && profile_b.avg_tx_per_day > 0.0 {
+        let ratio = (profile_a.avg_tx_per_day / profile_b.avg_tx_per_day)
+            .max(profile_b.avg_tx_per_day / profile_a.avg_tx_per_day);
+        let log_ratio = ratio.ln().abs();
+        (-log_ratio / 1.5).exp()
+    } else {
+        0.5
+    };
+
+    // 3. Most active hour similarity (time-of-day clustering)
+    let hour_sim = match (
+        profile_a.most_active_hour_utc,
+        profile_b.most_active_hour_utc,
+    ) {
+        (Some(h_a), Some(h_b)) => {
+            let diff = (h_a - h_b).abs();
+            let circular_diff = diff.min(24 - diff);
+            if circular_diff <= 2 {
+                1.0
+            } else if circular_diff <= 4 {
+                0.7
+            } else if circular_diff <= 8 {
+                0.4
+            } else {
+                0.1
+            }
+        }
+        _ => 0.3,
+    };
+
+    // 4. Direction similarity (share of transfers received)
+    let inbound_share = |p: &BehavioralProfile| {
+        let total = p.inbound_count + p.outbound_count;
+        if total > 0 {
+            Some(p.inbound_count as f64 / total as f64)
+        } else {
+            None
+        }
+    };
+    let direction_sim = match (inbound_share(profile_a), inbound_share(profile_b)) {
+        (Some(a), Some(b)) => 1.0 - (a - b).abs(),
+        _ => 0.5,
+    };
+
+    // 5. Counterparty breadth similarity
+    let counterparty_sim =
+        if profile_a.unique_counterparties > 0 && profile_b.unique_counterparties > 0 {
+            let (a, b) = (
+                profile_a.unique_counterparties as f64,
+                profile_b.unique_counterparties as f64,
+            );
+            (-(a.max(b) / a.min(b)).ln() / 2.0).exp()
+        } else {
+            0.5
+        };
+
+    let combined = (avg_sol_sim * 0.30)
+        + (freq_sim * 0.25)
+        + (hour_sim * 0.20)
+        + (direction_sim * 0.15)
+        + (counterparty_sim * 0.10);
+    if combined.is_finite() {
+        combined.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// The `limit` profiles most similar to `target` (excluding the target itself),
+/// most similar first.
+pub fn rank_similar_wallets(
+    target: &BehavioralProfile,
+    profiles: Vec<BehavioralProfile>,
+    limit: usize,
+) -> Vec<SimilarWallet> {
+    let mut out: Vec<SimilarWallet> = profiles
+        .into_iter()
+        .filter(|p| p.wallet != target.wallet)
+        .map(|profile| SimilarWallet {
+            wallet: profile.wallet.clone(),
+            similarity: behavioral_similarity(target, &profile),
+            profile,
+        })
+        .collect();
+
+    out.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.wallet.cmp(&b.wallet))
+    });
+    out.truncate(limit);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(wallet: &str, avg_sol: f64, per_day: f64, hour: i32) -> BehavioralProfile {
+        BehavioralProfile {
+            wallet: wallet.to_string(),
+            total_transfers: 10,
+            avg_sol_per_tx: avg_sol,
+            median_sol_per_tx: avg_sol,
+            total_days_active: 5,
+            avg_tx_per_day: per_day,
+            most_active_hour_utc: Some(hour),
+            first_tx_epoch: 0,
+            last_tx_epoch: 0,
+            inbound_count: 5,
+            outbound_count: 5,
+            inbound_outbound_ratio: 1.0,
+            unique_counterparties: 4,
+        }
+    }
+
+    fn with_direction(
+        mut p: BehavioralProfile,
+        inbound: u64,
+        counterparties: u64,
+    ) -> BehavioralProfile {
+        p.inbound_count = inbound;
+        p.outbound_count = p.total_transfers - inbound;
+        p.inbound_outbound_ratio = inbound as f64 / p.outbound_count.max(1) as f64;
+        p.unique_counterparties = counterparties;
+        p
+    }
+
+    #[test]
+    fn test_ranks_closest_pattern_first_and_skips_target() {
+        let target = profile("T", 2.0, 4.0, 23);
+        let ranked = rank_similar_wallets(
+            &target,
+            vec![
+                with_direction(profile("FAR", 500.0, 0.1, 11), 10, 200),
+                target.clone(),
+                // Hour 1 is two hours from 23 across midnight.
+                profile("NEAR", 2.2, 3.5, 1),
+            ],
+            10,
+        );
+
+        let order: Vec<_> = ranked.iter().map(|s| s.wallet.as_str()).collect();
+        assert_eq!(order, ["NEAR", "FAR"]);
+        assert!(ranked[0].similarity > 0.9);
+        assert!(ranked[1].similarity < 0.2);
+        assert_eq!(behavioral_similarity(&target, &target), 1.0);
+    }
+
+    #[test]
+    fn test_receivers_resemble_each_other_more_than_a_sender() {
+        let receiver = with_direction(profile("R1", 2.0, 4.0, 12), 10, 3);
+        let other_receiver = with_direction(profile("R2", 2.0, 4.0, 12), 9, 3);
+        let sender = with_direction(profile("S", 2.0, 4.0, 12), 0, 3);
+
+        let alike = behavioral_similarity(&receiver, &other_receiver);
+        let opposite = behavioral_similarity(&receiver, &sender);
+        assert!(alike > 0.95);
+        assert!((alike - opposite - 0.15 * 0.9).abs() < 1e-9);
+
+        // A hub with many counterparties is further from a wallet with a few.
+        let hub = with_direction(profile("H", 2.0, 4.0, 12), 10, 300);
+        assert!(behavioral_similarity(&receiver, &hub) < alike);
+    }
+}
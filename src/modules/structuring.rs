@@ -0,0 +1,129 @@
+use crate::storage::AmountBucket;
+use serde::Serialize;
+
+/// A burst of near-identical outbound transfers.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuringFlag {
+    /// Shared amount, rounded to 0.01 SOL.
+    pub amount_sol: f64,
+    /// Transfers of this amount in the whole lookback.
+    pub transfer_count: u64,
+    pub total_sol: f64,
+    /// Most transfers of this amount inside any single window.
+    pub window_count: usize,
+    pub window_start: u64,
+    pub window_end: u64,
+    /// 0.5 at `min_count` transfers in a window, 1.0 at twice that.
+    pub confidence: f64,
+}
+
+/// Structuring detection: flags amount buckets where at least `min_count`
+/// transfers land within `window_secs` of each other (mixers and bots split funds
+/// into many same-size transfers). Strongest first.
+pub fn detect_structuring(
+    buckets: &[AmountBucket],
+    min_count: usize,
+    window_secs: u64,
+) -> Vec<StructuringFlag> {
+    let min_count = min_count.max(2);
+
+    let mut out: Vec<StructuringFlag> = buckets
+        .iter()
+        .filter(|b| b.block_times.len() >= min_count)
+        .filter_map(|b| {
+            let (window_count, window_start, window_end) =
+                densest_window(&b.block_times, window_secs);
+            (window_count >= min_count).then(|| StructuringFlag {
+                amount_sol: b.amount_sol,
+                transfer_count: b.count,
+                total_sol: b.total_sol,
+                window_count,
+                window_start,
+                window_end,
+                confidence: (window_count as f64 / (2 * min_count) as f64).min(1.0),
+            })
+        })
+        .collect();
+
+    out.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.window_count.cmp(&a.window_count))
+    });
+    out
+}
+
+/// `detect_structuring` keeping only flags with `confidence >= min_confidence`.
+pub fn detect_structuring_filtered(
+    buckets: &[AmountBucket],
+    min_count: usize,
+    window_secs: u64,
+    min_confidence: f64,
+) -> Vec<StructuringFlag> {
+    let mut flags = detect_structuring(buckets, min_count, window_secs);
+    flags.retain(|f| f.confidence >= min_confidence);
+    flags
+}
+
+/// Largest number of ascending `times` within `window_secs`, with its first and last time.
+fn densest_window(times: &[u64], window_secs: u64) -> (usize, u64, u64) {
+    let mut best = (0, 0, 0);
+    let mut start = 0;
+    for end in 0..times.len() {
+        while times[end] - times[start] > window_secs {
+            start += 1;
+        }
+        let count = end - start + 1;
+        if count > best.0 {
+            best = (count, times[start], times[end]);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(amount_sol: f64, block_times: Vec<u64>) -> AmountBucket {
+        AmountBucket {
+            amount_sol,
+            count: block_times.len() as u64,
+            total_sol: amount_sol * block_times.len() as f64,
+            block_times,
+        }
+    }
+
+    #[test]
+    fn test_only_bursts_inside_the_window_are_flagged() {
+        let burst = bucket(0.5, (0..8).map(|i| 1_000 + i * 600).collect());
+        // Same count, but one transfer a day.
+        let spread = bucket(1.0, (0..8).map(|i| 1_000 + i * 86_400).collect());
+        let few = bucket(2.0, vec![1_000, 1_100]);
+
+        let flags = detect_structuring(&[spread, few, burst], 5, 3_600 * 6);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].amount_sol, 0.5);
+        assert_eq!(flags[0].window_count, 8);
+        assert_eq!(flags[0].window_start, 1_000);
+        assert_eq!(flags[0].window_end, 1_000 + 7 * 600);
+        assert!((flags[0].confidence - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_min_confidence_drops_weak_flags() {
+        // 6 in a window at min_count 5 -> 0.6; 10 -> 1.0.
+        let weak = bucket(0.5, (0..6).map(|i| 1_000 + i * 60).collect());
+        let strong = bucket(1.0, (0..10).map(|i| 1_000 + i * 60).collect());
+        let buckets = [weak, strong];
+
+        let all = detect_structuring_filtered(&buckets, 5, 3_600, 0.5);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].amount_sol, 1.0);
+
+        let strong_only = detect_structuring_filtered(&buckets, 5, 3_600, 0.7);
+        assert_eq!(strong_only.len(), 1);
+        assert_eq!(strong_only[0].amount_sol, 1.0);
+    }
+}
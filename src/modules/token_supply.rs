@@ -0,0 +1,121 @@
+use crate::core::env::env_parse_in;
+use crate::core::errors::Result;
+use crate::core::rpc_client::{Commitment, TokenSupply};
+use crate::core::SolanaRpcClient;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Token supplies fetched over RPC. Supply changes slowly, so entries live for
+/// minutes rather than seconds.
+pub struct TokenSupplyTracker {
+    rpc_client: Arc<SolanaRpcClient>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, (Instant, TokenSupply)>>,
+}
+
+impl TokenSupplyTracker {
+    /// Cache TTL comes from `TOKEN_SUPPLY_CACHE_TTL_SECS` (default 600).
+    pub fn new(rpc_client: Arc<SolanaRpcClient>) -> Self {
+        let ttl_secs = env_parse_in("TOKEN_SUPPLY_CACHE_TTL_SECS", 600u64, 0..=86_400);
+        Self::with_ttl(rpc_client, Duration::from_secs(ttl_secs))
+    }
+
+    pub fn with_ttl(rpc_client: Arc<SolanaRpcClient>, ttl: Duration) -> Self {
+        Self {
+            rpc_client,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_supply(&self, mint: &str) -> Result<TokenSupply> {
+        {
+            let cache = self.cache.read().await;
+            if let Some((at, supply)) = cache.get(mint) {
+                if at.elapsed() < self.ttl {
+                    return Ok(supply.clone());
+                }
+            }
+        }
+
+        let supply = self.rpc_client.get_token_supply(mint).await?;
+
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        cache.insert(mint.to_string(), (Instant::now(), supply.clone()));
+
+        Ok(supply)
+    }
+
+    /// Supply read at `commitment`. Only reads at the client's own commitment are cached.
+    pub async fn get_supply_at(&self, mint: &str, commitment: Commitment) -> Result<TokenSupply> {
+        if commitment == self.rpc_client.commitment() {
+            return self.get_supply(mint).await;
+        }
+        self.rpc_client
+            .with_commitment(commitment)
+            .get_token_supply(mint)
+            .await
+    }
+
+    /// Distinct non-zero holders of `mint` (uncached; scans all token accounts).
+    pub async fn count_holders(&self, mint: &str) -> Result<usize> {
+        self.rpc_client.count_token_holders(mint).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rpc_client::tests::mock_endpoint;
+
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    #[tokio::test]
+    async fn test_supply_is_populated_and_cached() {
+        let supply = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "context": { "slot": 1 },
+            "value": { "amount": "5000000000000", "decimals": 6, "uiAmountString": "5000000" }
+        } });
+        let (endpoint, requests) = mock_endpoint(vec![(200, supply.to_string())]).await;
+        let tracker = TokenSupplyTracker::with_ttl(
+            Arc::new(SolanaRpcClient::new(endpoint)),
+            Duration::from_secs(600),
+        );
+
+        let first = tracker.get_supply(MINT).await.unwrap();
+        assert_eq!(first.amount, 5_000_000_000_000);
+        assert_eq!(first.decimals, 6);
+        assert!((first.ui_amount - 5_000_000.0).abs() < 1e-6);
+
+        tracker.get_supply(MINT).await.unwrap();
+        assert_eq!(requests.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_holder_count_dedupes_owners_and_skips_empty_accounts() {
+        let account = |pubkey: &str, owner: &str, amount: &str| {
+            serde_json::json!({ "pubkey": pubkey, "account": { "data": { "parsed": { "info": {
+                "mint": MINT, "owner": owner,
+                "tokenAmount": { "amount": amount, "decimals": 6 }
+            } } } } })
+        };
+        let spl = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": [
+            account("Ata1", "OwnerA", "10"),
+            account("Ata2", "OwnerA", "5"),
+            account("Ata3", "OwnerB", "1"),
+            account("Ata4", "OwnerC", "0")
+        ] });
+        let token_2022 = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": [] });
+        let (endpoint, requests) =
+            mock_endpoint(vec![(200, spl.to_string()), (200, token_2022.to_string())]).await;
+        let tracker = TokenSupplyTracker::new(Arc::new(SolanaRpcClient::new(endpoint)));
+
+        assert_eq!(tracker.count_holders(MINT).await.unwrap(), 2);
+        let sent: serde_json::Value = serde_json::from_str(&requests.lock().await[0]).unwrap();
+        assert_eq!(sent["method"], "getProgramAccounts");
+        assert_eq!(sent["params"][1]["filters"][0]["memcmp"]["bytes"], MINT);
+    }
+}
@@ -1,27 +1,88 @@
-use crate::core::errors::Result;
+use crate::core::env::{env_parse_in, env_parse_where};
+use crate::core::errors::{BeastError, Result};
 /// Transaction Handler Module
 /// Integrates RPC client with enhanced transaction parser for real data processing
+use crate::core::rpc_client::{Commitment, TokenAccountMeta};
+use crate::core::units::amount_to_ui;
 use crate::core::{EnhancedTransaction, EnhancedTransactionParser, SolanaRpcClient};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Cached values keyed by signature or address, with their insertion time.
+type TimedCache<T> = HashMap<String, (Instant, T)>;
+
 pub struct TransactionHandler {
     rpc_client: Arc<SolanaRpcClient>,
     parser: EnhancedTransactionParser,
     /// Cache for parsed transactions
-    cache: Arc<RwLock<HashMap<String, EnhancedTransaction>>>,
+    cache: Arc<RwLock<TimedCache<EnhancedTransaction>>>,
+    /// Token account -> mint/decimals lookups (`None` = not a live token account)
+    token_accounts: Arc<RwLock<TimedCache<Option<TokenAccountMeta>>>>,
+    transaction_ttl: Duration,
+    token_account_ttl: Duration,
+    /// Slots a transaction must be behind the tip to be accepted; 0 disables the check.
+    min_confirmations: u64,
+    /// Last `getSlot` answer, reused for `TIP_SLOT_TTL`.
+    tip_slot: Arc<RwLock<Option<(Instant, u64)>>>,
+}
+
+/// How long a fetched tip slot is reused. A stale tip only makes the
+/// confirmation check stricter.
+const TIP_SLOT_TTL: Duration = Duration::from_secs(2);
+
+/// Positive TTL in seconds from `var`, or `default` when unset or invalid.
+fn ttl_from_env(var: &str, default: u64) -> Duration {
+    let secs = env_parse_where(
+        var,
+        default,
+        |secs: &u64| *secs > 0,
+        "a positive number of seconds",
+    );
+    Duration::from_secs(secs.min(86_400))
 }
 
 impl TransactionHandler {
+    /// Cache TTLs come from `TRANSACTION_CACHE_TTL_SECS` (default 600) and
+    /// `TOKEN_ACCOUNT_CACHE_TTL_SECS` (default 300); the confirmation guard from
+    /// `MIN_CONFIRMATIONS` (default 0, off).
     pub fn new(rpc_client: Arc<SolanaRpcClient>) -> Self {
+        let min_confirmations = env_parse_in("MIN_CONFIRMATIONS", 0u64, 0..=1_000);
+        Self::with_ttls(
+            rpc_client,
+            ttl_from_env("TRANSACTION_CACHE_TTL_SECS", 600),
+            ttl_from_env("TOKEN_ACCOUNT_CACHE_TTL_SECS", 300),
+        )
+        .with_min_confirmations(min_confirmations)
+    }
+
+    pub fn with_ttls(
+        rpc_client: Arc<SolanaRpcClient>,
+        transaction_ttl: Duration,
+        token_account_ttl: Duration,
+    ) -> Self {
         TransactionHandler {
             rpc_client,
             parser: EnhancedTransactionParser::new(),
             cache: Arc::new(RwLock::new(HashMap::new())),
+            token_accounts: Arc::new(RwLock::new(HashMap::new())),
+            transaction_ttl,
+            token_account_ttl,
+            min_confirmations: 0,
+            tip_slot: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Reject transactions fewer than `min_confirmations` slots behind the
+    /// `confirmed` tip, so rows that a fork could still roll back are never
+    /// stored. Passing the check does not mark a transaction `finalized`; only
+    /// reads at `finalized` commitment do.
+    pub fn with_min_confirmations(mut self, min_confirmations: u64) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
     /// Fetch and parse a single transaction with full transfer extraction
     pub async fn process_transaction(
         &self,
@@ -31,8 +92,10 @@ impl TransactionHandler {
         // Check cache first
         {
             let cache = self.cache.read().await;
-            if let Some(cached) = cache.get(signature) {
-                return Ok(cached.clone());
+            if let Some((at, cached)) = cache.get(signature) {
+                if at.elapsed() < self.transaction_ttl {
+                    return Ok(cached.clone());
+                }
             }
         }
 
@@ -42,9 +105,13 @@ impl TransactionHandler {
         let response = self.rpc_client.get_transaction(signature).await?;
 
         // Parse the transaction using enhanced parser
-        let parsed = self
+        let mut parsed = self
             .parser
             .parse(&response.raw_data, signature.to_string())?;
+        // `getTransaction` at `finalized` only returns transactions in a rooted block.
+        parsed.finalized = self.rpc_client.commitment() == Commitment::Finalized;
+        self.check_confirmations(&parsed).await?;
+        self.enrich_unknown_mints(&mut parsed).await;
 
         // Log transfer summary
         if !parsed.sol_transfers.is_empty() || !parsed.token_transfers.is_empty() {
@@ -59,12 +126,99 @@ impl TransactionHandler {
         // Cache the result
         {
             let mut cache = self.cache.write().await;
-            cache.insert(signature.to_string(), parsed.clone());
+            cache.retain(|_, (at, _)| at.elapsed() < self.transaction_ttl);
+            cache.insert(signature.to_string(), (Instant::now(), parsed.clone()));
         }
 
         Ok(parsed)
     }
 
+    /// Enforce `min_confirmations`; unconfirmed transactions are not cached so a
+    /// later call re-checks them.
+    async fn check_confirmations(&self, tx: &EnhancedTransaction) -> Result<()> {
+        if self.min_confirmations == 0 {
+            return Ok(());
+        }
+        let tip = self.tip_slot().await?;
+        let depth = tip.saturating_sub(tx.slot);
+        if depth < self.min_confirmations {
+            tracing::debug!(
+                "Skipping {}: slot {} is {} slots behind tip {}",
+                tx.signature,
+                tx.slot,
+                depth,
+                tip
+            );
+            return Err(BeastError::Unconfirmed {
+                message: format!(
+                    "{} is {} slots deep, {} required",
+                    tx.signature, depth, self.min_confirmations
+                ),
+                slots_missing: self.min_confirmations - depth,
+            });
+        }
+        Ok(())
+    }
+
+    async fn tip_slot(&self) -> Result<u64> {
+        if let Some((at, slot)) = *self.tip_slot.read().await {
+            if at.elapsed() < TIP_SLOT_TTL {
+                return Ok(slot);
+            }
+        }
+        let slot = self.rpc_client.get_slot().await?;
+        *self.tip_slot.write().await = Some((Instant::now(), slot));
+        Ok(slot)
+    }
+
+    /// Resolve token transfers the parser left with `mint = "unknown"` (token balances
+    /// missing from the transaction meta) by looking up their token accounts, then
+    /// recompute `amount_ui` with the real decimals and fill in missing owners.
+    async fn enrich_unknown_mints(&self, tx: &mut EnhancedTransaction) {
+        for transfer in tx
+            .token_transfers
+            .iter_mut()
+            .filter(|t| t.mint == "unknown")
+        {
+            let from = self.token_account_meta(&transfer.from_token_account).await;
+            let to = self.token_account_meta(&transfer.to_token_account).await;
+            let Some(meta) = from.as_ref().or(to.as_ref()) else {
+                continue;
+            };
+
+            transfer.mint = meta.mint.clone();
+            transfer.decimals = meta.decimals;
+            transfer.amount_ui = amount_to_ui(transfer.amount, meta.decimals);
+            if transfer.from_owner.is_none() {
+                transfer.from_owner = from.map(|m| m.owner);
+            }
+            if transfer.to_owner.is_none() {
+                transfer.to_owner = to.map(|m| m.owner);
+            }
+        }
+    }
+
+    /// Cached `getAccountInfo` lookup; RPC failures are not cached.
+    async fn token_account_meta(&self, account: &str) -> Option<TokenAccountMeta> {
+        if let Some((at, cached)) = self.token_accounts.read().await.get(account) {
+            if at.elapsed() < self.token_account_ttl {
+                return cached.clone();
+            }
+        }
+        match self.rpc_client.get_token_account(account).await {
+            Ok(meta) => {
+                let mut cache = self.token_accounts.write().await;
+                cache.retain(|_, (at, _)| at.elapsed() < self.token_account_ttl);
+                cache.insert(account.to_string(), (Instant::now(), meta.clone()));
+                meta
+            }
+            Err(e) => {
+                tracing::debug!("token account lookup failed for {}: {}", account, e);
+                None
+            }
+        }
+    }
+
     /// Fetch and parse wallet's transaction history
     pub async fn process_wallet_transactions(
         &self,
@@ -134,14 +288,29 @@ impl TransactionHandler {
         let cache = self.cache.read().await;
         cache.len()
     }
+
+    /// Token account lookups cached, live or not.
+    pub async fn token_account_cache_size(&self) -> usize {
+        self.token_accounts.read().await.len()
+    }
+
+    /// Token account owners remembered by the parser.
+    pub fn token_owner_cache_size(&self) -> usize {
+        self.parser.owner_cache().len()
+    }
 }
 
 impl Clone for TransactionHandler {
     fn clone(&self) -> Self {
         TransactionHandler {
             rpc_client: Arc::clone(&self.rpc_client),
-            parser: EnhancedTransactionParser::new(),
+            parser: EnhancedTransactionParser::new().with_owner_cache(self.parser.owner_cache()),
             cache: Arc::clone(&self.cache),
+            token_accounts: Arc::clone(&self.token_accounts),
+            transaction_ttl: self.transaction_ttl,
+            token_account_ttl: self.token_account_ttl,
+            min_confirmations: self.min_confirmations,
+            tip_slot: Arc::clone(&self.tip_slot),
         }
     }
 }
@@ -157,4 +326,190 @@ mod tests {
         let handler = TransactionHandler::new(rpc);
         assert_eq!(handler.cache_size().await, 0);
     }
+
+    #[test]
+    fn test_cache_ttls_come_from_env() {
+        let rpc = Arc::new(SolanaRpcClient::new("http://localhost".to_string()));
+        std::env::set_var("TOKEN_ACCOUNT_CACHE_TTL_SECS", "45");
+        assert_eq!(
+            TransactionHandler::new(Arc::clone(&rpc)).token_account_ttl,
+            Duration::from_secs(45)
+        );
+
+        // Zero and garbage fall back to the default.
+        std::env::set_var("TOKEN_ACCOUNT_CACHE_TTL_SECS", "0");
+        assert_eq!(
+            TransactionHandler::new(Arc::clone(&rpc)).token_account_ttl,
+            Duration::from_secs(300)
+        );
+        std::env::set_var("TOKEN_ACCOUNT_CACHE_TTL_SECS", "soon");
+        assert_eq!(
+            TransactionHandler::new(rpc).token_account_ttl,
+            Duration::from_secs(300)
+        );
+        std::env::remove_var("TOKEN_ACCOUNT_CACHE_TTL_SECS");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_mint_is_resolved_from_token_account() {
+        const SOURCE_ATA: &str = "5ZWj7a1f8tWkjBESHKgrLmXshuXxqeY9SYcfbshpAqPG";
+        const DEST_ATA: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        // A transfer whose token balances are absent from the meta.
+        let tx = serde_json::json!({
+            "slot": 276_000_000u64,
+            "blockTime": 1_717_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 2_039_280u64, 2_039_280u64, 1u64],
+                "postBalances": [999_995_000u64, 2_039_280u64, 2_039_280u64, 1u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Sender", "signer": true, "writable": true },
+                        { "pubkey": SOURCE_ATA, "signer": false, "writable": true },
+                        { "pubkey": DEST_ATA, "signer": false, "writable": true },
+                        { "pubkey": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP", "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                        "parsed": { "type": "transfer", "info": {
+                            "source": SOURCE_ATA, "destination": DEST_ATA,
+                            "authority": "Sender", "amount": "2500000"
+                        } }
+                    }]
+                }
+            }
+        });
+        let token_account = |owner: &str| {
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": {
+                "lamports": 2_039_280u64,
+                "owner": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                "executable": false,
+                "data": { "parsed": { "type": "account", "info": {
+                    "mint": USDC, "owner": owner,
+                    "tokenAmount": { "amount": "0", "decimals": 6 }
+                } } }
+            } } })
+            .to_string()
+        };
+        let (endpoint, requests) = crate::core::rpc_client::tests::mock_endpoint(vec![
+            (
+                200,
+                serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": tx }).to_string(),
+            ),
+            (200, token_account("Sender")),
+            (200, token_account("Receiver")),
+            (
+                200,
+                serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": tx }).to_string(),
+            ),
+        ])
+        .await;
+        let handler = TransactionHandler::new(Arc::new(SolanaRpcClient::new(endpoint)));
+
+        let parsed = handler.process_transaction("sig1", None).await.unwrap();
+        let transfer = &parsed.token_transfers[0];
+        assert_eq!(transfer.mint, USDC);
+        assert_eq!(transfer.decimals, 6);
+        assert_eq!(transfer.amount_ui, 2.5);
+        assert_eq!(transfer.from_owner.as_deref(), Some("Sender"));
+        assert_eq!(transfer.to_owner.as_deref(), Some("Receiver"));
+
+        // A second transaction touching the same accounts reuses the cached lookups.
+        let again = handler.process_transaction("sig2", None).await.unwrap();
+        assert_eq!(again.token_transfers[0].mint, USDC);
+        assert_eq!(requests.lock().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_too_recent_transaction_is_skipped_by_confirmation_guard() {
+        let tx = serde_json::json!({
+            "slot": 300_000_000u64,
+            "blockTime": 1_717_000_000u64,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 0u64, 1u64],
+                "postBalances": [899_995_000u64, 100_000_000u64, 1u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Sender", "signer": true, "writable": true },
+                        { "pubkey": "Receiver", "signer": false, "writable": true },
+                        { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "programId": "11111111111111111111111111111111",
+                        "parsed": { "type": "transfer", "info": {
+                            "source": "Sender", "destination": "Receiver", "lamports": 100_000_000u64
+                        } }
+                    }]
+                }
+            }
+        });
+        let tx_response =
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": tx }).to_string();
+        let slot_response = |slot: u64| {
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": slot }).to_string()
+        };
+
+        // Tip only 10 slots ahead: rejected and not cached.
+        let (endpoint, requests) = crate::core::rpc_client::tests::mock_endpoint(vec![
+            (200, tx_response.clone()),
+            (200, slot_response(300_000_010)),
+        ])
+        .await;
+        let handler = TransactionHandler::new(Arc::new(SolanaRpcClient::new(endpoint)))
+            .with_min_confirmations(32);
+        let err = handler.process_transaction("sig1", None).await.unwrap_err();
+        assert!(matches!(
+            err,
+            BeastError::Unconfirmed {
+                slots_missing: 22,
+                ..
+            }
+        ));
+        assert_eq!(handler.cache_size().await, 0);
+        assert!(requests.lock().await[1].contains("getSlot"));
+
+        // Deep enough: accepted, but read at `confirmed` so not finalized.
+        let (endpoint, _) = crate::core::rpc_client::tests::mock_endpoint(vec![
+            (200, tx_response.clone()),
+            (200, slot_response(300_000_100)),
+        ])
+        .await;
+        let handler = TransactionHandler::new(Arc::new(SolanaRpcClient::new(endpoint)))
+            .with_min_confirmations(32);
+        let parsed = handler.process_transaction("sig1", None).await.unwrap();
+        assert!(!parsed.finalized);
+
+        // Read at `finalized` commitment: flagged.
+        let (endpoint, requests) = crate::core::rpc_client::tests::mock_endpoint(vec![
+            (200, tx_response.clone()),
+            (200, slot_response(300_000_100)),
+        ])
+        .await;
+        let client = SolanaRpcClient::new(endpoint).with_commitment(Commitment::Finalized);
+        let handler = TransactionHandler::new(Arc::new(client)).with_min_confirmations(32);
+        let parsed = handler.process_transaction("sig1", None).await.unwrap();
+        assert!(parsed.finalized);
+        assert!(requests.lock().await[0].contains(r#""commitment":"finalized""#));
+
+        // Guard off: no getSlot call, not flagged.
+        let (endpoint, requests) =
+            crate::core::rpc_client::tests::mock_endpoint(vec![(200, tx_response)]).await;
+        let handler = TransactionHandler::with_ttls(
+            Arc::new(SolanaRpcClient::new(endpoint)),
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+        );
+        let parsed = handler.process_transaction("sig1", None).await.unwrap();
+        assert!(!parsed.finalized);
+        assert_eq!(requests.lock().await.len(), 1);
+    }
 }
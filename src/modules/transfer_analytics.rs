@@ -1,71 +1,279 @@
-use crate::core::errors::BeastResult;
+use crate::core::env::env_flag;
+use crate::core::errors::{BeastError, BeastResult};
 use crate::core::{EnhancedTransaction, SolTransfer, TokenTransfer};
-use crate::storage::DatabaseManager;
+use crate::storage::{DatabaseManager, StoredOutcome, TransferEvent};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::{broadcast, Notify};
+
+/// Persisted SOL transfers buffered per live subscriber before it starts lagging.
+const TRANSFER_FEED_CAPACITY: usize = 1_024;
 
 /// Transfer ingestion: persists event-level transfers and relationship edges.
+///
+/// Each transaction is written in its own task, so a write keeps going even if
+/// the request that started it is cancelled; `drain` waits for those writes at
+/// shutdown. The tasks run on the runtime `TransferAnalytics` was created on,
+/// not the calling actix worker's: `start_server` creates it on the main
+/// runtime, which outlives the workers and is still running during the drain.
 pub struct TransferAnalytics {
     db_manager: Arc<DatabaseManager>,
+    writes: Arc<WriteTracker>,
+    /// Runtime the write tasks are spawned on (the caller's when `None`).
+    runtime: Option<Handle>,
+    /// Whether transfers from a wallet to itself (e.g. between its own token
+    /// accounts) create relationship edges. Their events are stored either way.
+    record_self_transfers: bool,
+    /// Live feed of SOL transfers, published once their transaction is stored.
+    sol_transfers: broadcast::Sender<TransferEvent>,
 }
 
-impl TransferAnalytics {
-    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
-        Self { db_manager }
+/// Counts ingestion writes and wakes `drain` when none are in flight.
+#[derive(Default)]
+struct WriteTracker {
+    in_flight: AtomicUsize,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    cancelled: AtomicU64,
+    idle: Notify,
+}
+
+/// Marks one write as in flight until dropped. A guard dropped before `finish`
+/// belongs to a write whose task was cancelled (its runtime shut down).
+struct WriteGuard {
+    tracker: Arc<WriteTracker>,
+    finished: bool,
+}
+
+impl WriteGuard {
+    fn new(tracker: &Arc<WriteTracker>) -> Self {
+        tracker.in_flight.fetch_add(1, Ordering::SeqCst);
+        WriteGuard {
+            tracker: Arc::clone(tracker),
+            finished: false,
+        }
     }
 
-    /// Analyze transfers from a transaction and store them.
-    pub async fn analyze_transaction(&self, tx: &EnhancedTransaction) -> BeastResult<()> {
-        // Store raw transaction JSON for later evidence/debugging.
-        self.db_manager.store_transaction(tx).await?;
+    /// Record that the write ran to the end, storing everything or failing.
+    fn finish(mut self, stored: bool) {
+        let counter = if stored {
+            &self.tracker.completed
+        } else {
+            &self.tracker.failed
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+        self.finished = true;
+    }
+}
 
-        // Process SOL transfers
-        for (i, transfer) in tx.sol_transfers.iter().enumerate() {
-            self.store_sol_transfer(tx, transfer, i as i32).await?;
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.tracker.cancelled.fetch_add(1, Ordering::SeqCst);
+        }
+        if self.tracker.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.tracker.idle.notify_waiters();
         }
+    }
+}
+
+/// Outcome of waiting for in-flight writes at shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrainSummary {
+    /// Writes in flight when the drain started.
+    pub pending: usize,
+    /// Writes stored while draining.
+    pub flushed: u64,
+    /// Writes still running when the drain gave up.
+    pub unfinished: usize,
+    /// Writes stored since startup.
+    pub completed_total: u64,
+    /// Writes that returned an error since startup.
+    pub failed_total: u64,
+    /// Writes dropped before finishing since startup.
+    pub cancelled_total: u64,
+}
 
-        // Process token transfers
-        let token_offset = tx.sol_transfers.len() as i32;
-        for (j, transfer) in tx.token_transfers.iter().enumerate() {
-            self.store_token_transfer(tx, transfer, token_offset + j as i32)
-                .await?;
+impl TransferAnalytics {
+    /// Self-transfer edges are skipped unless `RECORD_SELF_TRANSFERS=true`.
+    /// Writes run on the current runtime, if any (see `with_runtime`).
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        let record_self_transfers = env_flag("RECORD_SELF_TRANSFERS", false);
+        Self {
+            db_manager,
+            writes: Arc::new(WriteTracker::default()),
+            runtime: Handle::try_current().ok(),
+            record_self_transfers,
+            sol_transfers: broadcast::channel(TRANSFER_FEED_CAPACITY).0,
         }
+    }
+
+    /// Spawn write tasks on `runtime`, which must outlive the callers.
+    pub fn with_runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    pub fn with_self_transfers(mut self, record: bool) -> Self {
+        self.record_self_transfers = record;
+        self
+    }
 
-        Ok(())
+    pub fn records_self_transfers(&self) -> bool {
+        self.record_self_transfers
     }
 
-    async fn store_sol_transfer(
+    /// Analyze transfers from a transaction and store them. `Updated` means the
+    /// transaction had been stored before.
+    pub async fn analyze_transaction(
         &self,
         tx: &EnhancedTransaction,
-        transfer: &SolTransfer,
-        event_index: i32,
-    ) -> BeastResult<()> {
-        self.db_manager
-            .store_sol_transfer_event(tx, transfer, event_index)
-            .await?;
+    ) -> BeastResult<StoredOutcome> {
+        let guard = WriteGuard::new(&self.writes);
+        let db_manager = Arc::clone(&self.db_manager);
+        let tx = tx.clone();
+        let record_self_transfers = self.record_self_transfers;
+        let feed = self.sol_transfers.clone();
+        let write = async move {
+            let stored = store_transaction(&db_manager, &tx, record_self_transfers).await;
+            guard.finish(stored.is_ok());
+            let (outcome, new_sol_events) = stored?;
+            // Only first-time events go out, so re-ingestion doesn't repeat them.
+            // No subscribers is not an error.
+            for event in new_sol_events {
+                let _ = feed.send(event);
+            }
+            Ok(outcome)
+        };
+        let task = match &self.runtime {
+            Some(runtime) => runtime.spawn(write),
+            None => tokio::spawn(write),
+        };
+        task.await
+            .map_err(|e| BeastError::DatabaseError(format!("Ingestion task failed: {}", e)))?
+    }
 
-        self.db_manager
-            .store_wallet_relationship(&transfer.from, &transfer.to, transfer.amount_sol, 0)
-            .await?;
+    /// Subscribe to SOL transfers as they are persisted. A subscriber that falls
+    /// more than 1024 events behind skips the oldest ones (`RecvError::Lagged`).
+    pub fn subscribe_sol_transfers(&self) -> broadcast::Receiver<TransferEvent> {
+        self.sol_transfers.subscribe()
+    }
 
-        Ok(())
+    /// Writes currently in flight.
+    pub fn pending_writes(&self) -> usize {
+        self.writes.in_flight.load(Ordering::SeqCst)
     }
 
-    async fn store_token_transfer(
-        &self,
-        tx: &EnhancedTransaction,
-        transfer: &TokenTransfer,
-        event_index: i32,
-    ) -> BeastResult<()> {
-        self.db_manager
-            .store_token_transfer_event(tx, transfer, event_index)
-            .await?;
+    /// Wait up to `timeout` for in-flight writes to finish.
+    pub async fn drain(&self, timeout: Duration) -> DrainSummary {
+        let pending = self.pending_writes();
+        let completed_before = self.writes.completed.load(Ordering::SeqCst);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let idle = self.writes.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+            if self.pending_writes() == 0 || tokio::time::timeout_at(deadline, idle).await.is_err()
+            {
+                break;
+            }
+        }
+
+        let completed_total = self.writes.completed.load(Ordering::SeqCst);
+        DrainSummary {
+            pending,
+            flushed: completed_total - completed_before,
+            unfinished: self.pending_writes(),
+            completed_total,
+            failed_total: self.writes.failed.load(Ordering::SeqCst),
+            cancelled_total: self.writes.cancelled.load(Ordering::SeqCst),
+        }
+    }
+}
 
-        if let (Some(from), Some(to)) = (&transfer.from_owner, &transfer.to_owner) {
-            self.db_manager
-                .store_wallet_relationship(from, to, 0.0, transfer.amount)
-                .await?;
+async fn store_transaction(
+    db_manager: &DatabaseManager,
+    tx: &EnhancedTransaction,
+    record_self_transfers: bool,
+) -> BeastResult<(StoredOutcome, Vec<TransferEvent>)> {
+    // Store raw transaction JSON for later evidence/debugging.
+    let outcome = db_manager.store_transaction(tx).await?;
+
+    // Process SOL transfers
+    let mut new_sol_events = Vec::new();
+    for (i, transfer) in tx.sol_transfers.iter().enumerate() {
+        if store_sol_transfer(db_manager, tx, transfer, i as i32, record_self_transfers).await? {
+            new_sol_events.push(TransferEvent::from_sol_transfer(tx, transfer, i as i32));
         }
+    }
+
+    // Process token transfers
+    let token_offset = tx.sol_transfers.len() as i32;
+    for (j, transfer) in tx.token_transfers.iter().enumerate() {
+        store_token_transfer(
+            db_manager,
+            tx,
+            transfer,
+            token_offset + j as i32,
+            record_self_transfers,
+        )
+        .await?;
+    }
+
+    // Process decoded swaps
+    for swap in &tx.swaps {
+        db_manager.store_swap_event(swap).await?;
+    }
+
+    // Process NFT sales
+    for trade in &tx.nft_trades {
+        db_manager.store_nft_trade(trade).await?;
+    }
+
+    Ok((outcome, new_sol_events))
+}
+
+async fn store_sol_transfer(
+    db_manager: &DatabaseManager,
+    tx: &EnhancedTransaction,
+    transfer: &SolTransfer,
+    event_index: i32,
+    record_self_transfers: bool,
+) -> BeastResult<bool> {
+    let inserted = db_manager
+        .store_sol_transfer_event(tx, transfer, event_index)
+        .await?;
+
+    if record_self_transfers || transfer.from != transfer.to {
+        db_manager
+            .store_wallet_relationship(&transfer.from, &transfer.to, transfer.amount_sol, 0)
+            .await?;
+    }
+
+    Ok(inserted)
+}
+
+async fn store_token_transfer(
+    db_manager: &DatabaseManager,
+    tx: &EnhancedTransaction,
+    transfer: &TokenTransfer,
+    event_index: i32,
+    record_self_transfers: bool,
+) -> BeastResult<()> {
+    db_manager
+        .store_token_transfer_event(tx, transfer, event_index)
+        .await?;
 
-        Ok(())
+    if let (Some(from), Some(to)) = (&transfer.from_owner, &transfer.to_owner) {
+        if from == to && !record_self_transfers {
+            return Ok(());
+        }
+        db_manager
+            .store_wallet_relationship(from, to, 0.0, transfer.amount)
+            .await?;
     }
+
+    Ok(())
 }
@@ -1,9 +1,14 @@
 use crate::core::enhanced_parser::{EnhancedTransaction, SolTransfer, TokenTransfer};
+use crate::core::env::env_parse_in;
 use crate::core::errors::{BeastError, BeastResult};
-use std::collections::{HashMap, HashSet};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::core::rpc_client::SolanaRpcClient;
+use crate::core::units::delta_to_ui;
+use crate::dex::{NftTrade, SwapEvent};
+use deadpool_postgres::{Object, Pool, PoolConfig, Runtime, Timeouts};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
-use tokio_postgres::{Client, NoTls, Row};
+use tokio_postgres::{NoTls, Row};
 
 /// Storage used by side-wallet tracing.
 pub struct DatabaseManager {
@@ -11,17 +16,25 @@ pub struct DatabaseManager {
 }
 
 enum DatabaseInner {
-    Postgres { client: Client },
-    Memory { state: RwLock<MemoryState> },
+    Postgres { pool: Pool },
+    Memory { state: Box<RwLock<MemoryState>> },
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct MemoryState {
     transactions: HashMap<String, EnhancedTransaction>,
     relationships: HashMap<(String, String), MemoryRelationship>,
     transfer_events: HashMap<(String, i32), TransferEvent>,
+    swap_events: HashMap<(String, i32), SwapEvent>,
+    nft_trades: HashMap<(String, String), NftTrade>,
+    watchlist: HashMap<(i64, String), WatchEntry>,
+    wallet_labels: HashMap<(String, String), WalletLabel>,
+    wallet_risk_scores: HashMap<String, WalletRiskScore>,
+    ingestion_failures: HashMap<String, IngestionFailure>,
+    block_time_misses: HashMap<u64, u32>,
 }
 
+#[derive(Clone)]
 struct MemoryRelationship {
     from_wallet: String,
     to_wallet: String,
@@ -32,6 +45,47 @@ struct MemoryRelationship {
     last_seen_epoch: u64,
 }
 
+/// Wallets whose transfers `scratch_copy` loads from Postgres.
+const SCRATCH_MAX_WALLETS: usize = 200;
+
+/// Transfers per wallet `scratch_copy` loads from Postgres (newest first).
+const SCRATCH_EVENTS_PER_WALLET: usize = 1_000;
+
+/// `getBlockTime` lookups that may come back empty for a slot before the
+/// backfill stops asking (pruned slots never get a time).
+const MAX_BLOCK_TIME_ATTEMPTS: i32 = 5;
+
+/// Longest a checkout waits for a free pooled connection (or for a new one to
+/// connect) before failing with a database error instead of hanging the request.
+const POOL_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Retry backoff for failed ingestion: `RETRY_BACKOFF_BASE_SECS` doubled per
+/// attempt after the first, at most `RETRY_BACKOFF_MAX_SECS`.
+const RETRY_BACKOFF_BASE_SECS: u64 = 60;
+const RETRY_BACKOFF_MAX_SECS: u64 = 6 * 3_600;
+
+/// `IngestionFailure::retry_at` over an `ingestion_failures` row.
+const RETRY_AT_SQL: &str =
+    "last_failed_at + LEAST(60::BIGINT << LEAST(GREATEST(attempts - 1, 0), 16), 21600)";
+
+fn ingestion_failure_from_row(row: &Row) -> IngestionFailure {
+    IngestionFailure {
+        signature: row.get::<_, String>(0),
+        stage: row.get::<_, String>(1),
+        error: row.get::<_, String>(2),
+        attempts: row.get::<_, i32>(3) as u32,
+        first_failed_at: row.get::<_, i64>(4) as u64,
+        last_failed_at: row.get::<_, i64>(5) as u64,
+    }
+}
+
+/// Take a connection from the pool; broken connections are replaced on checkout.
+async fn checkout(pool: &Pool) -> BeastResult<Object> {
+    pool.get()
+        .await
+        .map_err(|e| BeastError::DatabaseError(format!("Failed to get connection: {}", e)))
+}
+
 fn now_epoch() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -47,38 +101,198 @@ fn passes_since(block_time: Option<i64>, since_epoch: i64) -> bool {
 }
 
 impl DatabaseManager {
+    /// Empty in-process backend; nothing survives a restart and `init_schema` is a no-op.
+    /// Same as `new("memory")`.
+    ///
+    /// ```
+    /// use onchain_beast::core::enhanced_parser::EnhancedTransactionParser;
+    /// use onchain_beast::storage::{DatabaseManager, StoredOutcome};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let db = DatabaseManager::in_memory();
+    /// let raw = serde_json::json!({
+    ///     "slot": 1u64,
+    ///     "blockTime": 1_700_000_000u64,
+    ///     "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+    ///     "transaction": { "message": { "accountKeys": [], "instructions": [] } }
+    /// });
+    /// let tx = EnhancedTransactionParser::new()
+    ///     .parse(&raw, "sig".to_string())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(db.store_transaction(&tx).await.unwrap(), StoredOutcome::Inserted);
+    /// let types = db.get_transaction_types(&["sig".to_string()]).await.unwrap();
+    /// assert!(types.contains_key("sig"));
+    /// # }
+    /// ```
+    pub fn in_memory() -> Self {
+        Self {
+            inner: DatabaseInner::Memory {
+                state: Box::new(RwLock::new(MemoryState::default())),
+            },
+        }
+    }
+
+    /// `"memory"` selects the in-memory backend; anything else is a Postgres URL
+    /// served through a pool of `DB_POOL_SIZE` connections (default 16).
     pub async fn new(database_url: &str) -> BeastResult<Self> {
         let database_url = database_url.trim();
 
         if database_url.eq_ignore_ascii_case("memory") || database_url.starts_with("memory:") {
-            return Ok(Self {
-                inner: DatabaseInner::Memory {
-                    state: RwLock::new(MemoryState::default()),
-                },
-            });
+            return Ok(Self::in_memory());
         }
 
-        let (client, connection) = tokio_postgres::connect(database_url, NoTls)
-            .await
-            .map_err(|e| BeastError::DatabaseError(format!("Failed to connect: {}", e)))?;
+        let pool_size = env_parse_in("DB_POOL_SIZE", 16, 1..=256);
+        Self::connect(database_url, pool_size).await
+    }
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("Database connection error: {}", e);
-            }
-        });
+    /// Postgres backend with at most `pool_size` connections. One connection is
+    /// opened up front so a bad URL or unreachable server fails here.
+    pub async fn connect(database_url: &str, pool_size: usize) -> BeastResult<Self> {
+        let config = deadpool_postgres::Config {
+            url: Some(database_url.to_string()),
+            pool: Some(PoolConfig {
+                timeouts: Timeouts {
+                    wait: Some(POOL_WAIT_TIMEOUT),
+                    create: Some(POOL_WAIT_TIMEOUT),
+                    recycle: None,
+                },
+                ..PoolConfig::new(pool_size.max(1))
+            }),
+            ..Default::default()
+        };
+        let pool = config
+            .create_pool(Some(Runtime::Tokio1), NoTls)
+            .map_err(|e| BeastError::DatabaseError(format!("Failed to create pool: {}", e)))?;
+        drop(checkout(&pool).await?);
 
         Ok(Self {
-            inner: DatabaseInner::Postgres { client },
+            inner: DatabaseInner::Postgres { pool },
+        })
+    }
+
+    /// Connection pool usage; `None` for the in-memory backend.
+    pub fn pool_status(&self) -> Option<PoolStatus> {
+        let DatabaseInner::Postgres { pool } = &self.inner else {
+            return None;
+        };
+        let status = pool.status();
+        Some(PoolStatus {
+            max_size: status.max_size,
+            open: status.size,
+            idle: status.available,
+            waiting: status.waiting,
         })
     }
 
+    /// Close the Postgres pool: idle connections are dropped now, checked-out
+    /// ones when returned, and later checkouts fail. No-op in memory.
+    pub fn close(&self) {
+        if let DatabaseInner::Postgres { pool } = &self.inner {
+            pool.close();
+        }
+    }
+
+    /// Hold the in-memory backend's write lock until the returned guard drops,
+    /// stalling every read and write meanwhile.
+    #[cfg(test)]
+    pub(crate) async fn lock_memory(&self) -> Box<dyn Send + Sync + '_> {
+        match &self.inner {
+            DatabaseInner::Memory { state } => Box::new(state.write().await),
+            DatabaseInner::Postgres { .. } => panic!("lock_memory needs the in-memory backend"),
+        }
+    }
+
+    /// In-memory copy of the stored history around `wallet`, for dry runs that read
+    /// it but must not write to it. An in-memory store is copied whole. From
+    /// Postgres, transfers since `since_epoch` of `wallet` and, hop by hop, of its
+    /// counterparties up to `depth` hops away are copied (at most
+    /// `SCRATCH_MAX_WALLETS` wallets, `SCRATCH_EVENTS_PER_WALLET` transfers each)
+    /// and relationships rebuilt from them; other tables are not copied.
+    pub async fn scratch_copy(
+        &self,
+        wallet: &str,
+        depth: usize,
+        since_epoch: u64,
+        include_self_transfers: bool,
+    ) -> BeastResult<DatabaseManager> {
+        let state = match &self.inner {
+            DatabaseInner::Memory { state } => state.read().await.clone(),
+            DatabaseInner::Postgres { .. } => {
+                let mut copy = MemoryState::default();
+                let mut seen: HashSet<String> = HashSet::from([wallet.to_string()]);
+                let mut frontier = vec![wallet.to_string()];
+                for hop in 0..=depth {
+                    let mut next = Vec::new();
+                    for w in &frontier {
+                        let events = self
+                            .get_transfer_events_for_wallet(
+                                w,
+                                None,
+                                None,
+                                Some(since_epoch),
+                                SCRATCH_EVENTS_PER_WALLET,
+                            )
+                            .await?;
+                        for ev in events {
+                            if hop < depth {
+                                for other in [&ev.from_wallet, &ev.to_wallet].into_iter().flatten()
+                                {
+                                    if seen.len() < SCRATCH_MAX_WALLETS
+                                        && seen.insert(other.clone())
+                                    {
+                                        next.push(other.clone());
+                                    }
+                                }
+                            }
+                            copy.transfer_events
+                                .insert((ev.signature.clone(), ev.event_index), ev);
+                        }
+                    }
+                    frontier = next;
+                }
+                copy
+            }
+        };
+
+        let scratch = DatabaseManager {
+            inner: DatabaseInner::Memory {
+                state: Box::new(RwLock::new(state)),
+            },
+        };
+        if matches!(self.inner, DatabaseInner::Postgres { .. }) {
+            scratch
+                .rebuild_relationships_from_events(0, include_self_transfers)
+                .await?;
+        }
+        Ok(scratch)
+    }
+
+    /// Cheap round trip used by health checks (`SELECT 1`; a lock acquisition in memory).
+    pub async fn ping(&self) -> BeastResult<()> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .simple_query("SELECT 1")
+                    .await
+                    .map_err(|e| BeastError::DatabaseError(format!("Failed to ping: {}", e)))?;
+            }
+            DatabaseInner::Memory { state } => {
+                let _mem = state.read().await;
+            }
+        }
+        Ok(())
+    }
+
     /// Initialize minimal schema required for tracing.
     pub async fn init_schema(&self) -> BeastResult<()> {
-        let DatabaseInner::Postgres { client } = &self.inner else {
+        let DatabaseInner::Postgres { pool } = &self.inner else {
             // In-memory backend requires no schema.
             return Ok(());
         };
+        let client = checkout(pool).await?;
 
         client
             .execute(
@@ -100,6 +314,16 @@ impl DatabaseManager {
                 BeastError::DatabaseError(format!("Failed to create transactions table: {}", e))
             })?;
 
+        client
+            .execute(
+                "ALTER TABLE transactions ADD COLUMN IF NOT EXISTS finalized BOOLEAN NOT NULL DEFAULT FALSE",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!("Failed to add transactions.finalized: {}", e))
+            })?;
+
         client
             .execute(
                 "CREATE INDEX IF NOT EXISTS idx_transactions_slot ON transactions(slot)",
@@ -222,24 +446,236 @@ impl DatabaseManager {
             .await
             .ok();
 
+        // Net DEX swaps per wallet (one row per decoded swap).
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS swap_events (
+                    id SERIAL PRIMARY KEY,
+                    signature TEXT NOT NULL,
+                    event_index INTEGER NOT NULL,
+                    slot BIGINT NOT NULL,
+                    block_time BIGINT,
+                    wallet TEXT NOT NULL,
+                    dex_name TEXT NOT NULL,
+                    program_id TEXT,
+                    pool_address TEXT,
+                    token_in_mint TEXT NOT NULL,
+                    amount_in DOUBLE PRECISION NOT NULL,
+                    token_out_mint TEXT NOT NULL,
+                    amount_out DOUBLE PRECISION NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                    UNIQUE(signature, event_index)
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!("Failed to create swap_events table: {}", e))
+            })?;
+
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_swap_events_wallet ON swap_events(wallet)",
+                &[],
+            )
+            .await
+            .ok();
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_swap_events_token_in ON swap_events(token_in_mint)",
+                &[],
+            )
+            .await
+            .ok();
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_swap_events_token_out ON swap_events(token_out_mint)",
+                &[],
+            )
+            .await
+            .ok();
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_swap_events_block_time ON swap_events(block_time)",
+                &[],
+            )
+            .await
+            .ok();
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_swap_events_pool_slot ON swap_events(pool_address, slot)",
+                &[],
+            )
+            .await
+            .ok();
+
+        // NFT marketplace sales (one row per NFT sold).
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS nft_trades (
+                    id SERIAL PRIMARY KEY,
+                    signature TEXT NOT NULL,
+                    slot BIGINT NOT NULL,
+                    block_time BIGINT,
+                    marketplace TEXT NOT NULL,
+                    program_id TEXT NOT NULL,
+                    mint TEXT NOT NULL,
+                    seller TEXT NOT NULL,
+                    buyer TEXT NOT NULL,
+                    price_sol DOUBLE PRECISION NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                    UNIQUE(signature, mint)
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!("Failed to create nft_trades table: {}", e))
+            })?;
+
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_nft_trades_seller ON nft_trades(seller)",
+                &[],
+            )
+            .await
+            .ok();
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_nft_trades_buyer ON nft_trades(buyer)",
+                &[],
+            )
+            .await
+            .ok();
+
+        // Wallets watched by Telegram chats for transfer alerts.
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS watchlist (
+                    chat_id BIGINT NOT NULL,
+                    wallet TEXT NOT NULL,
+                    min_sol DOUBLE PRECISION NOT NULL,
+                    last_signature TEXT,
+                    created_at BIGINT NOT NULL,
+                    PRIMARY KEY (chat_id, wallet)
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!("Failed to create watchlist table: {}", e))
+            })?;
+
+        // Investigator labels; a wallet can carry several.
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS wallet_labels (
+                    wallet TEXT NOT NULL,
+                    label TEXT NOT NULL,
+                    note TEXT,
+                    author TEXT,
+                    created_at BIGINT NOT NULL,
+                    updated_at BIGINT NOT NULL,
+                    PRIMARY KEY (wallet, label)
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!("Failed to create wallet_labels table: {}", e))
+            })?;
+
+        // Latest risk score per wallet, for the high-risk listing.
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS wallet_risk_scores (
+                    wallet TEXT PRIMARY KEY,
+                    score DOUBLE PRECISION NOT NULL,
+                    computed_at BIGINT NOT NULL
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!(
+                    "Failed to create wallet_risk_scores table: {}",
+                    e
+                ))
+            })?;
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_wallet_risk_scores_score ON wallet_risk_scores(score DESC)",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!(
+                    "Failed to create wallet_risk_scores index: {}",
+                    e
+                ))
+            })?;
+
+        // Signatures whose ingestion failed, kept for retry.
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS ingestion_failures (
+                    signature TEXT PRIMARY KEY,
+                    stage TEXT NOT NULL,
+                    error TEXT NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 1,
+                    first_failed_at BIGINT NOT NULL,
+                    last_failed_at BIGINT NOT NULL
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!(
+                    "Failed to create ingestion_failures table: {}",
+                    e
+                ))
+            })?;
+
+        // Slots the block time backfill found no time for, so it can skip them.
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS block_time_misses (
+                    slot BIGINT PRIMARY KEY,
+                    attempts INTEGER NOT NULL DEFAULT 1,
+                    last_attempt_at BIGINT NOT NULL
+                )",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                BeastError::DatabaseError(format!(
+                    "Failed to create block_time_misses table: {}",
+                    e
+                ))
+            })?;
+
         Ok(())
     }
 
-    pub async fn store_transaction(&self, tx: &EnhancedTransaction) -> BeastResult<()> {
+    pub async fn store_transaction(&self, tx: &EnhancedTransaction) -> BeastResult<StoredOutcome> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 let data = serde_json::to_value(tx).map_err(|e| {
                     BeastError::DatabaseError(format!("Failed to serialize transaction: {}", e))
                 })?;
 
-                client
-                    .execute(
-                        "INSERT INTO transactions (signature, slot, block_time, success, fee, sol_transfers_count, token_transfers_count, data)
-                         VALUES ($1, $2, $3, $4, $5, $6, $7, ($8::text)::jsonb)
+                // xmax is 0 only on a freshly inserted row version.
+                let row = client
+                    .query_one(
+                        "INSERT INTO transactions (signature, slot, block_time, success, fee, sol_transfers_count, token_transfers_count, data, finalized)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, ($8::text)::jsonb, $9)
                          ON CONFLICT (signature) DO UPDATE SET
                             data = EXCLUDED.data,
                             sol_transfers_count = EXCLUDED.sol_transfers_count,
-                            token_transfers_count = EXCLUDED.token_transfers_count",
+                            token_transfers_count = EXCLUDED.token_transfers_count,
+                            finalized = transactions.finalized OR EXCLUDED.finalized
+                         RETURNING (xmax = 0)",
                         &[
                             &tx.signature,
                             &(tx.slot as i64),
@@ -249,6 +685,7 @@ impl DatabaseManager {
                             &(tx.sol_transfers.len() as i32),
                             &(tx.token_transfers.len() as i32),
                             &data.to_string(),
+                            &tx.finalized,
                         ],
                     )
                     .await
@@ -256,12 +693,16 @@ impl DatabaseManager {
                         BeastError::DatabaseError(format!("Failed to store transaction: {}", e))
                     })?;
 
-                Ok(())
+                Ok(StoredOutcome::from_inserted(row.get::<_, bool>(0)))
             }
             DatabaseInner::Memory { state } => {
                 let mut mem = state.write().await;
-                mem.transactions.insert(tx.signature.clone(), tx.clone());
-                Ok(())
+                let mut stored = tx.clone();
+                if let Some(previous) = mem.transactions.get(&tx.signature) {
+                    stored.finalized |= previous.finalized;
+                }
+                let previous = mem.transactions.insert(tx.signature.clone(), stored);
+                Ok(StoredOutcome::from_inserted(previous.is_none()))
             }
         }
     }
@@ -275,7 +716,8 @@ impl DatabaseManager {
         token_amount: u64,
     ) -> BeastResult<()> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 client
                     .execute(
                         "INSERT INTO wallet_relationships (from_wallet, to_wallet, sol_amount, token_amount, transaction_count)
@@ -320,16 +762,148 @@ impl DatabaseManager {
         }
     }
 
+    /// Recompute `wallet_relationships` from `transfer_events`, replacing the stored
+    /// aggregates: one edge per (from, to) pair with summed SOL and raw token
+    /// amounts, one count per event and first/last seen from event block times.
+    /// `since_epoch = 0` rebuilds every edge (edges without events are dropped);
+    /// otherwise only pairs with an event at or after `since_epoch` are rebuilt,
+    /// from all of their events. Self-transfer edges are kept only with
+    /// `include_self_transfers`. Returns the number of edges written.
+    pub async fn rebuild_relationships_from_events(
+        &self,
+        since_epoch: u64,
+        include_self_transfers: bool,
+    ) -> BeastResult<u64> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let mut client = checkout(pool).await?;
+                let since = since_epoch as i64;
+                let db_tx = client.transaction().await.map_err(|e| {
+                    BeastError::DatabaseError(format!("Failed to start rebuild: {}", e))
+                })?;
+                db_tx
+                    .execute(
+                        "DELETE FROM wallet_relationships
+                         WHERE $1::BIGINT <= 0
+                            OR (from_wallet, to_wallet) IN (
+                                SELECT from_wallet, to_wallet FROM transfer_events WHERE block_time >= $1
+                            )",
+                        &[&since],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to clear relationships: {}", e))
+                    })?;
+                // Ingestion may recreate a deleted edge before this insert runs;
+                // the rebuilt totals replace it instead of failing the rebuild.
+                let written = db_tx
+                    .execute(
+                        "INSERT INTO wallet_relationships
+                            (from_wallet, to_wallet, sol_amount, token_amount, transaction_count, first_seen, last_seen)
+                         SELECT from_wallet, to_wallet,
+                                COALESCE(SUM(amount_sol), 0),
+                                COALESCE(SUM(token_amount), 0)::bigint,
+                                COUNT(*)::int,
+                                COALESCE(to_timestamp(MIN(block_time)), NOW()),
+                                COALESCE(to_timestamp(MAX(block_time)), NOW())
+                         FROM transfer_events
+                         WHERE from_wallet IS NOT NULL AND to_wallet IS NOT NULL
+                           AND ($2 OR from_wallet <> to_wallet)
+                           AND ($1::BIGINT <= 0 OR (from_wallet, to_wallet) IN (
+                                SELECT from_wallet, to_wallet FROM transfer_events WHERE block_time >= $1
+                           ))
+                         GROUP BY from_wallet, to_wallet
+                         ON CONFLICT (from_wallet, to_wallet) DO UPDATE SET
+                            sol_amount = EXCLUDED.sol_amount,
+                            token_amount = EXCLUDED.token_amount,
+                            transaction_count = EXCLUDED.transaction_count,
+                            first_seen = EXCLUDED.first_seen,
+                            last_seen = EXCLUDED.last_seen",
+                        &[&since, &include_self_transfers],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to rebuild relationships: {}", e))
+                    })?;
+                db_tx.commit().await.map_err(|e| {
+                    BeastError::DatabaseError(format!("Failed to commit rebuild: {}", e))
+                })?;
+                Ok(written)
+            }
+            DatabaseInner::Memory { state } => {
+                let now = now_epoch();
+                let mut mem = state.write().await;
+                let touched: Option<HashSet<(String, String)>> = (since_epoch > 0).then(|| {
+                    mem.transfer_events
+                        .values()
+                        .filter(|e| e.block_time.is_some_and(|t| t >= since_epoch as i64))
+                        .filter_map(|e| Some((e.from_wallet.clone()?, e.to_wallet.clone()?)))
+                        .collect()
+                });
+                let rebuilt =
+                    |key: &(String, String)| touched.as_ref().is_none_or(|t| t.contains(key));
+
+                let mut edges: HashMap<(String, String), MemoryRelationship> = HashMap::new();
+                let mut seen: HashMap<(String, String), (Option<u64>, Option<u64>)> =
+                    HashMap::new();
+                for event in mem.transfer_events.values() {
+                    let (Some(from), Some(to)) = (&event.from_wallet, &event.to_wallet) else {
+                        continue;
+                    };
+                    let key = (from.clone(), to.clone());
+                    if (from == to && !include_self_transfers) || !rebuilt(&key) {
+                        continue;
+                    }
+                    let edge = edges
+                        .entry(key.clone())
+                        .or_insert_with(|| MemoryRelationship {
+                            from_wallet: from.clone(),
+                            to_wallet: to.clone(),
+                            sol_amount: 0.0,
+                            token_amount: 0,
+                            transaction_count: 0,
+                            first_seen_epoch: now,
+                            last_seen_epoch: now,
+                        });
+                    edge.sol_amount += event.amount_sol.unwrap_or(0.0);
+                    edge.token_amount = edge
+                        .token_amount
+                        .saturating_add(event.token_amount.unwrap_or(0).max(0) as u64);
+                    edge.transaction_count = edge.transaction_count.saturating_add(1);
+
+                    if let Some(t) = event.block_time.map(|t| t.max(0) as u64) {
+                        let (first, last) = seen.entry(key).or_default();
+                        *first = Some(first.map_or(t, |f| f.min(t)));
+                        *last = Some(last.map_or(t, |l| l.max(t)));
+                    }
+                }
+                for (key, (first, last)) in seen {
+                    if let Some(edge) = edges.get_mut(&key) {
+                        edge.first_seen_epoch = first.unwrap_or(now);
+                        edge.last_seen_epoch = last.unwrap_or(now);
+                    }
+                }
+
+                mem.relationships.retain(|key, _| !rebuilt(key));
+                let written = edges.len() as u64;
+                mem.relationships.extend(edges);
+                Ok(written)
+            }
+        }
+    }
+
     /// Store a SOL transfer as an event (idempotent per signature+event_index).
+    /// Returns whether it was new.
     pub async fn store_sol_transfer_event(
         &self,
         tx: &EnhancedTransaction,
         transfer: &SolTransfer,
         event_index: i32,
-    ) -> BeastResult<()> {
+    ) -> BeastResult<bool> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
-                client
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let inserted = client
                     .execute(
                         "INSERT INTO transfer_events (
                             signature,
@@ -366,34 +940,20 @@ impl DatabaseManager {
                         ))
                     })?;
 
-                Ok(())
+                Ok(inserted > 0)
             }
             DatabaseInner::Memory { state } => {
                 let mut mem = state.write().await;
                 let key = (tx.signature.clone(), event_index);
                 if mem.transfer_events.contains_key(&key) {
-                    return Ok(());
+                    return Ok(false);
                 }
 
                 mem.transfer_events.insert(
                     key,
-                    TransferEvent {
-                        signature: tx.signature.clone(),
-                        event_index,
-                        slot: tx.slot as i64,
-                        block_time: tx.block_time.map(|t| t as i64),
-                        kind: "sol".to_string(),
-                        transfer_type: transfer.transfer_type.clone(),
-                        from_wallet: Some(transfer.from.clone()),
-                        to_wallet: Some(transfer.to.clone()),
-                        mint: None,
-                        amount_sol: Some(transfer.amount_sol),
-                        token_amount_ui: None,
-                        token_amount: None,
-                        token_decimals: None,
-                    },
+                    TransferEvent::from_sol_transfer(tx, transfer, event_index),
                 );
-                Ok(())
+                Ok(true)
             }
         }
     }
@@ -409,7 +969,8 @@ impl DatabaseManager {
         let to_wallet = transfer.to_owner.as_deref();
 
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 client
                     .execute(
                         "INSERT INTO transfer_events (
@@ -487,200 +1048,216 @@ impl DatabaseManager {
         }
     }
 
-    /// Find shared inbound funders (wallets that sent to both A and B).
-    pub async fn get_shared_inbound_senders(
+    /// Store a decoded swap (idempotent per signature+event_index).
+    pub async fn store_swap_event(&self, swap: &SwapEvent) -> BeastResult<()> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .execute(
+                        "INSERT INTO swap_events (
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            wallet,
+                            dex_name,
+                            program_id,
+                            pool_address,
+                            token_in_mint,
+                            amount_in,
+                            token_out_mint,
+                            amount_out
+                         ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+                         ON CONFLICT (signature, event_index) DO NOTHING",
+                        &[
+                            &swap.signature,
+                            &swap.event_index,
+                            &(swap.slot as i64),
+                            &swap.block_time.map(|t| t as i64),
+                            &swap.wallet,
+                            &swap.dex_name,
+                            &swap.program_id,
+                            &swap.pool_address,
+                            &swap.token_in_mint,
+                            &swap.amount_in,
+                            &swap.token_out_mint,
+                            &swap.amount_out,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to store swap event: {}", e))
+                    })?;
+
+                Ok(())
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                mem.swap_events
+                    .entry((swap.signature.clone(), swap.event_index))
+                    .or_insert_with(|| swap.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Get swaps executed by a wallet (newest first).
+    pub async fn get_swaps_for_wallet(
         &self,
-        wallet_a: &str,
-        wallet_b: &str,
+        wallet: &str,
         since_epoch: Option<u64>,
         limit: usize,
-    ) -> BeastResult<Vec<SharedWalletSignal>> {
-        let since_epoch = since_epoch.unwrap_or(0) as i64;
-        let limit = (limit as i64).clamp(1, 50);
+    ) -> BeastResult<Vec<SwapEvent>> {
+        self.query_swaps(SwapFilter::Wallet(wallet), since_epoch, limit)
+            .await
+    }
+
+    /// Get swaps where a mint was bought or sold (newest first).
+    pub async fn get_swaps_for_mint(
+        &self,
+        mint: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<SwapEvent>> {
+        self.query_swaps(SwapFilter::Mint(mint), since_epoch, limit)
+            .await
+    }
+
+    /// Get swaps through `pool_address` landing in slots `from_slot..=to_slot` (by slot,
+    /// then signature).
+    pub async fn get_pool_swaps_in_slots(
+        &self,
+        pool_address: &str,
+        from_slot: u64,
+        to_slot: u64,
+        limit: usize,
+    ) -> BeastResult<Vec<SwapEvent>> {
+        let limit = (limit as i64).clamp(1, 1000);
 
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 let rows = client
                     .query(
-                        "WITH a AS (
-                            SELECT from_wallet,
-                                   COUNT(*)::BIGINT AS cnt,
-                                   MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
-                            FROM transfer_events
-                            WHERE to_wallet = $1
-                              AND from_wallet IS NOT NULL
-                              AND (block_time IS NULL OR block_time >= $3)
-                            GROUP BY from_wallet
-                         ),
-                         b AS (
-                            SELECT from_wallet,
-                                   COUNT(*)::BIGINT AS cnt,
-                                   MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
-                            FROM transfer_events
-                            WHERE to_wallet = $2
-                              AND from_wallet IS NOT NULL
-                              AND (block_time IS NULL OR block_time >= $3)
-                            GROUP BY from_wallet
-                         )
-                         SELECT a.from_wallet,
-                                (a.cnt + b.cnt)::BIGINT AS total_cnt,
-                                GREATEST(a.last_seen, b.last_seen)::BIGINT AS last_seen
-                         FROM a
-                         INNER JOIN b ON a.from_wallet = b.from_wallet
-                         ORDER BY total_cnt DESC
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            wallet,
+                            dex_name,
+                            program_id,
+                            pool_address,
+                            token_in_mint,
+                            amount_in,
+                            token_out_mint,
+                            amount_out
+                         FROM swap_events
+                         WHERE pool_address = $1 AND slot BETWEEN $2 AND $3
+                         ORDER BY slot, signature, event_index
                          LIMIT $4",
-                        &[&wallet_a, &wallet_b, &since_epoch, &limit],
+                        &[
+                            &pool_address,
+                            &(from_slot as i64),
+                            &(to_slot as i64),
+                            &limit,
+                        ],
                     )
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!(
-                            "Failed to get shared inbound senders: {}",
-                            e
-                        ))
+                        BeastError::DatabaseError(format!("Failed to get pool swaps: {}", e))
                     })?;
 
-                Ok(rows
-                    .iter()
-                    .map(|row| SharedWalletSignal {
-                        wallet: row.get::<_, String>(0),
-                        count: row.get::<_, i64>(1) as u64,
-                        last_seen_epoch: row.get::<_, i64>(2) as u64,
-                    })
-                    .collect())
+                Ok(rows.iter().map(swap_event_from_row).collect())
             }
             DatabaseInner::Memory { state } => {
                 let mem = state.read().await;
+                let mut out: Vec<SwapEvent> = mem
+                    .swap_events
+                    .values()
+                    .filter(|s| s.pool_address.as_deref() == Some(pool_address))
+                    .filter(|s| (from_slot..=to_slot).contains(&s.slot))
+                    .cloned()
+                    .collect();
 
-                let mut a: HashMap<String, (u64, u64)> = HashMap::new();
-                let mut b: HashMap<String, (u64, u64)> = HashMap::new();
-
-                for ev in mem.transfer_events.values() {
-                    if !passes_since(ev.block_time, since_epoch) {
-                        continue;
-                    }
-
-                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
-                    if ev.to_wallet.as_deref() == Some(wallet_a) {
-                        let Some(from) = ev.from_wallet.as_ref() else {
-                            continue;
-                        };
-                        let entry = a.entry(from.clone()).or_insert((0, 0));
-                        entry.0 += 1;
-                        entry.1 = entry.1.max(last_seen);
-                    }
-                    if ev.to_wallet.as_deref() == Some(wallet_b) {
-                        let Some(from) = ev.from_wallet.as_ref() else {
-                            continue;
-                        };
-                        let entry = b.entry(from.clone()).or_insert((0, 0));
-                        entry.0 += 1;
-                        entry.1 = entry.1.max(last_seen);
-                    }
-                }
-
-                let mut out: Vec<SharedWalletSignal> = Vec::new();
-                for (wallet, (cnt_a, last_a)) in a {
-                    let Some((cnt_b, last_b)) = b.get(&wallet) else {
-                        continue;
-                    };
-                    out.push(SharedWalletSignal {
-                        wallet,
-                        count: cnt_a + *cnt_b,
-                        last_seen_epoch: last_a.max(*last_b),
-                    });
-                }
-
-                out.sort_by(|x, y| y.count.cmp(&x.count));
+                out.sort_by(|a, b| {
+                    a.slot
+                        .cmp(&b.slot)
+                        .then_with(|| a.signature.cmp(&b.signature))
+                        .then_with(|| a.event_index.cmp(&b.event_index))
+                });
                 out.truncate(limit as usize);
                 Ok(out)
             }
         }
     }
 
-    /// Get top counterparties for a wallet from transfer_events.
-    pub async fn get_top_counterparties(
+    async fn query_swaps(
         &self,
-        wallet: &str,
+        filter: SwapFilter<'_>,
         since_epoch: Option<u64>,
         limit: usize,
-    ) -> BeastResult<Vec<SharedWalletSignal>> {
+    ) -> BeastResult<Vec<SwapEvent>> {
         let since_epoch = since_epoch.unwrap_or(0) as i64;
-        let limit = (limit as i64).clamp(1, 200);
+        let limit = (limit as i64).clamp(1, 1000);
 
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let (predicate, value) = match filter {
+                    SwapFilter::Wallet(w) => ("wallet = $1", w),
+                    SwapFilter::Mint(m) => ("(token_in_mint = $1 OR token_out_mint = $1)", m),
+                };
+                let sql = format!(
+                    "SELECT
+                        signature,
+                        event_index,
+                        slot,
+                        block_time,
+                        wallet,
+                        dex_name,
+                        program_id,
+                        pool_address,
+                        token_in_mint,
+                        amount_in,
+                        token_out_mint,
+                        amount_out
+                     FROM swap_events
+                     WHERE {}
+                       AND (block_time IS NULL OR block_time >= $2)
+                     ORDER BY COALESCE(block_time, 0) DESC, signature DESC, event_index DESC
+                     LIMIT $3",
+                    predicate
+                );
                 let rows = client
-                    .query(
-                        "SELECT
-                            CASE WHEN from_wallet = $1 THEN to_wallet ELSE from_wallet END AS counterparty,
-                            COUNT(*)::BIGINT AS cnt,
-                            MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
-                         FROM transfer_events
-                         WHERE (from_wallet = $1 OR to_wallet = $1)
-                           AND from_wallet IS NOT NULL
-                           AND to_wallet IS NOT NULL
-                           AND (block_time IS NULL OR block_time >= $2)
-                         GROUP BY counterparty
-                         ORDER BY cnt DESC
-                         LIMIT $3",
-                        &[&wallet, &since_epoch, &limit],
-                    )
+                    .query(sql.as_str(), &[&value, &since_epoch, &limit])
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!("Failed to get top counterparties: {}", e))
+                        BeastError::DatabaseError(format!("Failed to get swap events: {}", e))
                     })?;
 
-                Ok(rows
-                    .iter()
-                    .filter_map(|row| {
-                        let counterparty: Option<String> = row.get(0);
-                        counterparty.map(|w| SharedWalletSignal {
-                            wallet: w,
-                            count: row.get::<_, i64>(1) as u64,
-                            last_seen_epoch: row.get::<_, i64>(2) as u64,
-                        })
-                    })
-                    .collect())
+                Ok(rows.iter().map(swap_event_from_row).collect())
             }
             DatabaseInner::Memory { state } => {
                 let mem = state.read().await;
-
-                let mut agg: HashMap<String, (u64, u64)> = HashMap::new();
-                for ev in mem.transfer_events.values() {
-                    if !passes_since(ev.block_time, since_epoch) {
-                        continue;
-                    }
-
-                    let (Some(from), Some(to)) = (&ev.from_wallet, &ev.to_wallet) else {
-                        continue;
-                    };
-                    if from != wallet && to != wallet {
-                        continue;
-                    }
-
-                    let counterparty = if from == wallet {
-                        to.clone()
-                    } else {
-                        from.clone()
-                    };
-                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
-                    let entry = agg.entry(counterparty).or_insert((0, 0));
-                    entry.0 += 1;
-                    entry.1 = entry.1.max(last_seen);
-                }
-
-                let mut out: Vec<SharedWalletSignal> = agg
-                    .into_iter()
-                    .map(|(wallet, (count, last_seen_epoch))| SharedWalletSignal {
-                        wallet,
-                        count,
-                        last_seen_epoch,
+                let mut out: Vec<SwapEvent> = mem
+                    .swap_events
+                    .values()
+                    .filter(|s| passes_since(s.block_time.map(|t| t as i64), since_epoch))
+                    .filter(|s| match filter {
+                        SwapFilter::Wallet(w) => s.wallet == w,
+                        SwapFilter::Mint(m) => s.token_in_mint == m || s.token_out_mint == m,
                     })
+                    .cloned()
                     .collect();
+
                 out.sort_by(|a, b| {
-                    b.count
-                        .cmp(&a.count)
-                        .then_with(|| b.last_seen_epoch.cmp(&a.last_seen_epoch))
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    tb.cmp(&ta)
+                        .then_with(|| b.signature.cmp(&a.signature))
+                        .then_with(|| b.event_index.cmp(&a.event_index))
                 });
                 out.truncate(limit as usize);
                 Ok(out)
@@ -688,117 +1265,122 @@ impl DatabaseManager {
         }
     }
 
-    /// Get top outbound recipients for a wallet from transfer_events, with volume signals.
-    pub async fn get_top_outbound_recipients(
+    /// Store a decoded NFT sale (idempotent per signature+mint).
+    pub async fn store_nft_trade(&self, trade: &NftTrade) -> BeastResult<()> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .execute(
+                        "INSERT INTO nft_trades (
+                            signature,
+                            slot,
+                            block_time,
+                            marketplace,
+                            program_id,
+                            mint,
+                            seller,
+                            buyer,
+                            price_sol
+                         ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+                         ON CONFLICT (signature, mint) DO NOTHING",
+                        &[
+                            &trade.signature,
+                            &(trade.slot as i64),
+                            &trade.block_time.map(|t| t as i64),
+                            &trade.marketplace,
+                            &trade.program_id,
+                            &trade.mint,
+                            &trade.seller,
+                            &trade.buyer,
+                            &trade.price_sol,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to store NFT trade: {}", e))
+                    })?;
+
+                Ok(())
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                mem.nft_trades
+                    .entry((trade.signature.clone(), trade.mint.clone()))
+                    .or_insert_with(|| trade.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Get NFT sales where a wallet was the buyer or the seller (newest first).
+    pub async fn get_nft_trades_for_wallet(
         &self,
         wallet: &str,
         since_epoch: Option<u64>,
         limit: usize,
-    ) -> BeastResult<Vec<WalletVolumeSignal>> {
+    ) -> BeastResult<Vec<NftTrade>> {
         let since_epoch = since_epoch.unwrap_or(0) as i64;
-        let limit = (limit as i64).clamp(1, 200);
+        let limit = (limit as i64).clamp(1, 1000);
 
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 let rows = client
                     .query(
                         "SELECT
-                            to_wallet AS counterparty,
-                            COUNT(*)::BIGINT AS cnt,
-                            SUM(CASE WHEN kind = 'sol' THEN COALESCE(amount_sol, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS total_sol,
-                            SUM(CASE WHEN kind = 'token' THEN COALESCE(token_amount_ui, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS total_token_ui,
-                            MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
-                         FROM transfer_events
-                         WHERE from_wallet = $1
-                           AND to_wallet IS NOT NULL
+                            signature,
+                            slot,
+                            block_time,
+                            marketplace,
+                            program_id,
+                            mint,
+                            seller,
+                            buyer,
+                            price_sol
+                         FROM nft_trades
+                         WHERE (seller = $1 OR buyer = $1)
                            AND (block_time IS NULL OR block_time >= $2)
-                         GROUP BY to_wallet
-                         ORDER BY total_sol DESC, total_token_ui DESC, cnt DESC
+                         ORDER BY COALESCE(block_time, 0) DESC, signature DESC, mint DESC
                          LIMIT $3",
                         &[&wallet, &since_epoch, &limit],
                     )
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!(
-                            "Failed to get top outbound recipients: {}",
-                            e
-                        ))
+                        BeastError::DatabaseError(format!("Failed to get NFT trades: {}", e))
                     })?;
 
                 Ok(rows
                     .iter()
-                    .filter_map(|row| {
-                        let counterparty: Option<String> = row.get(0);
-                        counterparty.map(|w| WalletVolumeSignal {
-                            wallet: w,
-                            count: row.get::<_, i64>(1) as u64,
-                            total_sol: row.get::<_, f64>(2),
-                            total_token_ui: row.get::<_, f64>(3),
-                            last_seen_epoch: row.get::<_, i64>(4) as u64,
-                        })
+                    .map(|row| NftTrade {
+                        signature: row.get::<_, String>(0),
+                        slot: row.get::<_, i64>(1) as u64,
+                        block_time: row.get::<_, Option<i64>>(2).map(|t| t as u64),
+                        marketplace: row.get::<_, String>(3),
+                        program_id: row.get::<_, String>(4),
+                        mint: row.get::<_, String>(5),
+                        seller: row.get::<_, String>(6),
+                        buyer: row.get::<_, String>(7),
+                        price_sol: row.get::<_, f64>(8),
                     })
                     .collect())
             }
             DatabaseInner::Memory { state } => {
                 let mem = state.read().await;
-
-                let mut agg: HashMap<String, (u64, f64, f64, u64)> = HashMap::new();
-                for ev in mem.transfer_events.values() {
-                    if !passes_since(ev.block_time, since_epoch) {
-                        continue;
-                    }
-                    if ev.from_wallet.as_deref() != Some(wallet) {
-                        continue;
-                    }
-                    let Some(to) = ev.to_wallet.as_ref() else {
-                        continue;
-                    };
-
-                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
-                    let entry = agg.entry(to.clone()).or_insert((0, 0.0, 0.0, 0));
-                    entry.0 += 1;
-                    entry.3 = entry.3.max(last_seen);
-
-                    match ev.kind.as_str() {
-                        "sol" => {
-                            if let Some(a) = ev.amount_sol {
-                                entry.1 += a.max(0.0);
-                            }
-                        }
-                        "token" => {
-                            if let Some(a) = ev.token_amount_ui {
-                                entry.2 += a.max(0.0);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-
-                let mut out: Vec<WalletVolumeSignal> = agg
-                    .into_iter()
-                    .map(
-                        |(wallet, (count, total_sol, total_token_ui, last_seen_epoch))| {
-                            WalletVolumeSignal {
-                                wallet,
-                                count,
-                                total_sol,
-                                total_token_ui,
-                                last_seen_epoch,
-                            }
-                        },
-                    )
+                let mut out: Vec<NftTrade> = mem
+                    .nft_trades
+                    .values()
+                    .filter(|t| passes_since(t.block_time.map(|b| b as i64), since_epoch))
+                    .filter(|t| t.seller == wallet || t.buyer == wallet)
+                    .cloned()
                     .collect();
 
                 out.sort_by(|a, b| {
-                    b.total_sol
-                        .partial_cmp(&a.total_sol)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                        .then_with(|| {
-                            b.total_token_ui
-                                .partial_cmp(&a.total_token_ui)
-                                .unwrap_or(std::cmp::Ordering::Equal)
-                        })
-                        .then_with(|| b.count.cmp(&a.count))
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    tb.cmp(&ta)
+                        .then_with(|| b.signature.cmp(&a.signature))
+                        .then_with(|| b.mint.cmp(&a.mint))
                 });
                 out.truncate(limit as usize);
                 Ok(out)
@@ -806,415 +1388,2839 @@ impl DatabaseManager {
         }
     }
 
-    /// Get transfer events from one wallet to another (newest first).
-    pub async fn get_transfers_between(
-        &self,
-        from_wallet: &str,
-        to_wallet: &str,
-        since_epoch: Option<u64>,
-        limit: usize,
-    ) -> BeastResult<Vec<TransferEvent>> {
-        let since_epoch = since_epoch.unwrap_or(0) as i64;
-        let limit = (limit as i64).clamp(1, 500);
-
+    /// Watch `wallet` for `chat_id`. Returns false if it was already watched
+    /// (the threshold is updated, the alert cursor kept).
+    pub async fn add_watch(&self, chat_id: i64, wallet: &str, min_sol: f64) -> BeastResult<bool> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
-                let rows = client
-                    .query(
-                        "SELECT
-                            signature,
-                            event_index,
-                            slot,
-                            block_time,
-                            kind,
-                            transfer_type,
-                            from_wallet,
-                            to_wallet,
-                            mint,
-                            amount_sol,
-                            token_amount_ui,
-                            token_amount,
-                            token_decimals
-                         FROM transfer_events
-                         WHERE from_wallet = $1
-                           AND to_wallet = $2
-                           AND (block_time IS NULL OR block_time >= $3)
-                         ORDER BY COALESCE(block_time, 0) DESC
-                         LIMIT $4",
-                        &[&from_wallet, &to_wallet, &since_epoch, &limit],
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_one(
+                        "INSERT INTO watchlist (chat_id, wallet, min_sol, created_at)
+                         VALUES ($1, $2, $3, $4)
+                         ON CONFLICT (chat_id, wallet) DO UPDATE SET min_sol = EXCLUDED.min_sol
+                         RETURNING (xmax = 0)",
+                        &[&chat_id, &wallet, &min_sol, &(now_epoch() as i64)],
                     )
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!("Failed to get transfer events: {}", e))
+                        BeastError::DatabaseError(format!("Failed to add watch: {}", e))
                     })?;
 
-                Ok(rows.iter().map(TransferEvent::from_row).collect())
+                Ok(row.get::<_, bool>(0))
             }
             DatabaseInner::Memory { state } => {
-                let mem = state.read().await;
-                let mut out: Vec<TransferEvent> = mem
-                    .transfer_events
-                    .values()
-                    .filter(|ev| passes_since(ev.block_time, since_epoch))
-                    .filter(|ev| ev.from_wallet.as_deref() == Some(from_wallet))
-                    .filter(|ev| ev.to_wallet.as_deref() == Some(to_wallet))
-                    .cloned()
-                    .collect();
-
-                out.sort_by(|a, b| {
-                    let ta = a.block_time.unwrap_or(0);
-                    let tb = b.block_time.unwrap_or(0);
-                    tb.cmp(&ta)
-                        .then_with(|| b.signature.cmp(&a.signature))
-                        .then_with(|| b.event_index.cmp(&a.event_index))
-                });
-                out.truncate(limit as usize);
-                Ok(out)
+                let mut mem = state.write().await;
+                match mem.watchlist.get_mut(&(chat_id, wallet.to_string())) {
+                    Some(entry) => {
+                        entry.min_sol = min_sol;
+                        Ok(false)
+                    }
+                    None => {
+                        mem.watchlist.insert(
+                            (chat_id, wallet.to_string()),
+                            WatchEntry {
+                                chat_id,
+                                wallet: wallet.to_string(),
+                                min_sol,
+                                last_signature: None,
+                                created_at: now_epoch(),
+                            },
+                        );
+                        Ok(true)
+                    }
+                }
             }
         }
     }
 
-    /// Get outbound transfer events for a wallet within a time window (oldest first).
-    pub async fn get_outbound_transfers_in_window(
-        &self,
-        from_wallet: &str,
-        start_epoch: u64,
-        end_epoch: u64,
-        limit: usize,
-    ) -> BeastResult<Vec<TransferEvent>> {
-        let start_epoch = start_epoch as i64;
-        let end_epoch = end_epoch as i64;
-        let limit = (limit as i64).clamp(1, 2000);
-
+    /// Stop watching `wallet` for `chat_id`. Returns false if it was not watched.
+    pub async fn remove_watch(&self, chat_id: i64, wallet: &str) -> BeastResult<bool> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
-                let rows = client
-                    .query(
-                        "SELECT
-                            signature,
-                            event_index,
-                            slot,
-                            block_time,
-                            kind,
-                            transfer_type,
-                            from_wallet,
-                            to_wallet,
-                            mint,
-                            amount_sol,
-                            token_amount_ui,
-                            token_amount,
-                            token_decimals
-                         FROM transfer_events
-                         WHERE from_wallet = $1
-                           AND to_wallet IS NOT NULL
-                           AND block_time IS NOT NULL
-                           AND block_time >= $2
-                           AND block_time <= $3
-                         ORDER BY block_time ASC
-                         LIMIT $4",
-                        &[&from_wallet, &start_epoch, &end_epoch, &limit],
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let removed = client
+                    .execute(
+                        "DELETE FROM watchlist WHERE chat_id = $1 AND wallet = $2",
+                        &[&chat_id, &wallet],
                     )
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!(
-                            "Failed to get outbound transfer events: {}",
-                            e
-                        ))
+                        BeastError::DatabaseError(format!("Failed to remove watch: {}", e))
                     })?;
 
-                Ok(rows.iter().map(TransferEvent::from_row).collect())
+                Ok(removed > 0)
             }
             DatabaseInner::Memory { state } => {
-                let mem = state.read().await;
-
-                let mut out: Vec<TransferEvent> = mem
-                    .transfer_events
-                    .values()
-                    .filter(|ev| ev.from_wallet.as_deref() == Some(from_wallet))
-                    .filter(|ev| ev.to_wallet.is_some())
-                    .filter(|ev| {
-                        let Some(bt) = ev.block_time else {
-                            return false;
-                        };
-                        bt >= start_epoch && bt <= end_epoch
-                    })
-                    .cloned()
-                    .collect();
-
-                out.sort_by(|a, b| {
-                    let ta = a.block_time.unwrap_or(0);
-                    let tb = b.block_time.unwrap_or(0);
-                    ta.cmp(&tb)
-                        .then_with(|| a.signature.cmp(&b.signature))
-                        .then_with(|| a.event_index.cmp(&b.event_index))
-                });
-                out.truncate(limit as usize);
-                Ok(out)
+                let mut mem = state.write().await;
+                Ok(mem
+                    .watchlist
+                    .remove(&(chat_id, wallet.to_string()))
+                    .is_some())
             }
         }
     }
 
-    pub async fn get_wallet_connections(
-        &self,
-        wallet_address: &str,
-    ) -> BeastResult<Vec<WalletConnection>> {
+    /// Wallets watched by one chat (oldest first).
+    pub async fn get_watchlist(&self, chat_id: i64) -> BeastResult<Vec<WatchEntry>> {
+        self.query_watches(Some(chat_id)).await
+    }
+
+    /// Every watch across all chats, for the alert poller.
+    pub async fn get_all_watches(&self) -> BeastResult<Vec<WatchEntry>> {
+        self.query_watches(None).await
+    }
+
+    async fn query_watches(&self, chat_id: Option<i64>) -> BeastResult<Vec<WatchEntry>> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 let rows = client
                     .query(
-                        "SELECT
-                            from_wallet,
-                            to_wallet,
-                            sol_amount,
-                            token_amount,
-                            transaction_count,
-                            EXTRACT(EPOCH FROM first_seen)::BIGINT AS first_seen_epoch,
-                            EXTRACT(EPOCH FROM last_seen)::BIGINT AS last_seen_epoch
-                         FROM wallet_relationships
-                         WHERE from_wallet = $1 OR to_wallet = $1
-                         ORDER BY transaction_count DESC
-                         LIMIT 100",
-                        &[&wallet_address],
+                        "SELECT chat_id, wallet, min_sol, last_signature, created_at
+                         FROM watchlist
+                         WHERE ($1::BIGINT IS NULL OR chat_id = $1)
+                         ORDER BY chat_id, created_at, wallet",
+                        &[&chat_id],
                     )
                     .await
                     .map_err(|e| {
-                        BeastError::DatabaseError(format!("Failed to get connections: {}", e))
+                        BeastError::DatabaseError(format!("Failed to get watchlist: {}", e))
                     })?;
 
                 Ok(rows
                     .iter()
-                    .map(|row| WalletConnection {
-                        from_wallet: row.get(0),
-                        to_wallet: row.get(1),
-                        total_sol_transferred: row.get(2),
-                        total_token_transferred: row.get::<_, i64>(3) as u64,
-                        transaction_count: row.get::<_, i32>(4) as u32,
-                        first_seen_epoch: row.get::<_, i64>(5) as u64,
-                        last_seen_epoch: row.get::<_, i64>(6) as u64,
+                    .map(|row| WatchEntry {
+                        chat_id: row.get::<_, i64>(0),
+                        wallet: row.get::<_, String>(1),
+                        min_sol: row.get::<_, f64>(2),
+                        last_signature: row.get::<_, Option<String>>(3),
+                        created_at: row.get::<_, i64>(4) as u64,
                     })
                     .collect())
             }
             DatabaseInner::Memory { state } => {
                 let mem = state.read().await;
-                let mut out: Vec<WalletConnection> = mem
-                    .relationships
+                let mut out: Vec<WatchEntry> = mem
+                    .watchlist
                     .values()
-                    .filter(|rel| {
-                        rel.from_wallet == wallet_address || rel.to_wallet == wallet_address
-                    })
-                    .map(|rel| WalletConnection {
-                        from_wallet: rel.from_wallet.clone(),
-                        to_wallet: rel.to_wallet.clone(),
-                        total_sol_transferred: rel.sol_amount,
-                        total_token_transferred: rel.token_amount,
-                        transaction_count: rel.transaction_count,
-                        first_seen_epoch: rel.first_seen_epoch,
-                        last_seen_epoch: rel.last_seen_epoch,
-                    })
+                    .filter(|w| chat_id.is_none_or(|id| w.chat_id == id))
+                    .cloned()
                     .collect();
 
-                out.sort_by(|a, b| b.transaction_count.cmp(&a.transaction_count));
-                out.truncate(100);
+                out.sort_by(|a, b| {
+                    a.chat_id
+                        .cmp(&b.chat_id)
+                        .then_with(|| a.created_at.cmp(&b.created_at))
+                        .then_with(|| a.wallet.cmp(&b.wallet))
+                });
                 Ok(out)
             }
         }
     }
 
-    /// Get behavioral profile for a wallet from transfer_events.
-    pub async fn get_behavioral_profile(
+    /// Record the newest signature already alerted on for a watch.
+    pub async fn set_watch_cursor(
         &self,
+        chat_id: i64,
         wallet: &str,
-        since_epoch: Option<u64>,
-    ) -> BeastResult<Option<BehavioralProfile>> {
-        let since = since_epoch.unwrap_or(0) as i64;
+        last_signature: &str,
+    ) -> BeastResult<()> {
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
-                let row = client
-                    .query_opt(
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .execute(
+                        "UPDATE watchlist SET last_signature = $3 WHERE chat_id = $1 AND wallet = $2",
+                        &[&chat_id, &wallet, &last_signature],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to update watch cursor: {}", e))
+                    })?;
+
+                Ok(())
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                if let Some(entry) = mem.watchlist.get_mut(&(chat_id, wallet.to_string())) {
+                    entry.last_signature = Some(last_signature.to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Attach `label` to `wallet`, or replace the note and author of an existing one.
+    pub async fn set_label(
+        &self,
+        wallet: &str,
+        label: &str,
+        note: Option<&str>,
+        author: Option<&str>,
+    ) -> BeastResult<StoredOutcome> {
+        let now = now_epoch();
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_one(
+                        "INSERT INTO wallet_labels (wallet, label, note, author, created_at, updated_at)
+                         VALUES ($1, $2, $3, $4, $5, $5)
+                         ON CONFLICT (wallet, label) DO UPDATE SET
+                            note = EXCLUDED.note,
+                            author = EXCLUDED.author,
+                            updated_at = EXCLUDED.updated_at
+                         RETURNING (xmax = 0)",
+                        &[&wallet, &label, &note, &author, &(now as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to set label: {}", e))
+                    })?;
+
+                Ok(StoredOutcome::from_inserted(row.get::<_, bool>(0)))
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                let key = (wallet.to_string(), label.to_string());
+                let created_at = mem.wallet_labels.get(&key).map(|l| l.created_at);
+                mem.wallet_labels.insert(
+                    key,
+                    WalletLabel {
+                        wallet: wallet.to_string(),
+                        label: label.to_string(),
+                        note: note.map(str::to_string),
+                        author: author.map(str::to_string),
+                        created_at: created_at.unwrap_or(now),
+                        updated_at: now,
+                    },
+                );
+                Ok(StoredOutcome::from_inserted(created_at.is_none()))
+            }
+        }
+    }
+
+    /// Remove `label` from `wallet`. Returns false if the wallet did not have it.
+    pub async fn remove_label(&self, wallet: &str, label: &str) -> BeastResult<bool> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let removed = client
+                    .execute(
+                        "DELETE FROM wallet_labels WHERE wallet = $1 AND label = $2",
+                        &[&wallet, &label],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to remove label: {}", e))
+                    })?;
+
+                Ok(removed > 0)
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                Ok(mem
+                    .wallet_labels
+                    .remove(&(wallet.to_string(), label.to_string()))
+                    .is_some())
+            }
+        }
+    }
+
+    /// Labels of one wallet (oldest first).
+    pub async fn get_labels(&self, wallet: &str) -> BeastResult<Vec<WalletLabel>> {
+        Ok(self
+            .get_labels_for_wallets(&[wallet.to_string()])
+            .await?
+            .remove(wallet)
+            .unwrap_or_default())
+    }
+
+    /// Labels of each of `wallets` that has any, oldest first per wallet.
+    pub async fn get_labels_for_wallets(
+        &self,
+        wallets: &[String],
+    ) -> BeastResult<HashMap<String, Vec<WalletLabel>>> {
+        let mut labels: Vec<WalletLabel> = match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                if wallets.is_empty() {
+                    return Ok(HashMap::new());
+                }
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT wallet, label, note, author, created_at, updated_at
+                         FROM wallet_labels
+                         WHERE wallet = ANY($1)",
+                        &[&wallets],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get labels: {}", e))
+                    })?;
+
+                rows.iter()
+                    .map(|row| WalletLabel {
+                        wallet: row.get::<_, String>(0),
+                        label: row.get::<_, String>(1),
+                        note: row.get::<_, Option<String>>(2),
+                        author: row.get::<_, Option<String>>(3),
+                        created_at: row.get::<_, i64>(4) as u64,
+                        updated_at: row.get::<_, i64>(5) as u64,
+                    })
+                    .collect()
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let wanted: HashSet<&str> = wallets.iter().map(String::as_str).collect();
+                mem.wallet_labels
+                    .values()
+                    .filter(|l| wanted.contains(l.wallet.as_str()))
+                    .cloned()
+                    .collect()
+            }
+        };
+
+        labels.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.label.cmp(&b.label))
+        });
+        let mut out: HashMap<String, Vec<WalletLabel>> = HashMap::new();
+        for label in labels {
+            out.entry(label.wallet.clone()).or_default().push(label);
+        }
+        Ok(out)
+    }
+
+    /// Store `score` as the wallet's latest risk score, replacing any earlier one.
+    pub async fn set_wallet_risk_score(&self, wallet: &str, score: f64) -> BeastResult<()> {
+        let now = now_epoch();
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .execute(
+                        "INSERT INTO wallet_risk_scores (wallet, score, computed_at)
+                         VALUES ($1, $2, $3)
+                         ON CONFLICT (wallet) DO UPDATE SET
+                            score = EXCLUDED.score,
+                            computed_at = EXCLUDED.computed_at",
+                        &[&wallet, &score, &(now as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to store risk score: {}", e))
+                    })?;
+                Ok(())
+            }
+            DatabaseInner::Memory { state } => {
+                state.write().await.wallet_risk_scores.insert(
+                    wallet.to_string(),
+                    WalletRiskScore {
+                        wallet: wallet.to_string(),
+                        score,
+                        computed_at: now,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Stored risk scores of at least `min_score`, highest first (ties by wallet),
+    /// skipping `offset` and returning at most `limit`.
+    pub async fn get_high_risk_wallets(
+        &self,
+        min_score: f64,
+        offset: usize,
+        limit: usize,
+    ) -> BeastResult<Vec<WalletRiskScore>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT wallet, score, computed_at
+                         FROM wallet_risk_scores
+                         WHERE score >= $1
+                         ORDER BY score DESC, wallet
+                         OFFSET $2 LIMIT $3",
+                        &[&min_score, &(offset as i64), &(limit as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get high-risk wallets: {}", e))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .map(|row| WalletRiskScore {
+                        wallet: row.get::<_, String>(0),
+                        score: row.get::<_, f64>(1),
+                        computed_at: row.get::<_, i64>(2) as u64,
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut scores: Vec<WalletRiskScore> = mem
+                    .wallet_risk_scores
+                    .values()
+                    .filter(|s| s.score >= min_score)
+                    .cloned()
+                    .collect();
+                scores.sort_by(|a, b| {
+                    b.score
+                        .partial_cmp(&a.score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| a.wallet.cmp(&b.wallet))
+                });
+                Ok(scores.into_iter().skip(offset).take(limit).collect())
+            }
+        }
+    }
+
+    /// Record that ingesting `signature` failed at `stage` ("parse" | "persist").
+    /// Repeated failures bump the attempt count. Returns the attempts so far.
+    pub async fn record_ingestion_failure(
+        &self,
+        signature: &str,
+        stage: &str,
+        error: &str,
+    ) -> BeastResult<u32> {
+        let now = now_epoch();
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_one(
+                        "INSERT INTO ingestion_failures (signature, stage, error, attempts, first_failed_at, last_failed_at)
+                         VALUES ($1, $2, $3, 1, $4, $4)
+                         ON CONFLICT (signature) DO UPDATE SET
+                            stage = EXCLUDED.stage,
+                            error = EXCLUDED.error,
+                            attempts = ingestion_failures.attempts + 1,
+                            last_failed_at = EXCLUDED.last_failed_at
+                         RETURNING attempts",
+                        &[&signature, &stage, &error, &(now as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to record ingestion failure: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(row.get::<_, i32>(0) as u32)
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                let entry = mem
+                    .ingestion_failures
+                    .entry(signature.to_string())
+                    .or_insert_with(|| IngestionFailure {
+                        signature: signature.to_string(),
+                        stage: String::new(),
+                        error: String::new(),
+                        attempts: 0,
+                        first_failed_at: now,
+                        last_failed_at: now,
+                    });
+                entry.stage = stage.to_string();
+                entry.error = error.to_string();
+                entry.attempts = entry.attempts.saturating_add(1);
+                entry.last_failed_at = now;
+                Ok(entry.attempts)
+            }
+        }
+    }
+
+    /// Failures with fewer than `max_attempts` attempts whose backoff (see
+    /// `IngestionFailure::retry_at`) has elapsed by `due_by`, least recently
+    /// failed first. Not-yet-due entries are filtered before `limit`, so they
+    /// can't crowd out due ones.
+    pub async fn get_pending_failures(
+        &self,
+        max_attempts: u32,
+        due_by: u64,
+        limit: usize,
+    ) -> BeastResult<Vec<IngestionFailure>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        &format!(
+                            "SELECT signature, stage, error, attempts, first_failed_at, last_failed_at
+                             FROM ingestion_failures
+                             WHERE attempts < $1 AND {} <= $2
+                             ORDER BY last_failed_at, signature
+                             LIMIT $3",
+                            RETRY_AT_SQL
+                        ),
+                        &[
+                            &(max_attempts as i32),
+                            &(due_by.min(i64::MAX as u64) as i64),
+                            &(limit as i64),
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get ingestion failures: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(ingestion_failure_from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<IngestionFailure> = mem
+                    .ingestion_failures
+                    .values()
+                    .filter(|f| f.attempts < max_attempts && f.retry_at() <= due_by)
+                    .cloned()
+                    .collect();
+                out.sort_by(|a, b| {
+                    a.last_failed_at
+                        .cmp(&b.last_failed_at)
+                        .then_with(|| a.signature.cmp(&b.signature))
+                });
+                out.truncate(limit);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Failures that reached `max_attempts` and are no longer retried, most
+    /// recently failed first, skipping `offset` and returning at most `limit`.
+    pub async fn get_exhausted_failures(
+        &self,
+        max_attempts: u32,
+        offset: usize,
+        limit: usize,
+    ) -> BeastResult<Vec<IngestionFailure>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT signature, stage, error, attempts, first_failed_at, last_failed_at
+                         FROM ingestion_failures
+                         WHERE attempts >= $1
+                         ORDER BY last_failed_at DESC, signature
+                         OFFSET $2 LIMIT $3",
+                        &[&(max_attempts as i32), &(offset as i64), &(limit as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get exhausted ingestion failures: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(ingestion_failure_from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<IngestionFailure> = mem
+                    .ingestion_failures
+                    .values()
+                    .filter(|f| f.attempts >= max_attempts)
+                    .cloned()
+                    .collect();
+                out.sort_by(|a, b| {
+                    b.last_failed_at
+                        .cmp(&a.last_failed_at)
+                        .then_with(|| a.signature.cmp(&b.signature))
+                });
+                Ok(out.into_iter().skip(offset).take(limit).collect())
+            }
+        }
+    }
+
+    /// Stored failures split into due for retry by `due_by`, still backing off,
+    /// and exhausted (`max_attempts` reached).
+    pub async fn count_ingestion_failures(
+        &self,
+        max_attempts: u32,
+        due_by: u64,
+    ) -> BeastResult<FailureCounts> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_one(
+                        &format!(
+                            "SELECT
+                                COUNT(*) FILTER (WHERE attempts < $1 AND {retry_at} <= $2),
+                                COUNT(*) FILTER (WHERE attempts < $1 AND {retry_at} > $2),
+                                COUNT(*) FILTER (WHERE attempts >= $1)
+                             FROM ingestion_failures",
+                            retry_at = RETRY_AT_SQL
+                        ),
+                        &[
+                            &(max_attempts as i32),
+                            &(due_by.min(i64::MAX as u64) as i64),
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to count ingestion failures: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(FailureCounts {
+                    due: row.get::<_, i64>(0) as u64,
+                    waiting: row.get::<_, i64>(1) as u64,
+                    exhausted: row.get::<_, i64>(2) as u64,
+                })
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut counts = FailureCounts::default();
+                for f in mem.ingestion_failures.values() {
+                    if f.attempts >= max_attempts {
+                        counts.exhausted += 1;
+                    } else if f.retry_at() <= due_by {
+                        counts.due += 1;
+                    } else {
+                        counts.waiting += 1;
+                    }
+                }
+                Ok(counts)
+            }
+        }
+    }
+
+    /// Forget a failure once its signature has been ingested. Returns false if
+    /// none was recorded.
+    pub async fn clear_failure(&self, signature: &str) -> BeastResult<bool> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let removed = client
+                    .execute(
+                        "DELETE FROM ingestion_failures WHERE signature = $1",
+                        &[&signature],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to clear ingestion failure: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(removed > 0)
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                Ok(mem.ingestion_failures.remove(signature).is_some())
+            }
+        }
+    }
+
+    /// Distinct slots of transfer events stored without a block time, fewest failed
+    /// lookups first, then oldest. Slots whose lookup came back empty
+    /// `MAX_BLOCK_TIME_ATTEMPTS` times are skipped, so pruned slots can't starve
+    /// newer ones.
+    pub async fn get_slots_missing_block_time(&self, limit: usize) -> BeastResult<Vec<u64>> {
+        let limit = (limit as i64).clamp(1, 10_000);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT e.slot FROM (
+                            SELECT DISTINCT slot FROM transfer_events WHERE block_time IS NULL
+                         ) e
+                         LEFT JOIN block_time_misses m ON m.slot = e.slot
+                         WHERE COALESCE(m.attempts, 0) < $2
+                         ORDER BY COALESCE(m.attempts, 0) ASC, e.slot ASC
+                         LIMIT $1",
+                        &[&limit, &MAX_BLOCK_TIME_ATTEMPTS],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to find slots missing block time: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(|row| row.get::<_, i64>(0) as u64).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let slots: BTreeSet<(u32, u64)> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| ev.block_time.is_none())
+                    .map(|ev| {
+                        let slot = ev.slot as u64;
+                        let attempts = mem.block_time_misses.get(&slot).copied().unwrap_or(0);
+                        (attempts, slot)
+                    })
+                    .filter(|(attempts, _)| (*attempts as i32) < MAX_BLOCK_TIME_ATTEMPTS)
+                    .collect();
+                Ok(slots
+                    .into_iter()
+                    .take(limit as usize)
+                    .map(|(_, slot)| slot)
+                    .collect())
+            }
+        }
+    }
+
+    /// Count an empty `getBlockTime` lookup for `slot`.
+    pub async fn record_block_time_miss(&self, slot: u64) -> BeastResult<()> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                client
+                    .execute(
+                        "INSERT INTO block_time_misses (slot, attempts, last_attempt_at)
+                         VALUES ($1, 1, $2)
+                         ON CONFLICT (slot) DO UPDATE SET
+                            attempts = block_time_misses.attempts + 1,
+                            last_attempt_at = EXCLUDED.last_attempt_at",
+                        &[&(slot as i64), &(now_epoch() as i64)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to record block time miss: {}",
+                            e
+                        ))
+                    })?;
+                Ok(())
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                *mem.block_time_misses.entry(slot).or_insert(0) += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Set `block_time` on every transaction, transfer, swap and NFT sale stored
+    /// at `slot` without one. Returns the number of transfer events updated.
+    pub async fn set_block_time_for_slot(&self, slot: u64, block_time: u64) -> BeastResult<u64> {
+        let slot_i = slot as i64;
+        let time_i = block_time as i64;
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = &checkout(pool).await?;
+                let update = |sql: &'static str| async move {
+                    client.execute(sql, &[&slot_i, &time_i]).await.map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to backfill block time: {}", e))
+                    })
+                };
+                let events = update(
+                    "UPDATE transfer_events SET block_time = $2
+                     WHERE slot = $1 AND block_time IS NULL",
+                )
+                .await?;
+                update(
+                    "UPDATE transactions
+                     SET block_time = $2, data = jsonb_set(data, '{block_time}', to_jsonb($2::BIGINT))
+                     WHERE slot = $1 AND block_time IS NULL",
+                )
+                .await?;
+                update(
+                    "UPDATE swap_events SET block_time = $2 WHERE slot = $1 AND block_time IS NULL",
+                )
+                .await?;
+                update(
+                    "UPDATE nft_trades SET block_time = $2 WHERE slot = $1 AND block_time IS NULL",
+                )
+                .await?;
+                client
+                    .execute("DELETE FROM block_time_misses WHERE slot = $1", &[&slot_i])
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to backfill block time: {}", e))
+                    })?;
+
+                Ok(events)
+            }
+            DatabaseInner::Memory { state } => {
+                let mut mem = state.write().await;
+                let mut events = 0;
+                for ev in mem.transfer_events.values_mut() {
+                    if ev.slot == slot_i && ev.block_time.is_none() {
+                        ev.block_time = Some(time_i);
+                        events += 1;
+                    }
+                }
+                for tx in mem.transactions.values_mut() {
+                    if tx.slot == slot && tx.block_time.is_none() {
+                        tx.block_time = Some(block_time);
+                    }
+                }
+                for swap in mem.swap_events.values_mut() {
+                    if swap.slot == slot && swap.block_time.is_none() {
+                        swap.block_time = Some(block_time);
+                    }
+                }
+                for trade in mem.nft_trades.values_mut() {
+                    if trade.slot == slot && trade.block_time.is_none() {
+                        trade.block_time = Some(block_time);
+                    }
+                }
+                mem.block_time_misses.remove(&slot);
+                Ok(events)
+            }
+        }
+    }
+
+    /// Fill in missing block times for up to `max_slots` slots via `getBlockTime`.
+    /// Slots the RPC has no time for (or fails on) are left for a later run and
+    /// counted in `block_time_misses`, which moves them behind untried slots.
+    pub async fn backfill_block_times(
+        &self,
+        rpc_client: &SolanaRpcClient,
+        max_slots: usize,
+    ) -> BeastResult<BlockTimeBackfill> {
+        let slots = self.get_slots_missing_block_time(max_slots).await?;
+        let mut stats = BlockTimeBackfill {
+            slots_checked: slots.len(),
+            ..Default::default()
+        };
+
+        for slot in slots {
+            match rpc_client.get_block_time(slot).await {
+                Ok(Some(block_time)) => {
+                    stats.events_updated += self.set_block_time_for_slot(slot, block_time).await?;
+                    stats.slots_filled += 1;
+                }
+                Ok(None) => {
+                    self.record_block_time_miss(slot).await?;
+                    stats.slots_unavailable += 1;
+                }
+                Err(e) => {
+                    // Pruned slots fail with "block not available" rather than null.
+                    tracing::debug!("getBlockTime failed for slot {}: {}", slot, e);
+                    self.record_block_time_miss(slot).await?;
+                    stats.slots_unavailable += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Find shared inbound funders (wallets that sent to both A and B).
+    pub async fn get_shared_inbound_senders(
+        &self,
+        wallet_a: &str,
+        wallet_b: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<SharedWalletSignal>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 50);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "WITH a AS (
+                            SELECT from_wallet,
+                                   COUNT(*)::BIGINT AS cnt,
+                                   MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
+                            FROM transfer_events
+                            WHERE to_wallet = $1
+                              AND from_wallet IS NOT NULL
+                              AND (block_time IS NULL OR block_time >= $3)
+                            GROUP BY from_wallet
+                         ),
+                         b AS (
+                            SELECT from_wallet,
+                                   COUNT(*)::BIGINT AS cnt,
+                                   MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
+                            FROM transfer_events
+                            WHERE to_wallet = $2
+                              AND from_wallet IS NOT NULL
+                              AND (block_time IS NULL OR block_time >= $3)
+                            GROUP BY from_wallet
+                         )
+                         SELECT a.from_wallet,
+                                (a.cnt + b.cnt)::BIGINT AS total_cnt,
+                                GREATEST(a.last_seen, b.last_seen)::BIGINT AS last_seen
+                         FROM a
+                         INNER JOIN b ON a.from_wallet = b.from_wallet
+                         ORDER BY total_cnt DESC
+                         LIMIT $4",
+                        &[&wallet_a, &wallet_b, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get shared inbound senders: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .map(|row| SharedWalletSignal {
+                        wallet: row.get::<_, String>(0),
+                        count: row.get::<_, i64>(1) as u64,
+                        last_seen_epoch: row.get::<_, i64>(2) as u64,
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                let mut a: HashMap<String, (u64, u64)> = HashMap::new();
+                let mut b: HashMap<String, (u64, u64)> = HashMap::new();
+
+                for ev in mem.transfer_events.values() {
+                    if !passes_since(ev.block_time, since_epoch) {
+                        continue;
+                    }
+
+                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
+                    if ev.to_wallet.as_deref() == Some(wallet_a) {
+                        let Some(from) = ev.from_wallet.as_ref() else {
+                            continue;
+                        };
+                        let entry = a.entry(from.clone()).or_insert((0, 0));
+                        entry.0 += 1;
+                        entry.1 = entry.1.max(last_seen);
+                    }
+                    if ev.to_wallet.as_deref() == Some(wallet_b) {
+                        let Some(from) = ev.from_wallet.as_ref() else {
+                            continue;
+                        };
+                        let entry = b.entry(from.clone()).or_insert((0, 0));
+                        entry.0 += 1;
+                        entry.1 = entry.1.max(last_seen);
+                    }
+                }
+
+                let mut out: Vec<SharedWalletSignal> = Vec::new();
+                for (wallet, (cnt_a, last_a)) in a {
+                    let Some((cnt_b, last_b)) = b.get(&wallet) else {
+                        continue;
+                    };
+                    out.push(SharedWalletSignal {
+                        wallet,
+                        count: cnt_a + *cnt_b,
+                        last_seen_epoch: last_a.max(*last_b),
+                    });
+                }
+
+                out.sort_by_key(|x| std::cmp::Reverse(x.count));
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Token mints both wallets sent or received, with each wallet's transfer count
+    /// (most shared activity first). Mints in `ignore_mints` are left out.
+    pub async fn get_shared_mints(
+        &self,
+        wallet_a: &str,
+        wallet_b: &str,
+        since_epoch: Option<u64>,
+        ignore_mints: &[String],
+        limit: usize,
+    ) -> BeastResult<Vec<SharedMint>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 50);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "WITH touched AS (
+                            SELECT mint,
+                                   (from_wallet = $1 OR to_wallet = $1) AS by_a,
+                                   (from_wallet = $2 OR to_wallet = $2) AS by_b,
+                                   COALESCE(block_time, 0) AS bt
+                            FROM transfer_events
+                            WHERE kind = 'token'
+                              AND mint IS NOT NULL
+                              AND (from_wallet IN ($1, $2) OR to_wallet IN ($1, $2))
+                              AND (block_time IS NULL OR block_time >= $3)
+                              AND NOT (mint = ANY($4))
+                         )
+                         SELECT mint,
+                                COUNT(*) FILTER (WHERE by_a)::BIGINT AS cnt_a,
+                                COUNT(*) FILTER (WHERE by_b)::BIGINT AS cnt_b,
+                                MAX(bt)::BIGINT AS last_seen
+                         FROM touched
+                         GROUP BY mint
+                         HAVING COUNT(*) FILTER (WHERE by_a) > 0
+                            AND COUNT(*) FILTER (WHERE by_b) > 0
+                         ORDER BY cnt_a + cnt_b DESC, mint
+                         LIMIT $5",
+                        &[&wallet_a, &wallet_b, &since_epoch, &ignore_mints, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get shared mints: {}", e))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .map(|row| SharedMint {
+                        mint: row.get::<_, String>(0),
+                        count_a: row.get::<_, i64>(1) as u64,
+                        count_b: row.get::<_, i64>(2) as u64,
+                        last_seen_epoch: row.get::<_, i64>(3) as u64,
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                // mint -> (count_a, count_b, last_seen)
+                let mut by_mint: HashMap<&str, (u64, u64, u64)> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    if ev.kind != "token" || !passes_since(ev.block_time, since_epoch) {
+                        continue;
+                    }
+                    let Some(mint) = ev.mint.as_deref() else {
+                        continue;
+                    };
+                    if ignore_mints.iter().any(|m| m == mint) {
+                        continue;
+                    }
+                    let touches = |w: &str| {
+                        ev.from_wallet.as_deref() == Some(w) || ev.to_wallet.as_deref() == Some(w)
+                    };
+                    let (by_a, by_b) = (touches(wallet_a), touches(wallet_b));
+                    if !by_a && !by_b {
+                        continue;
+                    }
+                    let entry = by_mint.entry(mint).or_insert((0, 0, 0));
+                    entry.0 += by_a as u64;
+                    entry.1 += by_b as u64;
+                    entry.2 = entry.2.max(ev.block_time.unwrap_or(0).max(0) as u64);
+                }
+
+                let mut out: Vec<SharedMint> = by_mint
+                    .into_iter()
+                    .filter(|(_, (a, b, _))| *a > 0 && *b > 0)
+                    .map(|(mint, (count_a, count_b, last_seen_epoch))| SharedMint {
+                        mint: mint.to_string(),
+                        count_a,
+                        count_b,
+                        last_seen_epoch,
+                    })
+                    .collect();
+                out.sort_by(|x, y| {
+                    (y.count_a + y.count_b)
+                        .cmp(&(x.count_a + x.count_b))
+                        .then_with(|| x.mint.cmp(&y.mint))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Get top counterparties for a wallet from transfer_events.
+    pub async fn get_top_counterparties(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<SharedWalletSignal>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 200);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            CASE WHEN from_wallet = $1 THEN to_wallet ELSE from_wallet END AS counterparty,
+                            COUNT(*)::BIGINT AS cnt,
+                            MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
+                         FROM transfer_events
+                         WHERE (from_wallet = $1 OR to_wallet = $1)
+                           AND from_wallet IS NOT NULL
+                           AND to_wallet IS NOT NULL
+                           AND (block_time IS NULL OR block_time >= $2)
+                         GROUP BY counterparty
+                         ORDER BY cnt DESC
+                         LIMIT $3",
+                        &[&wallet, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get top counterparties: {}", e))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        let counterparty: Option<String> = row.get(0);
+                        counterparty.map(|w| SharedWalletSignal {
+                            wallet: w,
+                            count: row.get::<_, i64>(1) as u64,
+                            last_seen_epoch: row.get::<_, i64>(2) as u64,
+                        })
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                let mut agg: HashMap<String, (u64, u64)> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    if !passes_since(ev.block_time, since_epoch) {
+                        continue;
+                    }
+
+                    let (Some(from), Some(to)) = (&ev.from_wallet, &ev.to_wallet) else {
+                        continue;
+                    };
+                    if from != wallet && to != wallet {
+                        continue;
+                    }
+
+                    let counterparty = if from == wallet {
+                        to.clone()
+                    } else {
+                        from.clone()
+                    };
+                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
+                    let entry = agg.entry(counterparty).or_insert((0, 0));
+                    entry.0 += 1;
+                    entry.1 = entry.1.max(last_seen);
+                }
+
+                let mut out: Vec<SharedWalletSignal> = agg
+                    .into_iter()
+                    .map(|(wallet, (count, last_seen_epoch))| SharedWalletSignal {
+                        wallet,
+                        count,
+                        last_seen_epoch,
+                    })
+                    .collect();
+                out.sort_by(|a, b| {
+                    b.count
+                        .cmp(&a.count)
+                        .then_with(|| b.last_seen_epoch.cmp(&a.last_seen_epoch))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Get top outbound recipients for a wallet from transfer_events, with volume signals.
+    pub async fn get_top_outbound_recipients(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<WalletVolumeSignal>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 200);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            to_wallet AS counterparty,
+                            COUNT(*)::BIGINT AS cnt,
+                            SUM(CASE WHEN kind = 'sol' THEN COALESCE(amount_sol, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS total_sol,
+                            SUM(CASE WHEN kind = 'token' THEN COALESCE(token_amount_ui, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS total_token_ui,
+                            MAX(COALESCE(block_time, 0))::BIGINT AS last_seen
+                         FROM transfer_events
+                         WHERE from_wallet = $1
+                           AND to_wallet IS NOT NULL
+                           AND (block_time IS NULL OR block_time >= $2)
+                         GROUP BY to_wallet
+                         ORDER BY total_sol DESC, total_token_ui DESC, cnt DESC
+                         LIMIT $3",
+                        &[&wallet, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get top outbound recipients: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        let counterparty: Option<String> = row.get(0);
+                        counterparty.map(|w| WalletVolumeSignal {
+                            wallet: w,
+                            count: row.get::<_, i64>(1) as u64,
+                            total_sol: row.get::<_, f64>(2),
+                            total_token_ui: row.get::<_, f64>(3),
+                            last_seen_epoch: row.get::<_, i64>(4) as u64,
+                        })
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                let mut agg: HashMap<String, (u64, f64, f64, u64)> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    if !passes_since(ev.block_time, since_epoch) {
+                        continue;
+                    }
+                    if ev.from_wallet.as_deref() != Some(wallet) {
+                        continue;
+                    }
+                    let Some(to) = ev.to_wallet.as_ref() else {
+                        continue;
+                    };
+
+                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
+                    let entry = agg.entry(to.clone()).or_insert((0, 0.0, 0.0, 0));
+                    entry.0 += 1;
+                    entry.3 = entry.3.max(last_seen);
+
+                    match ev.kind.as_str() {
+                        "sol" => {
+                            if let Some(a) = ev.amount_sol {
+                                entry.1 += a.max(0.0);
+                            }
+                        }
+                        "token" => {
+                            if let Some(a) = ev.token_amount_ui {
+                                entry.2 += a.max(0.0);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                let mut out: Vec<WalletVolumeSignal> = agg
+                    .into_iter()
+                    .map(
+                        |(wallet, (count, total_sol, total_token_ui, last_seen_epoch))| {
+                            WalletVolumeSignal {
+                                wallet,
+                                count,
+                                total_sol,
+                                total_token_ui,
+                                last_seen_epoch,
+                            }
+                        },
+                    )
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    b.total_sol
+                        .partial_cmp(&a.total_sol)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| {
+                            b.total_token_ui
+                                .partial_cmp(&a.total_token_ui)
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .then_with(|| b.count.cmp(&a.count))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Top outbound recipients of `wallet`, with token volume kept per mint instead of
+    /// summed across mints, so 1000 USDC and 1000 BONK stay separate totals. UI
+    /// amounts missing from an event are derived from its raw amount and decimals.
+    /// Recipients are ranked by `top_asset_share` (their largest share of the
+    /// wallet's outbound volume in any one asset, SOL or a mint), then transfer
+    /// count, so a token-only recipient ranks like a SOL one.
+    pub async fn get_top_outbound_recipients_by_mint(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<WalletMintVolumes>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 200);
+
+        let mut agg: HashMap<String, WalletMintVolumes> = HashMap::new();
+        let mut add =
+            |to: String, mint: Option<String>, count: u64, sol: f64, ui: f64, last: u64| {
+                let entry = agg.entry(to.clone()).or_insert_with(|| WalletMintVolumes {
+                    wallet: to,
+                    count: 0,
+                    total_sol: 0.0,
+                    token_ui_by_mint: BTreeMap::new(),
+                    top_asset_share: 0.0,
+                    last_seen_epoch: 0,
+                });
+                entry.count += count;
+                entry.total_sol += sol;
+                entry.last_seen_epoch = entry.last_seen_epoch.max(last);
+                if let Some(mint) = mint {
+                    *entry.token_ui_by_mint.entry(mint).or_insert(0.0) += ui;
+                }
+            };
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "WITH per_asset AS (
+                            SELECT to_wallet, kind, CASE WHEN kind = 'token' THEN mint END AS mint,
+                                   COUNT(*) AS cnt,
+                                   SUM(CASE
+                                       WHEN kind = 'sol' THEN COALESCE(amount_sol, 0.0)
+                                       WHEN kind = 'token' THEN COALESCE(
+                                           token_amount_ui,
+                                           token_amount::DOUBLE PRECISION / POWER(10::DOUBLE PRECISION, token_decimals),
+                                           0.0
+                                       )
+                                       ELSE 0.0
+                                   END) AS vol
+                            FROM transfer_events
+                            WHERE from_wallet = $1
+                              AND to_wallet IS NOT NULL
+                              AND (block_time IS NULL OR block_time >= $2)
+                            GROUP BY to_wallet, kind, 3
+                         ),
+                         top AS (
+                            SELECT to_wallet, COALESCE(MAX(share), 0.0)::DOUBLE PRECISION AS top_share
+                            FROM (
+                                SELECT to_wallet, cnt,
+                                       vol / NULLIF(SUM(vol) OVER (PARTITION BY kind, mint), 0.0) AS share
+                                FROM per_asset
+                            ) shares
+                            GROUP BY to_wallet
+                            ORDER BY top_share DESC, SUM(cnt) DESC, to_wallet
+                            LIMIT $3
+                         )
+                         SELECT
+                            e.to_wallet,
+                            CASE WHEN e.kind = 'token' THEN e.mint END AS mint,
+                            COUNT(*)::BIGINT AS cnt,
+                            SUM(CASE WHEN e.kind = 'sol' THEN COALESCE(e.amount_sol, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS total_sol,
+                            SUM(CASE WHEN e.kind = 'token' THEN COALESCE(
+                                e.token_amount_ui,
+                                e.token_amount::DOUBLE PRECISION / POWER(10::DOUBLE PRECISION, e.token_decimals),
+                                0.0
+                            ) ELSE 0.0 END)::DOUBLE PRECISION AS total_ui,
+                            MAX(COALESCE(e.block_time, 0))::BIGINT AS last_seen,
+                            MAX(t.top_share) AS top_share
+                         FROM transfer_events e
+                         JOIN top t ON t.to_wallet = e.to_wallet
+                         WHERE e.from_wallet = $1
+                           AND (e.block_time IS NULL OR e.block_time >= $2)
+                         GROUP BY e.to_wallet, 2",
+                        &[&wallet, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get top outbound recipients by mint: {}",
+                            e
+                        ))
+                    })?;
+
+                let mut shares = HashMap::new();
+                for row in &rows {
+                    let to = row.get::<_, String>(0);
+                    shares.insert(to.clone(), row.get::<_, f64>(6));
+                    add(
+                        to,
+                        row.get::<_, Option<String>>(1),
+                        row.get::<_, i64>(2) as u64,
+                        row.get::<_, f64>(3),
+                        row.get::<_, f64>(4),
+                        row.get::<_, i64>(5) as u64,
+                    );
+                }
+                for (to, entry) in agg.iter_mut() {
+                    entry.top_asset_share = shares[to];
+                }
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                for ev in mem.transfer_events.values() {
+                    if !passes_since(ev.block_time, since_epoch)
+                        || ev.from_wallet.as_deref() != Some(wallet)
+                    {
+                        continue;
+                    }
+                    let Some(to) = ev.to_wallet.as_ref() else {
+                        continue;
+                    };
+                    let last_seen = ev.block_time.unwrap_or(0).max(0) as u64;
+                    match ev.kind.as_str() {
+                        "sol" => add(
+                            to.clone(),
+                            None,
+                            1,
+                            ev.amount_sol.unwrap_or(0.0).max(0.0),
+                            0.0,
+                            last_seen,
+                        ),
+                        "token" => add(
+                            to.clone(),
+                            ev.mint.clone(),
+                            1,
+                            0.0,
+                            ev.token_ui_amount().unwrap_or(0.0).max(0.0),
+                            last_seen,
+                        ),
+                        _ => add(to.clone(), None, 1, 0.0, 0.0, last_seen),
+                    }
+                }
+                drop(mem);
+
+                let mut sol_total = 0.0;
+                let mut mint_totals: HashMap<&str, f64> = HashMap::new();
+                for entry in agg.values() {
+                    sol_total += entry.total_sol;
+                    for (mint, ui) in &entry.token_ui_by_mint {
+                        *mint_totals.entry(mint.as_str()).or_insert(0.0) += ui;
+                    }
+                }
+                let share = |vol: f64, total: f64| if total > 0.0 { vol / total } else { 0.0 };
+                let top_shares: Vec<(String, f64)> = agg
+                    .values()
+                    .map(|entry| {
+                        let top = entry
+                            .token_ui_by_mint
+                            .iter()
+                            .map(|(mint, ui)| share(*ui, mint_totals[mint.as_str()]))
+                            .fold(share(entry.total_sol, sol_total), f64::max);
+                        (entry.wallet.clone(), top)
+                    })
+                    .collect();
+                for (wallet, top) in top_shares {
+                    if let Some(entry) = agg.get_mut(&wallet) {
+                        entry.top_asset_share = top;
+                    }
+                }
+            }
+        }
+
+        let mut out: Vec<WalletMintVolumes> = agg.into_values().collect();
+        out.sort_by(|a, b| {
+            b.top_asset_share
+                .total_cmp(&a.top_asset_share)
+                .then_with(|| b.count.cmp(&a.count))
+                .then_with(|| a.wallet.cmp(&b.wallet))
+        });
+        out.truncate(limit as usize);
+        Ok(out)
+    }
+
+    /// Inbound vs outbound totals per counterparty (tokens per mint), largest
+    /// absolute net SOL flow first, then largest absolute single-mint net.
+    /// Positive nets mean the wallet is accumulating from that counterparty.
+    pub async fn get_net_flow_by_counterparty(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<CounterpartyNetFlow>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 500);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                // One row per (counterparty, mint) of the top counterparties; the
+                // mint columns are NULL for a counterparty with no token flows.
+                let rows = client
+                    .query(
+                        "WITH flows AS (
+                            SELECT from_wallet AS counterparty, kind, mint, amount_sol, token_amount_ui, TRUE AS inbound
+                            FROM transfer_events
+                            WHERE to_wallet = $1
+                              AND from_wallet IS NOT NULL
+                              AND from_wallet <> $1
+                              AND (block_time IS NULL OR block_time >= $2)
+                            UNION ALL
+                            SELECT to_wallet AS counterparty, kind, mint, amount_sol, token_amount_ui, FALSE AS inbound
+                            FROM transfer_events
+                            WHERE from_wallet = $1
+                              AND to_wallet IS NOT NULL
+                              AND to_wallet <> $1
+                              AND (block_time IS NULL OR block_time >= $2)
+                        ),
+                        per_mint AS (
+                            SELECT
+                                counterparty,
+                                mint,
+                                SUM(CASE WHEN inbound THEN COALESCE(token_amount_ui, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS inbound_ui,
+                                SUM(CASE WHEN NOT inbound THEN COALESCE(token_amount_ui, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS outbound_ui
+                            FROM flows
+                            WHERE kind = 'token' AND mint IS NOT NULL
+                            GROUP BY counterparty, mint
+                        ),
+                        totals AS (
+                            SELECT
+                                counterparty,
+                                COUNT(*)::BIGINT AS cnt,
+                                SUM(CASE WHEN inbound AND kind = 'sol' THEN COALESCE(amount_sol, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS inbound_sol,
+                                SUM(CASE WHEN NOT inbound AND kind = 'sol' THEN COALESCE(amount_sol, 0.0) ELSE 0.0 END)::DOUBLE PRECISION AS outbound_sol
+                            FROM flows
+                            GROUP BY counterparty
+                        ),
+                        ranked AS (
+                            SELECT
+                                t.counterparty, t.cnt, t.inbound_sol, t.outbound_sol,
+                                ROW_NUMBER() OVER (
+                                    ORDER BY ABS(t.inbound_sol - t.outbound_sol) DESC,
+                                             COALESCE(m.top_token_net, 0.0) DESC,
+                                             t.counterparty ASC
+                                ) AS rank
+                            FROM totals t
+                            LEFT JOIN (
+                                SELECT counterparty, MAX(ABS(inbound_ui - outbound_ui)) AS top_token_net
+                                FROM per_mint
+                                GROUP BY counterparty
+                            ) m ON m.counterparty = t.counterparty
+                        )
+                        SELECT r.counterparty, r.cnt, r.inbound_sol, r.outbound_sol, p.mint, p.inbound_ui, p.outbound_ui
+                        FROM ranked r
+                        LEFT JOIN per_mint p ON p.counterparty = r.counterparty
+                        WHERE r.rank <= $3
+                        ORDER BY r.rank, p.mint",
+                        &[&wallet, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get net flow by counterparty: {}",
+                            e
+                        ))
+                    })?;
+
+                let mut out: Vec<CounterpartyNetFlow> = Vec::new();
+                for row in &rows {
+                    let counterparty = row.get::<_, String>(0);
+                    if out.last().map(|f| &f.counterparty) != Some(&counterparty) {
+                        out.push(CounterpartyNetFlow::new(
+                            counterparty,
+                            row.get::<_, i64>(1) as u64,
+                            (row.get::<_, f64>(2), row.get::<_, f64>(3)),
+                            BTreeMap::new(),
+                        ));
+                    }
+                    if let Some(mint) = row.get::<_, Option<String>>(4) {
+                        let flow = out.last_mut().expect("pushed above");
+                        flow.add_token_flow(mint, row.get::<_, f64>(5), row.get::<_, f64>(6));
+                    }
+                }
+                Ok(out)
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                // counterparty -> (count, sol in, sol out, mint -> (token in, token out))
+                type Flows = (u64, f64, f64, BTreeMap<String, (f64, f64)>);
+                let mut agg: HashMap<String, Flows> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    if !passes_since(ev.block_time, since_epoch) {
+                        continue;
+                    }
+                    let (counterparty, inbound) =
+                        match (ev.from_wallet.as_deref(), ev.to_wallet.as_deref()) {
+                            (Some(from), Some(to)) if to == wallet && from != wallet => {
+                                (from, true)
+                            }
+                            (Some(from), Some(to)) if from == wallet && to != wallet => (to, false),
+                            _ => continue,
+                        };
+
+                    let entry = agg.entry(counterparty.to_string()).or_insert((
+                        0,
+                        0.0,
+                        0.0,
+                        BTreeMap::new(),
+                    ));
+                    entry.0 += 1;
+                    match (ev.kind.as_str(), ev.mint.as_deref(), inbound) {
+                        ("sol", _, true) => entry.1 += ev.amount_sol.unwrap_or(0.0),
+                        ("sol", _, false) => entry.2 += ev.amount_sol.unwrap_or(0.0),
+                        ("token", Some(mint), inbound) => {
+                            let ui = ev.token_amount_ui.unwrap_or(0.0);
+                            let mint = entry.3.entry(mint.to_string()).or_insert((0.0, 0.0));
+                            if inbound {
+                                mint.0 += ui;
+                            } else {
+                                mint.1 += ui;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                let mut out: Vec<CounterpartyNetFlow> = agg
+                    .into_iter()
+                    .map(|(counterparty, (count, sol_in, sol_out, tokens))| {
+                        CounterpartyNetFlow::new(counterparty, count, (sol_in, sol_out), tokens)
+                    })
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    b.net_sol
+                        .abs()
+                        .partial_cmp(&a.net_sol.abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| {
+                            b.largest_token_net()
+                                .partial_cmp(&a.largest_token_net())
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .then_with(|| a.counterparty.cmp(&b.counterparty))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Outbound SOL transfers grouped by amount rounded to 0.01 SOL, most frequent first.
+    /// Each bucket carries the sorted block times of its transfers.
+    pub async fn get_amount_distribution(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+    ) -> BeastResult<Vec<AmountBucket>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+
+        let mut out = match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            ROUND(amount_sol::NUMERIC, 2)::DOUBLE PRECISION AS bucket,
+                            COUNT(*)::BIGINT AS cnt,
+                            SUM(amount_sol)::DOUBLE PRECISION AS total_sol,
+                            ARRAY_AGG(block_time ORDER BY block_time)
+                                FILTER (WHERE block_time IS NOT NULL) AS times
+                         FROM transfer_events
+                         WHERE from_wallet = $1
+                           AND kind = 'sol'
+                           AND amount_sol >= 0.005
+                           AND (block_time IS NULL OR block_time >= $2)
+                         GROUP BY 1",
+                        &[&wallet, &since_epoch],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get amount distribution: {}",
+                            e
+                        ))
+                    })?;
+
+                rows.iter()
+                    .map(|row| AmountBucket {
+                        amount_sol: row.get::<_, f64>(0),
+                        count: row.get::<_, i64>(1) as u64,
+                        total_sol: row.get::<_, f64>(2),
+                        block_times: row
+                            .get::<_, Option<Vec<i64>>>(3)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|t| t.max(0) as u64)
+                            .collect(),
+                    })
+                    .collect::<Vec<_>>()
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                // Keyed by amount in hundredths of a SOL.
+                let mut agg: HashMap<i64, AmountBucket> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    if ev.kind != "sol"
+                        || ev.from_wallet.as_deref() != Some(wallet)
+                        || !passes_since(ev.block_time, since_epoch)
+                    {
+                        continue;
+                    }
+                    let Some(amount) = ev.amount_sol.filter(|a| *a >= 0.005) else {
+                        continue;
+                    };
+                    let cents = (amount * 100.0).round() as i64;
+                    let bucket = agg.entry(cents).or_insert_with(|| AmountBucket {
+                        amount_sol: cents as f64 / 100.0,
+                        count: 0,
+                        total_sol: 0.0,
+                        block_times: Vec::new(),
+                    });
+                    bucket.count += 1;
+                    bucket.total_sol += amount;
+                    if let Some(t) = ev.block_time {
+                        bucket.block_times.push(t.max(0) as u64);
+                    }
+                }
+
+                agg.into_values()
+                    .map(|mut b| {
+                        b.block_times.sort_unstable();
+                        b
+                    })
+                    .collect()
+            }
+        };
+
+        out.sort_by(|a, b| {
+            b.count.cmp(&a.count).then_with(|| {
+                a.amount_sol
+                    .partial_cmp(&b.amount_sol)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        Ok(out)
+    }
+
+    /// Get transfer events from one wallet to another (newest first).
+    pub async fn get_transfers_between(
+        &self,
+        from_wallet: &str,
+        to_wallet: &str,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<TransferEvent>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 500);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            kind,
+                            transfer_type,
+                            from_wallet,
+                            to_wallet,
+                            mint,
+                            amount_sol,
+                            token_amount_ui,
+                            token_amount,
+                            token_decimals
+                         FROM transfer_events
+                         WHERE from_wallet = $1
+                           AND to_wallet = $2
+                           AND (block_time IS NULL OR block_time >= $3)
+                         ORDER BY COALESCE(block_time, 0) DESC
+                         LIMIT $4",
+                        &[&from_wallet, &to_wallet, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get transfer events: {}", e))
+                    })?;
+
+                Ok(rows.iter().map(TransferEvent::from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<TransferEvent> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| passes_since(ev.block_time, since_epoch))
+                    .filter(|ev| ev.from_wallet.as_deref() == Some(from_wallet))
+                    .filter(|ev| ev.to_wallet.as_deref() == Some(to_wallet))
+                    .cloned()
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    tb.cmp(&ta)
+                        .then_with(|| b.signature.cmp(&a.signature))
+                        .then_with(|| b.event_index.cmp(&a.event_index))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Get recent wallet-to-wallet transfer events across all wallets (newest first).
+    pub async fn get_recent_transfer_events(
+        &self,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<TransferEvent>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 50_000);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            kind,
+                            transfer_type,
+                            from_wallet,
+                            to_wallet,
+                            mint,
+                            amount_sol,
+                            token_amount_ui,
+                            token_amount,
+                            token_decimals
+                         FROM transfer_events
+                         WHERE from_wallet IS NOT NULL
+                           AND to_wallet IS NOT NULL
+                           AND (block_time IS NULL OR block_time >= $1)
+                         ORDER BY COALESCE(block_time, 0) DESC
+                         LIMIT $2",
+                        &[&since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get recent transfer events: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(TransferEvent::from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<TransferEvent> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| passes_since(ev.block_time, since_epoch))
+                    .filter(|ev| ev.from_wallet.is_some() && ev.to_wallet.is_some())
+                    .cloned()
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    tb.cmp(&ta)
+                        .then_with(|| b.signature.cmp(&a.signature))
+                        .then_with(|| b.event_index.cmp(&a.event_index))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Get transfer events sent or received by a wallet (newest first), optionally
+    /// restricted to one `kind` ("sol" | "token") and to transactions of one
+    /// `tx_type` (a `TransactionType` variant name, e.g. "TokenSwap").
+    pub async fn get_transfer_events_for_wallet(
+        &self,
+        wallet: &str,
+        kind: Option<&str>,
+        tx_type: Option<&str>,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<TransferEvent>> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 1000);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            e.signature,
+                            e.event_index,
+                            e.slot,
+                            e.block_time,
+                            e.kind,
+                            e.transfer_type,
+                            e.from_wallet,
+                            e.to_wallet,
+                            e.mint,
+                            e.amount_sol,
+                            e.token_amount_ui,
+                            e.token_amount,
+                            e.token_decimals
+                         FROM transfer_events e
+                         LEFT JOIN transactions t ON t.signature = e.signature
+                         WHERE (e.from_wallet = $1 OR e.to_wallet = $1)
+                           AND ($2::text IS NULL OR e.kind = $2)
+                           AND ($3::text IS NULL OR t.data->>'tx_type' = $3)
+                           AND (e.block_time IS NULL OR e.block_time >= $4)
+                         ORDER BY COALESCE(e.block_time, 0) DESC, e.signature DESC, e.event_index DESC
+                         LIMIT $5",
+                        &[&wallet, &kind, &tx_type, &since_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get wallet transfer events: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(TransferEvent::from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<TransferEvent> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| passes_since(ev.block_time, since_epoch))
+                    .filter(|ev| {
+                        ev.from_wallet.as_deref() == Some(wallet)
+                            || ev.to_wallet.as_deref() == Some(wallet)
+                    })
+                    .filter(|ev| kind.is_none_or(|k| ev.kind == k))
+                    .filter(|ev| {
+                        tx_type.is_none_or(|wanted| {
+                            mem.transactions
+                                .get(&ev.signature)
+                                .is_some_and(|tx| format!("{:?}", tx.tx_type) == wanted)
+                        })
+                    })
+                    .cloned()
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    tb.cmp(&ta)
+                        .then_with(|| b.signature.cmp(&a.signature))
+                        .then_with(|| b.event_index.cmp(&a.event_index))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Every transfer sent or received by `wallet`, oldest first by (block time,
+    /// signature, event index); events without a block time sort as time 0. Pass the
+    /// returned `next_cursor` back as `cursor` for the following page.
+    ///
+    /// Keyset paging never repeats an event, and over a stable set of events never
+    /// skips one. Events stored while paging are only seen if they sort after the
+    /// cursor, and storage does not happen in that order: bootstrap ingests newest
+    /// first, and the block-time backfill moves untimed events (time 0) to their
+    /// real time. Such events land behind a cursor that has passed their position
+    /// and are missed; page again from the start to pick them up.
+    pub async fn get_wallet_transfers(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> BeastResult<TransferPage> {
+        let since_epoch = since_epoch.unwrap_or(0) as i64;
+        let limit = limit.clamp(1, 1000);
+        let after = cursor.map(TransferCursor::decode).transpose()?;
+
+        let mut transfers = match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let (after_time, after_sig, after_idx) = match &after {
+                    Some(c) => (
+                        Some(c.block_time),
+                        Some(c.signature.as_str()),
+                        Some(c.event_index),
+                    ),
+                    None => (None, None, None),
+                };
+                let rows = client
+                    .query(
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            kind,
+                            transfer_type,
+                            from_wallet,
+                            to_wallet,
+                            mint,
+                            amount_sol,
+                            token_amount_ui,
+                            token_amount,
+                            token_decimals
+                         FROM transfer_events
+                         WHERE (from_wallet = $1 OR to_wallet = $1)
+                           AND (block_time IS NULL OR block_time >= $2)
+                           AND ($3::BIGINT IS NULL
+                                OR (COALESCE(block_time, 0), signature, event_index) > ($3, $4::TEXT, $5::INTEGER))
+                         ORDER BY COALESCE(block_time, 0), signature, event_index
+                         LIMIT $6",
+                        &[
+                            &wallet,
+                            &since_epoch,
+                            &after_time,
+                            &after_sig,
+                            &after_idx,
+                            &(limit as i64 + 1),
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get wallet transfers: {}", e))
+                    })?;
+
+                rows.iter().map(TransferEvent::from_row).collect::<Vec<_>>()
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<TransferEvent> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| passes_since(ev.block_time, since_epoch))
+                    .filter(|ev| {
+                        ev.from_wallet.as_deref() == Some(wallet)
+                            || ev.to_wallet.as_deref() == Some(wallet)
+                    })
+                    .filter(|ev| after.as_ref().is_none_or(|c| c.is_before(ev)))
+                    .cloned()
+                    .collect();
+                out.sort_by(|a, b| TransferCursor::key(a).cmp(&TransferCursor::key(b)));
+                out.truncate(limit + 1);
+                out
+            }
+        };
+
+        let next_cursor = if transfers.len() > limit {
+            transfers.truncate(limit);
+            transfers
+                .last()
+                .map(|ev| TransferCursor::after(ev).encode())
+        } else {
+            None
+        };
+        Ok(TransferPage {
+            transfers,
+            next_cursor,
+        })
+    }
+
+    /// Earliest timed transfer into `wallet` from another wallet: who funded it first.
+    pub async fn get_first_inbound_funder(
+        &self,
+        wallet: &str,
+    ) -> BeastResult<Option<TransferEvent>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_opt(
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            kind,
+                            transfer_type,
+                            from_wallet,
+                            to_wallet,
+                            mint,
+                            amount_sol,
+                            token_amount_ui,
+                            token_amount,
+                            token_decimals
+                         FROM transfer_events
+                         WHERE to_wallet = $1
+                           AND from_wallet IS NOT NULL
+                           AND from_wallet <> $1
+                           AND block_time IS NOT NULL
+                         ORDER BY block_time ASC, slot ASC, signature ASC, event_index ASC
+                         LIMIT 1",
+                        &[&wallet],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get first inbound funder: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(row.as_ref().map(TransferEvent::from_row))
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                Ok(mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| ev.to_wallet.as_deref() == Some(wallet) && ev.block_time.is_some())
+                    .filter(|ev| ev.from_wallet.as_deref().is_some_and(|from| from != wallet))
+                    .min_by(|a, b| {
+                        a.block_time
+                            .cmp(&b.block_time)
+                            .then_with(|| a.slot.cmp(&b.slot))
+                            .then_with(|| a.signature.cmp(&b.signature))
+                            .then_with(|| a.event_index.cmp(&b.event_index))
+                    })
+                    .cloned())
+            }
+        }
+    }
+
+    /// Earliest transfer `block_time` on either side of each of `wallets`: when the
+    /// wallet was first seen. Wallets with no timed transfers are absent.
+    pub async fn get_first_seen_epochs(
+        &self,
+        wallets: &[String],
+    ) -> BeastResult<HashMap<String, u64>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                if wallets.is_empty() {
+                    return Ok(HashMap::new());
+                }
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT wallet, MIN(block_time)
+                         FROM (
+                            SELECT from_wallet AS wallet, block_time
+                            FROM transfer_events
+                            WHERE from_wallet = ANY($1) AND block_time IS NOT NULL
+                            UNION ALL
+                            SELECT to_wallet AS wallet, block_time
+                            FROM transfer_events
+                            WHERE to_wallet = ANY($1) AND block_time IS NOT NULL
+                         ) seen
+                         GROUP BY wallet",
+                        &[&wallets],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get first-seen times: {}", e))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .map(|row| (row.get::<_, String>(0), row.get::<_, i64>(1) as u64))
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let wanted: HashSet<&str> = wallets.iter().map(String::as_str).collect();
+                let mut out: HashMap<String, u64> = HashMap::new();
+                for ev in mem.transfer_events.values() {
+                    let Some(block_time) = ev.block_time.map(|t| t as u64) else {
+                        continue;
+                    };
+                    for side in [ev.from_wallet.as_deref(), ev.to_wallet.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .filter(|w| wanted.contains(w))
+                    {
+                        let first = out.entry(side.to_string()).or_insert(block_time);
+                        *first = (*first).min(block_time);
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Classification of stored transactions, keyed by signature. Signatures that
+    /// were never ingested are absent.
+    pub async fn get_transaction_types(
+        &self,
+        signatures: &[String],
+    ) -> BeastResult<HashMap<String, String>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT signature, data->>'tx_type'
+                         FROM transactions
+                         WHERE signature = ANY($1)",
+                        &[&signatures],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get transaction types: {}", e))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        let tx_type = row.get::<_, Option<String>>(1)?;
+                        Some((row.get::<_, String>(0), tx_type))
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                Ok(signatures
+                    .iter()
+                    .filter_map(|sig| {
+                        let tx = mem.transactions.get(sig)?;
+                        Some((sig.clone(), format!("{:?}", tx.tx_type)))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Get outbound transfer events for a wallet within a time window (oldest first).
+    pub async fn get_outbound_transfers_in_window(
+        &self,
+        from_wallet: &str,
+        start_epoch: u64,
+        end_epoch: u64,
+        limit: usize,
+    ) -> BeastResult<Vec<TransferEvent>> {
+        let start_epoch = start_epoch as i64;
+        let end_epoch = end_epoch as i64;
+        let limit = (limit as i64).clamp(1, 2000);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            signature,
+                            event_index,
+                            slot,
+                            block_time,
+                            kind,
+                            transfer_type,
+                            from_wallet,
+                            to_wallet,
+                            mint,
+                            amount_sol,
+                            token_amount_ui,
+                            token_amount,
+                            token_decimals
+                         FROM transfer_events
+                         WHERE from_wallet = $1
+                           AND to_wallet IS NOT NULL
+                           AND block_time IS NOT NULL
+                           AND block_time >= $2
+                           AND block_time <= $3
+                         ORDER BY block_time ASC
+                         LIMIT $4",
+                        &[&from_wallet, &start_epoch, &end_epoch, &limit],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get outbound transfer events: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows.iter().map(TransferEvent::from_row).collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                let mut out: Vec<TransferEvent> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| ev.from_wallet.as_deref() == Some(from_wallet))
+                    .filter(|ev| ev.to_wallet.is_some())
+                    .filter(|ev| {
+                        let Some(bt) = ev.block_time else {
+                            return false;
+                        };
+                        bt >= start_epoch && bt <= end_epoch
+                    })
+                    .cloned()
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    let ta = a.block_time.unwrap_or(0);
+                    let tb = b.block_time.unwrap_or(0);
+                    ta.cmp(&tb)
+                        .then_with(|| a.signature.cmp(&b.signature))
+                        .then_with(|| a.event_index.cmp(&b.event_index))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Strongest connections of a wallet (first page of up to 100).
+    pub async fn get_wallet_connections(
+        &self,
+        wallet_address: &str,
+    ) -> BeastResult<Vec<WalletConnection>> {
+        Ok(self
+            .get_wallet_connections_page(wallet_address, 0, 100)
+            .await?
+            .connections)
+    }
+
+    /// A page of a wallet's connections, ordered by transaction count (desc) with
+    /// counterparties as a stable tie-break so consecutive offsets never overlap.
+    pub async fn get_wallet_connections_page(
+        &self,
+        wallet_address: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BeastResult<ConnectionPage> {
+        let limit = limit.clamp(1, 1000);
+
+        let mut connections = match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                // One extra row tells whether another page exists.
+                let rows = client
+                    .query(
+                        "SELECT
+                            from_wallet,
+                            to_wallet,
+                            sol_amount,
+                            token_amount,
+                            transaction_count,
+                            EXTRACT(EPOCH FROM first_seen)::BIGINT AS first_seen_epoch,
+                            EXTRACT(EPOCH FROM last_seen)::BIGINT AS last_seen_epoch
+                         FROM wallet_relationships
+                         WHERE from_wallet = $1 OR to_wallet = $1
+                         ORDER BY transaction_count DESC, from_wallet, to_wallet
+                         OFFSET $2
+                         LIMIT $3",
+                        &[&wallet_address, &(offset as i64), &(limit as i64 + 1)],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get connections: {}", e))
+                    })?;
+
+                rows.iter()
+                    .map(|row| WalletConnection {
+                        from_wallet: row.get(0),
+                        to_wallet: row.get(1),
+                        total_sol_transferred: row.get(2),
+                        total_token_transferred: row.get::<_, i64>(3) as u64,
+                        transaction_count: row.get::<_, i32>(4) as u32,
+                        first_seen_epoch: row.get::<_, i64>(5) as u64,
+                        last_seen_epoch: row.get::<_, i64>(6) as u64,
+                    })
+                    .collect::<Vec<_>>()
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut out: Vec<WalletConnection> = mem
+                    .relationships
+                    .values()
+                    .filter(|rel| {
+                        rel.from_wallet == wallet_address || rel.to_wallet == wallet_address
+                    })
+                    .map(|rel| WalletConnection {
+                        from_wallet: rel.from_wallet.clone(),
+                        to_wallet: rel.to_wallet.clone(),
+                        total_sol_transferred: rel.sol_amount,
+                        total_token_transferred: rel.token_amount,
+                        transaction_count: rel.transaction_count,
+                        first_seen_epoch: rel.first_seen_epoch,
+                        last_seen_epoch: rel.last_seen_epoch,
+                    })
+                    .collect();
+
+                out.sort_by(|a, b| {
+                    b.transaction_count
+                        .cmp(&a.transaction_count)
+                        .then_with(|| a.from_wallet.cmp(&b.from_wallet))
+                        .then_with(|| a.to_wallet.cmp(&b.to_wallet))
+                });
+                out.into_iter().skip(offset).take(limit + 1).collect()
+            }
+        };
+
+        let has_more = connections.len() > limit;
+        connections.truncate(limit);
+        Ok(ConnectionPage {
+            connections,
+            has_more,
+        })
+    }
+
+    /// Get behavioral profile for a wallet from transfer_events.
+    pub async fn get_behavioral_profile(
+        &self,
+        wallet: &str,
+        since_epoch: Option<u64>,
+    ) -> BeastResult<Option<BehavioralProfile>> {
+        let since = since_epoch.unwrap_or(0) as i64;
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let row = client
+                    .query_opt(
                         "WITH wallet_transfers AS (
                             SELECT
                                 COALESCE(amount_sol, 0.0) AS sol,
                                 block_time,
-                                EXTRACT(HOUR FROM TO_TIMESTAMP(block_time)) AS hour_utc
+                                EXTRACT(HOUR FROM TO_TIMESTAMP(block_time)) AS hour_utc,
+                                from_wallet IS NOT DISTINCT FROM $1 AS outbound,
+                                CASE WHEN from_wallet IS NOT DISTINCT FROM $1
+                                    THEN to_wallet ELSE from_wallet END AS counterparty
+                            FROM transfer_events
+                            WHERE (from_wallet = $1 OR to_wallet = $1)
+                              AND kind = 'sol'
+                              AND amount_sol > 0.0
+                              AND (block_time IS NULL OR block_time >= $2)
+                        ),
+                        time_bounds AS (
+                            SELECT
+                                MIN(block_time)::BIGINT AS first_tx,
+                                MAX(block_time)::BIGINT AS last_tx
+                            FROM wallet_transfers
+                        ),
+                        stats AS (
+                            SELECT
+                                COUNT(*)::BIGINT AS total_transfers,
+                                AVG(sol)::DOUBLE PRECISION AS avg_sol,
+                                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY sol)::DOUBLE PRECISION AS median_sol,
+                                MODE() WITHIN GROUP (ORDER BY hour_utc)::INTEGER AS most_active_hour,
+                                COUNT(*) FILTER (WHERE NOT outbound)::BIGINT AS inbound_count,
+                                COUNT(*) FILTER (WHERE outbound)::BIGINT AS outbound_count,
+                                COUNT(DISTINCT counterparty) FILTER (WHERE counterparty <> $1)::BIGINT AS unique_counterparties
+                            FROM wallet_transfers
+                        )
+                        SELECT
+                            COALESCE(s.total_transfers, 0)::BIGINT AS total_transfers,
+                            COALESCE(s.avg_sol, 0.0)::DOUBLE PRECISION AS avg_sol,
+                            COALESCE(s.median_sol, 0.0)::DOUBLE PRECISION AS median_sol,
+                            COALESCE(s.most_active_hour, -1)::INTEGER AS most_active_hour,
+                            COALESCE(t.first_tx, 0)::BIGINT AS first_tx,
+                            COALESCE(t.last_tx, 0)::BIGINT AS last_tx,
+                            s.inbound_count,
+                            s.outbound_count,
+                            s.unique_counterparties
+                        FROM stats s
+                        CROSS JOIN time_bounds t
+                        WHERE s.total_transfers > 0",
+                        &[&wallet, &since],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get behavioral profile: {}",
+                            e
+                        ))
+                    })?;
+
+                let Some(row) = row else {
+                    return Ok(None);
+                };
+
+                Ok(behavioral_profile_from_row(wallet, &row, 0))
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+
+                let samples: Vec<ProfileSample> = mem
+                    .transfer_events
+                    .values()
+                    .filter(|ev| ev.kind == "sol" && passes_since(ev.block_time, since))
+                    .filter_map(|ev| {
+                        let outbound = ev.from_wallet.as_deref() == Some(wallet);
+                        if !outbound && ev.to_wallet.as_deref() != Some(wallet) {
+                            return None;
+                        }
+                        let counterparty = if outbound {
+                            ev.to_wallet.as_deref()
+                        } else {
+                            ev.from_wallet.as_deref()
+                        };
+                        Some(ProfileSample {
+                            sol: ev.amount_sol?,
+                            block_time: ev.block_time,
+                            outbound,
+                            counterparty,
+                        })
+                    })
+                    .collect();
+
+                Ok(behavioral_profile_from_samples(wallet, samples))
+            }
+        }
+    }
+
+    /// Lifetime totals over every stored transfer of `wallet`, or `None` if it has
+    /// none. Self-transfers count as outbound, as in `get_behavioral_profile`.
+    pub async fn get_wallet_summary(&self, wallet: &str) -> BeastResult<Option<WalletSummary>> {
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let totals = client
+                    .query_one(
+                        "WITH w AS (
+                            SELECT
+                                signature,
+                                block_time,
+                                kind,
+                                amount_sol,
+                                from_wallet IS NOT DISTINCT FROM $1 AS outbound,
+                                CASE WHEN from_wallet IS NOT DISTINCT FROM $1
+                                    THEN to_wallet ELSE from_wallet END AS counterparty
+                            FROM transfer_events
+                            WHERE from_wallet = $1 OR to_wallet = $1
+                         )
+                         SELECT
+                            COUNT(*)::BIGINT,
+                            COUNT(DISTINCT signature)::BIGINT,
+                            COALESCE(MIN(block_time), 0)::BIGINT,
+                            COALESCE(MAX(block_time), 0)::BIGINT,
+                            COALESCE(SUM(amount_sol) FILTER (WHERE kind = 'sol' AND NOT outbound), 0.0)::DOUBLE PRECISION,
+                            COALESCE(SUM(amount_sol) FILTER (WHERE kind = 'sol' AND outbound), 0.0)::DOUBLE PRECISION,
+                            COUNT(DISTINCT counterparty) FILTER (WHERE counterparty <> $1)::BIGINT
+                         FROM w",
+                        &[&wallet],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get wallet summary: {}", e))
+                    })?;
+                if totals.get::<_, i64>(0) == 0 {
+                    return Ok(None);
+                }
+                let tokens = client
+                    .query(
+                        "SELECT
+                            from_wallet IS NOT DISTINCT FROM $1 AS outbound,
+                            mint,
+                            SUM(COALESCE(
+                                token_amount_ui,
+                                token_amount::DOUBLE PRECISION / POWER(10::DOUBLE PRECISION, token_decimals),
+                                0.0
+                            ))::DOUBLE PRECISION
+                         FROM transfer_events
+                         WHERE (from_wallet = $1 OR to_wallet = $1)
+                           AND kind = 'token'
+                           AND mint IS NOT NULL
+                         GROUP BY 1, 2",
+                        &[&wallet],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!("Failed to get wallet summary: {}", e))
+                    })?;
+
+                let mut summary = WalletSummary {
+                    wallet: wallet.to_string(),
+                    first_seen_epoch: totals.get::<_, i64>(2) as u64,
+                    last_seen_epoch: totals.get::<_, i64>(3) as u64,
+                    transaction_count: totals.get::<_, i64>(1) as u64,
+                    transfer_count: totals.get::<_, i64>(0) as u64,
+                    inbound_sol: totals.get::<_, f64>(4),
+                    outbound_sol: totals.get::<_, f64>(5),
+                    inbound_token_ui_by_mint: BTreeMap::new(),
+                    outbound_token_ui_by_mint: BTreeMap::new(),
+                    unique_counterparties: totals.get::<_, i64>(6) as u64,
+                };
+                for row in &tokens {
+                    let by_mint = if row.get::<_, bool>(0) {
+                        &mut summary.outbound_token_ui_by_mint
+                    } else {
+                        &mut summary.inbound_token_ui_by_mint
+                    };
+                    by_mint.insert(row.get::<_, String>(1), row.get::<_, f64>(2));
+                }
+                Ok(Some(summary))
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut summary = WalletSummary {
+                    wallet: wallet.to_string(),
+                    first_seen_epoch: 0,
+                    last_seen_epoch: 0,
+                    transaction_count: 0,
+                    transfer_count: 0,
+                    inbound_sol: 0.0,
+                    outbound_sol: 0.0,
+                    inbound_token_ui_by_mint: BTreeMap::new(),
+                    outbound_token_ui_by_mint: BTreeMap::new(),
+                    unique_counterparties: 0,
+                };
+                let mut signatures: HashSet<&str> = HashSet::new();
+                let mut counterparties: HashSet<&str> = HashSet::new();
+                let mut first_seen: Option<u64> = None;
+                for ev in mem.transfer_events.values() {
+                    let outbound = ev.from_wallet.as_deref() == Some(wallet);
+                    if !outbound && ev.to_wallet.as_deref() != Some(wallet) {
+                        continue;
+                    }
+                    summary.transfer_count += 1;
+                    signatures.insert(ev.signature.as_str());
+                    if let Some(t) = ev.block_time.map(|t| t as u64) {
+                        first_seen = Some(first_seen.map_or(t, |f| f.min(t)));
+                        summary.last_seen_epoch = summary.last_seen_epoch.max(t);
+                    }
+                    let counterparty = if outbound {
+                        ev.to_wallet.as_deref()
+                    } else {
+                        ev.from_wallet.as_deref()
+                    };
+                    if let Some(c) = counterparty.filter(|c| *c != wallet) {
+                        counterparties.insert(c);
+                    }
+                    match ev.kind.as_str() {
+                        "sol" => {
+                            let sol = ev.amount_sol.unwrap_or(0.0);
+                            if outbound {
+                                summary.outbound_sol += sol;
+                            } else {
+                                summary.inbound_sol += sol;
+                            }
+                        }
+                        "token" => {
+                            let Some(mint) = ev.mint.clone() else {
+                                continue;
+                            };
+                            let by_mint = if outbound {
+                                &mut summary.outbound_token_ui_by_mint
+                            } else {
+                                &mut summary.inbound_token_ui_by_mint
+                            };
+                            *by_mint.entry(mint).or_insert(0.0) +=
+                                ev.token_ui_amount().unwrap_or(0.0);
+                        }
+                        _ => {}
+                    }
+                }
+                if summary.transfer_count == 0 {
+                    return Ok(None);
+                }
+                summary.first_seen_epoch = first_seen.unwrap_or(0);
+                summary.transaction_count = signatures.len() as u64;
+                summary.unique_counterparties = counterparties.len() as u64;
+                Ok(Some(summary))
+            }
+        }
+    }
+
+    /// Transfer counts and volumes per `bucket_secs`-wide time bucket (oldest first).
+    /// Events without a block time are left out.
+    pub async fn get_activity_histogram(
+        &self,
+        wallet: &str,
+        bucket_secs: u64,
+        since_epoch: Option<u64>,
+    ) -> BeastResult<Vec<ActivityBucket>> {
+        let bucket_secs = bucket_secs.max(1) as i64;
+        let since = since_epoch.unwrap_or(0) as i64;
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "SELECT
+                            (block_time / $2) * $2 AS bucket_start,
+                            COUNT(*)::BIGINT AS transfer_count,
+                            COALESCE(SUM(amount_sol), 0.0)::DOUBLE PRECISION AS sol_volume,
+                            COALESCE(SUM(token_amount_ui), 0.0)::DOUBLE PRECISION AS token_volume
+                         FROM transfer_events
+                         WHERE (from_wallet = $1 OR to_wallet = $1)
+                           AND block_time IS NOT NULL
+                           AND block_time >= $3
+                         GROUP BY 1
+                         ORDER BY 1",
+                        &[&wallet, &bucket_secs, &since],
+                    )
+                    .await
+                    .map_err(|e| {
+                        BeastError::DatabaseError(format!(
+                            "Failed to get activity histogram: {}",
+                            e
+                        ))
+                    })?;
+
+                Ok(rows
+                    .iter()
+                    .map(|row| ActivityBucket {
+                        bucket_start: row.get::<_, i64>(0) as u64,
+                        transfer_count: row.get::<_, i64>(1) as u64,
+                        sol_volume: row.get::<_, f64>(2),
+                        token_volume: row.get::<_, f64>(3),
+                    })
+                    .collect())
+            }
+            DatabaseInner::Memory { state } => {
+                let mem = state.read().await;
+                let mut buckets: BTreeMap<i64, ActivityBucket> = BTreeMap::new();
+                for ev in mem.transfer_events.values().filter(|ev| {
+                    ev.from_wallet.as_deref() == Some(wallet)
+                        || ev.to_wallet.as_deref() == Some(wallet)
+                }) {
+                    let Some(block_time) = ev.block_time.filter(|t| *t >= since) else {
+                        continue;
+                    };
+                    let start = block_time.div_euclid(bucket_secs) * bucket_secs;
+                    let bucket = buckets.entry(start).or_insert_with(|| ActivityBucket {
+                        bucket_start: start as u64,
+                        transfer_count: 0,
+                        sol_volume: 0.0,
+                        token_volume: 0.0,
+                    });
+                    bucket.transfer_count += 1;
+                    bucket.sol_volume += ev.amount_sol.unwrap_or(0.0);
+                    bucket.token_volume += ev.token_amount_ui.unwrap_or(0.0);
+                }
+
+                Ok(buckets.into_values().collect())
+            }
+        }
+    }
+
+    /// Behavioral profiles for every wallet with SOL transfers in the window,
+    /// most active first.
+    pub async fn get_all_behavioral_profiles(
+        &self,
+        since_epoch: Option<u64>,
+        limit: usize,
+    ) -> BeastResult<Vec<BehavioralProfile>> {
+        let since = since_epoch.unwrap_or(0) as i64;
+        let limit = (limit as i64).clamp(1, 50_000);
+
+        match &self.inner {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
+                let rows = client
+                    .query(
+                        "WITH sol_events AS (
+                            SELECT from_wallet, to_wallet, amount_sol AS sol, block_time
                             FROM transfer_events
-                            WHERE (from_wallet = $1 OR to_wallet = $1)
-                              AND kind = 'sol'
+                            WHERE kind = 'sol'
                               AND amount_sol > 0.0
-                              AND (block_time IS NULL OR block_time >= $2)
-                        ),
-                        time_bounds AS (
-                            SELECT
-                                MIN(block_time)::BIGINT AS first_tx,
-                                MAX(block_time)::BIGINT AS last_tx
-                            FROM wallet_transfers
+                              AND (block_time IS NULL OR block_time >= $1)
                         ),
-                        stats AS (
-                            SELECT
-                                COUNT(*)::BIGINT AS total_transfers,
-                                AVG(sol)::DOUBLE PRECISION AS avg_sol,
-                                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY sol)::DOUBLE PRECISION AS median_sol,
-                                MODE() WITHIN GROUP (ORDER BY hour_utc)::INTEGER AS most_active_hour
-                            FROM wallet_transfers
+                        wallet_transfers AS (
+                            SELECT from_wallet AS wallet, sol, block_time,
+                                TRUE AS outbound, to_wallet AS counterparty
+                            FROM sol_events
+                            WHERE from_wallet IS NOT NULL
+                            UNION ALL
+                            SELECT to_wallet AS wallet, sol, block_time,
+                                FALSE AS outbound, from_wallet AS counterparty
+                            FROM sol_events
+                            WHERE to_wallet IS NOT NULL
+                              AND to_wallet IS DISTINCT FROM from_wallet
                         )
                         SELECT
-                            COALESCE(s.total_transfers, 0)::BIGINT AS total_transfers,
-                            COALESCE(s.avg_sol, 0.0)::DOUBLE PRECISION AS avg_sol,
-                            COALESCE(s.median_sol, 0.0)::DOUBLE PRECISION AS median_sol,
-                            COALESCE(s.most_active_hour, -1)::INTEGER AS most_active_hour,
-                            COALESCE(t.first_tx, 0)::BIGINT AS first_tx,
-                            COALESCE(t.last_tx, 0)::BIGINT AS last_tx
-                        FROM stats s
-                        CROSS JOIN time_bounds t
-                        WHERE s.total_transfers > 0",
-                        &[&wallet, &since],
+                            wallet,
+                            COUNT(*)::BIGINT AS total_transfers,
+                            AVG(sol)::DOUBLE PRECISION AS avg_sol,
+                            PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY sol)::DOUBLE PRECISION AS median_sol,
+                            COALESCE(MODE() WITHIN GROUP (ORDER BY EXTRACT(HOUR FROM TO_TIMESTAMP(block_time)))::INTEGER, -1) AS most_active_hour,
+                            COALESCE(MIN(block_time), 0)::BIGINT AS first_tx,
+                            COALESCE(MAX(block_time), 0)::BIGINT AS last_tx,
+                            COUNT(*) FILTER (WHERE NOT outbound)::BIGINT AS inbound_count,
+                            COUNT(*) FILTER (WHERE outbound)::BIGINT AS outbound_count,
+                            COUNT(DISTINCT counterparty) FILTER (WHERE counterparty <> wallet)::BIGINT AS unique_counterparties
+                        FROM wallet_transfers
+                        GROUP BY wallet
+                        ORDER BY total_transfers DESC, wallet ASC
+                        LIMIT $2",
+                        &[&since, &limit],
                     )
                     .await
                     .map_err(|e| {
                         BeastError::DatabaseError(format!(
-                            "Failed to get behavioral profile: {}",
+                            "Failed to get behavioral profiles: {}",
                             e
                         ))
                     })?;
 
-                let Some(row) = row else {
-                    return Ok(None);
-                };
-
-                let total_transfers: i64 = row.get(0);
-                if total_transfers <= 0 {
-                    return Ok(None);
-                }
-
-                let avg_sol: f64 = row.get(1);
-                let median_sol: f64 = row.get(2);
-                let most_active_hour: i32 = row.get(3);
-                let first_tx: i64 = row.get(4);
-                let last_tx: i64 = row.get(5);
-
-                let days_active = if last_tx > first_tx && first_tx > 0 {
-                    ((last_tx - first_tx) / 86_400).max(1) as u32
-                } else {
-                    1
-                };
-                let avg_tx_per_day = total_transfers as f64 / days_active as f64;
-
-                Ok(Some(BehavioralProfile {
-                    wallet: wallet.to_string(),
-                    total_transfers: total_transfers as u64,
-                    avg_sol_per_tx: avg_sol,
-                    median_sol_per_tx: median_sol,
-                    total_days_active: days_active,
-                    avg_tx_per_day,
-                    most_active_hour_utc: if most_active_hour >= 0 {
-                        Some(most_active_hour)
-                    } else {
-                        None
-                    },
-                    first_tx_epoch: first_tx as u64,
-                    last_tx_epoch: last_tx as u64,
-                }))
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        let wallet: String = row.get(0);
+                        behavioral_profile_from_row(&wallet, row, 1)
+                    })
+                    .collect())
             }
             DatabaseInner::Memory { state } => {
                 let mem = state.read().await;
 
-                let mut sol_amounts: Vec<f64> = Vec::new();
-                let mut hour_counts: HashMap<i32, u64> = HashMap::new();
-                let mut first_tx: i64 = i64::MAX;
-                let mut last_tx: i64 = 0;
-
+                let mut by_wallet: HashMap<&str, Vec<ProfileSample>> = HashMap::new();
                 for ev in mem.transfer_events.values() {
-                    if ev.kind != "sol" {
-                        continue;
-                    }
-                    if !passes_since(ev.block_time, since) {
-                        continue;
-                    }
-                    if ev.from_wallet.as_deref() != Some(wallet)
-                        && ev.to_wallet.as_deref() != Some(wallet)
-                    {
+                    if ev.kind != "sol" || !passes_since(ev.block_time, since) {
                         continue;
                     }
                     let Some(sol) = ev.amount_sol else {
                         continue;
                     };
-                    if sol <= 0.0 {
-                        continue;
+                    if let Some(from) = ev.from_wallet.as_deref() {
+                        by_wallet.entry(from).or_default().push(ProfileSample {
+                            sol,
+                            block_time: ev.block_time,
+                            outbound: true,
+                            counterparty: ev.to_wallet.as_deref(),
+                        });
                     }
-
-                    sol_amounts.push(sol);
-
-                    if let Some(bt) = ev.block_time {
-                        first_tx = first_tx.min(bt);
-                        last_tx = last_tx.max(bt);
-                        // bt is in UTC epoch seconds.
-                        let hour = ((bt.rem_euclid(86_400)) / 3600) as i32;
-                        *hour_counts.entry(hour).or_insert(0) += 1;
-                    }
-                }
-
-                if sol_amounts.is_empty() {
-                    return Ok(None);
-                }
-
-                let total_transfers = sol_amounts.len() as u64;
-                let sum: f64 = sol_amounts.iter().sum();
-                let avg_sol = sum / total_transfers as f64;
-
-                sol_amounts.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-                let median_sol = if sol_amounts.len() % 2 == 1 {
-                    sol_amounts[sol_amounts.len() / 2]
-                } else {
-                    let hi = sol_amounts.len() / 2;
-                    let lo = hi - 1;
-                    (sol_amounts[lo] + sol_amounts[hi]) / 2.0
-                };
-
-                let most_active_hour_utc = {
-                    let mut best: Option<(i32, u64)> = None;
-                    for (hour, cnt) in hour_counts {
-                        match best {
-                            None => best = Some((hour, cnt)),
-                            Some((best_hour, best_cnt)) => {
-                                if cnt > best_cnt || (cnt == best_cnt && hour < best_hour) {
-                                    best = Some((hour, cnt));
-                                }
-                            }
+                    if let Some(to) = ev.to_wallet.as_deref() {
+                        if ev.from_wallet.as_deref() != Some(to) {
+                            by_wallet.entry(to).or_default().push(ProfileSample {
+                                sol,
+                                block_time: ev.block_time,
+                                outbound: false,
+                                counterparty: ev.from_wallet.as_deref(),
+                            });
                         }
                     }
-                    best.map(|(h, _)| h)
-                };
-
-                let first_tx_epoch = if first_tx == i64::MAX {
-                    0
-                } else {
-                    first_tx.max(0) as u64
-                };
-                let last_tx_epoch = last_tx.max(0) as u64;
-
-                let days_active = if last_tx > first_tx && first_tx > 0 {
-                    ((last_tx - first_tx) / 86_400).max(1) as u32
-                } else {
-                    1
-                };
-                let avg_tx_per_day = total_transfers as f64 / days_active as f64;
+                }
 
-                Ok(Some(BehavioralProfile {
-                    wallet: wallet.to_string(),
-                    total_transfers,
-                    avg_sol_per_tx: avg_sol,
-                    median_sol_per_tx: median_sol,
-                    total_days_active: days_active,
-                    avg_tx_per_day,
-                    most_active_hour_utc,
-                    first_tx_epoch,
-                    last_tx_epoch,
-                }))
+                let mut out: Vec<BehavioralProfile> = by_wallet
+                    .into_iter()
+                    .filter_map(|(wallet, samples)| {
+                        behavioral_profile_from_samples(wallet, samples)
+                    })
+                    .collect();
+                out.sort_by(|a, b| {
+                    b.total_transfers
+                        .cmp(&a.total_transfers)
+                        .then_with(|| a.wallet.cmp(&b.wallet))
+                });
+                out.truncate(limit as usize);
+                Ok(out)
             }
         }
     }
 
     /// Detect temporal overlap between two wallets (synchronized activity).
+    ///
+    /// `same_block_count` is the number of distinct wallet_a signatures that land in
+    /// a slot with a different wallet_b signature, so a busy slot counts each of
+    /// wallet_a's transactions once however many events or partners it has. Only
+    /// transfers each wallet sends count, as those it must have signed: a third
+    /// party paying both in one block is not their own activity. A transaction
+    /// touching both wallets does not pair with itself, and a wallet compared with
+    /// itself has no same-block activity.
     pub async fn get_temporal_overlap(
         &self,
         wallet_a: &str,
@@ -1226,7 +4232,8 @@ impl DatabaseManager {
         let window_secs = (time_window_minutes.clamp(1, 60) * 60) as i64;
 
         match &self.inner {
-            DatabaseInner::Postgres { client } => {
+            DatabaseInner::Postgres { pool } => {
+                let client = checkout(pool).await?;
                 let row = client
                     .query_one(
                         "WITH a_times AS (
@@ -1254,10 +4261,11 @@ impl DatabaseManager {
                             INNER JOIN transfer_events b
                                 ON a.slot = b.slot
                                 AND a.signature != b.signature
-                            WHERE (a.from_wallet = $1 OR a.to_wallet = $1)
-                              AND (b.from_wallet = $2 OR b.to_wallet = $2)
+                            WHERE a.from_wallet = $1
+                              AND b.from_wallet = $2
                               AND a.block_time >= $3
                               AND b.block_time >= $3
+                              AND $1 <> $2
                         )
                         SELECT
                             COALESCE(o.overlap_count, 0)::INTEGER AS overlapping_minutes,
@@ -1316,10 +4324,14 @@ impl DatabaseManager {
 
                     if involved_a {
                         a_times.insert(bt / window_secs);
-                        a_events.push(ev);
                     }
                     if involved_b {
                         b_times.insert(bt / window_secs);
+                    }
+                    if ev.from_wallet.as_deref() == Some(wallet_a) {
+                        a_events.push(ev);
+                    }
+                    if ev.from_wallet.as_deref() == Some(wallet_b) {
                         b_events.push(ev);
                     }
                 }
@@ -1332,36 +4344,297 @@ impl DatabaseManager {
                     0.0
                 };
 
-                let mut slot_to_b_sigs: HashMap<i64, HashSet<String>> = HashMap::new();
-                for ev in b_events.iter() {
-                    slot_to_b_sigs
-                        .entry(ev.slot)
-                        .or_default()
-                        .insert(ev.signature.clone());
-                }
-
-                let mut same_sigs: HashSet<String> = HashSet::new();
-                for ev in a_events.iter() {
-                    let Some(b_sigs) = slot_to_b_sigs.get(&ev.slot) else {
-                        continue;
-                    };
-                    // Any signature in that slot that isn't this one?
-                    if b_sigs.iter().any(|sig| sig != &ev.signature) {
-                        same_sigs.insert(ev.signature.clone());
-                    }
-                }
+                let same_block_count = if wallet_a == wallet_b {
+                    0
+                } else {
+                    same_block_signatures(&a_events, &b_events).len() as u32
+                };
 
                 Ok(TemporalOverlap {
                     overlapping_minutes: overlap_count,
                     total_minutes_checked: total_minutes,
                     overlap_ratio,
-                    same_block_count: same_sigs.len() as u32,
+                    same_block_count,
                 })
             }
         }
     }
 }
 
+/// Signatures in `a_events` that share a slot with a different signature in
+/// `b_events`; the memory counterpart of the `same_block` CTE.
+fn same_block_signatures<'a>(
+    a_events: &[&'a TransferEvent],
+    b_events: &[&TransferEvent],
+) -> HashSet<&'a str> {
+    let mut slot_to_b_sigs: HashMap<i64, HashSet<&str>> = HashMap::new();
+    for ev in b_events {
+        slot_to_b_sigs
+            .entry(ev.slot)
+            .or_default()
+            .insert(ev.signature.as_str());
+    }
+
+    a_events
+        .iter()
+        .filter(|ev| {
+            slot_to_b_sigs
+                .get(&ev.slot)
+                .is_some_and(|b_sigs| b_sigs.iter().any(|sig| *sig != ev.signature))
+        })
+        .map(|ev| ev.signature.as_str())
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum SwapFilter<'a> {
+    Wallet(&'a str),
+    Mint(&'a str),
+}
+
+/// Build a profile from aggregate columns starting at `offset`:
+/// total_transfers, avg_sol, median_sol, most_active_hour (-1 if none), first_tx, last_tx.
+fn behavioral_profile_from_row(
+    wallet: &str,
+    row: &Row,
+    offset: usize,
+) -> Option<BehavioralProfile> {
+    let total_transfers: i64 = row.get(offset);
+    if total_transfers <= 0 {
+        return None;
+    }
+
+    let most_active_hour: i32 = row.get(offset + 3);
+    let first_tx: i64 = row.get(offset + 4);
+    let last_tx: i64 = row.get(offset + 5);
+
+    let inbound_count = row.get::<_, i64>(offset + 6) as u64;
+    let outbound_count = row.get::<_, i64>(offset + 7) as u64;
+
+    let days_active = days_active(first_tx, last_tx);
+    Some(BehavioralProfile {
+        wallet: wallet.to_string(),
+        total_transfers: total_transfers as u64,
+        avg_sol_per_tx: row.get(offset + 1),
+        median_sol_per_tx: row.get(offset + 2),
+        total_days_active: days_active,
+        avg_tx_per_day: total_transfers as f64 / days_active as f64,
+        most_active_hour_utc: if most_active_hour >= 0 {
+            Some(most_active_hour)
+        } else {
+            None
+        },
+        first_tx_epoch: first_tx as u64,
+        last_tx_epoch: last_tx as u64,
+        inbound_count,
+        outbound_count,
+        inbound_outbound_ratio: inbound_outbound_ratio(inbound_count, outbound_count),
+        unique_counterparties: row.get::<_, i64>(offset + 8) as u64,
+    })
+}
+
+fn inbound_outbound_ratio(inbound: u64, outbound: u64) -> f64 {
+    inbound as f64 / outbound.max(1) as f64
+}
+
+/// One SOL transfer seen from the profiled wallet's side.
+struct ProfileSample<'a> {
+    sol: f64,
+    block_time: Option<i64>,
+    outbound: bool,
+    counterparty: Option<&'a str>,
+}
+
+/// Build a profile from transfer samples; non-positive amounts are ignored.
+fn behavioral_profile_from_samples(
+    wallet: &str,
+    samples: Vec<ProfileSample>,
+) -> Option<BehavioralProfile> {
+    let mut sol_amounts: Vec<f64> = Vec::new();
+    let mut hour_counts: HashMap<i32, u64> = HashMap::new();
+    let mut first_tx: i64 = i64::MAX;
+    let mut last_tx: i64 = 0;
+    let mut outbound_count: u64 = 0;
+    let mut counterparties: HashSet<&str> = HashSet::new();
+
+    for ProfileSample {
+        sol,
+        block_time,
+        outbound,
+        counterparty,
+    } in samples
+    {
+        if sol <= 0.0 {
+            continue;
+        }
+        sol_amounts.push(sol);
+        if outbound {
+            outbound_count += 1;
+        }
+        if let Some(other) = counterparty.filter(|c| *c != wallet) {
+            counterparties.insert(other);
+        }
+
+        if let Some(bt) = block_time {
+            first_tx = first_tx.min(bt);
+            last_tx = last_tx.max(bt);
+            // bt is in UTC epoch seconds.
+            let hour = ((bt.rem_euclid(86_400)) / 3600) as i32;
+            *hour_counts.entry(hour).or_insert(0) += 1;
+        }
+    }
+
+    if sol_amounts.is_empty() {
+        return None;
+    }
+
+    let total_transfers = sol_amounts.len() as u64;
+    let sum: f64 = sol_amounts.iter().sum();
+    let avg_sol = sum / total_transfers as f64;
+
+    sol_amounts.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let median_sol = if sol_amounts.len() % 2 == 1 {
+        sol_amounts[sol_amounts.len() / 2]
+    } else {
+        let hi = sol_amounts.len() / 2;
+        let lo = hi - 1;
+        (sol_amounts[lo] + sol_amounts[hi]) / 2.0
+    };
+
+    let most_active_hour_utc = {
+        let mut best: Option<(i32, u64)> = None;
+        for (hour, cnt) in hour_counts {
+            match best {
+                None => best = Some((hour, cnt)),
+                Some((best_hour, best_cnt)) => {
+                    if cnt > best_cnt || (cnt == best_cnt && hour < best_hour) {
+                        best = Some((hour, cnt));
+                    }
+                }
+            }
+        }
+        best.map(|(h, _)| h)
+    };
+
+    let first_tx_epoch = if first_tx == i64::MAX {
+        0
+    } else {
+        first_tx.max(0) as u64
+    };
+    let last_tx_epoch = last_tx.max(0) as u64;
+
+    let days_active = days_active(first_tx, last_tx);
+    let avg_tx_per_day = total_transfers as f64 / days_active as f64;
+    let inbound_count = total_transfers - outbound_count;
+
+    Some(BehavioralProfile {
+        wallet: wallet.to_string(),
+        total_transfers,
+        avg_sol_per_tx: avg_sol,
+        median_sol_per_tx: median_sol,
+        total_days_active: days_active,
+        avg_tx_per_day,
+        most_active_hour_utc,
+        first_tx_epoch,
+        last_tx_epoch,
+        inbound_count,
+        outbound_count,
+        inbound_outbound_ratio: inbound_outbound_ratio(inbound_count, outbound_count),
+        unique_counterparties: counterparties.len() as u64,
+    })
+}
+
+fn days_active(first_tx: i64, last_tx: i64) -> u32 {
+    if last_tx > first_tx && first_tx > 0 {
+        ((last_tx - first_tx) / 86_400).max(1) as u32
+    } else {
+        1
+    }
+}
+
+fn swap_event_from_row(row: &Row) -> SwapEvent {
+    SwapEvent {
+        signature: row.get::<_, String>(0),
+        event_index: row.get::<_, i32>(1),
+        slot: row.get::<_, i64>(2) as u64,
+        block_time: row.get::<_, Option<i64>>(3).map(|t| t as u64),
+        wallet: row.get::<_, String>(4),
+        dex_name: row.get::<_, String>(5),
+        program_id: row.get::<_, Option<String>>(6),
+        pool_address: row.get::<_, Option<String>>(7),
+        token_in_mint: row.get::<_, String>(8),
+        amount_in: row.get::<_, f64>(9),
+        token_out_mint: row.get::<_, String>(10),
+        amount_out: row.get::<_, f64>(11),
+    }
+}
+
+/// A wallet watched by a Telegram chat for large transfers.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WatchEntry {
+    pub chat_id: i64,
+    pub wallet: String,
+    /// Smallest SOL transfer worth an alert.
+    pub min_sol: f64,
+    /// Newest signature already seen; alerts cover only later ones.
+    pub last_signature: Option<String>,
+    pub created_at: u64,
+}
+
+/// A signature whose ingestion failed, kept for retry.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct IngestionFailure {
+    pub signature: String,
+    /// "parse" or "persist".
+    pub stage: String,
+    /// Last error message.
+    pub error: String,
+    pub attempts: u32,
+    pub first_failed_at: u64,
+    pub last_failed_at: u64,
+}
+
+impl IngestionFailure {
+    /// When the failure is next due for a retry: one minute after the last
+    /// failure, doubling per attempt, at most six hours.
+    pub fn retry_at(&self) -> u64 {
+        let backoff = (RETRY_BACKOFF_BASE_SECS << self.attempts.saturating_sub(1).min(16))
+            .min(RETRY_BACKOFF_MAX_SECS);
+        self.last_failed_at.saturating_add(backoff)
+    }
+}
+
+/// Stored ingestion failures by retry state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FailureCounts {
+    pub due: u64,
+    /// Still backing off.
+    pub waiting: u64,
+    /// Out of attempts; kept for inspection only.
+    pub exhausted: u64,
+}
+
+/// An investigator's label on a wallet (e.g. "exchange", "cashout").
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WalletLabel {
+    pub wallet: String,
+    pub label: String,
+    pub note: Option<String>,
+    /// Who set it, as given by the client.
+    pub author: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// A wallet's most recently computed risk score.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WalletRiskScore {
+    pub wallet: String,
+    pub score: f64,
+    pub computed_at: u64,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct WalletConnection {
     pub from_wallet: String,
@@ -1373,6 +4646,74 @@ pub struct WalletConnection {
     pub last_seen_epoch: u64,
 }
 
+/// One page of `get_wallet_transfers`.
+#[derive(Debug, serde::Serialize)]
+pub struct TransferPage {
+    pub transfers: Vec<TransferEvent>,
+    /// Cursor for the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Position after which `get_wallet_transfers` resumes. Clients only see it as an
+/// opaque base58 string.
+#[derive(Debug, Clone, PartialEq)]
+struct TransferCursor {
+    block_time: i64,
+    signature: String,
+    event_index: i32,
+}
+
+impl TransferCursor {
+    fn after(ev: &TransferEvent) -> Self {
+        TransferCursor {
+            block_time: ev.block_time.unwrap_or(0),
+            signature: ev.signature.clone(),
+            event_index: ev.event_index,
+        }
+    }
+
+    /// Sort key matching the Postgres `ORDER BY`.
+    fn key(ev: &TransferEvent) -> (i64, &str, i32) {
+        (ev.block_time.unwrap_or(0), &ev.signature, ev.event_index)
+    }
+
+    fn is_before(&self, ev: &TransferEvent) -> bool {
+        (self.block_time, self.signature.as_str(), self.event_index) < Self::key(ev)
+    }
+
+    fn encode(&self) -> String {
+        bs58::encode(format!(
+            "{}:{}:{}",
+            self.block_time, self.event_index, self.signature
+        ))
+        .into_string()
+    }
+
+    fn decode(cursor: &str) -> BeastResult<Self> {
+        let invalid = || BeastError::ParseError(format!("invalid transfer cursor: {}", cursor));
+        let raw = bs58::decode(cursor).into_vec().map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let mut parts = raw.splitn(3, ':');
+        let (Some(time), Some(index), Some(signature)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(TransferCursor {
+            block_time: time.parse().map_err(|_| invalid())?,
+            event_index: index.parse().map_err(|_| invalid())?,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// One page of wallet connections.
+#[derive(Debug, serde::Serialize)]
+pub struct ConnectionPage {
+    pub connections: Vec<WalletConnection>,
+    /// Whether connections exist beyond this page.
+    pub has_more: bool,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SharedWalletSignal {
     pub wallet: String,
@@ -1380,15 +4721,124 @@ pub struct SharedWalletSignal {
     pub last_seen_epoch: u64,
 }
 
+/// Postgres pool usage, as reported by `/health/deep`.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct PoolStatus {
+    pub max_size: usize,
+    /// Connections currently open.
+    pub open: usize,
+    /// Open connections not checked out.
+    pub idle: usize,
+    /// Callers waiting for a connection.
+    pub waiting: usize,
+}
+
+/// A token mint two wallets both moved.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SharedMint {
+    pub mint: String,
+    pub count_a: u64,
+    pub count_b: u64,
+    pub last_seen_epoch: u64,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct WalletVolumeSignal {
     pub wallet: String,
     pub count: u64,
     pub total_sol: f64,
+    /// UI amounts summed across all mints; only meaningful as a tie-breaker.
+    /// `WalletMintVolumes` keeps them per mint.
     pub total_token_ui: f64,
     pub last_seen_epoch: u64,
 }
 
+/// Volume sent to one recipient, with token UI amounts per mint.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WalletMintVolumes {
+    pub wallet: String,
+    pub count: u64,
+    pub total_sol: f64,
+    /// Mint -> total UI amount (raw amount scaled by the mint's decimals).
+    pub token_ui_by_mint: BTreeMap<String, f64>,
+    /// Largest share (0-1) of the sender's outbound volume in one asset - SOL or
+    /// a mint - that went to this recipient.
+    pub top_asset_share: f64,
+    pub last_seen_epoch: u64,
+}
+
+/// Lifetime overview of a wallet's stored transfers.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WalletSummary {
+    pub wallet: String,
+    /// Earliest and latest transfer block times (0 if none is timed).
+    pub first_seen_epoch: u64,
+    pub last_seen_epoch: u64,
+    /// Distinct transactions with a transfer to or from the wallet.
+    pub transaction_count: u64,
+    pub transfer_count: u64,
+    pub inbound_sol: f64,
+    pub outbound_sol: f64,
+    /// Mint -> total UI amount received / sent.
+    pub inbound_token_ui_by_mint: BTreeMap<String, f64>,
+    pub outbound_token_ui_by_mint: BTreeMap<String, f64>,
+    pub unique_counterparties: u64,
+}
+
+/// Flows between a wallet and one counterparty; `net_*` is inbound minus outbound.
+/// Token amounts are per mint: UI amounts of different mints don't add up.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CounterpartyNetFlow {
+    pub counterparty: String,
+    pub transfer_count: u64,
+    pub inbound_sol: f64,
+    pub outbound_sol: f64,
+    pub net_sol: f64,
+    /// Mint -> total UI amount received from / sent to the counterparty, and net.
+    pub inbound_token_ui_by_mint: BTreeMap<String, f64>,
+    pub outbound_token_ui_by_mint: BTreeMap<String, f64>,
+    pub net_token_ui_by_mint: BTreeMap<String, f64>,
+}
+
+impl CounterpartyNetFlow {
+    fn new(
+        counterparty: String,
+        transfer_count: u64,
+        sol: (f64, f64),
+        tokens: BTreeMap<String, (f64, f64)>,
+    ) -> Self {
+        let mut flow = CounterpartyNetFlow {
+            counterparty,
+            transfer_count,
+            inbound_sol: sol.0,
+            outbound_sol: sol.1,
+            net_sol: sol.0 - sol.1,
+            inbound_token_ui_by_mint: BTreeMap::new(),
+            outbound_token_ui_by_mint: BTreeMap::new(),
+            net_token_ui_by_mint: BTreeMap::new(),
+        };
+        for (mint, (inbound, outbound)) in tokens {
+            flow.add_token_flow(mint, inbound, outbound);
+        }
+        flow
+    }
+
+    fn add_token_flow(&mut self, mint: String, inbound: f64, outbound: f64) {
+        self.inbound_token_ui_by_mint.insert(mint.clone(), inbound);
+        self.outbound_token_ui_by_mint
+            .insert(mint.clone(), outbound);
+        self.net_token_ui_by_mint.insert(mint, inbound - outbound);
+    }
+
+    /// Largest absolute net over the mints (0 without token flows), for ranking.
+    fn largest_token_net(&self) -> f64 {
+        self.net_token_ui_by_mint
+            .values()
+            .map(|net| net.abs())
+            .fold(0.0, f64::max)
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct BehavioralProfile {
     pub wallet: String,
@@ -1400,6 +4850,63 @@ pub struct BehavioralProfile {
     pub most_active_hour_utc: Option<i32>,
     pub first_tx_epoch: u64,
     pub last_tx_epoch: u64,
+    /// Transfers the wallet received.
+    pub inbound_count: u64,
+    /// Transfers the wallet sent (self-transfers count here).
+    pub outbound_count: u64,
+    /// `inbound_count / outbound_count`, taking no outbound transfers as one.
+    pub inbound_outbound_ratio: f64,
+    /// Distinct wallets on the other side of its transfers.
+    pub unique_counterparties: u64,
+}
+
+/// Whether `store_transaction` wrote a new row or replaced an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoredOutcome {
+    Inserted,
+    Updated,
+}
+
+impl StoredOutcome {
+    fn from_inserted(inserted: bool) -> Self {
+        if inserted {
+            StoredOutcome::Inserted
+        } else {
+            StoredOutcome::Updated
+        }
+    }
+}
+
+/// Outcome of one `backfill_block_times` run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct BlockTimeBackfill {
+    pub slots_checked: usize,
+    pub slots_filled: usize,
+    /// Slots with no block time from the RPC (skipped or failed lookups).
+    pub slots_unavailable: usize,
+    pub events_updated: u64,
+}
+
+/// Outbound SOL transfers sharing one rounded amount.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AmountBucket {
+    /// Amount rounded to 0.01 SOL.
+    pub amount_sol: f64,
+    pub count: u64,
+    pub total_sol: f64,
+    /// Block times of the bucket's transfers, ascending (untimed ones omitted).
+    pub block_times: Vec<u64>,
+}
+
+/// Transfer activity within one time bucket.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ActivityBucket {
+    /// Unix timestamp the bucket starts at.
+    pub bucket_start: u64,
+    pub transfer_count: u64,
+    pub sol_volume: f64,
+    pub token_volume: f64,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -1445,4 +4952,1431 @@ impl TransferEvent {
             token_decimals: row.get::<_, Option<i32>>(12),
         }
     }
+
+    /// Token amount in UI units: the stored UI amount, or the raw amount scaled by
+    /// the decimals when only those were recorded.
+    pub fn token_ui_amount(&self) -> Option<f64> {
+        self.token_amount_ui.or_else(|| {
+            let (raw, decimals) = (self.token_amount?, self.token_decimals?);
+            Some(delta_to_ui(raw.into(), u8::try_from(decimals).ok()?))
+        })
+    }
+
+    /// The event stored for a SOL transfer of `tx`.
+    pub fn from_sol_transfer(
+        tx: &EnhancedTransaction,
+        transfer: &SolTransfer,
+        event_index: i32,
+    ) -> Self {
+        TransferEvent {
+            signature: tx.signature.clone(),
+            event_index,
+            slot: tx.slot as i64,
+            block_time: tx.block_time.map(|t| t as i64),
+            kind: "sol".to_string(),
+            transfer_type: transfer.transfer_type.clone(),
+            from_wallet: Some(transfer.from.clone()),
+            to_wallet: Some(transfer.to.clone()),
+            mint: None,
+            amount_sol: Some(transfer.amount_sol),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(signature: &str, wallet: &str, block_time: u64, out_mint: &str) -> SwapEvent {
+        SwapEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot: block_time,
+            block_time: Some(block_time),
+            wallet: wallet.to_string(),
+            dex_name: "Raydium V4".to_string(),
+            program_id: None,
+            pool_address: None,
+            token_in_mint: crate::dex::types::WSOL_MINT.to_string(),
+            amount_in: 1.0,
+            token_out_mint: out_mint.to_string(),
+            amount_out: 100.0,
+        }
+    }
+
+    /// Stored SOL transfer in slot 1; override other fields with `..`.
+    fn sol_event(
+        signature: &str,
+        from: &str,
+        to: &str,
+        amount_sol: f64,
+        block_time: Option<i64>,
+    ) -> TransferEvent {
+        TransferEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot: 1,
+            block_time,
+            kind: "sol".to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: None,
+            amount_sol: Some(amount_sol),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        }
+    }
+
+    /// Insert events straight into the in-memory backend, as if ingested.
+    async fn seed(db: &DatabaseManager, events: impl IntoIterator<Item = TransferEvent>) {
+        let DatabaseInner::Memory { state } = &db.inner else {
+            panic!("seed needs the in-memory backend");
+        };
+        let mut mem = state.write().await;
+        for ev in events {
+            mem.transfer_events
+                .insert((ev.signature.clone(), ev.event_index), ev);
+        }
+    }
+
+    /// Stored token transfer of `ui` units of `mint` in slot 1.
+    fn token_event(
+        signature: &str,
+        from: &str,
+        to: &str,
+        mint: &str,
+        ui: Option<f64>,
+        block_time: Option<i64>,
+    ) -> TransferEvent {
+        TransferEvent {
+            kind: "token".to_string(),
+            mint: Some(mint.to_string()),
+            amount_sol: None,
+            token_amount_ui: ui,
+            ..sol_event(signature, from, to, 0.0, block_time)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_memory_backend() {
+        let db = DatabaseManager::new("MEMORY").await.unwrap();
+        db.ping().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_swap_events_are_idempotent() {
+        let db = DatabaseManager::in_memory();
+        let s = swap("sig1", "WALLET", 1_000, "MINT_A");
+
+        db.store_swap_event(&s).await.unwrap();
+        db.store_swap_event(&s).await.unwrap();
+
+        let swaps = db.get_swaps_for_wallet("WALLET", None, 10).await.unwrap();
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0], s);
+    }
+
+    #[tokio::test]
+    async fn test_swap_queries_filter_by_wallet_mint_and_time() {
+        let db = DatabaseManager::in_memory();
+        db.store_swap_event(&swap("sig1", "WALLET", 1_000, "MINT_A"))
+            .await
+            .unwrap();
+        db.store_swap_event(&swap("sig2", "WALLET", 2_000, "MINT_B"))
+            .await
+            .unwrap();
+        db.store_swap_event(&swap("sig3", "OTHER", 3_000, "MINT_A"))
+            .await
+            .unwrap();
+
+        let by_wallet = db.get_swaps_for_wallet("WALLET", None, 10).await.unwrap();
+        let sigs: Vec<&str> = by_wallet.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(sigs, vec!["sig2", "sig1"]);
+
+        let recent = db
+            .get_swaps_for_wallet("WALLET", Some(1_500), 10)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+
+        let by_mint = db.get_swaps_for_mint("MINT_A", None, 10).await.unwrap();
+        let sigs: Vec<&str> = by_mint.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(sigs, vec!["sig3", "sig1"]);
+    }
+
+    #[tokio::test]
+    async fn test_nft_trades_match_buyer_or_seller() {
+        let db = DatabaseManager::in_memory();
+        let trade = |signature: &str, seller: &str, buyer: &str, block_time: u64| NftTrade {
+            signature: signature.to_string(),
+            slot: block_time,
+            block_time: Some(block_time),
+            marketplace: "Magic Eden".to_string(),
+            program_id: "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K".to_string(),
+            mint: format!("NFT_{}", signature),
+            seller: seller.to_string(),
+            buyer: buyer.to_string(),
+            price_sol: 2.5,
+        };
+        let sold = trade("sig1", "WALLET", "OTHER", 1_000);
+        db.store_nft_trade(&sold).await.unwrap();
+        db.store_nft_trade(&sold).await.unwrap();
+        db.store_nft_trade(&trade("sig2", "OTHER", "WALLET", 2_000))
+            .await
+            .unwrap();
+        db.store_nft_trade(&trade("sig3", "OTHER", "THIRD", 3_000))
+            .await
+            .unwrap();
+
+        let trades = db
+            .get_nft_trades_for_wallet("WALLET", None, 10)
+            .await
+            .unwrap();
+        let sigs: Vec<&str> = trades.iter().map(|t| t.signature.as_str()).collect();
+        assert_eq!(sigs, vec!["sig2", "sig1"]);
+        assert_eq!(trades[1], sold);
+    }
+
+    #[tokio::test]
+    async fn test_wallet_transfer_filters_by_kind_and_tx_type() {
+        use crate::core::enhanced_parser::{EnhancedTransactionParser, TransactionType};
+
+        let db = DatabaseManager::in_memory();
+        let parser = EnhancedTransactionParser::new();
+        let raw = |instruction: serde_json::Value| {
+            serde_json::json!({
+                "slot": 1u64,
+                "blockTime": 1_000u64,
+                "meta": {
+                    "err": null, "fee": 0,
+                    "preBalances": [], "postBalances": [],
+                    "preTokenBalances": [
+                        { "accountIndex": 0, "mint": "MINT", "owner": "WALLET",
+                          "uiTokenAmount": { "amount": "10", "decimals": 0 } },
+                        { "accountIndex": 1, "mint": "MINT", "owner": "OTHER",
+                          "uiTokenAmount": { "amount": "0", "decimals": 0 } }
+                    ]
+                },
+                "transaction": { "message": {
+                    "accountKeys": ["WalletAta", "OtherAta"],
+                    "instructions": [instruction]
+                } }
+            })
+        };
+        let sol_tx = parser
+            .parse(
+                &raw(serde_json::json!({
+                    "programId": "11111111111111111111111111111111",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "WALLET", "destination": "OTHER", "lamports": 1_000_000_000u64
+                    } }
+                })),
+                "sol_sig".to_string(),
+            )
+            .unwrap();
+        let token_tx = parser
+            .parse(
+                &raw(serde_json::json!({
+                    "programId": "TokenkegQfeZyiNwAJbPVwwQQfKP3zHqy5RaCZ1NsqKFP",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "WalletAta", "destination": "OtherAta",
+                        "authority": "WALLET", "amount": "5"
+                    } }
+                })),
+                "token_sig".to_string(),
+            )
+            .unwrap();
+        assert_eq!(sol_tx.tx_type, TransactionType::SystemTransfer);
+        assert_eq!(token_tx.tx_type, TransactionType::TokenTransfer);
+        for tx in [&sol_tx, &token_tx] {
+            db.store_transaction(tx).await.unwrap();
+        }
+        db.store_sol_transfer_event(&sol_tx, &sol_tx.sol_transfers[0], 0)
+            .await
+            .unwrap();
+        db.store_token_transfer_event(&token_tx, &token_tx.token_transfers[0], 0)
+            .await
+            .unwrap();
+
+        let all = db
+            .get_transfer_events_for_wallet("WALLET", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let sol_only = db
+            .get_transfer_events_for_wallet("WALLET", Some("sol"), None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(sol_only.len(), 1);
+        assert_eq!(sol_only[0].signature, "sol_sig");
+
+        let token_txs = db
+            .get_transfer_events_for_wallet("WALLET", None, Some("TokenTransfer"), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(token_txs.len(), 1);
+        assert_eq!(token_txs[0].kind, "token");
+
+        let types = db
+            .get_transaction_types(&["sol_sig".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types["sol_sig"], "SystemTransfer");
+    }
+
+    #[tokio::test]
+    async fn test_wallet_connections_page_through_hub() {
+        let db = DatabaseManager::in_memory();
+        for i in 0..150 {
+            let counterparty = format!("CP{:03}", i);
+            // Vary counts so ordering is by count first, then counterparty.
+            for _ in 0..(i % 3 + 1) {
+                db.store_wallet_relationship("HUB", &counterparty, 1.0, 0)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut offset = 0;
+        let mut pages = 0;
+        loop {
+            let page = db
+                .get_wallet_connections_page("HUB", offset, 40)
+                .await
+                .unwrap();
+            pages += 1;
+            for c in &page.connections {
+                assert!(
+                    seen.insert(c.to_wallet.clone()),
+                    "duplicate {}",
+                    c.to_wallet
+                );
+            }
+            offset += page.connections.len();
+            if !page.has_more {
+                assert_eq!(page.connections.len(), 30);
+                break;
+            }
+            assert_eq!(page.connections.len(), 40);
+        }
+
+        assert_eq!(pages, 4);
+        assert_eq!(seen.len(), 150);
+        let first = db.get_wallet_connections("HUB").await.unwrap();
+        assert_eq!(first.len(), 100);
+        assert_eq!(first[0].transaction_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_fills_null_block_times_once_rpc_has_them() {
+        use crate::core::rpc_client::tests::mock_endpoint;
+
+        let db = DatabaseManager::in_memory();
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let ev = TransferEvent {
+                signature: "sig_untimed".to_string(),
+                event_index: 0,
+                slot: 4_242,
+                block_time: None,
+                kind: "sol".to_string(),
+                transfer_type: "system".to_string(),
+                from_wallet: Some("A".to_string()),
+                to_wallet: Some("B".to_string()),
+                mint: None,
+                amount_sol: Some(2.0),
+                token_amount_ui: None,
+                token_amount: None,
+                token_decimals: None,
+            };
+            state
+                .write()
+                .await
+                .transfer_events
+                .insert((ev.signature.clone(), ev.event_index), ev);
+        }
+        assert_eq!(db.get_slots_missing_block_time(10).await.unwrap(), [4_242]);
+
+        let (endpoint, requests) = mock_endpoint(vec![
+            (200, r#"{"jsonrpc":"2.0","id":1,"result":null}"#.to_string()),
+            (
+                200,
+                r#"{"jsonrpc":"2.0","id":1,"result":1700000123}"#.to_string(),
+            ),
+        ])
+        .await;
+        let rpc = SolanaRpcClient::new(endpoint);
+
+        // No time yet: the slot stays pending.
+        let first = db.backfill_block_times(&rpc, 10).await.unwrap();
+        assert_eq!((first.slots_checked, first.slots_unavailable), (1, 1));
+        assert_eq!(first.events_updated, 0);
+
+        let second = db.backfill_block_times(&rpc, 10).await.unwrap();
+        assert_eq!((second.slots_filled, second.events_updated), (1, 1));
+        assert!(requests.lock().await[1].contains("getBlockTime"));
+
+        let events = db
+            .get_transfer_events_for_wallet("A", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events[0].block_time, Some(1_700_000_123));
+        assert!(db
+            .get_slots_missing_block_time(10)
+            .await
+            .unwrap()
+            .is_empty());
+        let third = db.backfill_block_times(&rpc, 10).await.unwrap();
+        assert_eq!(third, BlockTimeBackfill::default());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_moves_past_slots_without_a_block_time() {
+        use crate::core::rpc_client::tests::mock_endpoint;
+
+        let db = DatabaseManager::in_memory();
+        seed(
+            &db,
+            [1u64, 2, 3].map(|slot| TransferEvent {
+                slot: slot as i64,
+                ..sol_event(&format!("sig_{}", slot), "A", "B", 1.0, None)
+            }),
+        )
+        .await;
+        let null = r#"{"jsonrpc":"2.0","id":1,"result":null}"#.to_string();
+        let pruned =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32004,"message":"Block not available"}}"#
+                .to_string();
+        let (endpoint, _) = mock_endpoint(vec![
+            (200, null),
+            (200, pruned),
+            (
+                200,
+                r#"{"jsonrpc":"2.0","id":1,"result":1700000003}"#.to_string(),
+            ),
+        ])
+        .await;
+        let rpc = SolanaRpcClient::new(endpoint);
+
+        let first = db.backfill_block_times(&rpc, 2).await.unwrap();
+        assert_eq!((first.slots_checked, first.slots_unavailable), (2, 2));
+        // The two misses now queue behind the untried slot.
+        assert_eq!(
+            db.get_slots_missing_block_time(10).await.unwrap(),
+            [3, 1, 2]
+        );
+        let second = db.backfill_block_times(&rpc, 1).await.unwrap();
+        assert_eq!(second.slots_filled, 1);
+
+        for _ in 1..MAX_BLOCK_TIME_ATTEMPTS {
+            db.record_block_time_miss(1).await.unwrap();
+        }
+        assert_eq!(db.get_slots_missing_block_time(10).await.unwrap(), [2]);
+    }
+
+    #[tokio::test]
+    async fn test_store_transaction_reports_insert_then_update() {
+        use crate::core::enhanced_parser::EnhancedTransactionParser;
+
+        let db = DatabaseManager::in_memory();
+        let raw = serde_json::json!({
+            "slot": 7u64,
+            "blockTime": 1_000u64,
+            "meta": { "err": null, "fee": 5000, "preBalances": [], "postBalances": [] },
+            "transaction": { "message": { "accountKeys": [], "instructions": [] } }
+        });
+        let tx = EnhancedTransactionParser::new()
+            .parse(&raw, "sig_twice".to_string())
+            .unwrap();
+
+        assert_eq!(
+            db.store_transaction(&tx).await.unwrap(),
+            StoredOutcome::Inserted
+        );
+        assert_eq!(
+            db.store_transaction(&tx).await.unwrap(),
+            StoredOutcome::Updated
+        );
+        let mut other = tx.clone();
+        other.signature = "sig_other".to_string();
+        assert_eq!(
+            db.store_transaction(&other).await.unwrap(),
+            StoredOutcome::Inserted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watchlist_crud() {
+        let db = DatabaseManager::in_memory();
+        assert!(db.add_watch(1, "WALLET_A", 10.0).await.unwrap());
+        assert!(db.add_watch(1, "WALLET_B", 5.0).await.unwrap());
+        assert!(db.add_watch(2, "WALLET_A", 1.0).await.unwrap());
+
+        // Re-adding updates the threshold and keeps the cursor.
+        db.set_watch_cursor(1, "WALLET_A", "sig1").await.unwrap();
+        assert!(!db.add_watch(1, "WALLET_A", 25.0).await.unwrap());
+        let chat1 = db.get_watchlist(1).await.unwrap();
+        assert_eq!(chat1.len(), 2);
+        let a = chat1.iter().find(|w| w.wallet == "WALLET_A").unwrap();
+        assert_eq!(a.min_sol, 25.0);
+        assert_eq!(a.last_signature.as_deref(), Some("sig1"));
+
+        assert_eq!(db.get_all_watches().await.unwrap().len(), 3);
+
+        assert!(db.remove_watch(1, "WALLET_A").await.unwrap());
+        assert!(!db.remove_watch(1, "WALLET_A").await.unwrap());
+        let wallets: Vec<String> = db
+            .get_watchlist(1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|w| w.wallet)
+            .collect();
+        assert_eq!(wallets, vec!["WALLET_B"]);
+        // Other chats are unaffected.
+        assert_eq!(db.get_watchlist(2).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shared_mints_skip_ignored_and_one_sided_mints() {
+        const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let db = DatabaseManager::in_memory();
+        let event = |signature: &str, from: &str, to: &str, mint: &str| TransferEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot: 1,
+            block_time: Some(1_000),
+            kind: "token".to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: Some(mint.to_string()),
+            amount_sol: None,
+            token_amount_ui: Some(1.0),
+            token_amount: None,
+            token_decimals: None,
+        };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                event("a1", "POOL", "A", "OBSCURE"),
+                event("a2", "A", "POOL", "OBSCURE"),
+                event("b1", "POOL", "B", "OBSCURE"),
+                event("a3", "POOL", "A", USDC),
+                event("b2", "POOL", "B", USDC),
+                event("a4", "POOL", "A", "ONLY_A"),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let ignore = vec![USDC.to_string()];
+        let shared = db
+            .get_shared_mints("A", "B", None, &ignore, 10)
+            .await
+            .unwrap();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].mint, "OBSCURE");
+        assert_eq!((shared[0].count_a, shared[0].count_b), (2, 1));
+        assert_eq!(shared[0].last_seen_epoch, 1_000);
+
+        let unfiltered = db.get_shared_mints("A", "B", None, &[], 10).await.unwrap();
+        assert_eq!(unfiltered.len(), 2);
+        assert!(db
+            .get_shared_mints("A", "B", Some(2_000), &[], 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_activity_histogram_buckets_across_day_boundary() {
+        const DAY: i64 = 86_400;
+        let db = DatabaseManager::in_memory();
+        let event =
+            |signature: &str, block_time: i64, amount_sol: Option<f64>, token: Option<f64>| {
+                TransferEvent {
+                    signature: signature.to_string(),
+                    event_index: 0,
+                    slot: block_time,
+                    block_time: Some(block_time),
+                    kind: if amount_sol.is_some() { "sol" } else { "token" }.to_string(),
+                    transfer_type: "transfer".to_string(),
+                    from_wallet: Some("WALLET".to_string()),
+                    to_wallet: Some("OTHER".to_string()),
+                    mint: None,
+                    amount_sol,
+                    token_amount_ui: token,
+                    token_amount: None,
+                    token_decimals: None,
+                }
+            };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                // 23:00 and 23:59 on day 1, 00:00 and 01:30 on day 2.
+                event("a", 20 * DAY - 3_600, Some(1.0), None),
+                event("b", 20 * DAY - 1, Some(2.0), None),
+                event("c", 20 * DAY, Some(4.0), None),
+                event("d", 20 * DAY + 5_400, None, Some(10.0)),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let daily = db
+            .get_activity_histogram("WALLET", DAY as u64, None)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].bucket_start, (19 * DAY) as u64);
+        assert_eq!(daily[0].transfer_count, 2);
+        assert_eq!(daily[0].sol_volume, 3.0);
+        assert_eq!(daily[1].bucket_start, (20 * DAY) as u64);
+        assert_eq!(daily[1].transfer_count, 2);
+        assert_eq!(daily[1].sol_volume, 4.0);
+        assert_eq!(daily[1].token_volume, 10.0);
+
+        let hourly = db
+            .get_activity_histogram("WALLET", 3_600, Some((20 * DAY) as u64))
+            .await
+            .unwrap();
+        let starts: Vec<u64> = hourly.iter().map(|b| b.bucket_start).collect();
+        assert_eq!(starts, vec![(20 * DAY) as u64, (20 * DAY + 3_600) as u64]);
+    }
+
+    #[tokio::test]
+    async fn test_first_inbound_funder_is_earliest_transfer_in() {
+        let db = DatabaseManager::in_memory();
+        let event =
+            |signature: &str, from: &str, to: &str, block_time: Option<i64>| TransferEvent {
+                signature: signature.to_string(),
+                event_index: 0,
+                slot: block_time.unwrap_or(0),
+                block_time,
+                kind: "sol".to_string(),
+                transfer_type: "system".to_string(),
+                from_wallet: Some(from.to_string()),
+                to_wallet: Some(to.to_string()),
+                mint: None,
+                amount_sol: Some(1.0),
+                token_amount_ui: None,
+                token_amount: None,
+                token_decimals: None,
+            };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                event("later", "LATE_FUNDER", "WALLET", Some(2_000)),
+                event("first", "FIRST_FUNDER", "WALLET", Some(1_000)),
+                event("untimed", "UNTIMED", "WALLET", None),
+                event("self", "WALLET", "WALLET", Some(10)),
+                event("outbound", "WALLET", "OTHER", Some(500)),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let funder = db
+            .get_first_inbound_funder("WALLET")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(funder.signature, "first");
+        assert_eq!(funder.from_wallet.as_deref(), Some("FIRST_FUNDER"));
+        assert!(db
+            .get_first_inbound_funder("NOBODY")
+            .await
+            .unwrap()
+            .is_none());
+
+        // First seen counts either side, self-transfers included, untimed ones not.
+        let first_seen = db
+            .get_first_seen_epochs(&[
+                "WALLET".to_string(),
+                "OTHER".to_string(),
+                "UNTIMED".to_string(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(first_seen.get("WALLET"), Some(&10));
+        assert_eq!(first_seen.get("OTHER"), Some(&500));
+        assert!(!first_seen.contains_key("UNTIMED"));
+    }
+
+    #[tokio::test]
+    async fn test_net_flow_signs_and_magnitudes() {
+        let db = DatabaseManager::in_memory();
+        let event =
+            |signature: &str, from: &str, to: &str, sol: Option<f64>, token: Option<f64>| {
+                TransferEvent {
+                    signature: signature.to_string(),
+                    event_index: 0,
+                    slot: 1,
+                    block_time: Some(1_000),
+                    kind: if sol.is_some() { "sol" } else { "token" }.to_string(),
+                    transfer_type: "transfer".to_string(),
+                    from_wallet: Some(from.to_string()),
+                    to_wallet: Some(to.to_string()),
+                    mint: token.map(|_| "MINT".to_string()),
+                    amount_sol: sol,
+                    token_amount_ui: token,
+                    token_amount: None,
+                    token_decimals: None,
+                }
+            };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                // WALLET accumulates 7 SOL net from SOURCE.
+                event("s1", "SOURCE", "WALLET", Some(10.0), None),
+                event("s2", "WALLET", "SOURCE", Some(3.0), None),
+                // WALLET distributes 2 SOL and 50 tokens net to SINK.
+                event("k1", "WALLET", "SINK", Some(2.0), None),
+                event("k2", "WALLET", "SINK", None, Some(80.0)),
+                event("k3", "SINK", "WALLET", None, Some(30.0)),
+                // Unrelated transfers are ignored.
+                event("u1", "SOURCE", "SINK", Some(100.0), None),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let flows = db
+            .get_net_flow_by_counterparty("WALLET", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].counterparty, "SOURCE");
+        assert_eq!(flows[0].transfer_count, 2);
+        assert_eq!(flows[0].inbound_sol, 10.0);
+        assert_eq!(flows[0].outbound_sol, 3.0);
+        assert_eq!(flows[0].net_sol, 7.0);
+        assert_eq!(flows[1].counterparty, "SINK");
+        assert_eq!(flows[1].transfer_count, 3);
+        assert_eq!(flows[1].net_sol, -2.0);
+        assert_eq!(flows[1].net_token_ui_by_mint["MINT"], -50.0);
+    }
+
+    #[tokio::test]
+    async fn test_net_token_flow_is_per_mint() {
+        let db = DatabaseManager::in_memory();
+        seed(
+            &db,
+            [
+                // 1000 BONK in and 1 USDC out must not net to 999.
+                token_event("t1", "PEER", "WALLET", "BONK", Some(1_000.0), Some(1_000)),
+                token_event("t2", "WALLET", "PEER", "USDC", Some(1.0), Some(1_000)),
+                token_event("t3", "PEER", "WALLET", "USDC", Some(0.25), Some(1_000)),
+                // Ranked after PEER: its largest single-mint net is smaller.
+                token_event("t4", "OTHER", "WALLET", "USDC", Some(500.0), Some(1_000)),
+            ],
+        )
+        .await;
+
+        let flows = db
+            .get_net_flow_by_counterparty("WALLET", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(flows.len(), 2);
+        let peer = &flows[0];
+        assert_eq!(peer.counterparty, "PEER");
+        assert_eq!(peer.transfer_count, 3);
+        assert_eq!(peer.net_sol, 0.0);
+        assert_eq!(peer.net_token_ui_by_mint["BONK"], 1_000.0);
+        assert_eq!(peer.net_token_ui_by_mint["USDC"], -0.75);
+        assert_eq!(peer.inbound_token_ui_by_mint["USDC"], 0.25);
+        assert_eq!(peer.outbound_token_ui_by_mint["USDC"], 1.0);
+        assert_eq!(flows[1].counterparty, "OTHER");
+        assert_eq!(flows[1].net_token_ui_by_mint.len(), 1);
+    }
+
+    /// Postgres for the `#[ignore]`d tests: `TEST_DATABASE_URL=... cargo test -- --ignored`.
+    fn test_database_url() -> String {
+        std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a Postgres")
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_small_pool_serves_concurrent_queries() {
+        let url = test_database_url();
+        let db = DatabaseManager::connect(&url, 2).await.unwrap();
+        db.init_schema().await.unwrap();
+
+        let results = futures::future::join_all((0..20).map(|_| db.get_watchlist(42))).await;
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        let status = db.pool_status().unwrap();
+        assert_eq!(status.max_size, 2);
+        assert!(status.open <= 2);
+        assert_eq!(status.waiting, 0);
+        assert!(DatabaseManager::in_memory().pool_status().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_relationships_matches_events() {
+        let db = DatabaseManager::in_memory();
+        let event = |signature: &str, index: i32, from: &str, to: &str, time: i64| TransferEvent {
+            signature: signature.to_string(),
+            event_index: index,
+            slot: time,
+            block_time: Some(time),
+            kind: "sol".to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: None,
+            amount_sol: Some(1.5),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for e in [
+                event("sig1", 0, "A", "B", 1_000),
+                event("sig2", 0, "A", "B", 3_000),
+                event("sig3", 0, "A", "A", 3_000),
+                event("sig4", 0, "C", "D", 500),
+            ] {
+                mem.transfer_events
+                    .insert((e.signature.clone(), e.event_index), e);
+            }
+        }
+        // Drift: A->B double counted, plus an edge with no events behind it.
+        for _ in 0..3 {
+            db.store_wallet_relationship("A", "B", 9.0, 0)
+                .await
+                .unwrap();
+        }
+        db.store_wallet_relationship("X", "Y", 1.0, 0)
+            .await
+            .unwrap();
+        db.store_wallet_relationship("C", "D", 7.0, 0)
+            .await
+            .unwrap();
+
+        // Partial: only pairs active since 2_000 are rebuilt, from all their events.
+        assert_eq!(
+            db.rebuild_relationships_from_events(2_000, false)
+                .await
+                .unwrap(),
+            1
+        );
+        let ab = &db.get_wallet_connections("A").await.unwrap()[0];
+        assert_eq!(ab.transaction_count, 2);
+        assert!((ab.total_sol_transferred - 3.0).abs() < 1e-9);
+        assert_eq!((ab.first_seen_epoch, ab.last_seen_epoch), (1_000, 3_000));
+        assert_eq!(db.get_wallet_connections("X").await.unwrap().len(), 1);
+        assert_eq!(
+            db.get_wallet_connections("C").await.unwrap()[0].total_sol_transferred,
+            7.0
+        );
+
+        // Full: every edge recomputed, orphans dropped, self-transfers kept on request.
+        assert_eq!(
+            db.rebuild_relationships_from_events(0, true).await.unwrap(),
+            3
+        );
+        assert!(db.get_wallet_connections("X").await.unwrap().is_empty());
+        let cd = &db.get_wallet_connections("C").await.unwrap()[0];
+        assert_eq!(cd.transaction_count, 1);
+        assert!((cd.total_sol_transferred - 1.5).abs() < 1e-9);
+        let a_edges = db.get_wallet_connections("A").await.unwrap();
+        assert!(a_edges.iter().any(|c| c.to_wallet == "A"));
+    }
+
+    #[tokio::test]
+    async fn test_wallet_labels_crud() {
+        let db = DatabaseManager::in_memory();
+        assert!(db.get_labels("W1").await.unwrap().is_empty());
+
+        assert_eq!(
+            db.set_label("W1", "exchange", None, Some("alice"))
+                .await
+                .unwrap(),
+            StoredOutcome::Inserted
+        );
+        assert_eq!(
+            db.set_label("W1", "flagged", Some("cashout"), None)
+                .await
+                .unwrap(),
+            StoredOutcome::Inserted
+        );
+        db.set_label("W2", "exchange", None, None).await.unwrap();
+
+        // Multi-valued per wallet.
+        let labels = db.get_labels("W1").await.unwrap();
+        let names: Vec<&str> = labels.iter().map(|l| l.label.as_str()).collect();
+        assert_eq!(names, vec!["exchange", "flagged"]);
+        assert_eq!(labels[0].author.as_deref(), Some("alice"));
+
+        // Re-setting updates the note and keeps the creation time.
+        let created = labels[1].created_at;
+        assert_eq!(
+            db.set_label("W1", "flagged", Some("confirmed cashout"), Some("bob"))
+                .await
+                .unwrap(),
+            StoredOutcome::Updated
+        );
+        let flagged = db.get_labels("W1").await.unwrap().remove(1);
+        assert_eq!(flagged.note.as_deref(), Some("confirmed cashout"));
+        assert_eq!(flagged.author.as_deref(), Some("bob"));
+        assert_eq!(flagged.created_at, created);
+
+        let many = db
+            .get_labels_for_wallets(&["W1".to_string(), "W2".to_string(), "W3".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(many["W1"].len(), 2);
+        assert_eq!(many["W2"].len(), 1);
+        assert!(!many.contains_key("W3"));
+
+        assert!(db.remove_label("W1", "exchange").await.unwrap());
+        assert!(!db.remove_label("W1", "exchange").await.unwrap());
+        assert_eq!(db.get_labels("W1").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ingestion_failures_are_recorded_and_cleared() {
+        let db = DatabaseManager::in_memory();
+        assert_eq!(
+            db.record_ingestion_failure("sig1", "parse", "RPC timeout")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.record_ingestion_failure("sig1", "persist", "db down")
+                .await
+                .unwrap(),
+            2
+        );
+        db.record_ingestion_failure("sig2", "parse", "skipped")
+            .await
+            .unwrap();
+
+        let later = now_epoch() + 3_600;
+        let pending = db.get_pending_failures(5, later, 10).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        let sig1 = pending.iter().find(|f| f.signature == "sig1").unwrap();
+        assert_eq!((sig1.stage.as_str(), sig1.attempts), ("persist", 2));
+        assert_eq!(sig1.error, "db down");
+        assert_eq!(sig1.retry_at(), sig1.last_failed_at + 120);
+
+        // Exhausted failures are no longer pending, but can be listed.
+        assert_eq!(
+            db.get_pending_failures(2, later, 10).await.unwrap().len(),
+            1
+        );
+        let exhausted = db.get_exhausted_failures(2, 0, 10).await.unwrap();
+        assert_eq!(exhausted.len(), 1);
+        assert_eq!(exhausted[0].signature, "sig1");
+        assert_eq!(
+            db.count_ingestion_failures(2, later).await.unwrap(),
+            FailureCounts {
+                due: 1,
+                waiting: 0,
+                exhausted: 1
+            }
+        );
+
+        assert!(db.clear_failure("sig1").await.unwrap());
+        assert!(!db.clear_failure("sig1").await.unwrap());
+        let pending = db.get_pending_failures(5, later, 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].signature, "sig2");
+    }
+
+    #[tokio::test]
+    async fn test_backing_off_failures_do_not_crowd_out_due_ones() {
+        let db = DatabaseManager::in_memory();
+        // Three fresh failures and one whose one-minute backoff has elapsed.
+        for sig in ["fresh_a", "fresh_b", "fresh_c"] {
+            db.record_ingestion_failure(sig, "parse", "skipped")
+                .await
+                .unwrap();
+        }
+        db.record_ingestion_failure("old", "parse", "skipped")
+            .await
+            .unwrap();
+        {
+            let DatabaseInner::Memory { state } = &db.inner else {
+                unreachable!()
+            };
+            let mut mem = state.write().await;
+            let old = mem.ingestion_failures.get_mut("old").unwrap();
+            old.first_failed_at -= 600;
+            old.last_failed_at -= 600;
+        }
+        // Fresh ones sort first by signature on a tie, so the due filter must
+        // run before the limit for "old" to be returned.
+        let due = db.get_pending_failures(8, now_epoch(), 1).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].signature, "old");
+        assert_eq!(
+            db.count_ingestion_failures(8, now_epoch()).await.unwrap(),
+            FailureCounts {
+                due: 1,
+                waiting: 3,
+                exhausted: 0
+            }
+        );
+    }
+
+    /// Seeds a busy shared slot and returns the `(a→b, b→a, a→a)` same-block counts.
+    async fn shared_slot_counts(db: &DatabaseManager, tag: &str) -> (u32, u32, u32) {
+        let (a, b) = (format!("A{}", tag), format!("B{}", tag));
+        let w = |name: &str| format!("{}{}", name, tag);
+        let tx = |sig: &str, slot: u64, transfers: Vec<(String, String)>| {
+            let sol_transfers = transfers
+                .into_iter()
+                .enumerate()
+                .map(|(i, (from, to))| SolTransfer {
+                    from,
+                    to,
+                    amount_lamports: 1_000_000_000,
+                    amount_sol: 1.0,
+                    instruction_index: i,
+                    transfer_type: "system".to_string(),
+                })
+                .collect();
+            EnhancedTransaction {
+                signature: format!("{}{}", sig, tag),
+                slot,
+                block_time: Some(1_000 + slot),
+                fee: 0,
+                success: true,
+                error: None,
+                compute_units_consumed: None,
+                compute_unit_price_micro_lamports: None,
+                priority_fee_lamports: None,
+                accounts: Vec::new(),
+                signers: Vec::new(),
+                writable_accounts: Vec::new(),
+                sol_transfers,
+                token_transfers: Vec::new(),
+                swaps: Vec::new(),
+                stake_actions: Vec::new(),
+                mint_burn_events: Vec::new(),
+                nft_trades: Vec::new(),
+                balance_changes: Vec::new(),
+                programs_called: Vec::new(),
+                program_names: Vec::new(),
+                tx_type: crate::core::enhanced_parser::TransactionType::Unknown,
+                is_versioned: false,
+                finalized: false,
+            }
+        };
+
+        let txs = [
+            // Slot 500: one wallet_a signature with two events, another receiving,
+            // a direct a→b transfer and an unrelated wallet_b transfer.
+            tx("a1", 500, vec![(a.clone(), w("X")), (a.clone(), w("Y"))]),
+            tx("a2", 500, vec![(w("Z"), a.clone())]),
+            tx("ab", 500, vec![(a.clone(), b.clone())]),
+            tx("b1", 500, vec![(b.clone(), w("X"))]),
+            // Neighbouring slots are not the same block.
+            tx("a3", 501, vec![(a.clone(), w("X"))]),
+            tx("b2", 502, vec![(b.clone(), w("Y"))]),
+        ];
+        for t in &txs {
+            for (i, transfer) in t.sol_transfers.iter().enumerate() {
+                db.store_sol_transfer_event(t, transfer, i as i32)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let count = |x: String, y: String| async move {
+            db.get_temporal_overlap(&x, &y, None, 1)
+                .await
+                .unwrap()
+                .same_block_count
+        };
+        (
+            count(a.clone(), b.clone()).await,
+            count(b.clone(), a.clone()).await,
+            count(a.clone(), a.clone()).await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_same_block_count_is_per_signature() {
+        let db = DatabaseManager::in_memory();
+        // a1 and ab, sent by wallet_a, share slot 500 with b1 sent by wallet_b; b1
+        // shares it with both. a2 (received by wallet_a) and ab (received by
+        // wallet_b) aren't counted for the receiver.
+        assert_eq!(shared_slot_counts(&db, "").await, (2, 1, 0));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_same_block_count_matches_in_memory_on_postgres() {
+        let memory = shared_slot_counts(&DatabaseManager::in_memory(), "").await;
+        let pg = DatabaseManager::connect(&test_database_url(), 2)
+            .await
+            .unwrap();
+        pg.init_schema().await.unwrap();
+        let tag = format!(
+            "-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        );
+        assert_eq!(shared_slot_counts(&pg, &tag).await, memory);
+    }
+
+    #[tokio::test]
+    async fn test_behavioral_profile_counts_direction_and_counterparties() {
+        let db = DatabaseManager::in_memory();
+        let event = |signature: &str, from: &str, to: &str, sol: f64| TransferEvent {
+            signature: signature.to_string(),
+            event_index: 0,
+            slot: 1,
+            block_time: Some(1_000),
+            kind: "sol".to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: None,
+            amount_sol: Some(sol),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for e in [
+                event("in1", "F1", "W", 1.0),
+                event("in2", "F1", "W", 1.0),
+                event("in3", "F2", "W", 1.0),
+                event("out1", "W", "S1", 1.0),
+                // Self-transfers are outbound with no counterparty; zero amounts are skipped.
+                event("self", "W", "W", 1.0),
+                event("zero", "F3", "W", 0.0),
+            ] {
+                mem.transfer_events
+                    .insert((e.signature.clone(), e.event_index), e);
+            }
+        }
+
+        let profile = db.get_behavioral_profile("W", None).await.unwrap().unwrap();
+        assert_eq!(profile.total_transfers, 5);
+        assert_eq!(profile.inbound_count, 3);
+        assert_eq!(profile.outbound_count, 2);
+        assert_eq!(profile.inbound_outbound_ratio, 1.5);
+        assert_eq!(profile.unique_counterparties, 3);
+
+        let all = db.get_all_behavioral_profiles(None, 10).await.unwrap();
+        let same = all.iter().find(|p| p.wallet == "W").unwrap();
+        assert_eq!(
+            (
+                same.inbound_count,
+                same.outbound_count,
+                same.unique_counterparties
+            ),
+            (3, 2, 3)
+        );
+        let funder = all.iter().find(|p| p.wallet == "F1").unwrap();
+        assert_eq!((funder.inbound_count, funder.outbound_count), (0, 2));
+        assert_eq!(funder.inbound_outbound_ratio, 0.0);
+        assert_eq!(funder.unique_counterparties, 1);
+        let sink = all.iter().find(|p| p.wallet == "S1").unwrap();
+        assert_eq!(sink.inbound_outbound_ratio, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_high_risk_wallets_ranked_filtered_and_paged() {
+        let db = DatabaseManager::in_memory();
+        for (wallet, score) in [("LOW", 0.2), ("HIGH", 0.9), ("MID", 0.6), ("TIE", 0.6)] {
+            db.set_wallet_risk_score(wallet, score).await.unwrap();
+        }
+        // Re-scoring replaces the stored score.
+        db.set_wallet_risk_score("LOW", 0.1).await.unwrap();
+
+        let wallets = |scores: Vec<WalletRiskScore>| -> Vec<String> {
+            scores.into_iter().map(|s| s.wallet).collect()
+        };
+        assert_eq!(
+            wallets(db.get_high_risk_wallets(0.0, 0, 10).await.unwrap()),
+            vec!["HIGH", "MID", "TIE", "LOW"]
+        );
+        assert_eq!(
+            wallets(db.get_high_risk_wallets(0.5, 0, 10).await.unwrap()),
+            vec!["HIGH", "MID", "TIE"]
+        );
+        assert_eq!(
+            wallets(db.get_high_risk_wallets(0.5, 1, 1).await.unwrap()),
+            vec!["MID"]
+        );
+        assert!(db
+            .get_high_risk_wallets(0.95, 0, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let low = db.get_high_risk_wallets(0.0, 3, 10).await.unwrap();
+        assert_eq!(low[0].score, 0.1);
+    }
+
+    #[tokio::test]
+    async fn test_outbound_recipients_by_mint_keep_mints_apart() {
+        let db = DatabaseManager::in_memory();
+        let token =
+            |sig: &str, mint: &str, ui: Option<f64>, raw: i64, decimals: i32| TransferEvent {
+                signature: sig.to_string(),
+                event_index: 0,
+                slot: 1,
+                block_time: Some(1_000),
+                kind: "token".to_string(),
+                transfer_type: "transfer".to_string(),
+                from_wallet: Some("SENDER".to_string()),
+                to_wallet: Some("RECIPIENT".to_string()),
+                mint: Some(mint.to_string()),
+                amount_sol: None,
+                token_amount_ui: ui,
+                token_amount: Some(raw),
+                token_decimals: Some(decimals),
+            };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                // 1000 USDC (6 decimals) and 1000 + 500 BONK (5 decimals); the
+                // second BONK event has no UI amount stored.
+                token("sig_usdc", "USDC", Some(1_000.0), 1_000_000_000, 6),
+                token("sig_bonk", "BONK", Some(1_000.0), 100_000_000, 5),
+                token("sig_bonk_raw", "BONK", None, 50_000_000, 5),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let recipients = db
+            .get_top_outbound_recipients_by_mint("SENDER", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(recipients.len(), 1);
+        let r = &recipients[0];
+        assert_eq!(r.wallet, "RECIPIENT");
+        assert_eq!(r.count, 3);
+        assert_eq!(r.token_ui_by_mint.len(), 2);
+        assert_eq!(r.token_ui_by_mint["USDC"], 1_000.0);
+        assert_eq!(r.token_ui_by_mint["BONK"], 1_500.0);
+        assert_eq!(r.top_asset_share, 1.0);
+
+        // The cross-mint sum this replaces.
+        let summed = db
+            .get_top_outbound_recipients("SENDER", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(summed[0].total_token_ui, 2_000.0);
+    }
+
+    #[tokio::test]
+    async fn test_top_recipients_rank_token_volume_per_mint() {
+        let db = DatabaseManager::in_memory();
+        seed(
+            &db,
+            [
+                // All of SENDER's USDC goes to DEPOSIT; SOL is split between two others.
+                token_event("sig_usdc", "SENDER", "DEPOSIT", "USDC", Some(50.0), Some(1)),
+                sol_event("sig_sol_1", "SENDER", "FRIEND", 3.0, Some(1)),
+                sol_event("sig_sol_2", "SENDER", "DUST", 1.0, Some(1)),
+                sol_event("sig_sol_3", "SENDER", "DUST", 0.5, Some(1)),
+                // Another sender's USDC doesn't dilute SENDER's shares.
+                token_event("sig_other", "OTHER", "DUST", "USDC", Some(1e6), Some(1)),
+            ],
+        )
+        .await;
+
+        let recipients = db
+            .get_top_outbound_recipients_by_mint("SENDER", None, 10)
+            .await
+            .unwrap();
+        let ranked: Vec<(&str, f64)> = recipients
+            .iter()
+            .map(|r| (r.wallet.as_str(), r.top_asset_share))
+            .collect();
+        assert_eq!(
+            ranked,
+            [("DEPOSIT", 1.0), ("FRIEND", 3.0 / 4.5), ("DUST", 1.5 / 4.5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wallet_transfer_pages_have_no_gaps_or_duplicates() {
+        let db = DatabaseManager::in_memory();
+        let event = |sig: usize, idx: i32, time: Option<i64>, from: &str, to: &str| TransferEvent {
+            signature: format!("sig{:02}", sig),
+            event_index: idx,
+            slot: sig as i64,
+            block_time: time,
+            kind: "sol".to_string(),
+            transfer_type: "transfer".to_string(),
+            from_wallet: Some(from.to_string()),
+            to_wallet: Some(to.to_string()),
+            mint: None,
+            amount_sol: Some(1.0),
+            token_amount_ui: None,
+            token_amount: None,
+            token_decimals: None,
+        };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            let mut add = |ev: TransferEvent| {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            };
+            for i in 0..20 {
+                // Several events per timestamp and per signature, both directions.
+                let time = Some(1_000 + (i / 3) as i64 * 60);
+                add(event(i, 0, time, "WALLET", "OTHER"));
+                if i % 4 == 0 {
+                    add(event(i, 1, time, "OTHER", "WALLET"));
+                }
+            }
+            add(event(90, 0, None, "OTHER", "WALLET"));
+            add(event(91, 0, Some(1_100), "OTHER", "ELSEWHERE"));
+        }
+
+        let all = db
+            .get_wallet_transfers("WALLET", None, None, 1000)
+            .await
+            .unwrap();
+        assert_eq!(all.transfers.len(), 26);
+        assert!(all.next_cursor.is_none());
+        // Untimed events sort as time 0.
+        assert_eq!(all.transfers[0].signature, "sig90");
+
+        let mut paged = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = db
+                .get_wallet_transfers("WALLET", None, cursor.as_deref(), 4)
+                .await
+                .unwrap();
+            assert!(page.transfers.len() <= 4);
+            paged.extend(page.transfers);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        let keys = |events: &[TransferEvent]| -> Vec<(String, i32)> {
+            events
+                .iter()
+                .map(|e| (e.signature.clone(), e.event_index))
+                .collect()
+        };
+        assert_eq!(keys(&paged), keys(&all.transfers));
+
+        let recent = db
+            .get_wallet_transfers("WALLET", Some(1_300), None, 1000)
+            .await
+            .unwrap();
+        assert!(recent
+            .transfers
+            .iter()
+            .all(|e| e.block_time.is_none_or(|t| t >= 1_300)));
+
+        let err = db
+            .get_wallet_transfers("WALLET", None, Some("not-a-cursor"), 10)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BeastError::ParseError(_)));
+    }
+    #[tokio::test]
+    async fn test_wallet_summary_totals_lifetime_transfers() {
+        let db = DatabaseManager::in_memory();
+        let event = |signature: &str, idx: i32, from: &str, to: &str, block_time: Option<i64>| {
+            TransferEvent {
+                signature: signature.to_string(),
+                event_index: idx,
+                slot: 1,
+                block_time,
+                kind: "sol".to_string(),
+                transfer_type: "transfer".to_string(),
+                from_wallet: Some(from.to_string()),
+                to_wallet: Some(to.to_string()),
+                mint: None,
+                amount_sol: Some(1.0),
+                token_amount_ui: None,
+                token_amount: None,
+                token_decimals: None,
+            }
+        };
+        let token = |ev: TransferEvent, mint: &str, ui: Option<f64>, raw: Option<(i64, i32)>| {
+            TransferEvent {
+                kind: "token".to_string(),
+                mint: Some(mint.to_string()),
+                amount_sol: None,
+                token_amount_ui: ui,
+                token_amount: raw.map(|(amount, _)| amount),
+                token_decimals: raw.map(|(_, decimals)| decimals),
+                ..ev
+            }
+        };
+        if let DatabaseInner::Memory { state } = &db.inner {
+            let mut mem = state.write().await;
+            for ev in [
+                // Two transfers in one transaction.
+                TransferEvent {
+                    amount_sol: Some(5.0),
+                    ..event("in", 0, "FUNDER", "W", Some(1_000))
+                },
+                TransferEvent {
+                    amount_sol: Some(2.5),
+                    ..event("in", 1, "FUNDER", "W", Some(1_000))
+                },
+                TransferEvent {
+                    amount_sol: Some(3.0),
+                    ..event("out", 0, "W", "DEST", Some(2_000))
+                },
+                token(
+                    event("tok_in", 0, "SELLER", "W", Some(3_000)),
+                    "MINT",
+                    Some(40.0),
+                    None,
+                ),
+                token(
+                    event("tok_out", 0, "W", "BUYER", None),
+                    "MINT",
+                    None,
+                    Some((1_500_000, 6)),
+                ),
+                event("self", 0, "W", "W", Some(500)),
+                event("other", 0, "FUNDER", "DEST", Some(100)),
+            ] {
+                mem.transfer_events
+                    .insert((ev.signature.clone(), ev.event_index), ev);
+            }
+        }
+
+        let summary = db.get_wallet_summary("W").await.unwrap().unwrap();
+        assert_eq!(summary.first_seen_epoch, 500);
+        assert_eq!(summary.last_seen_epoch, 3_000);
+        assert_eq!(summary.transaction_count, 5);
+        assert_eq!(summary.transfer_count, 6);
+        assert_eq!(summary.inbound_sol, 7.5);
+        // The self-transfer counts as outbound.
+        assert_eq!(summary.outbound_sol, 4.0);
+        assert_eq!(summary.inbound_token_ui_by_mint.get("MINT"), Some(&40.0));
+        assert_eq!(summary.outbound_token_ui_by_mint.get("MINT"), Some(&1.5));
+        assert_eq!(summary.unique_counterparties, 4);
+
+        assert!(db.get_wallet_summary("NOBODY").await.unwrap().is_none());
+    }
 }
@@ -2,6 +2,9 @@
 pub mod database;
 
 pub use database::{
-    BehavioralProfile, DatabaseManager, SharedWalletSignal, TemporalOverlap, TransferEvent,
-    WalletConnection, WalletVolumeSignal,
+    ActivityBucket, AmountBucket, BehavioralProfile, BlockTimeBackfill, ConnectionPage,
+    CounterpartyNetFlow, DatabaseManager, FailureCounts, IngestionFailure, PoolStatus, SharedMint,
+    SharedWalletSignal, StoredOutcome, TemporalOverlap, TransferEvent, TransferPage,
+    WalletConnection, WalletLabel, WalletMintVolumes, WalletRiskScore, WalletSummary,
+    WalletVolumeSignal, WatchEntry,
 };